@@ -0,0 +1,490 @@
+use crate::{
+    app_state::AppState,
+    codex::{compute_fingerprint, validate_auth_json},
+    crypto, homes,
+    models::{Account, BulkImportItem, BulkImportReport},
+    service::rewrite_live_auth_if_current,
+};
+use anyhow::{anyhow, Context, Result};
+use clap::ValueEnum;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+use zeroize::Zeroize;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "snake_case")]
+pub enum NameSource {
+    // 文件名去掉 auth 前缀后为空（如 auth.json）时退回邮箱
+    #[default]
+    Filename,
+    Email,
+}
+
+// 待导入的一个登录文件；名称为空时按 ensure_name 从登录数据推导。
+#[derive(Debug, Clone)]
+pub struct AuthCandidate {
+    pub path: PathBuf,
+    pub name: Option<String>,
+    pub tags: Vec<String>,
+}
+
+pub fn ensure_name(name: &str, auth_json: &Value) -> String {
+    if !name.trim().is_empty() {
+        return name.trim().to_string();
+    }
+    if let Some(email) = auth_json
+        .get("email")
+        .and_then(Value::as_str)
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+    {
+        return email.to_string();
+    }
+
+    if let Some(account_id) = auth_json
+        .get("account_id")
+        .and_then(Value::as_str)
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+    {
+        return account_id.to_string();
+    }
+
+    let suffix = compute_fingerprint(auth_json)
+        .ok()
+        .and_then(|fingerprint| fingerprint.split(':').nth(1).map(|hash| hash.to_string()))
+        .map(|hash| hash.chars().take(4).collect::<String>())
+        .filter(|hash| hash.len() == 4)
+        .unwrap_or_else(|| "0000".to_string());
+    format!("未命名账号-{suffix}")
+}
+
+pub fn unique_tags(tags: Vec<String>) -> Vec<String> {
+    let mut seen = std::collections::BTreeSet::new();
+    tags.into_iter()
+        .map(|tag| tag.trim().to_string())
+        .filter(|tag| !tag.is_empty())
+        .filter(|tag| seen.insert(tag.clone()))
+        .collect()
+}
+
+pub fn import_account_from_auth_json(
+    state: &AppState,
+    name: &str,
+    tags: Vec<String>,
+    auth_json: Value,
+) -> Result<Account> {
+    let fingerprint = compute_fingerprint(&auth_json)?;
+    if let Some(reason) = duplicate_reason(state, &fingerprint)? {
+        return Err(anyhow!(reason));
+    }
+
+    let mut key = state.get_vault_key()?;
+    let auth_text = serde_json::to_string_pretty(&auth_json)?;
+    let encrypted = crypto::encrypt_to_base64(&key, auth_text.as_bytes());
+    key.zeroize();
+    let account = state.store.create_account(
+        &ensure_name(name, &auth_json),
+        &unique_tags(tags),
+        &encrypted?,
+        &fingerprint,
+    )?;
+    let _ = homes::sync_account_home(state, &account.id);
+    Ok(account)
+}
+
+pub const REAUTHENTICATED_EVENT: &str = "reauthenticated";
+
+// 凭据被吊销后重新登录：只替换加密的登录数据，账号 ID、名称、标签、历史与配额趋势保持不变。
+// 以指纹确认是同一身份，避免把其他账号的凭据写进来。
+pub fn reauthenticate_account(
+    state: &AppState,
+    account_id: &str,
+    auth_json: Value,
+    source: &str,
+) -> Result<Account> {
+    let account = state
+        .store
+        .get_account(account_id.trim())?
+        .ok_or_else(|| anyhow!("目标账户不存在"))?;
+    let fingerprint = compute_fingerprint(&auth_json)?;
+    if fingerprint != account.auth_fingerprint {
+        let other = state
+            .store
+            .find_account_by_fingerprint(&fingerprint)?
+            .map(|other| format!("，它对应已保存的账号「{}」", other.name))
+            .unwrap_or_default();
+        return Err(anyhow!(
+            "登录的身份与账号「{}」不一致{other}，未更新凭据",
+            account.name
+        ));
+    }
+
+    let mut key = state.get_vault_key()?;
+    let auth_text = serde_json::to_string_pretty(&auth_json)?;
+    let encrypted = crypto::encrypt_to_base64(&key, auth_text.as_bytes());
+    key.zeroize();
+    state.store.replace_account_auth(
+        &account.id,
+        &encrypted?,
+        REAUTHENTICATED_EVENT,
+        Some(source),
+    )?;
+    let _ = homes::sync_account_home(state, &account.id);
+    rewrite_live_auth_if_current(state, &account.id, &auth_json)
+        .context("凭据已更新，但改写当前 Codex 登录文件失败")?;
+    state
+        .store
+        .get_account(&account.id)?
+        .ok_or_else(|| anyhow!("目标账户不存在"))
+}
+
+fn duplicate_reason(state: &AppState, fingerprint: &str) -> Result<Option<String>> {
+    if let Some(existing) = state.store.find_account_by_fingerprint(fingerprint)? {
+        return Ok(Some(format!(
+            "该账号已存在（{}），已跳过重复导入。",
+            existing.name
+        )));
+    }
+    if state
+        .store
+        .list_trashed_accounts()?
+        .iter()
+        .any(|(account, _)| account.auth_fingerprint == fingerprint)
+    {
+        return Ok(Some("该账号在回收站中，请先恢复。".to_string()));
+    }
+    Ok(None)
+}
+
+// 目录取其下所有 .json 文件（不递归）；否则按通配符匹配文件名，目录部分需为实际路径。
+pub fn resolve_auth_files(source: &str) -> Result<Vec<PathBuf>> {
+    let source = source.trim();
+    if source.is_empty() {
+        return Err(anyhow!("导入路径不能为空"));
+    }
+    let path = Path::new(source);
+    let (dir, pattern) = if path.is_dir() {
+        (path.to_path_buf(), "*.json".to_string())
+    } else {
+        let pattern = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .ok_or_else(|| anyhow!("导入路径无效: {source}"))?;
+        let dir = path
+            .parent()
+            .filter(|dir| !dir.as_os_str().is_empty())
+            .unwrap_or(Path::new("."));
+        (dir.to_path_buf(), pattern.to_string())
+    };
+    if dir.to_string_lossy().contains(['*', '?']) {
+        return Err(anyhow!("通配符只能出现在文件名中: {source}"));
+    }
+    let matcher = glob_regex(&pattern)?;
+    let mut files = fs::read_dir(&dir)
+        .with_context(|| format!("读取目录失败: {}", dir.display()))?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.is_file())
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| matcher.is_match(name))
+        })
+        .collect::<Vec<_>>();
+    files.sort();
+    Ok(files)
+}
+
+pub fn bulk_import_auth_files(
+    state: &AppState,
+    source: &str,
+    tags: Vec<String>,
+    name_source: NameSource,
+) -> Result<BulkImportReport> {
+    let files = resolve_auth_files(source)?;
+    if files.is_empty() {
+        return Err(anyhow!("没有找到可导入的登录文件: {}", source.trim()));
+    }
+    let candidates = files
+        .into_iter()
+        .map(|path| AuthCandidate {
+            name: match name_source {
+                NameSource::Filename => name_from_file(&path),
+                NameSource::Email => None,
+            },
+            tags: tags.clone(),
+            path,
+        })
+        .collect::<Vec<_>>();
+    import_candidates(state, &candidates)
+}
+
+// 逐个导入，单个文件出错只记录结果不中断；同一批次内按指纹去重。
+pub fn import_candidates(
+    state: &AppState,
+    candidates: &[AuthCandidate],
+) -> Result<BulkImportReport> {
+    if !state.is_vault_unlocked()? {
+        return Err(anyhow!("请先解锁保险库，再批量导入账号"));
+    }
+    let mut report = BulkImportReport {
+        total: candidates.len(),
+        imported: 0,
+        skipped: 0,
+        failed: 0,
+        items: Vec::new(),
+    };
+    let mut seen = HashMap::<String, PathBuf>::new();
+    for candidate in candidates {
+        let mut item = BulkImportItem {
+            path: candidate.path.display().to_string(),
+            name: candidate.name.clone(),
+            action: "failed".to_string(),
+            account_id: None,
+            message: None,
+        };
+        let result = (|| {
+            let text = fs::read_to_string(&candidate.path).context("读取文件失败")?;
+            let auth_json = validate_auth_json(&text)?;
+            let fingerprint = compute_fingerprint(&auth_json)?;
+            if let Some(first) = seen.get(&fingerprint) {
+                return Ok(Err(format!("与 {} 是同一账号，已跳过", first.display())));
+            }
+            seen.insert(fingerprint.clone(), candidate.path.clone());
+            if let Some(reason) = duplicate_reason(state, &fingerprint)? {
+                return Ok(Err(reason));
+            }
+            let account = import_account_from_auth_json(
+                state,
+                candidate.name.as_deref().unwrap_or_default(),
+                candidate.tags.clone(),
+                auth_json,
+            )?;
+            Ok::<_, anyhow::Error>(Ok(account))
+        })();
+        match result {
+            Ok(Ok(account)) => {
+                report.imported += 1;
+                item.action = "imported".to_string();
+                item.name = Some(account.name);
+                item.account_id = Some(account.id);
+            }
+            Ok(Err(reason)) => {
+                report.skipped += 1;
+                item.action = "skipped".to_string();
+                item.message = Some(reason);
+            }
+            Err(error) => {
+                report.failed += 1;
+                item.message = Some(format!("{error:#}"));
+            }
+        }
+        report.items.push(item);
+    }
+    Ok(report)
+}
+
+// auth-work.json / auth_work.json / work.auth.json -> work；auth.json 无法得出名称。
+pub fn name_from_file(path: &Path) -> Option<String> {
+    let stem = path.file_stem()?.to_str()?.trim();
+    let lower = stem.to_ascii_lowercase();
+    let prefixed = ["auth-", "auth_", "auth."]
+        .iter()
+        .any(|prefix| lower.starts_with(prefix));
+    let name = if lower == "auth" || prefixed {
+        stem[4..].trim_start_matches(['-', '_', '.'])
+    } else if lower.ends_with(".auth") {
+        &stem[..stem.len() - 5]
+    } else {
+        stem
+    };
+    let name = name.trim();
+    (!name.is_empty()).then(|| name.to_string())
+}
+
+fn glob_regex(pattern: &str) -> Result<Regex> {
+    let mut expr = String::from("^");
+    for ch in pattern.chars() {
+        match ch {
+            '*' => expr.push_str(".*"),
+            '?' => expr.push('.'),
+            _ => expr.push_str(&regex::escape(&ch.to_string())),
+        }
+    }
+    expr.push('$');
+    Regex::new(&expr).with_context(|| format!("通配符无效: {pattern}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        bulk_import_auth_files, import_account_from_auth_json, name_from_file,
+        reauthenticate_account, NameSource, REAUTHENTICATED_EVENT,
+    };
+    use crate::{
+        models::Account,
+        test_support::{auth_file_text, TestState, TEST_VAULT_KEY},
+    };
+    use serde_json::{json, Value};
+    use std::{
+        fs,
+        path::{Path, PathBuf},
+    };
+
+    // 两个新账号、一个重复文件、一个损坏文件，另有一个非 JSON 文件应被忽略。
+    fn export_dir(state: &TestState) -> PathBuf {
+        let dir = state.root.join("exports");
+        fs::create_dir_all(&dir).expect("应创建目录");
+        fs::write(
+            dir.join("auth-work.json"),
+            auth_file_text("work@example.com"),
+        )
+        .expect("应写入");
+        fs::write(dir.join("auth.json"), auth_file_text("me@example.com")).expect("应写入");
+        fs::write(
+            dir.join("copy-of-work.json"),
+            auth_file_text("work@example.com"),
+        )
+        .expect("应写入");
+        fs::write(dir.join("broken.json"), "{").expect("应写入");
+        fs::write(dir.join("notes.txt"), "ignored").expect("应写入");
+        dir
+    }
+
+    fn auth(email: &str, token: &str) -> Value {
+        json!({ "type": "codex", "access_token": token, "email": email })
+    }
+
+    fn revoked_account(state: &TestState) -> Account {
+        import_account_from_auth_json(
+            state,
+            "work",
+            vec!["team".to_string()],
+            auth("work@example.com", "revoked"),
+        )
+        .expect("应导入")
+    }
+
+    #[test]
+    fn bulk_import_reports_each_file_and_dedupes_by_fingerprint() {
+        let state = TestState::unlocked("bulk");
+        let dir = export_dir(&state);
+        let report = bulk_import_auth_files(
+            &state,
+            &dir.display().to_string(),
+            vec!["migrated".to_string()],
+            NameSource::Filename,
+        )
+        .expect("应导入");
+        assert_eq!(
+            (report.total, report.imported, report.skipped, report.failed),
+            (4, 2, 1, 1)
+        );
+        let mut names = state
+            .store
+            .list_accounts()
+            .expect("应读取")
+            .into_iter()
+            .map(|account| account.name)
+            .collect::<Vec<_>>();
+        names.sort();
+        assert_eq!(names, ["me@example.com", "work"]);
+    }
+
+    #[test]
+    fn bulk_import_by_pattern_skips_existing_accounts() {
+        let state = TestState::unlocked("bulk");
+        let dir = export_dir(&state);
+        let pattern = dir.join("auth*.json").display().to_string();
+        bulk_import_auth_files(&state, &pattern, Vec::new(), NameSource::Filename).expect("应导入");
+        let report = bulk_import_auth_files(&state, &pattern, Vec::new(), NameSource::Filename)
+            .expect("应导入");
+        assert_eq!((report.total, report.skipped), (2, 2));
+    }
+
+    #[test]
+    fn names_accounts_after_file_stem() {
+        assert_eq!(
+            name_from_file(Path::new("team.auth.json")).as_deref(),
+            Some("team")
+        );
+    }
+
+    #[test]
+    fn reauthenticate_rejects_a_different_identity() {
+        let state = TestState::unlocked("reauth");
+        let account = revoked_account(&state);
+        assert!(reauthenticate_account(
+            &state,
+            &account.id,
+            auth("other@example.com", "fresh"),
+            "file"
+        )
+        .is_err());
+        assert!(state
+            .store
+            .list_account_events(&account.id, 10)
+            .expect("应读取事件")
+            .is_empty());
+    }
+
+    #[test]
+    fn reauthenticate_replaces_only_credentials_of_matching_account() {
+        let state = TestState::unlocked("reauth");
+        let account = revoked_account(&state);
+        let updated = reauthenticate_account(
+            &state,
+            &account.id,
+            auth("Work@Example.com", "fresh"),
+            "login",
+        )
+        .expect("同一身份应可重新登录");
+        assert_eq!(
+            (
+                updated.id.as_str(),
+                updated.name.as_str(),
+                updated.tags.clone()
+            ),
+            (account.id.as_str(), "work", vec!["team".to_string()])
+        );
+        let secret = state
+            .store
+            .get_account_secret(&account.id)
+            .expect("应读取")
+            .expect("账号应存在");
+        let decrypted =
+            crate::crypto::decrypt_from_base64(&TEST_VAULT_KEY, &secret.encrypted_auth_blob)
+                .expect("应解密");
+        assert!(String::from_utf8(decrypted)
+            .expect("应为文本")
+            .contains("fresh"));
+    }
+
+    #[test]
+    fn reauthenticate_records_an_account_event() {
+        let state = TestState::unlocked("reauth");
+        let account = revoked_account(&state);
+        reauthenticate_account(
+            &state,
+            &account.id,
+            auth("work@example.com", "fresh"),
+            "login",
+        )
+        .expect("应重新登录");
+        let events = state
+            .store
+            .list_account_events(&account.id, 10)
+            .expect("应读取事件");
+        assert_eq!(events.len(), 1);
+        assert_eq!(
+            (events[0].kind.as_str(), events[0].detail.as_deref()),
+            (REAUTHENTICATED_EVENT, Some("login"))
+        );
+    }
+}
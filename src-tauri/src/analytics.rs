@@ -0,0 +1,460 @@
+use crate::{
+    models::{AccountUsage, SwitchHistory, SwitchPairCount, UsageBucket, UsageReport},
+    store::AppStore,
+};
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Datelike, Duration, FixedOffset, NaiveDate, NaiveTime, TimeZone, Utc};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+const DEFAULT_RANGE_DAYS: i64 = 30;
+const TOP_PAIR_LIMIT: usize = 10;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "snake_case")]
+pub enum UsageGranularity {
+    #[default]
+    Day,
+    Week,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "snake_case")]
+pub enum UsageCsvTable {
+    ActiveTime,
+    Accounts,
+    Pairs,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UsageQuery {
+    pub granularity: UsageGranularity,
+    pub since: Option<String>,
+    pub until: Option<String>,
+    // 按天/周切分时使用的时区偏移，未指定时按 UTC
+    pub utc_offset_minutes: Option<i32>,
+}
+
+type AccountKey = Option<String>;
+
+#[derive(Default)]
+struct AccountTally {
+    active_seconds: i64,
+    switches_in: usize,
+    failed_switches: usize,
+}
+
+// 由切换历史重建每个账号的生效区间：成功切换后目标账号生效，回滚恢复的是切换前的来源账号，
+// 失败记录不改变当前账号。首条生效记录之前的时间无法归属，不计入统计。
+pub fn usage_report(store: &AppStore, query: &UsageQuery) -> Result<UsageReport> {
+    let until = match query.until.as_deref().filter(|value| !value.is_empty()) {
+        Some(value) => parse_time(value)?,
+        None => Utc::now(),
+    };
+    let since = match query.since.as_deref().filter(|value| !value.is_empty()) {
+        Some(value) => parse_time(value)?,
+        None => until - Duration::days(DEFAULT_RANGE_DAYS),
+    };
+    if since >= until {
+        return Err(anyhow!("统计起始时间必须早于截止时间"));
+    }
+    let offset_minutes = query.utc_offset_minutes.unwrap_or(0);
+    let offset = FixedOffset::east_opt(offset_minutes * 60)
+        .ok_or_else(|| anyhow!("时区偏移无效: {offset_minutes} 分钟"))?;
+
+    let history = store.list_switch_history_chronological()?;
+    let names = store.list_account_names()?;
+
+    let mut buckets = BTreeMap::<(NaiveDate, AccountKey), i64>::new();
+    let mut tallies = HashMap::<AccountKey, AccountTally>::new();
+    let mut pairs = HashMap::<(AccountKey, AccountKey), usize>::new();
+    let (mut total_switches, mut failed_switches, mut rollbacks) = (0, 0, 0);
+    let mut active: Option<(AccountKey, DateTime<Utc>)> = None;
+
+    for item in &history {
+        let Ok(created_at) = parse_time(&item.created_at) else {
+            continue;
+        };
+        let in_range = created_at >= since && created_at < until;
+        if in_range {
+            match item.result.as_str() {
+                "success" => {
+                    total_switches += 1;
+                    tallies
+                        .entry(item.to_account_id.clone())
+                        .or_default()
+                        .switches_in += 1;
+                    *pairs
+                        .entry((item.from_account_id.clone(), item.to_account_id.clone()))
+                        .or_default() += 1;
+                }
+                "failed" => {
+                    failed_switches += 1;
+                    tallies
+                        .entry(item.to_account_id.clone())
+                        .or_default()
+                        .failed_switches += 1;
+                }
+                "rolled_back" => rollbacks += 1,
+                _ => {}
+            }
+        }
+        let Some(next) = activated_account(item) else {
+            continue;
+        };
+        if let Some((account, started_at)) = active.take() {
+            add_interval(
+                &mut buckets,
+                &mut tallies,
+                account,
+                started_at.max(since),
+                created_at.min(until),
+                query.granularity,
+                offset,
+            );
+        }
+        active = Some((next, created_at));
+    }
+    if let Some((account, started_at)) = active {
+        add_interval(
+            &mut buckets,
+            &mut tallies,
+            account,
+            started_at.max(since),
+            until,
+            query.granularity,
+            offset,
+        );
+    }
+
+    let name_of = |id: &AccountKey| id.as_ref().and_then(|id| names.get(id).cloned());
+    let buckets = buckets
+        .into_iter()
+        .map(|((period, account_id), active_seconds)| UsageBucket {
+            period_start: period.format("%Y-%m-%d").to_string(),
+            account_name: name_of(&account_id),
+            account_id,
+            active_seconds,
+        })
+        .collect();
+    let mut accounts = tallies
+        .into_iter()
+        .map(|(account_id, tally)| AccountUsage {
+            account_name: name_of(&account_id),
+            account_id,
+            active_seconds: tally.active_seconds,
+            switches_in: tally.switches_in,
+            failed_switches: tally.failed_switches,
+            failure_rate: ratio(tally.failed_switches, tally.switches_in),
+        })
+        .collect::<Vec<_>>();
+    accounts.sort_by(|left, right| {
+        right
+            .active_seconds
+            .cmp(&left.active_seconds)
+            .then_with(|| left.account_name.cmp(&right.account_name))
+    });
+    let mut top_pairs = pairs
+        .into_iter()
+        .map(
+            |((from_account_id, to_account_id), count)| SwitchPairCount {
+                from_account_name: name_of(&from_account_id),
+                to_account_name: name_of(&to_account_id),
+                from_account_id,
+                to_account_id,
+                count,
+            },
+        )
+        .collect::<Vec<_>>();
+    top_pairs.sort_by(|left, right| {
+        right
+            .count
+            .cmp(&left.count)
+            .then_with(|| left.from_account_name.cmp(&right.from_account_name))
+            .then_with(|| left.to_account_name.cmp(&right.to_account_name))
+    });
+    top_pairs.truncate(TOP_PAIR_LIMIT);
+
+    Ok(UsageReport {
+        granularity: match query.granularity {
+            UsageGranularity::Day => "day",
+            UsageGranularity::Week => "week",
+        }
+        .to_string(),
+        since: since.to_rfc3339(),
+        until: until.to_rfc3339(),
+        buckets,
+        accounts,
+        top_pairs,
+        total_switches,
+        failed_switches,
+        rollbacks,
+        failure_rate: ratio(failed_switches, total_switches),
+    })
+}
+
+pub fn report_to_csv(report: &UsageReport, table: UsageCsvTable) -> String {
+    let mut rows = Vec::new();
+    match table {
+        UsageCsvTable::ActiveTime => {
+            rows.push(vec![
+                "period_start".to_string(),
+                "account_id".to_string(),
+                "account_name".to_string(),
+                "active_seconds".to_string(),
+                "active_hours".to_string(),
+            ]);
+            for bucket in &report.buckets {
+                rows.push(vec![
+                    bucket.period_start.clone(),
+                    bucket.account_id.clone().unwrap_or_default(),
+                    account_label(&bucket.account_id, &bucket.account_name),
+                    bucket.active_seconds.to_string(),
+                    format!("{:.2}", bucket.active_seconds as f64 / 3600.0),
+                ]);
+            }
+        }
+        UsageCsvTable::Accounts => {
+            rows.push(vec![
+                "account_id".to_string(),
+                "account_name".to_string(),
+                "active_seconds".to_string(),
+                "switches_in".to_string(),
+                "failed_switches".to_string(),
+                "failure_rate".to_string(),
+            ]);
+            for account in &report.accounts {
+                rows.push(vec![
+                    account.account_id.clone().unwrap_or_default(),
+                    account_label(&account.account_id, &account.account_name),
+                    account.active_seconds.to_string(),
+                    account.switches_in.to_string(),
+                    account.failed_switches.to_string(),
+                    format!("{:.4}", account.failure_rate),
+                ]);
+            }
+        }
+        UsageCsvTable::Pairs => {
+            rows.push(vec![
+                "from_account_id".to_string(),
+                "from_account_name".to_string(),
+                "to_account_id".to_string(),
+                "to_account_name".to_string(),
+                "count".to_string(),
+            ]);
+            for pair in &report.top_pairs {
+                rows.push(vec![
+                    pair.from_account_id.clone().unwrap_or_default(),
+                    account_label(&pair.from_account_id, &pair.from_account_name),
+                    pair.to_account_id.clone().unwrap_or_default(),
+                    account_label(&pair.to_account_id, &pair.to_account_name),
+                    pair.count.to_string(),
+                ]);
+            }
+        }
+    }
+    rows.iter()
+        .map(|row| {
+            row.iter()
+                .map(|cell| csv_field(cell))
+                .collect::<Vec<_>>()
+                .join(",")
+        })
+        .map(|line| line + "\r\n")
+        .collect()
+}
+
+fn activated_account(item: &SwitchHistory) -> Option<AccountKey> {
+    match item.result.as_str() {
+        "success" => Some(item.to_account_id.clone()),
+        "rolled_back" => Some(item.from_account_id.clone()),
+        _ => None,
+    }
+}
+
+fn add_interval(
+    buckets: &mut BTreeMap<(NaiveDate, AccountKey), i64>,
+    tallies: &mut HashMap<AccountKey, AccountTally>,
+    account: AccountKey,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    granularity: UsageGranularity,
+    offset: FixedOffset,
+) {
+    let mut cursor = start;
+    while cursor < end {
+        let period = period_start(cursor.with_timezone(&offset).date_naive(), granularity);
+        let next_period = period
+            + Duration::days(match granularity {
+                UsageGranularity::Day => 1,
+                UsageGranularity::Week => 7,
+            });
+        let boundary = offset
+            .from_local_datetime(&next_period.and_time(NaiveTime::MIN))
+            .single()
+            .map(|time| time.with_timezone(&Utc))
+            .unwrap_or(end);
+        let slice_end = boundary.min(end);
+        let seconds = (slice_end - cursor).num_seconds();
+        *buckets.entry((period, account.clone())).or_default() += seconds;
+        tallies.entry(account.clone()).or_default().active_seconds += seconds;
+        cursor = slice_end;
+    }
+}
+
+fn period_start(date: NaiveDate, granularity: UsageGranularity) -> NaiveDate {
+    match granularity {
+        UsageGranularity::Day => date,
+        UsageGranularity::Week => {
+            date - Duration::days(i64::from(date.weekday().num_days_from_monday()))
+        }
+    }
+}
+
+fn parse_time(value: &str) -> Result<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|time| time.with_timezone(&Utc))
+        .map_err(|_| anyhow!("时间格式无效，应为 RFC 3339: {value}"))
+}
+
+fn ratio(part: usize, rest: usize) -> f64 {
+    let total = part + rest;
+    if total == 0 {
+        0.0
+    } else {
+        part as f64 / total as f64
+    }
+}
+
+fn account_label(id: &AccountKey, name: &Option<String>) -> String {
+    match (id, name) {
+        (_, Some(name)) => name.clone(),
+        (Some(id), None) => id.clone(),
+        (None, None) => "已删除账号".to_string(),
+    }
+}
+
+// 账号名可由用户填写，以公式字符开头时加前缀，避免在表格软件中被当作公式执行。
+fn csv_field(value: &str) -> String {
+    let value = if value.starts_with(['=', '+', '-', '@']) {
+        format!("'{value}")
+    } else {
+        value.to_string()
+    };
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{report_to_csv, usage_report, UsageCsvTable, UsageGranularity, UsageQuery};
+    use crate::store::{now, AppStore};
+    use rusqlite::params;
+
+    #[test]
+    fn reconstructs_active_time_per_day_and_counts_switches() {
+        let root =
+            std::env::temp_dir().join(format!("codex-switch-usage-{}", uuid::Uuid::new_v4()));
+        let store = AppStore::new(root.clone());
+        store.init().expect("应初始化");
+        let conn = store.conn().expect("应获取连接");
+        for (id, name) in [("a", "工作"), ("b", "=个人")] {
+            conn.execute(
+                r#"
+                INSERT INTO accounts(id, name, tags_json, encrypted_auth_blob, auth_fingerprint, created_at, updated_at)
+                VALUES (?1, ?2, '[]', 'blob', ?1, ?3, ?3)
+                "#,
+                params![id, name, now()],
+            )
+            .expect("应写入账号");
+        }
+        let rows = [
+            (
+                "h1",
+                None,
+                Some("a"),
+                "success",
+                "2026-03-01T20:00:00+00:00",
+            ),
+            (
+                "h2",
+                Some("a"),
+                Some("b"),
+                "success",
+                "2026-03-02T02:00:00+00:00",
+            ),
+            (
+                "h3",
+                Some("b"),
+                Some("a"),
+                "failed",
+                "2026-03-02T03:00:00+00:00",
+            ),
+            (
+                "h4",
+                Some("a"),
+                Some("b"),
+                "rolled_back",
+                "2026-03-02T06:00:00+00:00",
+            ),
+        ];
+        for (id, from, to, result, created_at) in rows {
+            conn.execute(
+                "INSERT INTO switch_history(id, from_account_id, to_account_id, result, created_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![id, from, to, result, created_at],
+            )
+            .expect("应写入历史");
+        }
+        drop(conn);
+
+        let report = usage_report(
+            &store,
+            &UsageQuery {
+                granularity: UsageGranularity::Day,
+                since: Some("2026-03-01T00:00:00+00:00".into()),
+                until: Some("2026-03-02T12:00:00+00:00".into()),
+                utc_offset_minutes: Some(0),
+            },
+        )
+        .expect("应统计");
+
+        // a: 3-01 20:00~24:00 + 3-02 00:00~02:00 + 回滚后 06:00~12:00；b: 02:00~06:00
+        let buckets = report
+            .buckets
+            .iter()
+            .map(|bucket| {
+                (
+                    bucket.period_start.as_str(),
+                    bucket.account_id.as_deref(),
+                    bucket.active_seconds / 3600,
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            buckets,
+            [
+                ("2026-03-01", Some("a"), 4),
+                ("2026-03-02", Some("a"), 8),
+                ("2026-03-02", Some("b"), 4)
+            ]
+        );
+        assert_eq!(report.total_switches, 2);
+        assert_eq!(report.failed_switches, 1);
+        assert_eq!(report.rollbacks, 1);
+        assert_eq!(report.accounts[0].account_id.as_deref(), Some("a"));
+        assert_eq!(report.accounts[0].failure_rate, 0.5);
+        assert_eq!(report.top_pairs.len(), 2);
+
+        let csv = report_to_csv(&report, UsageCsvTable::ActiveTime);
+        assert!(
+            csv.starts_with("period_start,account_id,account_name,active_seconds,active_hours\r\n")
+        );
+        assert!(csv.contains("2026-03-02,b,'=个人,14400,4.00\r\n"));
+        std::fs::remove_dir_all(&root).expect("应清理目录");
+    }
+}
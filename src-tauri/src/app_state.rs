@@ -1,8 +1,30 @@
-use crate::{crypto, store::AppStore};
+use crate::{
+    codex::clear_isolated_homes,
+    compat::{resolve_compat, CodexCompat},
+    crypto,
+    device_auth::{DeviceLoginFlow, DeviceLoginSession},
+    discovery::{resolve_codex_binary, CodexBinary},
+    models::AppEvent,
+    rpc::ControlServerState,
+    snapshots,
+    store::AppStore,
+};
 use anyhow::{anyhow, Result};
-use std::sync::Mutex;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+};
+use tokio::sync::broadcast;
+use uuid::Uuid;
 use zeroize::Zeroize;
 
+const EVENT_CHANNEL_CAPACITY: usize = 64;
+
 #[derive(Debug)]
 pub struct VaultSession {
     key: Option<Vec<u8>>,
@@ -41,15 +63,41 @@ impl VaultSession {
 pub struct AppState {
     pub store: AppStore,
     pub vault: Mutex<VaultSession>,
+    pub device_logins: Mutex<HashMap<String, DeviceLoginSession>>,
+    pub control_server: Mutex<ControlServerState>,
+    // 外层 None 表示尚未探测；探测结果缓存到固定路径变更或重新扫描为止。
+    codex_binary: Mutex<Option<Option<CodexBinary>>>,
+    events: broadcast::Sender<AppEvent>,
 }
 
 impl AppState {
     pub fn initialize(store: AppStore) -> Result<Self> {
+        let state = Self::open(store)?;
+        clear_isolated_homes(&state.store.login_homes_dir)?;
+        Ok(state)
+    }
+
+    pub fn open(store: AppStore) -> Result<Self> {
         store.init()?;
-        Ok(Self {
+        let _ = snapshots::adopt_legacy_snapshots(&store);
+        Ok(Self::with_store(store))
+    }
+
+    // 跳过初始化、迁移与旧快照接管；数据库不存在或版本落后时返回错误，由调用方退回 open。
+    pub fn open_read_only(store: AppStore) -> Result<Self> {
+        store.open_read_only()?;
+        Ok(Self::with_store(store))
+    }
+
+    fn with_store(store: AppStore) -> Self {
+        Self {
             store,
             vault: Mutex::new(VaultSession::new()),
-        })
+            device_logins: Mutex::new(HashMap::new()),
+            control_server: Mutex::new(ControlServerState::default()),
+            codex_binary: Mutex::new(None),
+            events: broadcast::channel(EVENT_CHANNEL_CAPACITY).0,
+        }
     }
 
     pub fn init_vault(&self, master_password: &str) -> Result<bool> {
@@ -102,4 +150,181 @@ impl AppState {
             .map_err(|_| anyhow!("保险库状态锁失败"))?
             .get_key()
     }
+
+    pub fn publish_event<T: Serialize>(&self, event: &str, payload: &T) {
+        let Ok(payload) = serde_json::to_value(payload) else {
+            return;
+        };
+        let _ = self.events.send(AppEvent {
+            event: event.to_string(),
+            payload,
+        });
+    }
+
+    pub fn subscribe_events(&self) -> broadcast::Receiver<AppEvent> {
+        self.events.subscribe()
+    }
+
+    pub fn verify_vault_key(&self) -> Result<()> {
+        let mut key = self.get_vault_key()?;
+        let secret = match self.store.list_accounts()?.first() {
+            Some(account) => self.store.get_account_secret(&account.id)?,
+            None => None,
+        };
+        let verified = secret
+            .map(|secret| crypto::decrypt_from_base64(&key, &secret.encrypted_auth_blob).is_ok())
+            .unwrap_or(true);
+        key.zeroize();
+        if !verified {
+            self.lock_vault()?;
+            return Err(anyhow!("主密码错误，无法解密账号数据"));
+        }
+        Ok(())
+    }
+
+    pub fn resolve_codex_binary(&self) -> Result<Option<CodexBinary>> {
+        if let Some(cached) = self.cached_codex_binary()? {
+            return Ok(cached);
+        }
+        let pinned = self.store.get_codex_binary_path()?;
+        self.cache_codex_binary(resolve_codex_binary(pinned.as_deref()))
+    }
+
+    // 异步路径上的探测会启动登录 shell 和 `codex --version`，放到阻塞线程池里执行。
+    pub async fn resolve_codex_binary_async(&self) -> Result<Option<CodexBinary>> {
+        if let Some(cached) = self.cached_codex_binary()? {
+            return Ok(cached);
+        }
+        let pinned = self.store.get_codex_binary_path()?;
+        let binary = tokio::task::spawn_blocking(move || resolve_codex_binary(pinned.as_deref()))
+            .await
+            .map_err(|error| anyhow!("等待 Codex CLI 探测任务失败: {error}"))?;
+        self.cache_codex_binary(binary)
+    }
+
+    pub fn resolve_codex_compat(&self) -> Result<(Option<CodexBinary>, CodexCompat)> {
+        let binary = self.resolve_codex_binary()?;
+        let compat = compat_for(binary.as_ref());
+        Ok((binary, compat))
+    }
+
+    pub async fn resolve_codex_compat_async(&self) -> Result<(Option<CodexBinary>, CodexCompat)> {
+        let binary = self.resolve_codex_binary_async().await?;
+        let compat = compat_for(binary.as_ref());
+        Ok((binary, compat))
+    }
+
+    pub fn invalidate_codex_binary(&self) -> Result<()> {
+        *self
+            .codex_binary
+            .lock()
+            .map_err(|_| anyhow!("Codex CLI 缓存锁失败"))? = None;
+        Ok(())
+    }
+
+    fn cached_codex_binary(&self) -> Result<Option<Option<CodexBinary>>> {
+        let cached = self
+            .codex_binary
+            .lock()
+            .map_err(|_| anyhow!("Codex CLI 缓存锁失败"))?
+            .clone();
+        // 缓存的可执行文件被卸载或升级替换后重新探测。
+        Ok(cached.filter(|binary| binary.as_ref().is_none_or(|binary| binary.path.is_file())))
+    }
+
+    fn cache_codex_binary(&self, binary: Option<CodexBinary>) -> Result<Option<CodexBinary>> {
+        *self
+            .codex_binary
+            .lock()
+            .map_err(|_| anyhow!("Codex CLI 缓存锁失败"))? = Some(binary.clone());
+        Ok(binary)
+    }
+
+    pub fn register_device_login(
+        &self,
+        flow: DeviceLoginFlow,
+        expires_at: DateTime<Utc>,
+    ) -> Result<String> {
+        self.expire_device_logins(Utc::now())?;
+        let session_id = Uuid::new_v4().to_string();
+        self.device_logins
+            .lock()
+            .map_err(|_| anyhow!("设备码登录状态锁失败"))?
+            .insert(
+                session_id.clone(),
+                DeviceLoginSession {
+                    cancelled: Arc::new(AtomicBool::new(false)),
+                    flow: Some(flow),
+                    expires_at,
+                },
+            );
+        Ok(session_id)
+    }
+
+    // 会话在锁外析构：CLI 流程的 Drop 会结束并回收子进程、删除临时 CODEX_HOME，较慢。
+    pub fn expire_device_logins(&self, now: DateTime<Utc>) -> Result<usize> {
+        let expired = {
+            let mut sessions = self
+                .device_logins
+                .lock()
+                .map_err(|_| anyhow!("设备码登录状态锁失败"))?;
+            let ids = sessions
+                .iter()
+                .filter(|(_, session)| session.expires_at <= now)
+                .map(|(id, _)| id.clone())
+                .collect::<Vec<_>>();
+            ids.iter()
+                .filter_map(|id| sessions.remove(id))
+                .collect::<Vec<_>>()
+        };
+        for session in &expired {
+            session.cancelled.store(true, Ordering::SeqCst);
+        }
+        Ok(expired.len())
+    }
+
+    pub fn begin_device_login(
+        &self,
+        session_id: &str,
+    ) -> Result<(DeviceLoginFlow, Arc<AtomicBool>)> {
+        let mut sessions = self
+            .device_logins
+            .lock()
+            .map_err(|_| anyhow!("设备码登录状态锁失败"))?;
+        let session = sessions
+            .get_mut(session_id)
+            .ok_or_else(|| anyhow!("设备码登录会话不存在或已结束"))?;
+        let flow = session
+            .flow
+            .take()
+            .ok_or_else(|| anyhow!("设备码登录会话正在等待授权"))?;
+        Ok((flow, session.cancelled.clone()))
+    }
+
+    pub fn finish_device_login(&self, session_id: &str) -> Result<()> {
+        self.device_logins
+            .lock()
+            .map_err(|_| anyhow!("设备码登录状态锁失败"))?
+            .remove(session_id);
+        Ok(())
+    }
+
+    pub fn cancel_device_login(&self, session_id: &str) -> Result<bool> {
+        let mut sessions = self
+            .device_logins
+            .lock()
+            .map_err(|_| anyhow!("设备码登录状态锁失败"))?;
+        let Some(session) = sessions.get_mut(session_id) else {
+            return Ok(false);
+        };
+        session.cancelled.store(true, Ordering::SeqCst);
+        if session.flow.take().is_some() {
+            sessions.remove(session_id);
+        }
+        Ok(true)
+    }
+}
+
+fn compat_for(binary: Option<&CodexBinary>) -> CodexCompat {
+    resolve_compat(binary.and_then(|binary| binary.version.as_deref()))
 }
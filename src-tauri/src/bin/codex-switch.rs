@@ -0,0 +1,3 @@
+fn main() -> std::process::ExitCode {
+    codex_switch_app_lib::cli::main()
+}
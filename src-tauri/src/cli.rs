@@ -0,0 +1,1172 @@
+use crate::{
+    account_import::{self, NameSource},
+    analytics::{self, UsageCsvTable, UsageGranularity, UsageQuery},
+    app_state::AppState,
+    codex::read_and_validate_auth_json,
+    failover::{self, FailoverConfig, RestartMode},
+    guards::{GuardAction, SwitchGuardPolicy},
+    health, homes, importers, integrity,
+    models::{
+        Account, BulkImportReport, PoolDecision, QuotaDashboardItem, QuotaSnapshot, SimpleStatus,
+        SwitchHistoryQuery,
+    },
+    pools::{self, AccountPoolInput, RotationStrategy},
+    service::{self, SwitchOptions},
+    shell::{self, ShellKind, ACCOUNT_ENV, DEFAULT_PROMPT_FORMAT},
+    snapshots,
+    store::AppStore,
+    vault_bundle::{self, ConflictStrategy, VaultExportFilter},
+};
+use anyhow::{anyhow, Context, Result};
+use chrono::Local;
+use clap::{Parser, Subcommand};
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::{
+    io::{self, Read},
+    path::PathBuf,
+    process::ExitCode,
+};
+use zeroize::Zeroize;
+
+const PASSWORD_ENV: &str = "CODEX_SWITCH_PASSWORD";
+const EXPORT_PASSPHRASE_ENV: &str = "CODEX_SWITCH_EXPORT_PASSPHRASE";
+
+#[derive(Debug, Parser)]
+#[command(name = "codex-switch", version, about = "Codex Switch 命令行工具")]
+struct Cli {
+    /// 以 JSON 输出结果
+    #[arg(long, global = true)]
+    json: bool,
+
+    /// 应用数据目录，默认与桌面端共用
+    #[arg(long, global = true, env = "CODEX_SWITCH_DATA_DIR")]
+    data_dir: Option<PathBuf>,
+
+    /// 从标准输入读取主密码（否则读取 CODEX_SWITCH_PASSWORD）
+    #[arg(long, global = true)]
+    password_stdin: bool,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// 校验主密码能否解锁保险库
+    Unlock,
+    /// 列出已保存的账号
+    List,
+    /// 切换到指定账号（ID 或名称）
+    Switch {
+        account: String,
+        /// 切换后重启正在运行的 Codex 进程
+        #[arg(long)]
+        restart: bool,
+        /// 忽略阻止级别的切换守卫（令牌过期、凭据异常、配额耗尽等）继续切换
+        #[arg(long)]
+        override_guards: bool,
+    },
+    /// 查看或设置切换守卫：每项可设为 block / warn / ignore
+    Guards {
+        #[arg(long, value_enum)]
+        token_expiry: Option<GuardAction>,
+        #[arg(long, value_enum)]
+        health: Option<GuardAction>,
+        #[arg(long, value_enum)]
+        quota: Option<GuardAction>,
+        #[arg(long, value_enum)]
+        running_processes: Option<GuardAction>,
+    },
+    /// 查看或设置配额耗尽时的自动切换；--check 立即检查一次当前账号
+    Failover {
+        #[arg(long, conflicts_with = "disable")]
+        enable: bool,
+        #[arg(long)]
+        disable: bool,
+        /// 剩余百分比不高于该值时也切换
+        #[arg(long)]
+        threshold: Option<f64>,
+        /// 后台探测间隔（秒）
+        #[arg(long)]
+        interval: Option<u64>,
+        /// 触发后的冷却时间（秒）
+        #[arg(long)]
+        cooldown: Option<u64>,
+        /// 优先切换到带有该标签的账号，可重复；设置后替换原有列表
+        #[arg(long = "prefer-tag")]
+        preferred_tags: Vec<String>,
+        /// 切换后是否重启运行中的 Codex，默认 never
+        #[arg(long, value_enum)]
+        restart_mode: Option<RestartMode>,
+        #[arg(long)]
+        check: bool,
+    },
+    /// 回滚到指定切换记录之前的 auth.json
+    Rollback { history_id: String },
+    /// 查看切换历史
+    History {
+        #[arg(long, default_value_t = 50)]
+        limit: usize,
+        /// 仅显示涉及该账号（名称或 ID）的记录
+        #[arg(long)]
+        account: Option<String>,
+        /// success / failed / rolled_back
+        #[arg(long)]
+        result: Option<String>,
+        /// 起始时间（RFC 3339，含）
+        #[arg(long)]
+        since: Option<String>,
+        /// 截止时间（RFC 3339，不含）
+        #[arg(long)]
+        until: Option<String>,
+        /// 上一页输出的分页游标
+        #[arg(long)]
+        cursor: Option<String>,
+    },
+    /// 按天或周统计各账号生效时长、切换次数与失败率
+    Usage {
+        #[arg(long, value_enum, default_value_t = UsageGranularity::Day)]
+        granularity: UsageGranularity,
+        /// 起始时间（RFC 3339），默认截止前 30 天
+        #[arg(long)]
+        since: Option<String>,
+        /// 截止时间（RFC 3339），默认当前时间
+        #[arg(long)]
+        until: Option<String>,
+        /// 以 CSV 输出指定表格
+        #[arg(long, value_enum)]
+        csv: Option<UsageCsvTable>,
+    },
+    /// 批量导入目录或通配符匹配的 auth.json 文件，例如 codex-switch import-files ~/backups/auth-*.json
+    ImportFiles {
+        source: String,
+        /// 为导入的账号添加标签，可重复
+        #[arg(long = "tag")]
+        tags: Vec<String>,
+        #[arg(long, value_enum, default_value_t = NameSource::Filename)]
+        name_from: NameSource,
+    },
+    /// 从其他切换工具的账号目录或索引导入（不指定 --importer 时自动识别）
+    ImportFrom {
+        source: PathBuf,
+        /// named_files / json_index
+        #[arg(long)]
+        importer: Option<String>,
+        /// 为导入的账号添加标签，可重复
+        #[arg(long = "tag")]
+        tags: Vec<String>,
+    },
+    /// 用新的 auth.json 更新已保存账号的凭据（须为同一身份），保留名称、标签与历史
+    Reauth {
+        account: String,
+        /// 重新登录后得到的 auth.json
+        file: PathBuf,
+    },
+    /// 将账号导出为以导出口令加密的文件（口令读取 CODEX_SWITCH_EXPORT_PASSPHRASE）
+    Export {
+        path: PathBuf,
+        /// 仅导出指定账号（名称或 ID），可重复
+        #[arg(long = "account")]
+        accounts: Vec<String>,
+        /// 仅导出带有该标签的账号，可重复
+        #[arg(long = "tag")]
+        tags: Vec<String>,
+    },
+    /// 从导出文件导入账号，按指纹合并（口令读取 CODEX_SWITCH_EXPORT_PASSPHRASE）
+    Import {
+        path: PathBuf,
+        #[arg(long, value_enum, default_value_t = ConflictStrategy::Skip)]
+        on_conflict: ConflictStrategy,
+    },
+    /// 配额查询
+    #[command(subcommand)]
+    Quota(QuotaCommand),
+    /// 账号池：按标签或显式成员分组，并按策略轮换
+    #[command(subcommand)]
+    Pool(PoolCommand),
+    /// 检查已保存账号的凭据：结构、令牌过期时间，以及（可选）远程校验是否被吊销
+    Health {
+        account: Option<String>,
+        /// 本次强制请求远程校验地址（默认按设置）
+        #[arg(long)]
+        remote: bool,
+    },
+    /// 输出运行诊断信息
+    Diagnostics,
+    /// 按保留策略清理切换快照
+    PruneSnapshots {
+        /// 仅列出将被清理的快照
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// 检查孤儿数据与快照文件
+    Integrity {
+        /// 修复发现的问题：清理孤儿记录与未引用的快照文件
+        #[arg(long)]
+        repair: bool,
+    },
+    /// 输出 Shell 集成脚本，例如 eval "$(codex-switch shell bash)"
+    Shell {
+        #[arg(value_enum)]
+        shell: ShellKind,
+    },
+    /// 输出提示符片段：当前终端账号（或全局当前账号）及最新配额状态
+    Prompt {
+        /// 可用占位符：{name} {state} {remaining}
+        #[arg(long, default_value = DEFAULT_PROMPT_FORMAT)]
+        format: String,
+    },
+    /// 输出让当前终端使用指定账号的环境变量（CODEX_HOME 指向该账号的独立目录）
+    Env {
+        account: Option<String>,
+        #[arg(long, value_enum, default_value_t = ShellKind::Bash)]
+        shell: ShellKind,
+        /// 输出清除这些环境变量的脚本
+        #[arg(long, conflicts_with = "account")]
+        reset: bool,
+    },
+    /// 使用指定账号的独立 CODEX_HOME 在当前终端运行 Codex，例如 codex-switch run work -- --model o3
+    Run {
+        account: String,
+        #[arg(last = true)]
+        args: Vec<String>,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum QuotaCommand {
+    /// 刷新配额（不指定账号时刷新全部）
+    Refresh {
+        account: Option<String>,
+        /// 忽略缓存强制刷新
+        #[arg(long)]
+        force: bool,
+    },
+    /// 查看各账号最新配额
+    Dashboard,
+}
+
+#[derive(Debug, Subcommand)]
+enum PoolCommand {
+    /// 列出账号池
+    List,
+    /// 新建或更新账号池（同名即更新，未指定的项保持不变）
+    Save {
+        name: String,
+        /// 带该标签的账号自动归入池中
+        #[arg(long)]
+        tag: Option<String>,
+        /// 显式成员（账号 ID 或名称），可重复，顺序即轮询顺序
+        #[arg(long = "account")]
+        accounts: Vec<String>,
+        #[arg(long, value_enum)]
+        strategy: Option<RotationStrategy>,
+    },
+    /// 删除账号池（不影响其中的账号）
+    Delete { pool: String },
+    /// 按池策略切换到下一个账号，并说明选择原因
+    Next {
+        pool: String,
+        /// 只显示将选择的账号，不切换
+        #[arg(long)]
+        dry_run: bool,
+        #[arg(long)]
+        restart: bool,
+        #[arg(long)]
+        override_guards: bool,
+    },
+}
+
+struct Output {
+    data: Value,
+    text: String,
+}
+
+impl Output {
+    fn new<T: Serialize>(data: &T, text: String) -> Result<Self> {
+        Ok(Self {
+            data: serde_json::to_value(data)?,
+            text,
+        })
+    }
+}
+
+pub fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let json_output = cli.json;
+    match run(cli) {
+        Ok(output) => {
+            if json_output {
+                println!("{}", output.data);
+            } else if !output.text.is_empty() {
+                println!("{}", output.text);
+            }
+            ExitCode::SUCCESS
+        }
+        Err(error) => {
+            if json_output {
+                println!("{}", json!({ "ok": false, "error": format!("{error:#}") }));
+            } else {
+                eprintln!("错误：{error:#}");
+            }
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(cli: Cli) -> Result<Output> {
+    let base_dir = cli
+        .data_dir
+        .clone()
+        .unwrap_or_else(service::default_base_dir);
+    // 提示符每次渲染都会调用，优先走只读路径，避免每次都初始化数据库。
+    let state = match cli.command {
+        Command::Prompt { .. } => AppState::open_read_only(AppStore::new(base_dir.clone()))
+            .or_else(|_| AppState::open(AppStore::new(base_dir)))?,
+        _ => AppState::open(AppStore::new(base_dir))?,
+    };
+
+    match cli.command {
+        Command::Unlock => {
+            unlock(&state, cli.password_stdin)?;
+            let status = SimpleStatus {
+                ok: true,
+                message: "主密码校验通过".to_string(),
+            };
+            let text = status.message.clone();
+            Output::new(&status, text)
+        }
+        Command::List => {
+            let accounts = state.store.list_accounts()?;
+            let current = state.store.get_current_account_id()?;
+            let text = accounts
+                .iter()
+                .map(|account| {
+                    let marker = if current.as_deref() == Some(account.id.as_str()) {
+                        "*"
+                    } else {
+                        " "
+                    };
+                    format!(
+                        "{marker} {}\t{}\t[{}]\t{}",
+                        account.name,
+                        account.id,
+                        account.tags.join(","),
+                        account.last_used_at.as_deref().unwrap_or("-")
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join("\n");
+            Output::new(
+                &json!({ "current_account_id": current, "accounts": accounts }),
+                text,
+            )
+        }
+        Command::Switch {
+            account,
+            restart,
+            override_guards,
+        } => {
+            unlock(&state, cli.password_stdin)?;
+            let accounts = state.store.list_accounts()?;
+            let account = find_account(&accounts, &account)?;
+            let result = service::switch_to_account(
+                &state,
+                &account.id,
+                SwitchOptions {
+                    force_restart: restart,
+                    override_guards,
+                    ..SwitchOptions::default()
+                },
+            )?;
+            if result.blocked {
+                return Err(anyhow!(
+                    "{}（可加 --override-guards 强制切换）",
+                    result.message
+                ));
+            }
+            let text = format!("{}（历史记录 {}）", result.message, result.history_id);
+            Output::new(&result, text)
+        }
+        Command::Guards {
+            token_expiry,
+            health,
+            quota,
+            running_processes,
+        } => {
+            let current = state.store.get_switch_guard_policy()?;
+            let policy = SwitchGuardPolicy {
+                token_expiry: token_expiry.unwrap_or(current.token_expiry),
+                health: health.unwrap_or(current.health),
+                quota: quota.unwrap_or(current.quota),
+                running_processes: running_processes.unwrap_or(current.running_processes),
+            };
+            if policy != current {
+                state.store.set_switch_guard_policy(&policy)?;
+            }
+            let text = format!(
+                "token_expiry\t{}\nhealth\t{}\nquota\t{}\nrunning_processes\t{}",
+                policy.token_expiry.as_str(),
+                policy.health.as_str(),
+                policy.quota.as_str(),
+                policy.running_processes.as_str()
+            );
+            Output::new(&policy, text)
+        }
+        Command::Failover {
+            enable,
+            disable,
+            threshold,
+            interval,
+            cooldown,
+            preferred_tags,
+            restart_mode,
+            check,
+        } => {
+            let current = state.store.get_failover_config()?;
+            let config = FailoverConfig {
+                enabled: if enable || disable {
+                    enable
+                } else {
+                    current.enabled
+                },
+                interval_seconds: interval.unwrap_or(current.interval_seconds),
+                threshold_percent: threshold.or(current.threshold_percent),
+                cooldown_seconds: cooldown.unwrap_or(current.cooldown_seconds),
+                preferred_tags: if preferred_tags.is_empty() {
+                    current.preferred_tags.clone()
+                } else {
+                    account_import::unique_tags(preferred_tags)
+                },
+                restart_mode: restart_mode.unwrap_or(current.restart_mode),
+            };
+            if config.interval_seconds < failover::MIN_FAILOVER_INTERVAL_SECONDS {
+                return Err(anyhow!(
+                    "探测间隔不能少于 {} 秒",
+                    failover::MIN_FAILOVER_INTERVAL_SECONDS
+                ));
+            }
+            if config
+                .threshold_percent
+                .is_some_and(|threshold| !(0.0..=100.0).contains(&threshold))
+            {
+                return Err(anyhow!("切换阈值必须在 0 到 100 之间"));
+            }
+            if config != current {
+                state.store.set_failover_config(&config)?;
+            }
+            if check {
+                unlock(&state, cli.password_stdin)?;
+                let runtime = tokio::runtime::Builder::new_current_thread()
+                    .enable_all()
+                    .build()
+                    .context("创建异步运行时失败")?;
+                let event = runtime.block_on(failover::check_and_failover(&state, &config))?;
+                let text = event
+                    .as_ref()
+                    .map(|event| event.message.clone())
+                    .unwrap_or_else(|| "当前账号未达到自动切换条件".to_string());
+                return Output::new(&event, text);
+            }
+            let text = format!(
+                "enabled\t{}\ninterval_seconds\t{}\nthreshold_percent\t{}\ncooldown_seconds\t{}\npreferred_tags\t{}\nrestart_mode\t{}",
+                config.enabled,
+                config.interval_seconds,
+                config
+                    .threshold_percent
+                    .map(|threshold| threshold.to_string())
+                    .unwrap_or_else(|| "-".to_string()),
+                config.cooldown_seconds,
+                config.preferred_tags.join(","),
+                config.restart_mode.as_str()
+            );
+            Output::new(&config, text)
+        }
+        Command::Rollback { history_id } => {
+            let status = service::rollback_to_history(&state, &history_id)?;
+            let text = status.message.clone();
+            Output::new(&status, text)
+        }
+        Command::History {
+            limit,
+            account,
+            result,
+            since,
+            until,
+            cursor,
+        } => {
+            // 回收站中的账号不在列表里，按名称找不到时直接当作账号 ID 筛选
+            let accounts = state.store.list_accounts()?;
+            let account_id = account.map(|key| {
+                find_account(&accounts, &key)
+                    .map(|account| account.id.clone())
+                    .unwrap_or(key)
+            });
+            let page = state.store.query_switch_history(&SwitchHistoryQuery {
+                account_id,
+                result,
+                since,
+                until,
+                cursor,
+                limit: Some(limit),
+            })?;
+            let name_of =
+                |id: Option<&str>, name: Option<&str>, trashed: bool, missing: &str| match (
+                    id, name,
+                ) {
+                    (Some(_), Some(name)) if trashed => format!("{name}（回收站）"),
+                    (Some(_), Some(name)) => name.to_string(),
+                    (Some(id), None) => id.to_string(),
+                    (None, _) => missing.to_string(),
+                };
+            let mut lines = page
+                .items
+                .iter()
+                .map(|entry| {
+                    let item = &entry.history;
+                    format!(
+                        "{}\t{}\t{} -> {}\t{}",
+                        item.created_at,
+                        item.result,
+                        name_of(
+                            item.from_account_id.as_deref(),
+                            entry.from_account_name.as_deref(),
+                            entry.from_account_trashed,
+                            "-"
+                        ),
+                        name_of(
+                            item.to_account_id.as_deref(),
+                            entry.to_account_name.as_deref(),
+                            entry.to_account_trashed,
+                            "已删除账号"
+                        ),
+                        item.id
+                    )
+                })
+                .collect::<Vec<_>>();
+            lines.push(match &page.next_cursor {
+                Some(cursor) => format!(
+                    "共 {} 条，已显示 {} 条；下一页：--cursor '{cursor}'",
+                    page.total,
+                    page.items.len()
+                ),
+                None => format!("共 {} 条", page.total),
+            });
+            let text = lines.join("\n");
+            Output::new(&page, text)
+        }
+        Command::Usage {
+            granularity,
+            since,
+            until,
+            csv,
+        } => {
+            let report = analytics::usage_report(
+                &state.store,
+                &UsageQuery {
+                    granularity,
+                    since,
+                    until,
+                    utc_offset_minutes: Some(Local::now().offset().local_minus_utc() / 60),
+                },
+            )?;
+            let text = match csv {
+                Some(table) => analytics::report_to_csv(&report, table)
+                    .trim_end()
+                    .to_string(),
+                None => {
+                    let mut lines = vec![format!(
+                        "{} ~ {}：切换 {} 次，失败 {} 次（{:.1}%），回滚 {} 次",
+                        report.since,
+                        report.until,
+                        report.total_switches,
+                        report.failed_switches,
+                        report.failure_rate * 100.0,
+                        report.rollbacks
+                    )];
+                    lines.extend(report.accounts.iter().map(|account| {
+                        format!(
+                            "{}\t生效 {:.1} 小时\t切入 {} 次\t失败 {} 次",
+                            account.account_name.as_deref().unwrap_or("已删除账号"),
+                            account.active_seconds as f64 / 3600.0,
+                            account.switches_in,
+                            account.failed_switches
+                        )
+                    }));
+                    lines.extend(report.top_pairs.iter().map(|pair| {
+                        format!(
+                            "{} -> {}\t{} 次",
+                            pair.from_account_name.as_deref().unwrap_or("-"),
+                            pair.to_account_name.as_deref().unwrap_or("已删除账号"),
+                            pair.count
+                        )
+                    }));
+                    lines.join("\n")
+                }
+            };
+            Output::new(&report, text)
+        }
+        Command::ImportFiles {
+            source,
+            tags,
+            name_from,
+        } => {
+            unlock(&state, cli.password_stdin)?;
+            let report = account_import::bulk_import_auth_files(&state, &source, tags, name_from)?;
+            bulk_import_output(&report)
+        }
+        Command::ImportFrom {
+            source,
+            importer,
+            tags,
+        } => {
+            unlock(&state, cli.password_stdin)?;
+            let report = importers::import_from(&state, importer.as_deref(), &source, tags)?;
+            bulk_import_output(&report)
+        }
+        Command::Reauth { account, file } => {
+            unlock(&state, cli.password_stdin)?;
+            let accounts = state.store.list_accounts()?;
+            let account = find_account(&accounts, &account)?;
+            let auth_json = read_and_validate_auth_json(&file)?;
+            let updated = account_import::reauthenticate_account(
+                &state,
+                &account.id,
+                auth_json,
+                &format!("file:{}", file.display()),
+            )?;
+            let text = format!("已更新账号 {} 的登录凭据", updated.name);
+            Output::new(&updated, text)
+        }
+        Command::Export {
+            path,
+            accounts: keys,
+            tags,
+        } => {
+            unlock(&state, cli.password_stdin)?;
+            let accounts = state.store.list_accounts()?;
+            let account_ids = keys
+                .iter()
+                .map(|key| find_account(&accounts, key).map(|account| account.id.clone()))
+                .collect::<Result<Vec<_>>>()?;
+            let mut passphrase = export_passphrase()?;
+            let result = vault_bundle::export_vault(
+                &state,
+                &path,
+                &passphrase,
+                &VaultExportFilter { account_ids, tags },
+            );
+            passphrase.zeroize();
+            let summary = result?;
+            let text = format!("已导出 {} 个账号到 {}", summary.account_count, summary.path);
+            Output::new(&summary, text)
+        }
+        Command::Import { path, on_conflict } => {
+            unlock(&state, cli.password_stdin)?;
+            let mut passphrase = export_passphrase()?;
+            let result = vault_bundle::import_vault(&state, &path, &passphrase, on_conflict);
+            passphrase.zeroize();
+            let report = result?;
+            let mut lines = report
+                .items
+                .iter()
+                .map(|item| {
+                    format!(
+                        "{}\t{}\t{}",
+                        item.action,
+                        item.name,
+                        item.message.as_deref().unwrap_or("")
+                    )
+                })
+                .collect::<Vec<_>>();
+            lines.push(format!(
+                "共 {} 个：导入 {}，覆盖 {}，重命名 {}，跳过 {}，失败 {}",
+                report.total,
+                report.imported,
+                report.overwritten,
+                report.renamed,
+                report.skipped,
+                report.failed
+            ));
+            let text = lines.join("\n");
+            Output::new(&report, text)
+        }
+        Command::Quota(QuotaCommand::Refresh { account, force }) => {
+            unlock(&state, cli.password_stdin)?;
+            let accounts = state.store.list_accounts()?;
+            let account_id = account
+                .as_deref()
+                .map(|key| find_account(&accounts, key).map(|account| account.id.clone()))
+                .transpose()?;
+            let runtime = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .context("创建异步运行时失败")?;
+            let snapshots =
+                runtime.block_on(service::refresh_quota(&state, account_id.as_deref(), force))?;
+            let text = format_snapshots(&accounts, &snapshots);
+            Output::new(&snapshots, text)
+        }
+        Command::Health { account, remote } => {
+            unlock(&state, cli.password_stdin)?;
+            let accounts = state.store.list_accounts()?;
+            let account_id = account
+                .as_deref()
+                .map(|key| find_account(&accounts, key).map(|account| account.id.clone()))
+                .transpose()?;
+            let runtime = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .context("创建异步运行时失败")?;
+            let results = runtime.block_on(health::check_accounts_health(
+                &state,
+                account_id.as_deref(),
+                remote.then_some(true),
+            ))?;
+            let text = results
+                .iter()
+                .map(|health| {
+                    let name = accounts
+                        .iter()
+                        .find(|account| account.id == health.account_id)
+                        .map(|account| account.name.as_str())
+                        .unwrap_or(health.account_id.as_str());
+                    format!(
+                        "{name}\t{}\t{}\t{}",
+                        health.status,
+                        health.expires_at.as_deref().unwrap_or("-"),
+                        health.reason.as_deref().unwrap_or("-")
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join("\n");
+            Output::new(&results, text)
+        }
+        Command::Pool(PoolCommand::List) => {
+            let pools = state.store.list_account_pools()?;
+            let accounts = state.store.list_accounts()?;
+            let text = pools
+                .iter()
+                .map(|pool| {
+                    let members = pool
+                        .account_ids
+                        .iter()
+                        .map(|id| {
+                            accounts
+                                .iter()
+                                .find(|account| &account.id == id)
+                                .map(|account| account.name.clone())
+                                .unwrap_or_else(|| id.clone())
+                        })
+                        .collect::<Vec<_>>()
+                        .join(",");
+                    format!(
+                        "{}\t{}\ttag={}\t[{}]",
+                        pool.name,
+                        pool.strategy,
+                        pool.tag.as_deref().unwrap_or("-"),
+                        members
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join("\n");
+            Output::new(&pools, text)
+        }
+        Command::Pool(PoolCommand::Save {
+            name,
+            tag,
+            accounts,
+            strategy,
+        }) => {
+            let existing = pools::find_pool(&state, &name).ok();
+            let known = state.store.list_accounts()?;
+            let account_ids = accounts
+                .iter()
+                .map(|key| find_account(&known, key).map(|account| account.id.clone()))
+                .collect::<Result<Vec<_>>>()?;
+            let input = AccountPoolInput {
+                id: existing.as_ref().map(|pool| pool.id.clone()),
+                name: existing
+                    .as_ref()
+                    .map(|pool| pool.name.clone())
+                    .unwrap_or(name),
+                tag: tag.or_else(|| existing.as_ref().and_then(|pool| pool.tag.clone())),
+                account_ids: if account_ids.is_empty() {
+                    existing
+                        .as_ref()
+                        .map(|pool| pool.account_ids.clone())
+                        .unwrap_or_default()
+                } else {
+                    account_ids
+                },
+                strategy: strategy
+                    .or_else(|| {
+                        existing
+                            .as_ref()
+                            .and_then(|pool| RotationStrategy::parse(&pool.strategy))
+                    })
+                    .unwrap_or_default(),
+            };
+            let pool = pools::save_pool(&state, &input)?;
+            let text = format!("账号池 {} 已保存（{}）", pool.name, pool.strategy);
+            Output::new(&pool, text)
+        }
+        Command::Pool(PoolCommand::Delete { pool }) => {
+            let pool = pools::find_pool(&state, &pool)?;
+            state.store.delete_account_pool(&pool.id)?;
+            let status = SimpleStatus {
+                ok: true,
+                message: format!("账号池 {} 已删除", pool.name),
+            };
+            let text = status.message.clone();
+            Output::new(&status, text)
+        }
+        Command::Pool(PoolCommand::Next {
+            pool,
+            dry_run,
+            restart,
+            override_guards,
+        }) => {
+            let pool = pools::find_pool(&state, &pool)?;
+            if dry_run {
+                let decision = pools::plan_rotation(&state, &pool)?;
+                let text = format_pool_decision(&decision);
+                return Output::new(&decision, text);
+            }
+            unlock(&state, cli.password_stdin)?;
+            let outcome = pools::switch_to_next_in_pool(
+                &state,
+                &pool.id,
+                SwitchOptions {
+                    force_restart: restart,
+                    override_guards,
+                    ..SwitchOptions::default()
+                },
+            )?;
+            if outcome.result.blocked {
+                return Err(anyhow!(
+                    "{}（可加 --override-guards 强制切换）",
+                    outcome.result.message
+                ));
+            }
+            let text = format!(
+                "{}\n{}",
+                format_pool_decision(&outcome.decision),
+                outcome.result.message
+            );
+            Output::new(&outcome, text)
+        }
+        Command::Quota(QuotaCommand::Dashboard) => {
+            let items = service::quota_dashboard(&state)?;
+            let text = format_dashboard(&items);
+            Output::new(&items, text)
+        }
+        Command::Shell { shell } => {
+            let exe = std::env::current_exe()
+                .map(|path| path.display().to_string())
+                .unwrap_or_else(|_| "codex-switch".to_string());
+            let script = shell::init_script(shell, &exe);
+            Output::new(&json!({ "script": script }), script.trim_end().to_string())
+        }
+        Command::Prompt { format } => {
+            let item = match std::env::var(ACCOUNT_ENV) {
+                Ok(account_id) if !account_id.trim().is_empty() => {
+                    service::account_with_quota(&state, account_id.trim())?
+                }
+                _ => service::active_account(&state)?,
+            };
+            let text = item
+                .as_ref()
+                .map(|item| shell::render_prompt(&format, item))
+                .unwrap_or_default();
+            Output::new(&item, text)
+        }
+        Command::Env {
+            account,
+            shell,
+            reset,
+        } => {
+            if reset {
+                let script = shell::env_script(shell, &[("CODEX_HOME", None), (ACCOUNT_ENV, None)]);
+                return Output::new(&json!({ "script": script }), script);
+            }
+            let key = account.ok_or_else(|| anyhow!("请指定账号，或使用 --reset 清除"))?;
+            let accounts = state.store.list_accounts()?;
+            let account = find_account(&accounts, &key)?;
+            let home = ensure_account_home(&state, &account.id, cli.password_stdin)?
+                .display()
+                .to_string();
+            let script = shell::env_script(
+                shell,
+                &[
+                    ("CODEX_HOME", Some(home.as_str())),
+                    (ACCOUNT_ENV, Some(account.id.as_str())),
+                ],
+            );
+            Output::new(
+                &json!({ "account": account, "codex_home": home, "script": script }),
+                script,
+            )
+        }
+        Command::Run { account, args } => {
+            let accounts = state.store.list_accounts()?;
+            let account = find_account(&accounts, &account)?;
+            let home = ensure_account_home(&state, &account.id, cli.password_stdin)?;
+            let binary = state.resolve_codex_binary()?;
+            let program = binary
+                .as_ref()
+                .map(|binary| binary.path.clone())
+                .unwrap_or_else(|| PathBuf::from("codex"));
+            let status = std::process::Command::new(&program)
+                .args(&args)
+                .env("CODEX_HOME", &home)
+                .env(ACCOUNT_ENV, &account.id)
+                .status()
+                .with_context(|| format!("启动 Codex CLI 失败: {}", program.display()))?;
+            if !status.success() {
+                return Err(anyhow!("Codex CLI 退出：{status}"));
+            }
+            Output::new(
+                &json!({ "account": account, "codex_home": home, "exit_code": status.code() }),
+                String::new(),
+            )
+        }
+        Command::Diagnostics => {
+            let diagnostics = service::runtime_diagnostics(&state)?;
+            let text = serde_json::to_value(&diagnostics)?
+                .as_object()
+                .map(|fields| {
+                    fields
+                        .iter()
+                        .map(|(key, value)| match value {
+                            Value::String(text) => format!("{key}: {text}"),
+                            Value::Null => format!("{key}: -"),
+                            other => format!("{key}: {other}"),
+                        })
+                        .collect::<Vec<_>>()
+                        .join("\n")
+                })
+                .unwrap_or_default();
+            Output::new(&diagnostics, text)
+        }
+        Command::PruneSnapshots { dry_run } => {
+            let report = snapshots::prune_snapshots(&state.store, dry_run)?;
+            let mut lines = report
+                .pruned
+                .iter()
+                .map(|item| {
+                    format!(
+                        "{}\t{}\t{}",
+                        item.created_at, item.reason, item.snapshot_path
+                    )
+                })
+                .collect::<Vec<_>>();
+            lines.push(format!(
+                "{} {} 个快照，保留 {} 个",
+                if dry_run { "将清理" } else { "已清理" },
+                report.pruned.len(),
+                report.kept
+            ));
+            Output::new(&report, lines.join("\n"))
+        }
+        Command::Integrity { repair } => {
+            let report = integrity::check_integrity(&state.store, repair)?;
+            let text = format!(
+                "孤儿配额快照: {}\n失效账号引用的历史: {}\n孤儿项目绑定: {}\n缺失的快照文件: {}\n未引用的快照文件: {}{}",
+                report.orphan_quota_snapshots,
+                report.orphan_history_refs,
+                report.orphan_project_bindings,
+                report.missing_snapshot_files,
+                report.unreferenced_snapshot_files,
+                if report.repaired { "\n已修复" } else { "" }
+            );
+            Output::new(&report, text)
+        }
+    }
+}
+
+fn unlock(state: &AppState, from_stdin: bool) -> Result<()> {
+    let mut password = if from_stdin {
+        let mut input = String::new();
+        io::stdin()
+            .read_to_string(&mut input)
+            .context("读取标准输入失败")?;
+        input
+    } else {
+        std::env::var(PASSWORD_ENV)
+            .map_err(|_| anyhow!("需要主密码：请设置 {PASSWORD_ENV} 或使用 --password-stdin"))?
+    };
+    let result = state
+        .unlock_vault(password.trim())
+        .and_then(|_| state.verify_vault_key());
+    password.zeroize();
+    result
+}
+
+fn bulk_import_output(report: &BulkImportReport) -> Result<Output> {
+    let mut lines = report
+        .items
+        .iter()
+        .map(|item| {
+            format!(
+                "{}\t{}\t{}\t{}",
+                item.action,
+                item.path,
+                item.name.as_deref().unwrap_or("-"),
+                item.message.as_deref().unwrap_or("")
+            )
+        })
+        .collect::<Vec<_>>();
+    lines.push(format!(
+        "共 {} 个文件：导入 {}，跳过 {}，失败 {}",
+        report.total, report.imported, report.skipped, report.failed
+    ));
+    let text = lines.join("\n");
+    Output::new(report, text)
+}
+
+fn export_passphrase() -> Result<String> {
+    std::env::var(EXPORT_PASSPHRASE_ENV)
+        .map_err(|_| anyhow!("需要导出口令：请设置 {EXPORT_PASSPHRASE_ENV}"))
+}
+
+fn ensure_account_home(state: &AppState, account_id: &str, from_stdin: bool) -> Result<PathBuf> {
+    if homes::materialized_auth_path(state, account_id).is_some() {
+        return Ok(homes::account_home_dir(state, account_id));
+    }
+    unlock(state, from_stdin)?;
+    homes::materialize_account_home(state, account_id)
+}
+
+fn find_account<'a>(accounts: &'a [Account], key: &str) -> Result<&'a Account> {
+    let key = key.trim();
+    if let Some(account) = accounts.iter().find(|account| account.id == key) {
+        return Ok(account);
+    }
+    let matched = accounts
+        .iter()
+        .filter(|account| account.name.eq_ignore_ascii_case(key))
+        .collect::<Vec<_>>();
+    match matched.as_slice() {
+        [account] => Ok(account),
+        [] => Err(anyhow!("未找到账号：{key}")),
+        _ => Err(anyhow!("存在多个名为 {key} 的账号，请改用账号 ID")),
+    }
+}
+
+fn format_quota_line(name: &str, snapshot: &QuotaSnapshot) -> String {
+    let remaining = snapshot
+        .remaining_value
+        .map(|value| {
+            format!(
+                "{value} {}",
+                snapshot.remaining_unit.as_deref().unwrap_or_default()
+            )
+        })
+        .unwrap_or_else(|| "-".to_string());
+    format!(
+        "{name}\t{}\t{}\t{}\t{}",
+        snapshot.quota_state,
+        remaining.trim_end(),
+        snapshot.reset_at.as_deref().unwrap_or("-"),
+        snapshot.source
+    )
+}
+
+fn format_snapshots(accounts: &[Account], snapshots: &[QuotaSnapshot]) -> String {
+    snapshots
+        .iter()
+        .map(|snapshot| {
+            let name = accounts
+                .iter()
+                .find(|account| account.id == snapshot.account_id)
+                .map(|account| account.name.as_str())
+                .unwrap_or(snapshot.account_id.as_str());
+            format_quota_line(name, snapshot)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn format_pool_decision(decision: &PoolDecision) -> String {
+    let mut lines = vec![format!(
+        "-> {}：{}",
+        decision.account_name, decision.explanation
+    )];
+    lines.extend(decision.members.iter().map(|member| {
+        let marker = if member.account_id == decision.account_id {
+            "*"
+        } else if member.eligible {
+            " "
+        } else {
+            "x"
+        };
+        format!("{marker} {}\t{}", member.account_name, member.note)
+    }));
+    lines.join("\n")
+}
+
+fn format_dashboard(items: &[QuotaDashboardItem]) -> String {
+    items
+        .iter()
+        .map(|item| match &item.snapshot {
+            Some(snapshot) => format_quota_line(&item.account.name, snapshot),
+            None => format!("{}\tunknown\t-\t-\t-", item.account.name),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{find_account, Cli, Command, QuotaCommand};
+    use crate::models::Account;
+    use clap::Parser;
+
+    fn account(id: &str, name: &str) -> Account {
+        Account {
+            id: id.to_string(),
+            name: name.to_string(),
+            tags: Vec::new(),
+            auth_fingerprint: format!("account:{id}"),
+            created_at: "2026-01-01T00:00:00Z".to_string(),
+            updated_at: "2026-01-01T00:00:00Z".to_string(),
+            last_used_at: None,
+        }
+    }
+
+    #[test]
+    fn parses_nested_quota_command_with_global_flags() {
+        let cli = Cli::try_parse_from([
+            "codex-switch",
+            "quota",
+            "refresh",
+            "work",
+            "--force",
+            "--json",
+        ])
+        .expect("应解析命令");
+        assert!(cli.json);
+        assert!(matches!(
+            cli.command,
+            Command::Quota(QuotaCommand::Refresh { account: Some(ref name), force: true }) if name == "work"
+        ));
+    }
+
+    #[test]
+    fn finds_account_by_id_then_unique_name() {
+        let accounts = vec![
+            account("a", "Work"),
+            account("b", "home"),
+            account("c", "HOME"),
+        ];
+        assert_eq!(find_account(&accounts, "a").expect("应命中 ID").id, "a");
+        assert_eq!(
+            find_account(&accounts, " work ").expect("应命中名称").id,
+            "a"
+        );
+        assert!(find_account(&accounts, "home").is_err());
+        assert!(find_account(&accounts, "missing").is_err());
+    }
+}
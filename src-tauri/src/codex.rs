@@ -1,21 +1,29 @@
+use crate::{compat::normalize_auth_json, discovery};
 use anyhow::{anyhow, Context, Result};
-use chrono::Utc;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use regex::Regex;
 use serde_json::Value;
 use sha2::{Digest, Sha256};
 use std::{
     collections::HashSet,
     ffi::OsString,
     fs,
-    io::{Read, Write},
+    io::{BufRead, BufReader, Read, Write},
     path::{Path, PathBuf},
     process::{Child, Command, Stdio},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver, RecvTimeoutError, Sender},
+    },
     time::{Duration, Instant},
 };
 use sysinfo::{
     get_current_pid, Pid, ProcessRefreshKind, ProcessesToUpdate, RefreshKind, Signal, System,
 };
+use uuid::Uuid;
 
 const CODEX_ENTRY_NAMES: [&str; 5] = ["codex", "codex.exe", "codex.cmd", "codex.ps1", "codex.bat"];
+const DEVICE_LOGIN_PROMPT_TIMEOUT_SECONDS: u64 = 20;
 
 #[derive(Clone, Debug)]
 struct CodexCommandTarget {
@@ -33,6 +41,7 @@ impl CodexCommandTarget {
         }
     }
 
+    #[cfg(target_os = "windows")]
     fn with_prefix_args(
         program: impl Into<PathBuf>,
         prefix_args: Vec<OsString>,
@@ -46,6 +55,112 @@ impl CodexCommandTarget {
     }
 }
 
+#[derive(Debug)]
+pub struct IsolatedCodexHome {
+    path: PathBuf,
+}
+
+impl IsolatedCodexHome {
+    pub fn create(parent: &Path) -> Result<Self> {
+        create_private_dir(parent)?;
+        let path = parent.join(format!("login-{}", Uuid::new_v4()));
+        create_private_dir(&path)?;
+        Ok(Self { path })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn auth_path(&self) -> PathBuf {
+        self.path.join("auth.json")
+    }
+}
+
+impl Drop for IsolatedCodexHome {
+    fn drop(&mut self) {
+        let _ = secure_remove_dir(&self.path);
+    }
+}
+
+pub fn create_private_dir(path: &Path) -> Result<()> {
+    fs::create_dir_all(path).with_context(|| format!("创建目录失败: {}", path.display()))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(path, fs::Permissions::from_mode(0o700))
+            .with_context(|| format!("设置目录权限失败: {}", path.display()))?;
+    }
+    Ok(())
+}
+
+// 符号链接只删除链接本身，不跟随，避免覆写或删除目录树之外的数据。
+pub fn secure_remove_dir(path: &Path) -> Result<()> {
+    let Ok(metadata) = fs::symlink_metadata(path) else {
+        return Ok(());
+    };
+    if metadata.file_type().is_symlink() {
+        return remove_symlink(path);
+    }
+    for entry in fs::read_dir(path).with_context(|| format!("读取目录失败: {}", path.display()))?
+    {
+        let entry_path = entry?.path();
+        let file_type = fs::symlink_metadata(&entry_path)
+            .with_context(|| format!("读取文件信息失败: {}", entry_path.display()))?
+            .file_type();
+        if file_type.is_symlink() {
+            remove_symlink(&entry_path)?;
+        } else if file_type.is_dir() {
+            secure_remove_dir(&entry_path)?;
+        } else {
+            secure_remove_file(&entry_path)?;
+        }
+    }
+    fs::remove_dir(path).with_context(|| format!("删除目录失败: {}", path.display()))?;
+    Ok(())
+}
+
+fn remove_symlink(path: &Path) -> Result<()> {
+    // Windows 上指向目录的链接需要用 remove_dir 删除
+    fs::remove_file(path)
+        .or_else(|_| fs::remove_dir(path))
+        .with_context(|| format!("删除链接失败: {}", path.display()))
+}
+
+pub fn secure_remove_file(path: &Path) -> Result<()> {
+    let metadata = fs::symlink_metadata(path)
+        .with_context(|| format!("读取文件信息失败: {}", path.display()))?;
+    if metadata.is_file() {
+        let mut file = fs::OpenOptions::new()
+            .write(true)
+            .open(path)
+            .with_context(|| format!("打开待清理文件失败: {}", path.display()))?;
+        file.write_all(&vec![0_u8; metadata.len() as usize])
+            .with_context(|| format!("覆写待清理文件失败: {}", path.display()))?;
+        file.sync_all()
+            .with_context(|| format!("同步待清理文件失败: {}", path.display()))?;
+    }
+    fs::remove_file(path).with_context(|| format!("删除文件失败: {}", path.display()))?;
+    Ok(())
+}
+
+pub fn clear_isolated_homes(parent: &Path) -> Result<()> {
+    if !parent.exists() {
+        return Ok(());
+    }
+    for entry in
+        fs::read_dir(parent).with_context(|| format!("读取目录失败: {}", parent.display()))?
+    {
+        let entry_path = entry?.path();
+        let is_dir =
+            fs::symlink_metadata(&entry_path).is_ok_and(|metadata| metadata.file_type().is_dir());
+        if is_dir {
+            secure_remove_dir(&entry_path)?;
+        }
+    }
+    Ok(())
+}
+
 pub fn codex_auth_path() -> Result<PathBuf> {
     let home = dirs::home_dir().ok_or_else(|| anyhow!("无法定位当前用户 Home 目录"))?;
     Ok(home.join(".codex").join("auth.json"))
@@ -59,6 +174,7 @@ pub fn read_and_validate_auth_json(path: &Path) -> Result<Value> {
 
 pub fn validate_auth_json(text: &str) -> Result<Value> {
     let value: Value = serde_json::from_str(text).context("认证文件 JSON 解析失败")?;
+    let value = normalize_auth_json(value);
     let auth_type = value
         .get("type")
         .and_then(Value::as_str)
@@ -79,6 +195,12 @@ pub fn validate_auth_json(text: &str) -> Result<Value> {
     Ok(value)
 }
 
+pub fn decode_jwt_claims(token: &str) -> Option<Value> {
+    let payload = token.split('.').nth(1)?;
+    let bytes = URL_SAFE_NO_PAD.decode(payload.trim_end_matches('=')).ok()?;
+    serde_json::from_slice(&bytes).ok()
+}
+
 pub fn compute_fingerprint(value: &Value) -> Result<String> {
     let (prefix, raw_seed) = if let Some(account_id) = value
         .get("account_id")
@@ -138,24 +260,6 @@ pub fn atomic_write(path: &Path, content: &str) -> Result<()> {
     Ok(())
 }
 
-pub fn create_snapshot(auth_path: &Path, snapshot_dir: &Path) -> Result<Option<PathBuf>> {
-    if !auth_path.exists() {
-        return Ok(None);
-    }
-    fs::create_dir_all(snapshot_dir)
-        .with_context(|| format!("创建快照目录失败: {}", snapshot_dir.display()))?;
-    let snapshot_name = format!("snapshot-{}.json", Utc::now().format("%Y%m%d%H%M%S%.3f"));
-    let snapshot_path = snapshot_dir.join(snapshot_name);
-    fs::copy(auth_path, &snapshot_path).with_context(|| {
-        format!(
-            "备份登录文件失败: {} -> {}",
-            auth_path.display(),
-            snapshot_path.display()
-        )
-    })?;
-    Ok(Some(snapshot_path))
-}
-
 fn refresh_processes() -> System {
     let refresh = RefreshKind::nothing().with_processes(ProcessRefreshKind::everything());
     let mut system = System::new_with_specifics(refresh);
@@ -276,6 +380,20 @@ pub fn count_codex_processes() -> usize {
     collect_codex_cli_pids(&system).len()
 }
 
+pub fn list_codex_process_cwds() -> Vec<(u32, Option<PathBuf>)> {
+    let system = refresh_processes();
+    collect_codex_cli_pids(&system)
+        .into_iter()
+        .map(|pid| {
+            let cwd = system
+                .process(pid)
+                .and_then(|process| process.cwd())
+                .map(Path::to_path_buf);
+            (pid.as_u32(), cwd)
+        })
+        .collect()
+}
+
 pub fn kill_codex_processes() -> usize {
     let system = refresh_processes();
     let target_pids = collect_codex_cli_pids(&system);
@@ -293,22 +411,53 @@ pub fn kill_codex_processes() -> usize {
     killed
 }
 
-pub fn restart_codex() -> Result<()> {
-    #[cfg(target_os = "windows")]
-    {
-        Command::new("cmd")
-            .args(["/C", "start", "", "codex"])
-            .spawn()
-            .context("重启 Codex CLI 失败")?;
+// 只结束指定的 Codex 进程；pid 已退出或被其他程序复用时不做任何操作。
+pub fn kill_codex_process(pid: u32) -> bool {
+    let system = refresh_processes();
+    let pid = Pid::from_u32(pid);
+    if !collect_codex_cli_pids(&system).contains(&pid) {
+        return false;
     }
+    system.process(pid).is_some_and(|process| {
+        process
+            .kill_with(Signal::Kill)
+            .unwrap_or_else(|| process.kill())
+    })
+}
+
+pub fn restart_codex(binary: Option<&Path>) -> Result<()> {
+    launch_codex(binary, None, None).context("重启 Codex CLI 失败")
+}
+
+pub fn launch_codex(
+    binary: Option<&Path>,
+    codex_home: Option<&Path>,
+    cwd: Option<&Path>,
+) -> Result<()> {
+    let program = binary.unwrap_or_else(|| Path::new("codex"));
+    #[cfg(target_os = "windows")]
+    let mut command = {
+        let mut command = Command::new("cmd");
+        command.args(["/C", "start", ""]).arg(program);
+        command
+    };
     #[cfg(not(target_os = "windows"))]
-    {
-        Command::new("sh")
+    let mut command = {
+        let mut command = Command::new("sh");
+        command
             .arg("-lc")
-            .arg("codex >/dev/null 2>&1 &")
-            .spawn()
-            .context("重启 Codex CLI 失败")?;
+            .arg("\"$0\" >/dev/null 2>&1 &")
+            .arg(program)
+            .env("PATH", discovery::child_path_env(program));
+        command
+    };
+    if let Some(codex_home) = codex_home {
+        command.env("CODEX_HOME", codex_home);
     }
+    if let Some(cwd) = cwd {
+        command.current_dir(cwd);
+    }
+    command.spawn().context("启动 Codex CLI 失败")?;
     Ok(())
 }
 
@@ -365,8 +514,20 @@ fn dedupe_command_targets(targets: Vec<CodexCommandTarget>) -> Vec<CodexCommandT
     deduped
 }
 
+fn collect_codex_login_targets(binary: Option<&Path>) -> Vec<CodexCommandTarget> {
+    let mut targets = Vec::new();
+    if let Some(binary) = binary {
+        targets.push(CodexCommandTarget::direct(
+            binary,
+            format!("codex ({})", binary.display()),
+        ));
+    }
+    targets.extend(platform_codex_login_targets());
+    dedupe_command_targets(targets)
+}
+
 #[cfg(target_os = "windows")]
-fn collect_codex_login_targets() -> Vec<CodexCommandTarget> {
+fn platform_codex_login_targets() -> Vec<CodexCommandTarget> {
     let mut targets = vec![
         CodexCommandTarget::direct("codex.cmd", "codex.cmd (PATH)"),
         CodexCommandTarget::direct("codex.exe", "codex.exe (PATH)"),
@@ -408,12 +569,20 @@ fn collect_codex_login_targets() -> Vec<CodexCommandTarget> {
         ));
     }
 
-    dedupe_command_targets(targets)
+    targets
 }
 
 #[cfg(not(target_os = "windows"))]
-fn collect_codex_login_targets() -> Vec<CodexCommandTarget> {
-    vec![CodexCommandTarget::direct("codex", "codex (PATH)")]
+fn platform_codex_login_targets() -> Vec<CodexCommandTarget> {
+    let mut targets = discovery::candidate_binaries(None)
+        .into_iter()
+        .map(|(path, source)| {
+            let display = format!("codex ({source}: {})", path.display());
+            CodexCommandTarget::direct(path, display)
+        })
+        .collect::<Vec<_>>();
+    targets.push(CodexCommandTarget::direct("codex", "codex (PATH)"));
+    targets
 }
 
 fn format_login_command(args: &[&str]) -> String {
@@ -423,11 +592,14 @@ fn format_login_command(args: &[&str]) -> String {
 fn spawn_codex_login_process(
     target: &CodexCommandTarget,
     args: &[&str],
+    codex_home: &Path,
 ) -> std::result::Result<Child, String> {
     let mut command = Command::new(&target.program);
     command
         .args(&target.prefix_args)
         .args(args)
+        .env("CODEX_HOME", codex_home)
+        .env("PATH", discovery::child_path_env(&target.program))
         .stdout(Stdio::null())
         .stderr(Stdio::piped());
     command.spawn().map_err(|error| format!("启动失败：{error}"))
@@ -467,33 +639,54 @@ fn wait_for_login_completion(
     }
 }
 
-pub fn run_codex_login(timeout_seconds: u64) -> Result<()> {
-    match run_codex_login_once(&["login", "--web"], timeout_seconds) {
-        Ok(()) => Ok(()),
-        Err(web_error) => {
-            if !is_web_login_unsupported(&web_error) {
-                return Err(anyhow!("`codex login --web` 执行失败：{web_error}"));
-            }
+pub fn run_codex_login(
+    timeout_seconds: u64,
+    codex_home: &Path,
+    binary: Option<&Path>,
+    login_args: &[&[&str]],
+) -> Result<()> {
+    let mut unsupported = Vec::new();
+    for (index, args) in login_args.iter().enumerate() {
+        let command_text = format_login_command(args);
+        let error = match run_codex_login_once(args, timeout_seconds, codex_home, binary) {
+            Ok(()) => return Ok(()),
+            Err(error) => error,
+        };
 
-            run_codex_login_once(&["login"], timeout_seconds).map_err(|fallback_error| {
-                anyhow!(
-                    "当前 Codex CLI 不支持 `--web`，已自动回退到 `codex login`，但仍失败：{fallback_error}"
-                )
-            })
+        let unsupported_flag = args
+            .iter()
+            .find(|arg| arg.starts_with("--") && is_login_flag_unsupported(&error, arg));
+        match unsupported_flag {
+            Some(flag) if index + 1 < login_args.len() => unsupported.push(*flag),
+            _ if unsupported.is_empty() => {
+                return Err(anyhow!("`{command_text}` 执行失败：{error}"));
+            }
+            _ => {
+                return Err(anyhow!(
+                    "当前 Codex CLI 不支持 `{}`，已自动回退到 `{command_text}`，但仍失败：{error}",
+                    unsupported.join("`、`")
+                ));
+            }
         }
     }
+    Err(anyhow!("兼容配置中没有可用的 Codex 登录参数"))
 }
 
-fn run_codex_login_once(args: &[&str], timeout_seconds: u64) -> std::result::Result<(), String> {
+fn run_codex_login_once(
+    args: &[&str],
+    timeout_seconds: u64,
+    codex_home: &Path,
+    binary: Option<&Path>,
+) -> std::result::Result<(), String> {
     let command_text = format_login_command(args);
-    let targets = collect_codex_login_targets();
+    let targets = collect_codex_login_targets(binary);
     if targets.is_empty() {
         return Err(format!("`{command_text}` 执行失败：未找到可用的 Codex CLI 启动入口"));
     }
 
     let mut attempts = Vec::new();
     for target in targets {
-        let mut child = match spawn_codex_login_process(&target, args) {
+        let mut child = match spawn_codex_login_process(&target, args, codex_home) {
             Ok(child) => child,
             Err(error) => {
                 attempts.push(format!("{} => {}", target.display, error));
@@ -513,6 +706,199 @@ fn run_codex_login_once(args: &[&str], timeout_seconds: u64) -> std::result::Res
     ))
 }
 
+#[derive(Debug)]
+pub struct CodexDeviceLogin {
+    child: Child,
+    output: Receiver<String>,
+    display: String,
+    command_text: String,
+    pub verification_uri: String,
+    pub user_code: String,
+}
+
+impl Drop for CodexDeviceLogin {
+    fn drop(&mut self) {
+        if let Ok(None) = self.child.try_wait() {
+            let _ = self.child.kill();
+            let _ = self.child.wait();
+        }
+    }
+}
+
+pub fn start_codex_device_login(
+    codex_home: &Path,
+    binary: Option<&Path>,
+    args: &[&str],
+) -> std::result::Result<CodexDeviceLogin, String> {
+    let command_text = format_login_command(args);
+    let targets = collect_codex_login_targets(binary);
+    if targets.is_empty() {
+        return Err(format!(
+            "`{command_text}` 执行失败：未找到可用的 Codex CLI 启动入口"
+        ));
+    }
+
+    let mut attempts = Vec::new();
+    for target in targets {
+        match start_device_login_with_target(&target, args, &command_text, codex_home) {
+            Ok(login) => return Ok(login),
+            Err(error) => attempts.push(format!("{} => {}", target.display, error)),
+        }
+    }
+
+    Err(format!(
+        "`{command_text}` 执行失败，已尝试路径：{}",
+        attempts.join(" | ")
+    ))
+}
+
+fn start_device_login_with_target(
+    target: &CodexCommandTarget,
+    args: &[&str],
+    command_text: &str,
+    codex_home: &Path,
+) -> std::result::Result<CodexDeviceLogin, String> {
+    let mut command = Command::new(&target.program);
+    command
+        .args(&target.prefix_args)
+        .args(args)
+        .env("CODEX_HOME", codex_home)
+        .env("PATH", discovery::child_path_env(&target.program))
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    let mut child = command
+        .spawn()
+        .map_err(|error| format!("启动失败：{error}"))?;
+
+    let (sender, receiver) = mpsc::channel();
+    if let Some(stdout) = child.stdout.take() {
+        forward_output_lines(stdout, sender.clone());
+    }
+    if let Some(stderr) = child.stderr.take() {
+        forward_output_lines(stderr, sender);
+    }
+
+    let mut login = CodexDeviceLogin {
+        child,
+        output: receiver,
+        display: target.display.clone(),
+        command_text: command_text.to_string(),
+        verification_uri: String::new(),
+        user_code: String::new(),
+    };
+    let deadline = Instant::now() + Duration::from_secs(DEVICE_LOGIN_PROMPT_TIMEOUT_SECONDS);
+    let mut collected = String::new();
+    loop {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        match login.output.recv_timeout(remaining) {
+            Ok(line) => {
+                collected.push_str(&line);
+                collected.push('\n');
+                if let Some((verification_uri, user_code)) = parse_device_login_prompt(&collected) {
+                    login.verification_uri = verification_uri;
+                    login.user_code = user_code;
+                    return Ok(login);
+                }
+            }
+            Err(RecvTimeoutError::Timeout) => {
+                return Err(format!(
+                    "`{command_text}` 在 {DEVICE_LOGIN_PROMPT_TIMEOUT_SECONDS}s 内未输出设备码"
+                ));
+            }
+            Err(RecvTimeoutError::Disconnected) => {
+                let _ = login.child.wait();
+                let output = truncate_for_error(collected.trim(), 400);
+                if let Some(flag) = args
+                    .iter()
+                    .find(|arg| arg.starts_with("--") && is_login_flag_unsupported(&output, arg))
+                {
+                    return Err(format!("当前 Codex CLI 不支持 `{flag}`：{output}"));
+                }
+                return Err(if output.is_empty() {
+                    format!("`{command_text}` 未输出设备码即退出")
+                } else {
+                    format!("`{command_text}` 未输出设备码即退出（输出：{output}）")
+                });
+            }
+        }
+    }
+}
+
+pub fn wait_for_codex_device_login(
+    login: &mut CodexDeviceLogin,
+    timeout_seconds: u64,
+    cancelled: &AtomicBool,
+) -> Result<()> {
+    let command_text = login.command_text.clone();
+    let started_at = Instant::now();
+    loop {
+        if cancelled.load(Ordering::SeqCst) {
+            let _ = login.child.kill();
+            let _ = login.child.wait();
+            return Err(anyhow!("设备码登录已取消"));
+        }
+
+        if let Some(status) = login
+            .child
+            .try_wait()
+            .with_context(|| format!("等待 `{command_text}` 进程失败"))?
+        {
+            if status.success() {
+                return Ok(());
+            }
+            let output = login.output.try_iter().collect::<Vec<_>>().join(" ");
+            let output = truncate_for_error(output.trim(), 400);
+            return Err(if output.is_empty() {
+                anyhow!(
+                    "{} => `{command_text}` 未成功完成（退出码：{status}）",
+                    login.display
+                )
+            } else {
+                anyhow!(
+                    "{} => `{command_text}` 未成功完成（退出码：{status}，输出：{output}）",
+                    login.display
+                )
+            });
+        }
+
+        if started_at.elapsed() > Duration::from_secs(timeout_seconds) {
+            let _ = login.child.kill();
+            let _ = login.child.wait();
+            return Err(anyhow!(
+                "`{command_text}` 登录超时（{timeout_seconds}s），请在浏览器输入设备码后重试。"
+            ));
+        }
+        std::thread::sleep(Duration::from_millis(500));
+    }
+}
+
+fn forward_output_lines<R: Read + Send + 'static>(stream: R, sender: Sender<String>) {
+    std::thread::spawn(move || {
+        for line in BufReader::new(stream).lines() {
+            let Ok(line) = line else {
+                break;
+            };
+            let _ = sender.send(line);
+        }
+    });
+}
+
+fn parse_device_login_prompt(text: &str) -> Option<(String, String)> {
+    let ansi = Regex::new(r"\x1b\[[0-9;?]*[A-Za-z]").ok()?;
+    let cleaned = ansi.replace_all(text, "");
+    let url_regex = Regex::new(r#"https?://[^\s'"<>]+"#).ok()?;
+    let url = url_regex
+        .find(&cleaned)?
+        .as_str()
+        .trim_end_matches(['.', ',', ')'])
+        .to_string();
+    let without_urls = url_regex.replace_all(&cleaned, " ");
+    let code_regex = Regex::new(r"\b[A-Z0-9]{4,}(?:-[A-Z0-9]{4,})+\b").ok()?;
+    let code = code_regex.find(&without_urls)?.as_str().to_string();
+    Some((url, code))
+}
+
 fn capture_child_output(child: &mut Child) -> String {
     let stderr = read_pipe_to_string(&mut child.stderr);
     truncate_for_error(stderr.trim(), 400)
@@ -528,31 +914,79 @@ fn read_pipe_to_string<R: Read>(pipe: &mut Option<R>) -> String {
 }
 
 fn truncate_for_error(text: &str, max_len: usize) -> String {
-    let cleaned = text.replace('\n', " ").replace('\r', " ").trim().to_string();
+    let cleaned = text.replace(['\n', '\r'], " ").trim().to_string();
     if cleaned.chars().count() <= max_len {
         return cleaned;
     }
     cleaned.chars().take(max_len).collect::<String>() + "..."
 }
 
-fn is_web_login_unsupported(message: &str) -> bool {
+fn is_login_flag_unsupported(message: &str, flag: &str) -> bool {
     let lower = message.to_lowercase();
-    let has_web_flag = lower.contains("--web");
+    let has_flag = lower.contains(flag);
     let unsupported = lower.contains("unexpected argument")
         || lower.contains("wasn't expected")
         || lower.contains("unknown option")
         || lower.contains("unrecognized option")
         || lower.contains("no such option");
-    has_web_flag && unsupported
+    has_flag && unsupported
 }
 
 #[cfg(test)]
 mod tests {
     use super::{
-        compute_fingerprint, is_codex_cli_process_fields, is_web_login_unsupported,
-        validate_auth_json,
+        compute_fingerprint, decode_jwt_claims, is_codex_cli_process_fields,
+        is_login_flag_unsupported, parse_device_login_prompt, secure_remove_dir,
+        validate_auth_json, IsolatedCodexHome,
     };
     use serde_json::json;
+    use std::fs;
+
+    #[test]
+    fn isolated_codex_home_is_removed_on_drop() {
+        let parent =
+            std::env::temp_dir().join(format!("codex-switch-test-{}", uuid::Uuid::new_v4()));
+        let home = IsolatedCodexHome::create(&parent).expect("应创建临时目录");
+        let home_path = home.path().to_path_buf();
+        fs::write(
+            home.auth_path(),
+            r#"{"type":"codex","access_token":"secret"}"#,
+        )
+        .expect("应写入 auth.json");
+        fs::create_dir_all(home_path.join("sessions")).expect("应创建子目录");
+        fs::write(home_path.join("sessions").join("log.jsonl"), "{}").expect("应写入子文件");
+
+        drop(home);
+
+        assert!(!home_path.exists());
+        fs::remove_dir_all(&parent).ok();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn secure_remove_dir_does_not_follow_symlinks() {
+        let root = std::env::temp_dir().join(format!("codex-switch-test-{}", uuid::Uuid::new_v4()));
+        let outside = root.join("outside");
+        fs::create_dir_all(outside.join("nested")).expect("应创建目录");
+        fs::write(outside.join("config.toml"), "model = \"o3\"").expect("应写入");
+        fs::write(outside.join("nested").join("keep.txt"), "keep").expect("应写入");
+        let home = root.join("home");
+        fs::create_dir_all(&home).expect("应创建目录");
+        std::os::unix::fs::symlink(outside.join("config.toml"), home.join("config.toml"))
+            .expect("应创建文件链接");
+        std::os::unix::fs::symlink(outside.join("nested"), home.join("nested"))
+            .expect("应创建目录链接");
+
+        secure_remove_dir(&home).expect("应删除目录");
+
+        assert!(!home.exists());
+        assert_eq!(
+            fs::read_to_string(outside.join("config.toml")).expect("应保留"),
+            "model = \"o3\""
+        );
+        assert!(outside.join("nested").join("keep.txt").is_file());
+        fs::remove_dir_all(&root).ok();
+    }
 
     #[test]
     fn detects_real_codex_cli_process() {
@@ -593,11 +1027,53 @@ mod tests {
 
     #[test]
     fn detects_web_flag_not_supported_error() {
-        assert!(is_web_login_unsupported(
-            "error: unexpected argument '--web' found"
+        assert!(is_login_flag_unsupported(
+            "error: unexpected argument '--web' found",
+            "--web"
+        ));
+    }
+
+    #[test]
+    fn detects_device_auth_flag_not_supported_error() {
+        assert!(is_login_flag_unsupported(
+            "error: unexpected argument '--device-auth' found",
+            "--device-auth"
+        ));
+        assert!(!is_login_flag_unsupported(
+            "error: unexpected argument '--web' found",
+            "--device-auth"
         ));
     }
 
+    #[test]
+    fn parses_device_login_prompt_with_ansi_colors() {
+        let output = "Follow these steps to sign in with ChatGPT using device code authorization:\n\n\
+            1. Open this link in your browser and sign in to your account\n   \u{1b}[94mhttps://auth.openai.com/codex/device\u{1b}[0m\n\n\
+            2. Enter this one-time code \u{1b}[90m(expires in 15 minutes)\u{1b}[0m\n   \u{1b}[94mABCD-12345\u{1b}[0m\n";
+        let (url, code) = parse_device_login_prompt(output).expect("应解析出设备码");
+        assert_eq!(url, "https://auth.openai.com/codex/device");
+        assert_eq!(code, "ABCD-12345");
+    }
+
+    #[test]
+    fn waits_for_device_code_before_reporting_prompt() {
+        let partial =
+            "1. Open this link in your browser\n   https://auth.openai.com/codex/device\n";
+        assert!(parse_device_login_prompt(partial).is_none());
+    }
+
+    #[test]
+    fn decodes_jwt_claims_without_padding() {
+        let payload = base64::Engine::encode(
+            &base64::engine::general_purpose::URL_SAFE_NO_PAD,
+            r#"{"email":"alice@example.com"}"#,
+        );
+        let token = format!("header.{payload}.signature");
+        let claims = decode_jwt_claims(&token).expect("应解析 JWT");
+        assert_eq!(claims["email"], "alice@example.com");
+        assert!(decode_jwt_claims("not-a-jwt").is_none());
+    }
+
     #[test]
     fn validates_codex_auth_json() {
         let text = r#"{
@@ -608,6 +1084,19 @@ mod tests {
         assert!(validate_auth_json(text).is_ok());
     }
 
+    #[test]
+    fn accepts_nested_token_layout() {
+        let text = r#"{
+            "OPENAI_API_KEY": null,
+            "tokens": { "access_token": "token-123", "account_id": "acc-1" }
+        }"#;
+        let value = validate_auth_json(text).expect("应兼容 tokens 嵌套格式");
+        assert_eq!(value["access_token"], "token-123");
+        assert!(compute_fingerprint(&value)
+            .expect("应生成指纹")
+            .starts_with("account:"));
+    }
+
     #[test]
     fn rejects_auth_json_without_type() {
         let text = r#"{
@@ -0,0 +1,277 @@
+use crate::codex::decode_jwt_claims;
+use serde_json::{Map, Value};
+
+const CODEX_ORIGINATOR: &str = "codex_cli_rs";
+const CODEX_OPENAI_BETA: &str = "responses=experimental";
+const NESTED_TOKEN_FIELDS: [&str; 4] = ["access_token", "account_id", "refresh_token", "id_token"];
+
+type Version = (u64, u64, u64);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuthLayout {
+    Flat,
+    Nested,
+}
+
+#[derive(Debug)]
+pub struct CompatProfile {
+    pub name: &'static str,
+    pub min_version: Version,
+    pub max_version: Version,
+    pub client_version: &'static str,
+    pub openai_beta: &'static str,
+    pub auth_layout: AuthLayout,
+    pub login_args: &'static [&'static [&'static str]],
+    pub device_login_args: Option<&'static [&'static str]>,
+}
+
+// 按版本升序排列，max_version 为开区间；新版本 CLI 验证通过后在末尾追加条目。
+static COMPAT_TABLE: [CompatProfile; 2] = [
+    CompatProfile {
+        name: "codex-0.1-0.29",
+        min_version: (0, 1, 0),
+        max_version: (0, 30, 0),
+        client_version: "0.29.0",
+        openai_beta: CODEX_OPENAI_BETA,
+        auth_layout: AuthLayout::Nested,
+        login_args: &[&["login"]],
+        device_login_args: None,
+    },
+    CompatProfile {
+        name: "codex-0.30-0.98",
+        min_version: (0, 30, 0),
+        max_version: (0, 99, 0),
+        client_version: "0.98.0",
+        openai_beta: CODEX_OPENAI_BETA,
+        auth_layout: AuthLayout::Flat,
+        login_args: &[&["login", "--web"], &["login"]],
+        device_login_args: Some(&["login", "--device-auth"]),
+    },
+];
+
+#[derive(Debug, Clone)]
+pub struct CodexCompat {
+    pub profile: &'static CompatProfile,
+    pub client_version: String,
+    pub warning: Option<String>,
+}
+
+impl CodexCompat {
+    pub fn user_agent(&self) -> String {
+        format!(
+            "{CODEX_ORIGINATOR}/{} ({}; {}) codex-switch",
+            self.client_version,
+            platform_label(),
+            std::env::consts::ARCH
+        )
+    }
+
+    pub fn originator(&self) -> &'static str {
+        CODEX_ORIGINATOR
+    }
+}
+
+pub fn resolve_compat(version: Option<&str>) -> CodexCompat {
+    let newest = &COMPAT_TABLE[COMPAT_TABLE.len() - 1];
+    let Some((raw, parsed)) = version.and_then(|raw| Some((raw, parse_version(raw)?))) else {
+        return CodexCompat {
+            profile: newest,
+            client_version: newest.client_version.to_string(),
+            warning: version.map(|raw| {
+                format!(
+                    "无法识别 Codex CLI 版本号 {raw}，已按 {} 兼容配置处理",
+                    newest.name
+                )
+            }),
+        };
+    };
+
+    if let Some(profile) = COMPAT_TABLE
+        .iter()
+        .find(|profile| parsed >= profile.min_version && parsed < profile.max_version)
+    {
+        return CodexCompat {
+            profile,
+            client_version: raw.to_string(),
+            warning: None,
+        };
+    }
+
+    let oldest = &COMPAT_TABLE[0];
+    let (profile, warning) = if parsed < oldest.min_version {
+        (
+            oldest,
+            format!(
+                "Codex CLI {raw} 低于已知最低兼容版本 {}，建议升级",
+                format_version(oldest.min_version)
+            ),
+        )
+    } else {
+        (
+            newest,
+            format!(
+                "Codex CLI {raw} 高于兼容表中的最新版本（< {}），已按 {} 处理；如遇登录或配额异常请更新 Codex Switch",
+                format_version(newest.max_version),
+                newest.name
+            ),
+        )
+    };
+    CodexCompat {
+        profile,
+        client_version: raw.to_string(),
+        warning: Some(warning),
+    }
+}
+
+pub fn auth_text_field<'a>(value: &'a Value, key: &str) -> Option<&'a str> {
+    value
+        .get(key)
+        .or_else(|| value.get("tokens").and_then(|tokens| tokens.get(key)))
+        .and_then(Value::as_str)
+        .map(str::trim)
+        .filter(|field| !field.is_empty())
+}
+
+pub fn normalize_auth_json(mut value: Value) -> Value {
+    let Some(tokens) = value.get("tokens").and_then(Value::as_object).cloned() else {
+        return value;
+    };
+    let Some(map) = value.as_object_mut() else {
+        return value;
+    };
+
+    for key in NESTED_TOKEN_FIELDS {
+        let missing = map
+            .get(key)
+            .and_then(Value::as_str)
+            .map(|field| field.trim().is_empty())
+            .unwrap_or(true);
+        if let Some(field) = tokens.get(key).and_then(Value::as_str).filter(|_| missing) {
+            map.insert(key.to_string(), Value::String(field.to_string()));
+        }
+    }
+
+    if !map.contains_key("email") {
+        let email = map
+            .get("id_token")
+            .and_then(Value::as_str)
+            .and_then(decode_jwt_claims)
+            .and_then(|claims| {
+                claims
+                    .get("email")
+                    .and_then(Value::as_str)
+                    .map(ToString::to_string)
+            });
+        if let Some(email) = email {
+            map.insert("email".to_string(), Value::String(email));
+        }
+    }
+
+    if !map.contains_key("type") && map.contains_key("access_token") {
+        map.insert("type".to_string(), Value::String("codex".to_string()));
+    }
+    value
+}
+
+pub fn render_auth_json(value: &Value, layout: AuthLayout) -> Value {
+    let mut rendered = value.clone();
+    if layout == AuthLayout::Flat {
+        return rendered;
+    }
+    let Some(map) = rendered.as_object_mut() else {
+        return rendered;
+    };
+
+    let mut tokens = map
+        .get("tokens")
+        .and_then(Value::as_object)
+        .cloned()
+        .unwrap_or_else(Map::new);
+    for key in NESTED_TOKEN_FIELDS {
+        if tokens.contains_key(key) {
+            continue;
+        }
+        if let Some(field) = map.get(key).and_then(Value::as_str) {
+            tokens.insert(key.to_string(), Value::String(field.to_string()));
+        }
+    }
+    map.insert("tokens".to_string(), Value::Object(tokens));
+    rendered
+}
+
+fn parse_version(raw: &str) -> Option<Version> {
+    let core = raw.trim().trim_start_matches('v');
+    let core = core.split(['-', '+']).next()?;
+    let mut parts = core.split('.').map(|part| part.parse::<u64>().ok());
+    let major = parts.next()??;
+    let minor = parts.next().unwrap_or(Some(0))?;
+    let patch = parts.next().unwrap_or(Some(0))?;
+    Some((major, minor, patch))
+}
+
+fn format_version((major, minor, patch): Version) -> String {
+    format!("{major}.{minor}.{patch}")
+}
+
+fn platform_label() -> &'static str {
+    match std::env::consts::OS {
+        "windows" => "Windows NT 10.0",
+        "macos" => "Mac OS",
+        "linux" => "Linux",
+        other => other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{normalize_auth_json, render_auth_json, resolve_compat, AuthLayout};
+    use serde_json::json;
+
+    #[test]
+    fn selects_profile_by_installed_version() {
+        let compat = resolve_compat(Some("0.98.0"));
+        assert_eq!(compat.profile.name, "codex-0.30-0.98");
+        assert_eq!(compat.client_version, "0.98.0");
+        assert!(compat.warning.is_none());
+        assert!(compat.user_agent().starts_with("codex_cli_rs/0.98.0 ("));
+
+        let legacy = resolve_compat(Some("0.12.3-alpha.1"));
+        assert_eq!(legacy.profile.auth_layout, AuthLayout::Nested);
+        assert!(legacy.profile.device_login_args.is_none());
+    }
+
+    #[test]
+    fn warns_when_cli_is_newer_than_table() {
+        let compat = resolve_compat(Some("1.2.0"));
+        assert_eq!(compat.profile.name, "codex-0.30-0.98");
+        assert_eq!(compat.client_version, "1.2.0");
+        assert!(compat.warning.expect("应提示版本过新").contains("1.2.0"));
+
+        let unknown = resolve_compat(None);
+        assert_eq!(unknown.client_version, "0.98.0");
+        assert!(unknown.warning.is_none());
+    }
+
+    #[test]
+    fn converts_between_nested_and_flat_layouts() {
+        let nested = json!({
+            "OPENAI_API_KEY": null,
+            "tokens": {
+                "access_token": "token-a",
+                "account_id": "account-a",
+                "refresh_token": "refresh-a"
+            }
+        });
+        let flat = normalize_auth_json(nested);
+        assert_eq!(flat["type"], "codex");
+        assert_eq!(flat["access_token"], "token-a");
+        assert_eq!(flat["account_id"], "account-a");
+
+        let rendered = render_auth_json(
+            &json!({ "type": "codex", "access_token": "token-b", "account_id": "account-b" }),
+            AuthLayout::Nested,
+        );
+        assert_eq!(rendered["tokens"]["access_token"], "token-b");
+        assert_eq!(rendered["access_token"], "token-b");
+    }
+}
@@ -6,11 +6,26 @@ use chacha20poly1305::{
     XChaCha20Poly1305, XNonce,
 };
 use rand::{rngs::OsRng, RngCore};
+use sha2::{Digest, Sha256};
 
 pub fn generate_salt() -> String {
     SaltString::generate(&mut OsRng).as_str().to_string()
 }
 
+pub fn generate_token() -> String {
+    let mut bytes = [0_u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    hex::encode(bytes)
+}
+
+pub fn hash_token(token: &str) -> String {
+    sha256_hex(token.trim().as_bytes())
+}
+
+pub fn sha256_hex(data: &[u8]) -> String {
+    hex::encode(Sha256::digest(data))
+}
+
 pub fn derive_key(master_password: &str, salt: &str) -> Result<Vec<u8>> {
     let salt = SaltString::from_b64(salt).context("主密码盐值格式不正确")?;
     let mut key = vec![0_u8; 32];
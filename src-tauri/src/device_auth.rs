@@ -0,0 +1,350 @@
+use crate::{
+    app_state::AppState,
+    codex::{decode_jwt_claims, CodexDeviceLogin, IsolatedCodexHome},
+    models::DeviceAuthConfig,
+};
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Utc};
+use reqwest::{Client, StatusCode};
+use serde_json::{Map, Value};
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread,
+    time::Duration,
+};
+use tauri::{AppHandle, Manager};
+
+const DEVICE_CODE_GRANT_TYPE: &str = "urn:ietf:params:oauth:grant-type:device_code";
+const DEVICE_AUTH_HTTP_TIMEOUT_MS: u64 = 15_000;
+pub const DEFAULT_POLL_INTERVAL_SECONDS: u64 = 5;
+const SLOW_DOWN_STEP_SECONDS: u64 = 5;
+const DEFAULT_EXPIRES_IN_SECONDS: i64 = 900;
+const DEVICE_LOGIN_SWEEP_INTERVAL: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone)]
+pub struct DeviceCodeGrant {
+    pub device_code: String,
+    pub user_code: String,
+    pub verification_uri: String,
+    pub verification_uri_complete: Option<String>,
+    pub expires_at: DateTime<Utc>,
+    pub interval_seconds: u64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DevicePollOutcome {
+    Pending,
+    SlowDown,
+    Completed(Value),
+}
+
+#[derive(Debug)]
+pub enum DeviceLoginFlow {
+    Cli {
+        login: CodexDeviceLogin,
+        home: IsolatedCodexHome,
+    },
+    Native {
+        config: DeviceAuthConfig,
+        grant: DeviceCodeGrant,
+    },
+}
+
+#[derive(Debug)]
+pub struct DeviceLoginSession {
+    pub cancelled: Arc<AtomicBool>,
+    pub flow: Option<DeviceLoginFlow>,
+    pub expires_at: DateTime<Utc>,
+}
+
+// 用户放弃的会话不会再调用完成或取消，过期后由后台回收：结束 codex 子进程并删除临时目录。
+pub fn spawn_device_login_reaper(app: AppHandle) {
+    thread::spawn(move || loop {
+        thread::sleep(DEVICE_LOGIN_SWEEP_INTERVAL);
+        let state = app.state::<AppState>();
+        let _ = state.expire_device_logins(Utc::now());
+    });
+}
+
+pub async fn request_device_code(config: &DeviceAuthConfig) -> Result<DeviceCodeGrant> {
+    let client = build_client()?;
+    let response = client
+        .post(&config.device_authorization_url)
+        .form(&[
+            ("client_id", config.client_id.as_str()),
+            ("scope", config.scope.as_str()),
+        ])
+        .send()
+        .await
+        .with_context(|| format!("请求设备码失败: {}", config.device_authorization_url))?;
+    let status = response.status();
+    let json = response
+        .json::<Value>()
+        .await
+        .with_context(|| format!("设备码响应解析失败（HTTP {}）", status.as_u16()))?;
+    if !status.is_success() {
+        return Err(anyhow!(
+            "设备码请求被拒绝（HTTP {}）：{}",
+            status.as_u16(),
+            oauth_error_text(&json)
+        ));
+    }
+    parse_device_code_response(&json, Utc::now())
+}
+
+pub async fn poll_device_token(
+    config: &DeviceAuthConfig,
+    grant: &DeviceCodeGrant,
+) -> Result<DevicePollOutcome> {
+    let client = build_client()?;
+    let response = client
+        .post(&config.token_url)
+        .form(&[
+            ("grant_type", DEVICE_CODE_GRANT_TYPE),
+            ("device_code", grant.device_code.as_str()),
+            ("client_id", config.client_id.as_str()),
+        ])
+        .send()
+        .await
+        .with_context(|| format!("轮询设备码授权失败: {}", config.token_url))?;
+    let status = response.status();
+    let json = response.json::<Value>().await.unwrap_or(Value::Null);
+    parse_token_poll_response(status, &json)
+}
+
+pub async fn wait_for_device_tokens(
+    config: &DeviceAuthConfig,
+    grant: &DeviceCodeGrant,
+    cancelled: &AtomicBool,
+) -> Result<Value> {
+    let mut interval = grant.interval_seconds.max(1);
+    loop {
+        if cancelled.load(Ordering::SeqCst) {
+            return Err(anyhow!("设备码登录已取消"));
+        }
+        if Utc::now() >= grant.expires_at {
+            return Err(anyhow!("设备码已过期，请重新发起设备码登录"));
+        }
+        tokio::time::sleep(Duration::from_secs(interval)).await;
+        match poll_device_token(config, grant).await? {
+            DevicePollOutcome::Pending => {}
+            DevicePollOutcome::SlowDown => interval += SLOW_DOWN_STEP_SECONDS,
+            DevicePollOutcome::Completed(tokens) => return auth_json_from_token_response(&tokens),
+        }
+    }
+}
+
+pub fn auth_json_from_token_response(tokens: &Value) -> Result<Value> {
+    let access_token = text_field(tokens, "access_token")
+        .ok_or_else(|| anyhow!("授权响应缺少 access_token 字段"))?;
+    let id_token = text_field(tokens, "id_token");
+    let claims = id_token
+        .as_deref()
+        .and_then(decode_jwt_claims)
+        .or_else(|| decode_jwt_claims(&access_token))
+        .unwrap_or(Value::Null);
+
+    let mut auth = Map::new();
+    auth.insert("type".to_string(), Value::from("codex"));
+    auth.insert("access_token".to_string(), Value::from(access_token));
+    if let Some(refresh_token) = text_field(tokens, "refresh_token") {
+        auth.insert("refresh_token".to_string(), Value::from(refresh_token));
+    }
+    if let Some(id_token) = id_token {
+        auth.insert("id_token".to_string(), Value::from(id_token));
+    }
+    if let Some(account_id) = claims
+        .get("https://api.openai.com/auth")
+        .and_then(|auth_claims| text_field(auth_claims, "chatgpt_account_id"))
+        .or_else(|| text_field(&claims, "chatgpt_account_id"))
+    {
+        auth.insert("account_id".to_string(), Value::from(account_id));
+    }
+    if let Some(email) = text_field(&claims, "email") {
+        auth.insert("email".to_string(), Value::from(email));
+    }
+    auth.insert(
+        "last_refresh".to_string(),
+        Value::from(Utc::now().to_rfc3339()),
+    );
+    Ok(Value::Object(auth))
+}
+
+fn parse_device_code_response(json: &Value, now: DateTime<Utc>) -> Result<DeviceCodeGrant> {
+    let device_code =
+        text_field(json, "device_code").ok_or_else(|| anyhow!("设备码响应缺少 device_code"))?;
+    let user_code =
+        text_field(json, "user_code").ok_or_else(|| anyhow!("设备码响应缺少 user_code"))?;
+    let verification_uri = text_field(json, "verification_uri")
+        .or_else(|| text_field(json, "verification_url"))
+        .ok_or_else(|| anyhow!("设备码响应缺少 verification_uri"))?;
+    let expires_in = json
+        .get("expires_in")
+        .and_then(Value::as_i64)
+        .filter(|value| *value > 0)
+        .unwrap_or(DEFAULT_EXPIRES_IN_SECONDS);
+    let interval_seconds = json
+        .get("interval")
+        .and_then(Value::as_u64)
+        .filter(|value| *value > 0)
+        .unwrap_or(DEFAULT_POLL_INTERVAL_SECONDS);
+
+    Ok(DeviceCodeGrant {
+        device_code,
+        user_code,
+        verification_uri,
+        verification_uri_complete: text_field(json, "verification_uri_complete"),
+        expires_at: now + chrono::Duration::seconds(expires_in),
+        interval_seconds,
+    })
+}
+
+fn parse_token_poll_response(status: StatusCode, json: &Value) -> Result<DevicePollOutcome> {
+    if status.is_success() {
+        return Ok(DevicePollOutcome::Completed(json.clone()));
+    }
+    match json.get("error").and_then(Value::as_str).unwrap_or("") {
+        "authorization_pending" => Ok(DevicePollOutcome::Pending),
+        "slow_down" => Ok(DevicePollOutcome::SlowDown),
+        "access_denied" => Err(anyhow!("设备码授权已被拒绝")),
+        "expired_token" => Err(anyhow!("设备码已过期，请重新发起设备码登录")),
+        _ => Err(anyhow!(
+            "设备码授权失败（HTTP {}）：{}",
+            status.as_u16(),
+            oauth_error_text(json)
+        )),
+    }
+}
+
+fn oauth_error_text(json: &Value) -> String {
+    text_field(json, "error_description")
+        .or_else(|| text_field(json, "error"))
+        .unwrap_or_else(|| "未知错误".to_string())
+}
+
+fn text_field(value: &Value, key: &str) -> Option<String> {
+    value
+        .get(key)
+        .and_then(Value::as_str)
+        .map(str::trim)
+        .filter(|field| !field.is_empty())
+        .map(ToString::to_string)
+}
+
+fn build_client() -> Result<Client> {
+    Client::builder()
+        .timeout(Duration::from_millis(DEVICE_AUTH_HTTP_TIMEOUT_MS))
+        .build()
+        .context("初始化设备码登录 HTTP 客户端失败")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        auth_json_from_token_response, parse_device_code_response, parse_token_poll_response,
+        DeviceCodeGrant, DeviceLoginFlow, DevicePollOutcome,
+    };
+    use crate::{models::DeviceAuthConfig, test_support::TestState};
+    use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+    use chrono::{Duration, TimeZone, Utc};
+    use reqwest::StatusCode;
+    use serde_json::json;
+
+    #[test]
+    fn parses_device_code_response_with_defaults() {
+        let now = Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).unwrap();
+        let grant = parse_device_code_response(
+            &json!({
+                "device_code": "dev-1",
+                "user_code": "ABCD-EFGH",
+                "verification_url": "https://auth.example.com/device"
+            }),
+            now,
+        )
+        .expect("应解析设备码响应");
+
+        assert_eq!(grant.user_code, "ABCD-EFGH");
+        assert_eq!(grant.verification_uri, "https://auth.example.com/device");
+        assert_eq!(grant.interval_seconds, 5);
+        assert_eq!(grant.expires_at, now + chrono::Duration::seconds(900));
+    }
+
+    #[test]
+    fn maps_token_poll_errors() {
+        assert_eq!(
+            parse_token_poll_response(
+                StatusCode::BAD_REQUEST,
+                &json!({ "error": "authorization_pending" })
+            )
+            .unwrap(),
+            DevicePollOutcome::Pending
+        );
+        assert_eq!(
+            parse_token_poll_response(StatusCode::BAD_REQUEST, &json!({ "error": "slow_down" }))
+                .unwrap(),
+            DevicePollOutcome::SlowDown
+        );
+        assert!(parse_token_poll_response(
+            StatusCode::BAD_REQUEST,
+            &json!({ "error": "access_denied" })
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn builds_codex_auth_json_from_tokens() {
+        let claims = URL_SAFE_NO_PAD.encode(
+            json!({
+                "email": "alice@example.com",
+                "https://api.openai.com/auth": { "chatgpt_account_id": "acc-1" }
+            })
+            .to_string(),
+        );
+        let auth = auth_json_from_token_response(&json!({
+            "access_token": "token-1",
+            "refresh_token": "refresh-1",
+            "id_token": format!("header.{claims}.sig")
+        }))
+        .expect("应生成 auth.json");
+
+        assert_eq!(auth["type"], "codex");
+        assert_eq!(auth["access_token"], "token-1");
+        assert_eq!(auth["account_id"], "acc-1");
+        assert_eq!(auth["email"], "alice@example.com");
+    }
+
+    #[test]
+    fn expired_sessions_are_reclaimed() {
+        let state = TestState::locked("device-login");
+        let now = Utc::now();
+        let flow = |code: &str| DeviceLoginFlow::Native {
+            config: DeviceAuthConfig::default(),
+            grant: DeviceCodeGrant {
+                device_code: code.to_string(),
+                user_code: code.to_string(),
+                verification_uri: "https://example.com/device".to_string(),
+                verification_uri_complete: None,
+                expires_at: now,
+                interval_seconds: 5,
+            },
+        };
+        let stale = state
+            .register_device_login(flow("stale"), now + Duration::seconds(60))
+            .expect("应登记");
+        let fresh = state
+            .register_device_login(flow("fresh"), now + Duration::seconds(600))
+            .expect("应登记");
+
+        assert_eq!(
+            state
+                .expire_device_logins(now + Duration::seconds(120))
+                .expect("应回收"),
+            1
+        );
+        assert!(state.begin_device_login(&stale).is_err());
+        assert!(state.begin_device_login(&fresh).is_ok());
+    }
+}
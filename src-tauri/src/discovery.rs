@@ -0,0 +1,281 @@
+use regex::Regex;
+use std::{
+    collections::HashSet,
+    ffi::OsString,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    sync::OnceLock,
+    time::{Duration, Instant},
+};
+
+const VERSION_PROBE_TIMEOUT_MS: u64 = 5000;
+const LOGIN_SHELL_TIMEOUT_MS: u64 = 3000;
+const LOGIN_SHELL_PATH_MARKER: &str = "__CODEX_SWITCH_PATH__";
+
+#[cfg(target_os = "windows")]
+const CODEX_BINARY_NAMES: [&str; 2] = ["codex.cmd", "codex.exe"];
+#[cfg(not(target_os = "windows"))]
+const CODEX_BINARY_NAMES: [&str; 1] = ["codex"];
+
+#[derive(Debug, Clone)]
+pub struct CodexBinary {
+    pub path: PathBuf,
+    pub version: Option<String>,
+    pub source: String,
+}
+
+pub fn discover_codex_binaries(pinned: Option<&Path>) -> Vec<CodexBinary> {
+    candidate_binaries(pinned)
+        .into_iter()
+        .map(|(path, source)| CodexBinary {
+            version: probe_codex_version(&path),
+            path,
+            source,
+        })
+        .collect()
+}
+
+pub fn resolve_codex_binary(pinned: Option<&Path>) -> Option<CodexBinary> {
+    if let Some(pinned) = pinned.filter(|path| path.is_file()) {
+        return Some(CodexBinary {
+            path: pinned.to_path_buf(),
+            version: probe_codex_version(pinned),
+            source: "pinned".to_string(),
+        });
+    }
+
+    candidate_binaries(None)
+        .into_iter()
+        .find_map(|(path, source)| {
+            let version = probe_codex_version(&path)?;
+            Some(CodexBinary {
+                path,
+                version: Some(version),
+                source,
+            })
+        })
+}
+
+pub fn candidate_binaries(pinned: Option<&Path>) -> Vec<(PathBuf, String)> {
+    let mut seen = HashSet::new();
+    let mut candidates = Vec::new();
+    if let Some(pinned) = pinned.filter(|path| path.is_file()) {
+        seen.insert(dedupe_key(pinned));
+        candidates.push((pinned.to_path_buf(), "pinned".to_string()));
+    }
+
+    for (dir, source) in candidate_dirs() {
+        for name in CODEX_BINARY_NAMES {
+            let path = dir.join(name);
+            if path.is_file() && seen.insert(dedupe_key(&path)) {
+                candidates.push((path, source.to_string()));
+            }
+        }
+    }
+    candidates
+}
+
+pub fn child_path_env(program: &Path) -> OsString {
+    let mut dirs = Vec::new();
+    if let Some(parent) = program.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        dirs.push(parent.to_path_buf());
+    }
+    if let Some(path_env) = login_shell_path() {
+        dirs.extend(std::env::split_paths(path_env));
+    }
+    if let Some(path_env) = std::env::var_os("PATH") {
+        dirs.extend(std::env::split_paths(&path_env));
+    }
+
+    let mut seen = HashSet::new();
+    dirs.retain(|dir| seen.insert(dir.clone()));
+    std::env::join_paths(dirs).unwrap_or_else(|_| std::env::var_os("PATH").unwrap_or_default())
+}
+
+pub fn probe_codex_version(path: &Path) -> Option<String> {
+    let mut child = Command::new(path)
+        .arg("--version")
+        .env("PATH", child_path_env(path))
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .ok()?;
+    let output = wait_with_timeout(&mut child, VERSION_PROBE_TIMEOUT_MS)?;
+    parse_codex_version(&output)
+}
+
+pub fn parse_codex_version(output: &str) -> Option<String> {
+    let regex = Regex::new(r"(\d+\.\d+\.\d+(?:-[0-9A-Za-z.]+)?)").ok()?;
+    Some(regex.captures(output)?.get(1)?.as_str().to_string())
+}
+
+fn candidate_dirs() -> Vec<(PathBuf, &'static str)> {
+    let mut dirs = Vec::new();
+    if let Some(path_env) = std::env::var_os("PATH") {
+        dirs.extend(std::env::split_paths(&path_env).map(|dir| (dir, "PATH")));
+    }
+    if let Some(path_env) = login_shell_path() {
+        dirs.extend(std::env::split_paths(path_env).map(|dir| (dir, "login-shell")));
+    }
+    if let Some(prefix) = std::env::var_os("NPM_CONFIG_PREFIX") {
+        dirs.push((PathBuf::from(prefix).join("bin"), "npm-global"));
+    }
+    if let Some(home) = dirs::home_dir() {
+        dirs.extend(home_candidate_dirs(&home));
+    }
+    #[cfg(target_os = "windows")]
+    {
+        if let Some(app_data) = std::env::var_os("APPDATA") {
+            dirs.push((PathBuf::from(app_data).join("npm"), "npm-global"));
+        }
+    }
+    #[cfg(not(target_os = "windows"))]
+    {
+        dirs.push((PathBuf::from("/opt/homebrew/bin"), "homebrew"));
+        dirs.push((PathBuf::from("/usr/local/bin"), "system"));
+        dirs.push((PathBuf::from("/usr/bin"), "system"));
+    }
+    dirs
+}
+
+fn home_candidate_dirs(home: &Path) -> Vec<(PathBuf, &'static str)> {
+    let mut dirs = vec![
+        (home.join(".local").join("bin"), "local-bin"),
+        (home.join(".npm-global").join("bin"), "npm-global"),
+        (home.join(".volta").join("bin"), "volta"),
+        (home.join(".bun").join("bin"), "bun"),
+        (home.join(".cargo").join("bin"), "cargo"),
+        (home.join(".yarn").join("bin"), "yarn"),
+        (home.join(".local").join("share").join("pnpm"), "pnpm"),
+        (home.join("Library").join("pnpm"), "pnpm"),
+    ];
+
+    let nvm_versions = home.join(".nvm").join("versions").join("node");
+    if let Ok(entries) = std::fs::read_dir(&nvm_versions) {
+        let mut versions = entries
+            .filter_map(Result::ok)
+            .map(|entry| entry.path().join("bin"))
+            .filter(|path| path.is_dir())
+            .collect::<Vec<_>>();
+        versions.sort();
+        versions.reverse();
+        dirs.extend(versions.into_iter().map(|dir| (dir, "nvm")));
+    }
+    dirs
+}
+
+fn login_shell_path() -> Option<&'static OsString> {
+    static LOGIN_SHELL_PATH: OnceLock<Option<OsString>> = OnceLock::new();
+    LOGIN_SHELL_PATH.get_or_init(read_login_shell_path).as_ref()
+}
+
+#[cfg(target_os = "windows")]
+fn read_login_shell_path() -> Option<OsString> {
+    None
+}
+
+#[cfg(not(target_os = "windows"))]
+fn read_login_shell_path() -> Option<OsString> {
+    let shell = std::env::var_os("SHELL").unwrap_or_else(|| OsString::from("/bin/sh"));
+    let mut child = Command::new(shell)
+        .arg("-lc")
+        .arg(format!("printf '{LOGIN_SHELL_PATH_MARKER}%s' \"$PATH\""))
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .ok()?;
+    let output = wait_with_timeout(&mut child, LOGIN_SHELL_TIMEOUT_MS)?;
+    parse_login_shell_path(&output).map(OsString::from)
+}
+
+fn parse_login_shell_path(output: &str) -> Option<String> {
+    let (_, path) = output.rsplit_once(LOGIN_SHELL_PATH_MARKER)?;
+    let path = path.trim();
+    if path.is_empty() {
+        None
+    } else {
+        Some(path.to_string())
+    }
+}
+
+fn wait_with_timeout(child: &mut std::process::Child, timeout_ms: u64) -> Option<String> {
+    let started_at = Instant::now();
+    loop {
+        match child.try_wait() {
+            Ok(Some(_)) => break,
+            Ok(None) if started_at.elapsed() < Duration::from_millis(timeout_ms) => {
+                std::thread::sleep(Duration::from_millis(50));
+            }
+            _ => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return None;
+            }
+        }
+    }
+    let mut output = String::new();
+    let stdout = child.stdout.as_mut()?;
+    std::io::Read::read_to_string(stdout, &mut output).ok()?;
+    Some(output)
+}
+
+fn dedupe_key(path: &Path) -> String {
+    std::fs::canonicalize(path)
+        .unwrap_or_else(|_| path.to_path_buf())
+        .to_string_lossy()
+        .to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{home_candidate_dirs, parse_codex_version, parse_login_shell_path};
+    use std::fs;
+
+    #[test]
+    fn parses_codex_version_output() {
+        assert_eq!(
+            parse_codex_version("codex-cli 0.46.0\n").as_deref(),
+            Some("0.46.0")
+        );
+        assert_eq!(
+            parse_codex_version("codex-cli 0.50.0-alpha.2").as_deref(),
+            Some("0.50.0-alpha.2")
+        );
+        assert_eq!(parse_codex_version("command not found"), None);
+    }
+
+    #[test]
+    fn parses_login_shell_path_after_motd_noise() {
+        let output = "Welcome back!\n__CODEX_SWITCH_PATH__/usr/local/bin:/usr/bin\n";
+        assert_eq!(
+            parse_login_shell_path(output).as_deref(),
+            Some("/usr/local/bin:/usr/bin")
+        );
+        assert_eq!(parse_login_shell_path("no marker"), None);
+    }
+
+    #[test]
+    fn includes_nvm_node_versions_newest_first() {
+        let home = std::env::temp_dir().join(format!("codex-switch-home-{}", uuid::Uuid::new_v4()));
+        let versions = home.join(".nvm").join("versions").join("node");
+        fs::create_dir_all(versions.join("v18.20.0").join("bin")).unwrap();
+        fs::create_dir_all(versions.join("v20.11.1").join("bin")).unwrap();
+
+        let nvm_dirs = home_candidate_dirs(&home)
+            .into_iter()
+            .filter(|(_, source)| *source == "nvm")
+            .map(|(dir, _)| dir)
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            nvm_dirs,
+            vec![
+                versions.join("v20.11.1").join("bin"),
+                versions.join("v18.20.0").join("bin")
+            ]
+        );
+        fs::remove_dir_all(&home).ok();
+    }
+}
@@ -0,0 +1,410 @@
+use crate::{
+    app_state::AppState,
+    health::HealthStatus,
+    models::{Account, FailoverCandidate, FailoverEvent, QuotaDashboardItem, QuotaSnapshot},
+    service::{self, SwitchOptions},
+    store::now,
+};
+use anyhow::Result;
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::{
+    cmp::Ordering,
+    thread,
+    time::{Duration, Instant},
+};
+use tauri::{AppHandle, Emitter, Manager};
+
+pub const AUTO_FAILOVER_EVENT: &str = "auto-failover";
+// 后台循环的检查粒度，实际探测间隔由设置决定
+const FAILOVER_TICK: Duration = Duration::from_secs(15);
+pub const MIN_FAILOVER_INTERVAL_SECONDS: u64 = 60;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "snake_case")]
+pub enum RestartMode {
+    // 结束并重启运行中的 Codex 进程，新凭据立即生效；会中断进行中的会话，需显式开启
+    Force,
+    // 只替换 auth.json，运行中的进程下次启动才生效
+    #[default]
+    Never,
+}
+
+impl RestartMode {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Force => "force",
+            Self::Never => "never",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "force" => Some(Self::Force),
+            "never" => Some(Self::Never),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FailoverConfig {
+    pub enabled: bool,
+    pub interval_seconds: u64,
+    // 剩余百分比不高于该值也触发；为空时只在配额耗尽时切换
+    pub threshold_percent: Option<f64>,
+    pub cooldown_seconds: u64,
+    // 候选账号带有其中任一标签时优先；为空时优先与当前账号标签相同的账号
+    pub preferred_tags: Vec<String>,
+    pub restart_mode: RestartMode,
+}
+
+impl Default for FailoverConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_seconds: 300,
+            threshold_percent: None,
+            cooldown_seconds: 1800,
+            preferred_tags: Vec::new(),
+            restart_mode: RestartMode::Never,
+        }
+    }
+}
+
+pub fn spawn_failover_monitor(app: AppHandle) {
+    thread::spawn(move || {
+        let mut last_probe: Option<Instant> = None;
+        let mut cooldown_until: Option<Instant> = None;
+        loop {
+            thread::sleep(FAILOVER_TICK);
+            let state = app.state::<AppState>();
+            let Ok(config) = state.store.get_failover_config() else {
+                continue;
+            };
+            if !config.enabled || !state.is_vault_unlocked().unwrap_or(false) {
+                last_probe = None;
+                continue;
+            }
+            let now = Instant::now();
+            if cooldown_until.is_some_and(|until| now < until) {
+                continue;
+            }
+            let interval = Duration::from_secs(config.interval_seconds);
+            if last_probe.is_some_and(|probed| now.duration_since(probed) < interval) {
+                continue;
+            }
+            last_probe = Some(now);
+            // 无论切换成功与否都进入冷却，避免没有可用账号时每轮重复提醒
+            if let Ok(Some(event)) =
+                tauri::async_runtime::block_on(check_and_failover(&state, &config))
+            {
+                cooldown_until =
+                    Some(Instant::now() + Duration::from_secs(config.cooldown_seconds));
+                let _ = app.emit(AUTO_FAILOVER_EVENT, &event);
+            }
+        }
+    });
+}
+
+// 探测当前账号配额，达到触发条件时按排名依次尝试候选账号；被切换守卫阻止或切换出错的候选会跳过。
+// 未触发时返回 None；触发后无论是否切换成功都返回事件并广播。
+pub async fn check_and_failover(
+    state: &AppState,
+    config: &FailoverConfig,
+) -> Result<Option<FailoverEvent>> {
+    let Some(active) = state
+        .store
+        .get_current_account_id()?
+        .and_then(|id| state.store.get_account(&id).ok().flatten())
+    else {
+        return Ok(None);
+    };
+    let snapshot = service::refresh_quota(state, Some(&active.id), true)
+        .await?
+        .into_iter()
+        .next();
+    let Some(trigger) = snapshot
+        .as_ref()
+        .and_then(|snapshot| failover_trigger(snapshot, config.threshold_percent))
+    else {
+        return Ok(None);
+    };
+
+    // 其余账号沿用配额缓存有效期，不必每次全部重新探测
+    let _ = service::refresh_quota(state, None, false).await;
+    let candidates = rank_candidates(
+        &active,
+        &service::quota_dashboard(state)?,
+        &config.preferred_tags,
+    );
+    let options = SwitchOptions {
+        force_restart: config.restart_mode == RestartMode::Force,
+        ..SwitchOptions::default()
+    };
+    let mut skipped = Vec::new();
+    for candidate in &candidates {
+        let result = match service::switch_to_account(state, &candidate.account_id, options) {
+            Ok(result) if !result.blocked && result.success => result,
+            Ok(result) => {
+                skipped.push(format!("{}：{}", candidate.account_name, result.message));
+                continue;
+            }
+            Err(error) => {
+                skipped.push(format!("{}：{error}", candidate.account_name));
+                continue;
+            }
+        };
+        let message = format!(
+            "「{}」{trigger}，已自动切换到「{}」（{}）",
+            active.name, candidate.account_name, candidate.reason
+        );
+        let event = failover_event(
+            &active,
+            &trigger,
+            Some(candidate.clone()),
+            Some(result),
+            candidates.clone(),
+            message,
+        );
+        state.publish_event(AUTO_FAILOVER_EVENT, &event);
+        return Ok(Some(event));
+    }
+
+    let message = if candidates.is_empty() {
+        format!("「{}」{trigger}，但没有可用的候选账号", active.name)
+    } else {
+        format!(
+            "「{}」{trigger}，但候选账号均未能切换：{}",
+            active.name,
+            skipped.join("；")
+        )
+    };
+    let event = failover_event(&active, &trigger, None, None, candidates, message);
+    state.publish_event(AUTO_FAILOVER_EVENT, &event);
+    Ok(Some(event))
+}
+
+fn failover_event(
+    active: &Account,
+    trigger: &str,
+    switched_to: Option<FailoverCandidate>,
+    result: Option<crate::models::SwitchResult>,
+    candidates: Vec<FailoverCandidate>,
+    message: String,
+) -> FailoverEvent {
+    FailoverEvent {
+        from_account_id: active.id.clone(),
+        from_account_name: active.name.clone(),
+        trigger: trigger.to_string(),
+        switched_to,
+        result,
+        candidates,
+        message,
+        created_at: now(),
+    }
+}
+
+pub fn failover_trigger(
+    snapshot: &QuotaSnapshot,
+    threshold_percent: Option<f64>,
+) -> Option<String> {
+    if snapshot.quota_state == "exhausted" {
+        return Some("配额已耗尽".to_string());
+    }
+    let threshold = threshold_percent?;
+    let remaining = remaining_percent(snapshot)?;
+    (remaining <= threshold).then(|| format!("剩余配额 {remaining:.0}% 已低于阈值 {threshold:.0}%"))
+}
+
+// 仅在配额以百分比计量时返回剩余比例；自动切换与账号池共用。
+pub fn remaining_percent(snapshot: &QuotaSnapshot) -> Option<f64> {
+    snapshot
+        .remaining_value
+        .filter(|_| snapshot.remaining_unit.as_deref() == Some("%"))
+}
+
+// 不能作为切换目标的原因：当前账号、配额已耗尽或凭据不可用；自动切换与账号池共用。
+pub fn exclusion_reason(item: &QuotaDashboardItem, current: Option<&str>) -> Option<String> {
+    if current == Some(item.account.id.as_str()) {
+        return Some("当前账号".to_string());
+    }
+    if item
+        .snapshot
+        .as_ref()
+        .is_some_and(|snapshot| snapshot.quota_state == "exhausted")
+    {
+        return Some("配额已耗尽".to_string());
+    }
+    item.health
+        .as_ref()
+        .filter(|health| {
+            HealthStatus::parse(&health.status).is_some_and(|status| !status.is_usable())
+        })
+        .map(|health| format!("凭据状态为 {}", health.status))
+}
+
+// 排除当前账号、配额已耗尽与凭据异常的账号，再依次比较：配额状态、是否带偏好标签、
+// 剩余百分比（高者优先）、重置时间（早者优先，额度过期前先用掉）、最近使用时间（久未使用优先）。
+pub fn rank_candidates(
+    active: &Account,
+    items: &[QuotaDashboardItem],
+    preferred_tags: &[String],
+) -> Vec<FailoverCandidate> {
+    let preferred = if preferred_tags.is_empty() {
+        &active.tags
+    } else {
+        preferred_tags
+    };
+    let mut ranked = items
+        .iter()
+        .filter(|item| exclusion_reason(item, Some(&active.id)).is_none())
+        .map(|item| {
+            let tag = item
+                .account
+                .tags
+                .iter()
+                .find(|tag| preferred.contains(tag))
+                .cloned();
+            (item, tag)
+        })
+        .collect::<Vec<_>>();
+    ranked.sort_by(|(a, a_tag), (b, b_tag)| {
+        let state = |item: &QuotaDashboardItem| match item
+            .snapshot
+            .as_ref()
+            .map(|snapshot| snapshot.quota_state.as_str())
+        {
+            Some("available") => 0,
+            Some("near_limit") => 1,
+            _ => 2,
+        };
+        let percent =
+            |item: &QuotaDashboardItem| item.snapshot.as_ref().and_then(remaining_percent);
+        let reset = |item: &QuotaDashboardItem| {
+            item.snapshot
+                .as_ref()
+                .and_then(|snapshot| snapshot.reset_at.clone())
+        };
+        state(a)
+            .cmp(&state(b))
+            .then_with(|| b_tag.is_some().cmp(&a_tag.is_some()))
+            .then_with(|| match (percent(a), percent(b)) {
+                (Some(a), Some(b)) => b.partial_cmp(&a).unwrap_or(Ordering::Equal),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => Ordering::Equal,
+            })
+            .then_with(|| match (reset(a), reset(b)) {
+                (Some(a), Some(b)) => a.cmp(&b),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => Ordering::Equal,
+            })
+            .then_with(|| a.account.last_used_at.cmp(&b.account.last_used_at))
+    });
+    ranked
+        .into_iter()
+        .map(|(item, tag)| candidate(item, tag))
+        .collect()
+}
+
+fn candidate(item: &QuotaDashboardItem, tag: Option<String>) -> FailoverCandidate {
+    let snapshot = item.snapshot.as_ref();
+    let quota_state = snapshot
+        .map(|snapshot| snapshot.quota_state.clone())
+        .unwrap_or_else(|| "unknown".to_string());
+    let mut reasons = vec![match quota_state.as_str() {
+        "available" => "配额充足".to_string(),
+        "near_limit" => "配额接近上限".to_string(),
+        _ => "配额状态未知".to_string(),
+    }];
+    if let Some(value) = snapshot.and_then(|snapshot| snapshot.remaining_value) {
+        let unit = snapshot
+            .and_then(|snapshot| snapshot.remaining_unit.as_deref())
+            .unwrap_or("");
+        reasons.push(format!("剩余 {value:.0}{unit}"));
+    }
+    if let Some(tag) = &tag {
+        reasons.push(format!("带有偏好标签 {tag}"));
+    }
+    if let Some(reset_at) = snapshot.and_then(|snapshot| snapshot.reset_at.as_deref()) {
+        reasons.push(format!("预计 {reset_at} 重置"));
+    }
+    reasons.push(match item.account.last_used_at.as_deref() {
+        Some(last_used_at) => format!("最近使用于 {last_used_at}"),
+        None => "从未使用".to_string(),
+    });
+    FailoverCandidate {
+        account_id: item.account.id.clone(),
+        account_name: item.account.name.clone(),
+        quota_state,
+        remaining_value: snapshot.and_then(|snapshot| snapshot.remaining_value),
+        remaining_unit: snapshot.and_then(|snapshot| snapshot.remaining_unit.clone()),
+        reset_at: snapshot.and_then(|snapshot| snapshot.reset_at.clone()),
+        tag_match: tag.is_some(),
+        reason: reasons.join("，"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{failover_trigger, rank_candidates};
+    use crate::{
+        models::{AccountHealth, QuotaDashboardItem},
+        test_support::dashboard_item,
+    };
+
+    fn with_health(mut item: QuotaDashboardItem, status: &str) -> QuotaDashboardItem {
+        item.health = Some(AccountHealth {
+            account_id: item.account.id.clone(),
+            status: status.to_string(),
+            expires_at: None,
+            reason: None,
+            remote_checked: true,
+            checked_at: "2026-01-01T00:00:00Z".to_string(),
+        });
+        item
+    }
+
+    #[test]
+    fn ranks_usable_candidates_and_detects_triggers() {
+        let active = dashboard_item("active", &["team"], "exhausted", Some(0.0));
+        let items = vec![
+            active.clone(),
+            dashboard_item("low", &[], "available", Some(20.0)),
+            dashboard_item("high", &[], "available", Some(80.0)),
+            with_health(
+                dashboard_item("teammate", &["team"], "available", Some(10.0)),
+                "valid",
+            ),
+            dashboard_item("tight", &["team"], "near_limit", Some(5.0)),
+            dashboard_item("empty", &[], "exhausted", Some(0.0)),
+            with_health(
+                dashboard_item("revoked", &["team"], "available", Some(90.0)),
+                "revoked",
+            ),
+        ];
+
+        let ranked = rank_candidates(&active.account, &items, &[]);
+        let order = ranked
+            .iter()
+            .map(|candidate| candidate.account_id.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(order, ["teammate", "high", "low", "tight"]);
+        assert!(ranked[0].reason.contains("带有偏好标签 team"));
+
+        let ranked = rank_candidates(&active.account, &items, &["other".to_string()]);
+        assert_eq!(ranked[0].account_id, "high");
+
+        let snapshot = |state: &str, percent: f64| {
+            dashboard_item("x", &[], state, Some(percent))
+                .snapshot
+                .expect("应有快照")
+        };
+        assert!(failover_trigger(&snapshot("exhausted", 0.0), None).is_some());
+        assert!(failover_trigger(&snapshot("near_limit", 8.0), None).is_none());
+        assert!(failover_trigger(&snapshot("near_limit", 8.0), Some(10.0)).is_some());
+        assert!(failover_trigger(&snapshot("available", 40.0), Some(10.0)).is_none());
+    }
+}
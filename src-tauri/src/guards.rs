@@ -0,0 +1,263 @@
+use crate::{
+    app_state::AppState,
+    codex::count_codex_processes,
+    compat::auth_text_field,
+    health::{token_expires_at, HealthStatus},
+    models::GuardResult,
+};
+use anyhow::Result;
+use chrono::{SecondsFormat, Utc};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "snake_case")]
+pub enum GuardAction {
+    Block,
+    #[default]
+    Warn,
+    Ignore,
+}
+
+impl GuardAction {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Block => "block",
+            Self::Warn => "warn",
+            Self::Ignore => "ignore",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "block" => Some(Self::Block),
+            "warn" => Some(Self::Warn),
+            "ignore" => Some(Self::Ignore),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SwitchGuardPolicy {
+    pub token_expiry: GuardAction,
+    pub health: GuardAction,
+    pub quota: GuardAction,
+    pub running_processes: GuardAction,
+}
+
+impl Default for SwitchGuardPolicy {
+    fn default() -> Self {
+        Self {
+            token_expiry: GuardAction::Block,
+            health: GuardAction::Block,
+            quota: GuardAction::Warn,
+            running_processes: GuardAction::Warn,
+        }
+    }
+}
+
+// 切换前的检查：令牌是否过期、最近健康检查、最新配额状态与是否有运行中的 Codex 进程。
+// 只读不写；是否拦截由调用方结合 override 决定。check_processes 为 false 时（将重启进程，
+// 或切换本身由新启动的进程触发）跳过进程检查。
+pub fn evaluate_guards(
+    state: &AppState,
+    policy: &SwitchGuardPolicy,
+    account_id: &str,
+    auth_json: &Value,
+    check_processes: bool,
+) -> Result<Vec<GuardResult>> {
+    let mut results = Vec::new();
+    let mut push = |guard: &str, action: GuardAction, failure: Option<String>, note: String| {
+        results.push(GuardResult {
+            guard: guard.to_string(),
+            action: action.as_str().to_string(),
+            passed: failure.is_none(),
+            message: failure.unwrap_or(note),
+        });
+    };
+
+    if policy.token_expiry != GuardAction::Ignore {
+        let refreshable = auth_text_field(auth_json, "refresh_token").is_some();
+        let (failure, note) = match token_expires_at(auth_json) {
+            // 带 refresh_token 的账号闲置久了 access_token 必然过期，Codex 启动时会自动续期
+            Some(expires_at) if expires_at <= Utc::now() && refreshable => (
+                None,
+                format!(
+                    "令牌已于 {} 过期，将由 refresh_token 自动续期",
+                    expires_at.to_rfc3339_opts(SecondsFormat::Secs, true)
+                ),
+            ),
+            Some(expires_at) if expires_at <= Utc::now() => (
+                Some(format!(
+                    "令牌已于 {} 过期",
+                    expires_at.to_rfc3339_opts(SecondsFormat::Secs, true)
+                )),
+                String::new(),
+            ),
+            Some(expires_at) => (
+                None,
+                format!(
+                    "令牌有效至 {}",
+                    expires_at.to_rfc3339_opts(SecondsFormat::Secs, true)
+                ),
+            ),
+            None => (None, "令牌不含过期时间".to_string()),
+        };
+        push("token_expiry", policy.token_expiry, failure, note);
+    }
+
+    if policy.health != GuardAction::Ignore {
+        let (failure, note) = match state.store.get_account_health(account_id)? {
+            Some(health) => match HealthStatus::parse(&health.status) {
+                Some(status) if !status.is_usable() => (
+                    Some(format!(
+                        "最近一次凭据检查结果为 {}：{}",
+                        health.status,
+                        health.reason.as_deref().unwrap_or("-")
+                    )),
+                    String::new(),
+                ),
+                _ => (None, format!("最近一次凭据检查结果为 {}", health.status)),
+            },
+            None => (None, "尚未检查凭据健康状态".to_string()),
+        };
+        push("health", policy.health, failure, note);
+    }
+
+    if policy.quota != GuardAction::Ignore {
+        let (failure, note) = match state.store.latest_quota_by_account(account_id)? {
+            Some(snapshot) if snapshot.quota_state == "exhausted" => (
+                Some(match snapshot.reset_at.as_deref() {
+                    Some(reset_at) => format!("最新配额已耗尽，预计 {reset_at} 重置"),
+                    None => "最新配额已耗尽".to_string(),
+                }),
+                String::new(),
+            ),
+            Some(snapshot) => (None, format!("最新配额状态为 {}", snapshot.quota_state)),
+            None => (None, "暂无配额记录".to_string()),
+        };
+        push("quota", policy.quota, failure, note);
+    }
+
+    if policy.running_processes != GuardAction::Ignore && check_processes {
+        let count = count_codex_processes();
+        let failure = (count > 0)
+            .then(|| format!("有 {count} 个 Codex 进程正在运行，未选择重启时它们仍使用旧凭据"));
+        push(
+            "running_processes",
+            policy.running_processes,
+            failure,
+            "没有运行中的 Codex 进程".to_string(),
+        );
+    }
+    Ok(results)
+}
+
+pub fn failed_guards(results: &[GuardResult], action: GuardAction) -> Vec<&GuardResult> {
+    results
+        .iter()
+        .filter(|result| !result.passed && result.action == action.as_str())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{evaluate_guards, failed_guards, GuardAction, SwitchGuardPolicy};
+    use crate::{
+        account_import::import_account_from_auth_json,
+        test_support::{access_token_expiring_in, TestState},
+    };
+    use chrono::Duration;
+    use serde_json::{json, Value};
+
+    // 令牌已过期且没有 refresh_token、最新配额已耗尽的账号。
+    fn stale_account(state: &TestState) -> (String, Value) {
+        let auth_json = json!({
+            "type": "codex",
+            "access_token": access_token_expiring_in(-Duration::days(30)),
+            "email": "old@example.com",
+        });
+        let account = import_account_from_auth_json(state, "old", Vec::new(), auth_json.clone())
+            .expect("应导入");
+        state
+            .store
+            .save_quota_snapshot(
+                &account.id,
+                "state",
+                None,
+                None,
+                "exhausted",
+                Some("2026-01-02T00:00:00Z"),
+                "api",
+                95,
+                None,
+            )
+            .expect("应写入配额");
+        (account.id, auth_json)
+    }
+
+    #[test]
+    fn default_policy_blocks_expired_tokens_and_warns_on_exhausted_quota() {
+        let state = TestState::unlocked("guards");
+        let (account_id, auth_json) = stale_account(&state);
+        let policy = state.store.get_switch_guard_policy().expect("应读取设置");
+        assert_eq!(policy, SwitchGuardPolicy::default());
+        let results =
+            evaluate_guards(&state, &policy, &account_id, &auth_json, false).expect("应完成检查");
+        assert_eq!(results.len(), 3);
+        let blocked = failed_guards(&results, GuardAction::Block);
+        assert_eq!(blocked.len(), 1);
+        assert_eq!(blocked[0].guard, "token_expiry");
+        assert_eq!(failed_guards(&results, GuardAction::Warn)[0].guard, "quota");
+    }
+
+    #[test]
+    fn saved_policy_decides_which_guards_run_and_block() {
+        let state = TestState::unlocked("guards");
+        let (account_id, auth_json) = stale_account(&state);
+        let relaxed = SwitchGuardPolicy {
+            token_expiry: GuardAction::Ignore,
+            quota: GuardAction::Block,
+            ..SwitchGuardPolicy::default()
+        };
+        state
+            .store
+            .set_switch_guard_policy(&relaxed)
+            .expect("应保存设置");
+        let policy = state.store.get_switch_guard_policy().expect("应读取设置");
+        assert_eq!(policy, relaxed);
+        let results =
+            evaluate_guards(&state, &policy, &account_id, &auth_json, false).expect("应完成检查");
+        assert!(results.iter().all(|result| result.guard != "token_expiry"));
+        assert_eq!(
+            failed_guards(&results, GuardAction::Block)[0].guard,
+            "quota"
+        );
+    }
+
+    #[test]
+    fn expired_token_with_refresh_token_passes() {
+        let state = TestState::locked("guards");
+        let auth_json = json!({
+            "type": "codex",
+            "access_token": access_token_expiring_in(-Duration::days(30)),
+            "refresh_token": "refresh",
+        });
+        let results = evaluate_guards(
+            &state,
+            &SwitchGuardPolicy::default(),
+            "missing",
+            &auth_json,
+            false,
+        )
+        .expect("应完成检查");
+        let expiry = results
+            .iter()
+            .find(|result| result.guard == "token_expiry")
+            .expect("应包含令牌检查");
+        assert!(expiry.passed, "{}", expiry.message);
+        assert!(failed_guards(&results, GuardAction::Block).is_empty());
+    }
+}
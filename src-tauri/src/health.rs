@@ -0,0 +1,389 @@
+use crate::{
+    app_state::AppState,
+    codex::{decode_jwt_claims, validate_auth_json},
+    compat::auth_text_field,
+    crypto,
+    models::AccountHealth,
+    quota::{probe_auth, AuthProbe},
+    store::AccountSecret,
+};
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Duration, SecondsFormat, Utc};
+use serde_json::Value;
+use zeroize::Zeroize;
+
+pub const ACCOUNT_HEALTH_EVENT: &str = "account-health-updated";
+// access_token 在该窗口内过期即提示即将过期
+const EXPIRING_WINDOW_HOURS: i64 = 24;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HealthStatus {
+    Valid,
+    Expiring,
+    // access_token 已过期但带有 refresh_token，Codex 启动时会自动续期
+    Refreshable,
+    Expired,
+    Revoked,
+    Malformed,
+}
+
+impl HealthStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Valid => "valid",
+            Self::Expiring => "expiring",
+            Self::Refreshable => "refreshable",
+            Self::Expired => "expired",
+            Self::Revoked => "revoked",
+            Self::Malformed => "malformed",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "valid" => Some(Self::Valid),
+            "expiring" => Some(Self::Expiring),
+            "refreshable" => Some(Self::Refreshable),
+            "expired" => Some(Self::Expired),
+            "revoked" => Some(Self::Revoked),
+            "malformed" => Some(Self::Malformed),
+            _ => None,
+        }
+    }
+
+    // 即将过期或可续期的仍可使用，其余异常状态不宜直接切换过去
+    pub fn is_usable(self) -> bool {
+        matches!(self, Self::Valid | Self::Expiring | Self::Refreshable)
+    }
+}
+
+struct Assessment {
+    status: HealthStatus,
+    expires_at: Option<DateTime<Utc>>,
+    reason: Option<String>,
+}
+
+impl Assessment {
+    fn malformed(reason: String) -> Self {
+        Self {
+            status: HealthStatus::Malformed,
+            expires_at: None,
+            reason: Some(reason),
+        }
+    }
+}
+
+// 逐个解密并校验账号凭据；remote 为空时按设置决定是否请求远程校验地址。
+// 单个账号的失败记为 malformed 而不中断，其余账号照常检查。
+pub async fn check_accounts_health(
+    state: &AppState,
+    account_id: Option<&str>,
+    remote: Option<bool>,
+) -> Result<Vec<AccountHealth>> {
+    let mut key = state.get_vault_key()?;
+    let secrets = match account_id {
+        Some(account_id) => vec![state
+            .store
+            .get_account_secret(account_id.trim())?
+            .ok_or_else(|| anyhow!("账户不存在"))?],
+        None => {
+            let mut secrets = Vec::new();
+            for account in state.store.list_accounts()? {
+                secrets.extend(state.store.get_account_secret(&account.id)?);
+            }
+            secrets
+        }
+    };
+    let config = state.store.get_health_check_config()?;
+    let remote = remote.unwrap_or(config.remote_enabled);
+    let (timeout_ms, _, _) = state.store.get_quota_policy()?;
+    let (_, compat) = state.resolve_codex_compat_async().await?;
+
+    let mut results = Vec::new();
+    for secret in secrets {
+        let now = Utc::now();
+        let auth_json = match decrypt_auth(&key, &secret) {
+            Ok(auth_json) => auth_json,
+            Err(error) => {
+                results.push(save(state, &secret, Assessment::malformed(error), false)?);
+                continue;
+            }
+        };
+        let mut assessment = assess_expiry(&auth_json, now);
+        let remote_checked = remote && assessment.status != HealthStatus::Malformed;
+        if remote_checked {
+            let access_token = auth_text_field(&auth_json, "access_token").unwrap_or_default();
+            let chatgpt_account_id = auth_text_field(&auth_json, "account_id");
+            let probe = probe_auth(
+                &config.endpoint,
+                access_token,
+                chatgpt_account_id,
+                timeout_ms,
+                &compat,
+            )
+            .await;
+            apply_probe(&mut assessment, probe);
+        }
+        results.push(save(state, &secret, assessment, remote_checked)?);
+    }
+    key.zeroize();
+    state.publish_event(ACCOUNT_HEALTH_EVENT, &results);
+    Ok(results)
+}
+
+fn decrypt_auth(key: &[u8], secret: &AccountSecret) -> std::result::Result<Value, String> {
+    let decrypted = crypto::decrypt_from_base64(key, &secret.encrypted_auth_blob)
+        .map_err(|error| format!("无法解密登录数据：{error}"))?;
+    let text =
+        String::from_utf8(decrypted).map_err(|_| "登录数据不是有效的 UTF-8 文本".to_string())?;
+    validate_auth_json(&text).map_err(|error| format!("登录数据结构无效：{error}"))
+}
+
+pub fn token_expires_at(auth_json: &Value) -> Option<DateTime<Utc>> {
+    auth_text_field(auth_json, "access_token")
+        .and_then(decode_jwt_claims)
+        .and_then(|claims| claims.get("exp").and_then(Value::as_i64))
+        .and_then(|exp| DateTime::from_timestamp(exp, 0))
+}
+
+fn assess_expiry(auth_json: &Value, now: DateTime<Utc>) -> Assessment {
+    if auth_text_field(auth_json, "access_token").is_none() {
+        return Assessment::malformed("登录数据缺少 access_token".to_string());
+    }
+    let Some(expires_at) = token_expires_at(auth_json) else {
+        return Assessment {
+            status: HealthStatus::Valid,
+            expires_at: None,
+            reason: Some("access_token 不含过期时间，仅校验了结构".to_string()),
+        };
+    };
+
+    let (status, reason) = if expires_at <= now {
+        if auth_text_field(auth_json, "refresh_token").is_some() {
+            (
+                HealthStatus::Refreshable,
+                Some(
+                    "access_token 已过期，Codex 会用 refresh_token 自动续期；若续期失败请重新登录"
+                        .to_string(),
+                ),
+            )
+        } else {
+            (
+                HealthStatus::Expired,
+                Some("access_token 已过期且没有 refresh_token，请重新登录".to_string()),
+            )
+        }
+    } else if expires_at - now <= Duration::hours(EXPIRING_WINDOW_HOURS) {
+        (
+            HealthStatus::Expiring,
+            Some(format!(
+                "access_token 将在 {EXPIRING_WINDOW_HOURS} 小时内过期"
+            )),
+        )
+    } else {
+        (HealthStatus::Valid, None)
+    };
+    Assessment {
+        status,
+        expires_at: Some(expires_at),
+        reason,
+    }
+}
+
+fn apply_probe(assessment: &mut Assessment, probe: AuthProbe) {
+    match probe {
+        AuthProbe::Accepted => {
+            // 服务端仍接受令牌时，本地判断的过期多半是时钟偏差
+            if matches!(
+                assessment.status,
+                HealthStatus::Expired | HealthStatus::Refreshable
+            ) {
+                assessment.status = HealthStatus::Expiring;
+                assessment.reason = Some("本地时间显示已过期，但服务端仍接受该令牌".to_string());
+            }
+        }
+        // 已过期的 access_token 被拒绝是预期行为，不能据此判定吊销，但也无法确认续期可用
+        AuthProbe::Rejected(reason) if assessment.status == HealthStatus::Refreshable => {
+            assessment.status = HealthStatus::Expired;
+            assessment.reason = Some(format!(
+                "access_token 已过期且服务端拒绝（{reason}），无法确认 refresh_token 是否仍有效，请重新登录"
+            ));
+        }
+        AuthProbe::Rejected(reason) => {
+            assessment.status = HealthStatus::Revoked;
+            assessment.reason = Some(format!("服务端拒绝了该令牌（{reason}），请重新登录"));
+        }
+        AuthProbe::Unreachable(reason) => {
+            let local = assessment.reason.take();
+            assessment.reason = Some(match local {
+                Some(local) => format!("{local}；远程校验未完成：{reason}"),
+                None => format!("远程校验未完成：{reason}"),
+            });
+        }
+    }
+}
+
+fn save(
+    state: &AppState,
+    secret: &AccountSecret,
+    assessment: Assessment,
+    remote_checked: bool,
+) -> Result<AccountHealth> {
+    let expires_at = assessment
+        .expires_at
+        .map(|time| time.to_rfc3339_opts(SecondsFormat::Secs, true));
+    state.store.save_account_health(
+        &secret.account.id,
+        assessment.status.as_str(),
+        expires_at.as_deref(),
+        assessment.reason.as_deref(),
+        remote_checked,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{apply_probe, assess_expiry, check_accounts_health, HealthStatus};
+    use crate::quota::AuthProbe;
+    use crate::{
+        account_import::import_account_from_auth_json,
+        crypto,
+        models::HealthCheckConfig,
+        test_support::{access_token_expiring_in, TestState, TEST_VAULT_KEY},
+    };
+    use chrono::{Duration, Utc};
+    use serde_json::{json, Value};
+    use std::{
+        collections::HashMap,
+        io::{Read, Write},
+        net::TcpListener,
+    };
+
+    fn expired_auth(refresh_token: Option<&str>) -> Value {
+        let mut auth = json!({
+            "type": "codex",
+            "access_token": access_token_expiring_in(-Duration::days(3)),
+        });
+        if let Some(refresh_token) = refresh_token {
+            auth["refresh_token"] = json!(refresh_token);
+        }
+        auth
+    }
+
+    // 账号名即预期的本地检查结果。
+    fn seeded_state() -> TestState {
+        let state = TestState::unlocked("health");
+        for (name, offset) in [
+            ("valid", Duration::days(7)),
+            ("expiring", Duration::hours(2)),
+            ("expired", -Duration::hours(1)),
+        ] {
+            import_account_from_auth_json(
+                &state,
+                name,
+                Vec::new(),
+                json!({
+                    "type": "codex",
+                    "access_token": access_token_expiring_in(offset),
+                    "email": format!("{name}@example.com"),
+                }),
+            )
+            .expect("应导入");
+        }
+        let blob =
+            crypto::encrypt_to_base64(&TEST_VAULT_KEY, b"{\"type\":\"codex\"}").expect("应加密");
+        state
+            .store
+            .create_account("malformed", &[], &blob, "email:broken")
+            .expect("应写入");
+        state
+    }
+
+    fn check(state: &TestState, remote: Option<bool>) -> HashMap<String, String> {
+        let names = state
+            .store
+            .list_accounts()
+            .expect("应读取")
+            .into_iter()
+            .map(|account| (account.id, account.name))
+            .collect::<HashMap<_, _>>();
+        tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .expect("应创建运行时")
+            .block_on(check_accounts_health(state, None, remote))
+            .expect("应完成检查")
+            .into_iter()
+            .map(|health| (names[&health.account_id].clone(), health.status))
+            .collect()
+    }
+
+    // 本地模拟的校验地址，对所有请求返回 401。
+    fn rejecting_endpoint() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("应监听端口");
+        let endpoint = format!("http://{}/me", listener.local_addr().expect("应有地址"));
+        std::thread::spawn(move || {
+            for mut stream in listener.incoming().flatten() {
+                let mut buffer = [0_u8; 4096];
+                let _ = stream.read(&mut buffer);
+                let _ = stream.write_all(
+                    b"HTTP/1.1 401 Unauthorized\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                );
+            }
+        });
+        endpoint
+    }
+
+    #[test]
+    fn expired_token_with_refresh_token_is_refreshable() {
+        let assessment = assess_expiry(&expired_auth(Some("refresh")), Utc::now());
+        assert_eq!(assessment.status, HealthStatus::Refreshable);
+        assert!(assessment.status.is_usable());
+    }
+
+    #[test]
+    fn rejected_refreshable_token_is_expired() {
+        let mut assessment = assess_expiry(&expired_auth(Some("refresh")), Utc::now());
+        apply_probe(&mut assessment, AuthProbe::Rejected("HTTP 401".to_string()));
+        assert_eq!(assessment.status, HealthStatus::Expired);
+    }
+
+    #[test]
+    fn expired_token_without_refresh_token_is_expired() {
+        let assessment = assess_expiry(&expired_auth(None), Utc::now());
+        assert_eq!(assessment.status, HealthStatus::Expired);
+        assert!(!assessment.status.is_usable());
+    }
+
+    #[test]
+    fn local_check_classifies_expiry_and_malformed_data() {
+        let state = seeded_state();
+        let statuses = check(&state, Some(false));
+        for name in ["valid", "expiring", "expired", "malformed"] {
+            assert_eq!(statuses[name], name);
+        }
+    }
+
+    #[test]
+    fn remote_rejection_marks_tokens_revoked() {
+        let state = seeded_state();
+        state
+            .store
+            .set_health_check_config(&HealthCheckConfig {
+                remote_enabled: true,
+                endpoint: rejecting_endpoint(),
+            })
+            .expect("应保存设置");
+        let statuses = check(&state, None);
+        assert_eq!(statuses["valid"], "revoked");
+        assert_eq!(statuses["malformed"], "malformed");
+        let stored = state
+            .store
+            .list_account_health()
+            .expect("应读取")
+            .into_iter()
+            .filter(|health| health.status == "revoked" && health.remote_checked)
+            .count();
+        assert_eq!(stored, 3);
+    }
+}
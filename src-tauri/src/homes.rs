@@ -0,0 +1,312 @@
+use crate::{
+    app_state::AppState,
+    codex::{
+        atomic_write, compute_fingerprint, create_private_dir, read_and_validate_auth_json,
+        secure_remove_dir, validate_auth_json,
+    },
+    compat::{auth_text_field, render_auth_json},
+    crypto,
+    models::{AccountHome, AccountHomesStatus},
+    store::AccountSecret,
+};
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Utc};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+use zeroize::Zeroize;
+
+const SYNCED_TOKEN_FIELDS: [&str; 3] = ["access_token", "refresh_token", "id_token"];
+
+pub fn account_home_dir(state: &AppState, account_id: &str) -> PathBuf {
+    state.store.homes_dir.join(account_id)
+}
+
+pub fn materialized_auth_path(state: &AppState, account_id: &str) -> Option<PathBuf> {
+    let auth_path = account_home_dir(state, account_id).join("auth.json");
+    auth_path.is_file().then_some(auth_path)
+}
+
+pub fn homes_status(state: &AppState) -> Result<AccountHomesStatus> {
+    let (enabled, share_config) = state.store.get_account_homes_config()?;
+    let homes = state
+        .store
+        .list_accounts()?
+        .into_iter()
+        .map(|account| {
+            let path = account_home_dir(state, &account.id);
+            AccountHome {
+                materialized: path.join("auth.json").is_file(),
+                path: path.display().to_string(),
+                account_id: account.id,
+                account_name: account.name,
+            }
+        })
+        .collect();
+    Ok(AccountHomesStatus {
+        enabled,
+        share_config,
+        homes_dir: state.store.homes_dir.display().to_string(),
+        homes,
+    })
+}
+
+pub fn materialize_account_home(state: &AppState, account_id: &str) -> Result<PathBuf> {
+    let secret = state
+        .store
+        .get_account_secret(account_id)?
+        .ok_or_else(|| anyhow!("账户不存在"))?;
+    let mut key = state.get_vault_key()?;
+    let result = refresh_account_home(state, &key, secret).map(|(home, _)| home);
+    key.zeroize();
+    result
+}
+
+pub fn sync_account_home(state: &AppState, account_id: &str) -> Result<()> {
+    let (enabled, _) = state.store.get_account_homes_config()?;
+    if enabled && state.is_vault_unlocked()? {
+        materialize_account_home(state, account_id)?;
+    }
+    Ok(())
+}
+
+// 返回 (已同步目录数, 回写到保险库的令牌数)；Codex 在独立目录中刷新令牌后需先回写再覆盖。
+pub fn sync_account_homes(state: &AppState) -> Result<(usize, usize)> {
+    let mut key = state.get_vault_key()?;
+    let result = (|| {
+        let mut synced = 0;
+        let mut pulled = 0;
+        for account in state.store.list_accounts()? {
+            let Some(secret) = state.store.get_account_secret(&account.id)? else {
+                continue;
+            };
+            if refresh_account_home(state, &key, secret)?.1 {
+                pulled += 1;
+            }
+            synced += 1;
+        }
+        Ok((synced, pulled))
+    })();
+    key.zeroize();
+    result
+}
+
+pub fn remove_account_home(state: &AppState, account_id: &str) -> Result<()> {
+    secure_remove_dir(&account_home_dir(state, account_id))
+}
+
+// Codex 可能已在独立目录中刷新或轮换令牌，删除前必须先回写保险库；未解锁时无法回写，拒绝清理。
+pub fn clear_account_homes(state: &AppState) -> Result<()> {
+    if !state.store.homes_dir.exists() {
+        return Ok(());
+    }
+    let mut key = state
+        .get_vault_key()
+        .map_err(|_| anyhow!("保险库未解锁，无法回写独立目录中刷新过的令牌，请先解锁再清理"))?;
+    let result = (|| -> Result<()> {
+        for account in state.store.list_accounts()? {
+            if let Some(secret) = state.store.get_account_secret(&account.id)? {
+                let _ = pull_refreshed_auth(state, &key, &secret);
+            }
+        }
+        Ok(())
+    })();
+    key.zeroize();
+    result?;
+    secure_remove_dir(&state.store.homes_dir)
+}
+
+// 先回写 Codex 在目录中轮换过的令牌再覆盖，否则会写回已作废的一次性 refresh_token。
+fn refresh_account_home(
+    state: &AppState,
+    key: &[u8],
+    secret: AccountSecret,
+) -> Result<(PathBuf, bool)> {
+    let pulled = pull_refreshed_auth(state, key, &secret)?;
+    let secret = if pulled {
+        state
+            .store
+            .get_account_secret(&secret.account.id)?
+            .unwrap_or(secret)
+    } else {
+        secret
+    };
+    Ok((write_account_home(state, key, &secret)?, pulled))
+}
+
+fn write_account_home(state: &AppState, key: &[u8], secret: &AccountSecret) -> Result<PathBuf> {
+    let decrypted = crypto::decrypt_from_base64(key, &secret.encrypted_auth_blob)?;
+    let auth_json = validate_auth_json(&String::from_utf8(decrypted)?)?;
+    let (_, compat) = state.resolve_codex_compat()?;
+    let auth_text =
+        serde_json::to_string_pretty(&render_auth_json(&auth_json, compat.profile.auth_layout))?;
+
+    create_private_dir(&state.store.homes_dir)?;
+    let home = account_home_dir(state, &secret.account.id);
+    create_private_dir(&home)?;
+    atomic_write(&home.join("auth.json"), &auth_text)?;
+    let (_, share_config) = state.store.get_account_homes_config()?;
+    link_shared_config(&home, share_config)?;
+    Ok(home)
+}
+
+fn pull_refreshed_auth(state: &AppState, key: &[u8], secret: &AccountSecret) -> Result<bool> {
+    let auth_path = account_home_dir(state, &secret.account.id).join("auth.json");
+    let Ok(metadata) = fs::metadata(&auth_path) else {
+        return Ok(false);
+    };
+    let modified: DateTime<Utc> = metadata.modified()?.into();
+    let updated_at = DateTime::parse_from_rfc3339(&secret.account.updated_at)
+        .map(|time| time.with_timezone(&Utc))
+        .unwrap_or(DateTime::<Utc>::MIN_UTC);
+    if modified <= updated_at {
+        return Ok(false);
+    }
+    let Ok(home_json) = read_and_validate_auth_json(&auth_path) else {
+        return Ok(false);
+    };
+    if compute_fingerprint(&home_json)? != secret.account.auth_fingerprint {
+        return Ok(false);
+    }
+
+    let decrypted = crypto::decrypt_from_base64(key, &secret.encrypted_auth_blob)?;
+    let vault_json = validate_auth_json(&String::from_utf8(decrypted)?)?;
+    let unchanged = SYNCED_TOKEN_FIELDS
+        .iter()
+        .all(|field| auth_text_field(&home_json, field) == auth_text_field(&vault_json, field));
+    if unchanged {
+        return Ok(false);
+    }
+
+    let auth_text = serde_json::to_string_pretty(&home_json)?;
+    let encrypted = crypto::encrypt_to_base64(key, auth_text.as_bytes())?;
+    state
+        .store
+        .update_account_auth_blob(&secret.account.id, &encrypted)?;
+    Ok(true)
+}
+
+fn link_shared_config(home: &Path, share_config: bool) -> Result<()> {
+    let link = home.join("config.toml");
+    if fs::symlink_metadata(&link).is_ok() {
+        fs::remove_file(&link)
+            .with_context(|| format!("移除旧配置链接失败: {}", link.display()))?;
+    }
+    if !share_config {
+        return Ok(());
+    }
+    let Some(shared) = dirs::home_dir().map(|home| home.join(".codex").join("config.toml")) else {
+        return Ok(());
+    };
+    if !shared.is_file() {
+        return Ok(());
+    }
+    #[cfg(unix)]
+    std::os::unix::fs::symlink(&shared, &link)
+        .with_context(|| format!("创建配置链接失败: {}", link.display()))?;
+    #[cfg(windows)]
+    if std::os::windows::fs::symlink_file(&shared, &link).is_err() {
+        fs::copy(&shared, &link)
+            .with_context(|| format!("复制共享配置失败: {}", link.display()))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{clear_account_homes, materialize_account_home, sync_account_homes};
+    use crate::{
+        app_state::AppState,
+        codex::compute_fingerprint,
+        crypto,
+        store::AppStore,
+        test_support::{TestState, TEST_VAULT_KEY},
+    };
+    use serde_json::{json, Value};
+    use std::{fs, path::PathBuf};
+
+    fn account_with_home(state: &TestState) -> (String, PathBuf) {
+        let auth = json!({ "type": "codex", "access_token": "token-a", "account_id": "acc-1" });
+        let blob = crypto::encrypt_to_base64(&TEST_VAULT_KEY, auth.to_string().as_bytes())
+            .expect("应加密");
+        let fingerprint = compute_fingerprint(&auth).expect("应生成指纹");
+        let account = state
+            .store
+            .create_account("work", &[], &blob, &fingerprint)
+            .expect("应创建账号");
+        let home = materialize_account_home(state, &account.id).expect("应生成目录");
+        (account.id, home)
+    }
+
+    #[test]
+    fn materialized_home_holds_decrypted_auth() {
+        let state = TestState::unlocked("homes");
+        let (_, home) = account_with_home(&state);
+        let written: Value =
+            serde_json::from_str(&fs::read_to_string(home.join("auth.json")).expect("应存在"))
+                .expect("JSON");
+        assert_eq!(written["access_token"], "token-a");
+        assert_eq!(sync_account_homes(&state).expect("应同步"), (1, 0));
+    }
+
+    #[test]
+    fn clearing_pulls_back_refreshed_tokens() {
+        let state = TestState::unlocked("homes");
+        let (account_id, home) = account_with_home(&state);
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        fs::write(
+            home.join("auth.json"),
+            json!({ "type": "codex", "access_token": "token-b", "account_id": "acc-1" })
+                .to_string(),
+        )
+        .expect("应模拟令牌刷新");
+        clear_account_homes(&state).expect("应清理目录");
+        assert!(!state.store.homes_dir.exists());
+
+        let secret = state
+            .store
+            .get_account_secret(&account_id)
+            .expect("应读取")
+            .expect("应存在");
+        let decrypted = crypto::decrypt_from_base64(&TEST_VAULT_KEY, &secret.encrypted_auth_blob)
+            .expect("应解密");
+        assert!(String::from_utf8(decrypted)
+            .expect("UTF-8")
+            .contains("token-b"));
+    }
+
+    #[test]
+    fn rematerializing_keeps_tokens_rotated_in_the_home() {
+        let state = TestState::unlocked("homes");
+        let (account_id, home) = account_with_home(&state);
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        fs::write(
+            home.join("auth.json"),
+            json!({ "type": "codex", "access_token": "token-b", "account_id": "acc-1" })
+                .to_string(),
+        )
+        .expect("应模拟令牌刷新");
+
+        materialize_account_home(&state, &account_id).expect("应重新生成目录");
+        let written: Value =
+            serde_json::from_str(&fs::read_to_string(home.join("auth.json")).expect("应存在"))
+                .expect("JSON");
+        assert_eq!(written["access_token"], "token-b");
+    }
+
+    #[test]
+    fn keeps_homes_while_vault_is_locked() {
+        let fixture = TestState::locked("homes");
+        let home = fixture.store.homes_dir.join("account");
+        fs::create_dir_all(&home).expect("应创建目录");
+        fs::write(home.join("auth.json"), "{}").expect("应写入");
+
+        // 重启应用不应删除独立目录，未解锁时也不能清理
+        let state =
+            AppState::initialize(AppStore::new(fixture.root.clone())).expect("应重新初始化");
+        assert!(home.join("auth.json").is_file());
+        assert!(clear_account_homes(&state).is_err());
+        assert!(home.join("auth.json").is_file());
+    }
+}
@@ -0,0 +1,296 @@
+use crate::{
+    account_import::{import_candidates, name_from_file, unique_tags, AuthCandidate},
+    app_state::AppState,
+    models::{BulkImportReport, ImporterInfo},
+};
+use anyhow::{anyhow, Context, Result};
+use serde_json::Value;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+const INDEX_FILE_NAMES: [&str; 3] = ["accounts.json", "index.json", "profiles.json"];
+const NESTED_AUTH_FILE: &str = "auth.json";
+
+// 其他切换工具的账号存储格式；只负责把来源解析为待导入文件，校验、加密与去重统一由 import_candidates 处理。
+pub trait AccountImporter {
+    fn id(&self) -> &'static str;
+    fn label(&self) -> &'static str;
+    fn description(&self) -> &'static str;
+    fn detect(&self, source: &Path) -> bool;
+    fn discover(&self, source: &Path) -> Result<Vec<AuthCandidate>>;
+}
+
+pub fn importers() -> Vec<Box<dyn AccountImporter>> {
+    // 自动识别时按顺序尝试，索引格式更具体，放在前面
+    vec![Box::new(JsonIndexImporter), Box::new(NamedFilesImporter)]
+}
+
+pub fn list_importers() -> Vec<ImporterInfo> {
+    importers()
+        .iter()
+        .map(|importer| ImporterInfo {
+            id: importer.id().to_string(),
+            label: importer.label().to_string(),
+            description: importer.description().to_string(),
+        })
+        .collect()
+}
+
+pub fn import_from(
+    state: &AppState,
+    importer_id: Option<&str>,
+    source: &Path,
+    tags: Vec<String>,
+) -> Result<BulkImportReport> {
+    let importer = match importer_id.filter(|id| !id.is_empty()) {
+        Some(id) => importers()
+            .into_iter()
+            .find(|importer| importer.id() == id)
+            .ok_or_else(|| anyhow!("未知的导入器: {id}"))?,
+        None => importers()
+            .into_iter()
+            .find(|importer| importer.detect(source))
+            .ok_or_else(|| anyhow!("无法识别的账号来源: {}", source.display()))?,
+    };
+    let mut candidates = importer.discover(source)?;
+    if candidates.is_empty() {
+        return Err(anyhow!(
+            "{}未在 {} 中找到账号",
+            importer.label(),
+            source.display()
+        ));
+    }
+    for candidate in &mut candidates {
+        candidate.tags = unique_tags(candidate.tags.iter().chain(tags.iter()).cloned().collect());
+    }
+    import_candidates(state, &candidates)
+}
+
+// 目录下的具名副本（work.json、auth-work.json）或按账号分的子目录（work/auth.json）。
+pub struct NamedFilesImporter;
+
+impl AccountImporter for NamedFilesImporter {
+    fn id(&self) -> &'static str {
+        "named_files"
+    }
+
+    fn label(&self) -> &'static str {
+        "具名登录文件目录"
+    }
+
+    fn description(&self) -> &'static str {
+        "目录中的 work.json、auth-work.json 或 work/auth.json，以文件或子目录名作为账号名"
+    }
+
+    fn detect(&self, source: &Path) -> bool {
+        source.is_dir()
+    }
+
+    fn discover(&self, source: &Path) -> Result<Vec<AuthCandidate>> {
+        let mut entries = fs::read_dir(source)
+            .with_context(|| format!("读取目录失败: {}", source.display()))?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .collect::<Vec<_>>();
+        entries.sort();
+        let mut candidates = Vec::new();
+        for path in entries {
+            if path.is_dir() {
+                let auth_path = path.join(NESTED_AUTH_FILE);
+                if auth_path.is_file() {
+                    candidates.push(AuthCandidate {
+                        name: path
+                            .file_name()
+                            .map(|name| name.to_string_lossy().to_string()),
+                        path: auth_path,
+                        tags: Vec::new(),
+                    });
+                }
+                continue;
+            }
+            let is_json = path
+                .extension()
+                .is_some_and(|extension| extension.eq_ignore_ascii_case("json"));
+            let is_index = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| INDEX_FILE_NAMES.contains(&name));
+            if is_json && !is_index {
+                candidates.push(AuthCandidate {
+                    name: name_from_file(&path),
+                    path,
+                    tags: Vec::new(),
+                });
+            }
+        }
+        Ok(candidates)
+    }
+}
+
+// JSON 索引 + 登录文件。索引可以是数组、{"accounts": [...]} 或以账号名为键的对象，
+// 条目为文件路径字符串或含 file/path、name、tags 的对象；相对路径按索引所在目录解析。
+pub struct JsonIndexImporter;
+
+impl JsonIndexImporter {
+    fn index_path(source: &Path) -> Option<PathBuf> {
+        if source.is_file() {
+            return Some(source.to_path_buf());
+        }
+        INDEX_FILE_NAMES
+            .iter()
+            .map(|name| source.join(name))
+            .find(|path| path.is_file())
+    }
+}
+
+impl AccountImporter for JsonIndexImporter {
+    fn id(&self) -> &'static str {
+        "json_index"
+    }
+
+    fn label(&self) -> &'static str {
+        "JSON 索引"
+    }
+
+    fn description(&self) -> &'static str {
+        "accounts.json / index.json 记录账号名、标签与登录文件路径"
+    }
+
+    fn detect(&self, source: &Path) -> bool {
+        Self::index_path(source)
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|text| serde_json::from_str::<Value>(&text).ok())
+            .is_some_and(|index| !index_entries(&index).is_empty())
+    }
+
+    fn discover(&self, source: &Path) -> Result<Vec<AuthCandidate>> {
+        let index_path = Self::index_path(source)
+            .ok_or_else(|| anyhow!("未找到账号索引文件: {}", source.display()))?;
+        let text = fs::read_to_string(&index_path)
+            .with_context(|| format!("读取索引文件失败: {}", index_path.display()))?;
+        let index = serde_json::from_str::<Value>(&text)
+            .with_context(|| format!("索引文件 JSON 解析失败: {}", index_path.display()))?;
+        let base_dir = index_path.parent().unwrap_or(Path::new("."));
+        index_entries(&index)
+            .into_iter()
+            .map(|(key, entry)| {
+                let file = match entry {
+                    Value::String(file) => Some(file.as_str()),
+                    _ => ["file", "path", "auth_file", "auth_path"]
+                        .iter()
+                        .find_map(|field| entry.get(field).and_then(Value::as_str)),
+                }
+                .ok_or_else(|| anyhow!("索引条目缺少登录文件路径: {}", key.unwrap_or("-")))?;
+                let name = ["name", "alias", "label"]
+                    .iter()
+                    .find_map(|field| entry.get(field).and_then(Value::as_str))
+                    .or(key)
+                    .map(ToString::to_string);
+                let tags = match entry.get("tags").or_else(|| entry.get("group")) {
+                    Some(Value::Array(tags)) => tags
+                        .iter()
+                        .filter_map(Value::as_str)
+                        .map(ToString::to_string)
+                        .collect(),
+                    Some(Value::String(tag)) => vec![tag.clone()],
+                    _ => Vec::new(),
+                };
+                Ok(AuthCandidate {
+                    path: base_dir.join(file),
+                    name,
+                    tags,
+                })
+            })
+            .collect()
+    }
+}
+
+fn index_entries(index: &Value) -> Vec<(Option<&str>, &Value)> {
+    let index = index.get("accounts").unwrap_or(index);
+    match index {
+        Value::Array(entries) => entries.iter().map(|entry| (None, entry)).collect(),
+        // 登录文件本身也是对象，带 access_token/tokens 的不视为索引
+        Value::Object(map) if !map.contains_key("access_token") && !map.contains_key("tokens") => {
+            map.iter()
+                .filter(|(_, entry)| entry.is_string() || entry.is_object())
+                .map(|(key, entry)| (Some(key.as_str()), entry))
+                .collect()
+        }
+        _ => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::import_from;
+    use crate::test_support::{auth_file_text, TestState};
+    use std::{fs, path::PathBuf};
+
+    fn indexed_dir(state: &TestState) -> PathBuf {
+        let indexed = state.root.join("indexed");
+        fs::create_dir_all(indexed.join("store")).expect("应创建目录");
+        fs::write(
+            indexed.join("store/a.json"),
+            auth_file_text("a@example.com"),
+        )
+        .expect("应写入");
+        fs::write(
+            indexed.join("store/b.json"),
+            auth_file_text("b@example.com"),
+        )
+        .expect("应写入");
+        fs::write(
+            indexed.join("accounts.json"),
+            r#"{"accounts":[{"name":"客户 A","file":"store/a.json","tags":["client"]},{"alias":"b","path":"store/b.json","group":"personal"}]}"#,
+        )
+        .expect("应写入");
+        indexed
+    }
+
+    fn named_dir(state: &TestState) -> PathBuf {
+        let named = state.root.join("named");
+        fs::create_dir_all(named.join("work")).expect("应创建目录");
+        fs::write(
+            named.join("work/auth.json"),
+            auth_file_text("work@example.com"),
+        )
+        .expect("应写入");
+        fs::write(named.join("personal.json"), auth_file_text("a@example.com")).expect("应写入");
+        named
+    }
+
+    #[test]
+    fn imports_accounts_listed_in_json_index() {
+        let state = TestState::unlocked("importers");
+        let indexed = indexed_dir(&state);
+        let report = import_from(&state, None, &indexed, vec!["migrated".into()]).expect("应导入");
+        assert_eq!((report.imported, report.failed), (2, 0));
+        let accounts = state.store.list_accounts().expect("应读取");
+        let client = accounts
+            .iter()
+            .find(|account| account.name == "客户 A")
+            .expect("应按索引命名");
+        assert_eq!(client.tags, ["client", "migrated"]);
+    }
+
+    #[test]
+    fn imports_named_directories_and_skips_known_accounts() {
+        let state = TestState::unlocked("importers");
+        import_from(&state, None, &indexed_dir(&state), Vec::new()).expect("应导入");
+        let report = import_from(&state, None, &named_dir(&state), Vec::new()).expect("应导入");
+        assert_eq!((report.imported, report.skipped), (1, 1));
+        assert!(state
+            .store
+            .list_accounts()
+            .expect("应读取")
+            .iter()
+            .any(|account| account.name == "work"));
+    }
+
+    #[test]
+    fn rejects_unknown_importer() {
+        let state = TestState::unlocked("importers");
+        assert!(import_from(&state, Some("unknown"), &named_dir(&state), Vec::new()).is_err());
+    }
+}
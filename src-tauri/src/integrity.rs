@@ -0,0 +1,190 @@
+use crate::{codex::secure_remove_file, models::IntegrityReport, store::AppStore};
+use anyhow::{Context, Result};
+use std::{
+    collections::HashSet,
+    fs,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+// 切换时先写快照再写历史，刚生成的快照可能尚未被引用，清理时跳过。
+const SNAPSHOT_GRACE_PERIOD: Duration = Duration::from_secs(60);
+
+pub fn check_integrity(store: &AppStore, repair: bool) -> Result<IntegrityReport> {
+    let (orphan_quota_snapshots, orphan_history_refs, orphan_project_bindings) =
+        store.count_orphan_rows()?;
+    let references = store.list_history_snapshot_paths()?;
+    let missing = references
+        .iter()
+        .filter(|(_, path)| !Path::new(path).is_file())
+        .map(|(id, _)| id.clone())
+        .collect::<Vec<_>>();
+    let unreferenced = unreferenced_snapshot_files(store, &references)?;
+
+    if repair {
+        store.repair_orphan_rows()?;
+        store.clear_history_snapshot_paths(&missing)?;
+        remove_snapshot_files(&unreferenced)?;
+    }
+    Ok(IntegrityReport {
+        orphan_quota_snapshots,
+        orphan_history_refs,
+        orphan_project_bindings,
+        missing_snapshot_files: missing.len(),
+        unreferenced_snapshot_files: unreferenced.len(),
+        repaired: repair,
+    })
+}
+
+pub fn remove_unreferenced_snapshots(store: &AppStore) -> Result<usize> {
+    let references = store.list_history_snapshot_paths()?;
+    remove_snapshot_files(&unreferenced_snapshot_files(store, &references)?)
+}
+
+pub fn remove_snapshot_files<P: AsRef<Path>>(paths: &[P]) -> Result<usize> {
+    let mut removed = 0;
+    for path in paths {
+        let path = path.as_ref();
+        if path.is_file() {
+            secure_remove_file(path)?;
+            removed += 1;
+        }
+    }
+    Ok(removed)
+}
+
+// 按文件名比对，数据目录迁移后历史中的绝对路径前缀可能不同。
+fn unreferenced_snapshot_files(
+    store: &AppStore,
+    references: &[(String, String)],
+) -> Result<Vec<PathBuf>> {
+    if !store.snapshots_dir.exists() {
+        return Ok(Vec::new());
+    }
+    let referenced = references
+        .iter()
+        .filter_map(|(_, path)| Path::new(path).file_name().map(|name| name.to_os_string()))
+        .collect::<HashSet<_>>();
+    let cutoff = SystemTime::now() - SNAPSHOT_GRACE_PERIOD;
+    let mut files = Vec::new();
+    for entry in fs::read_dir(&store.snapshots_dir)
+        .with_context(|| format!("读取快照目录失败: {}", store.snapshots_dir.display()))?
+    {
+        let entry = entry?;
+        let metadata = entry.metadata()?;
+        if !metadata.is_file() || referenced.contains(&entry.file_name()) {
+            continue;
+        }
+        if metadata
+            .modified()
+            .map(|time| time < cutoff)
+            .unwrap_or(true)
+        {
+            files.push(entry.path());
+        }
+    }
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::check_integrity;
+    use crate::{
+        snapshots::{release_snapshots, Snapshot},
+        store::AppStore,
+    };
+    use std::fs;
+
+    #[test]
+    fn delete_account_cascades_and_integrity_repairs_orphans() {
+        let root =
+            std::env::temp_dir().join(format!("codex-switch-integrity-{}", uuid::Uuid::new_v4()));
+        let store = AppStore::new(root.clone());
+        store.init().expect("应初始化");
+        let work = store
+            .create_account("work", &[], "blob-a", "account:a")
+            .expect("应创建账号");
+        let home = store
+            .create_account("home", &[], "blob-b", "account:b")
+            .expect("应创建账号");
+
+        let snapshot = Snapshot {
+            path: store.snapshots_dir.join("snapshot-1.json"),
+            hash: "snapshot-1".to_string(),
+        };
+        fs::write(&snapshot.path, "{}").expect("应写入快照");
+        let history_id = store
+            .create_switch_history(
+                Some(&work.id),
+                Some(&home.id),
+                Some(&snapshot),
+                "success",
+                None,
+            )
+            .expect("应写入历史");
+        store
+            .save_quota_snapshot(
+                &work.id,
+                "exact",
+                Some(1.0),
+                None,
+                "available",
+                None,
+                "usage_api",
+                90,
+                None,
+            )
+            .expect("应写入配额");
+        store
+            .upsert_project_binding("/tmp/work", Some(&work.id), None)
+            .expect("应写入绑定");
+
+        let detached = store.delete_account(&work.id).expect("应删除账号");
+        assert_eq!(release_snapshots(&store, &detached).expect("应删除快照"), 1);
+        let history = store
+            .get_switch_history(&history_id)
+            .expect("应读取")
+            .expect("历史应保留");
+        assert_eq!(history.from_account_id, None);
+        assert_eq!(history.to_account_id.as_deref(), Some(home.id.as_str()));
+        assert_eq!(history.snapshot_path, None);
+        assert!(store
+            .list_quota_snapshots(&work.id, 10)
+            .expect("应读取")
+            .is_empty());
+        assert!(store.list_project_bindings().expect("应读取").is_empty());
+
+        let conn = store.open_conn().expect("应打开数据库");
+        conn.pragma_update(None, "foreign_keys", false)
+            .expect("应关闭外键");
+        conn.execute_batch(
+            r#"
+            INSERT INTO quota_snapshots
+              VALUES ('q', 'gone', 'exact', 1, '%', 'available', NULL, 'usage_api', 90, NULL, 't');
+            INSERT INTO switch_history(id, from_account_id, to_account_id, snapshot_path, result, error_message, created_at)
+              VALUES ('h', 'gone', NULL, '/nowhere/snapshot-2.json', 'success', NULL, 't');
+            "#,
+        )
+        .expect("应写入孤儿数据");
+        let report = check_integrity(&store, false).expect("应检查");
+        assert_eq!(
+            (
+                report.orphan_quota_snapshots,
+                report.orphan_history_refs,
+                report.missing_snapshot_files
+            ),
+            (1, 1, 1)
+        );
+        check_integrity(&store, true).expect("应修复");
+        let report = check_integrity(&store, false).expect("应检查");
+        assert_eq!(
+            (
+                report.orphan_quota_snapshots,
+                report.orphan_history_refs,
+                report.missing_snapshot_files
+            ),
+            (0, 0, 0)
+        );
+        fs::remove_dir_all(&root).expect("应清理目录");
+    }
+}
@@ -1,146 +1,101 @@
+mod account_import;
+mod analytics;
 mod app_state;
+pub mod cli;
 mod codex;
+mod compat;
 mod crypto;
+mod device_auth;
+mod discovery;
+mod failover;
+mod guards;
+mod health;
+mod homes;
+mod importers;
+mod integrity;
+mod migrations;
 mod models;
+mod pools;
+mod projects;
 mod quota;
+mod rpc;
+mod service;
+mod shell;
+mod snapshots;
 mod store;
+#[cfg(test)]
+mod test_support;
+mod trash;
+mod vault_bundle;
+mod watcher;
 
+use account_import::{import_account_from_auth_json, unique_tags, NameSource};
+use analytics::{UsageCsvTable, UsageQuery};
 use anyhow::Context;
 use app_state::AppState;
 use codex::{
-    atomic_write, codex_auth_path, compute_fingerprint, count_codex_processes, create_snapshot,
-    kill_codex_processes, read_and_validate_auth_json, restart_codex, run_codex_login,
-    validate_auth_json,
+    codex_auth_path, count_codex_processes, launch_codex, read_and_validate_auth_json,
+    run_codex_login, start_codex_device_login, validate_auth_json, wait_for_codex_device_login,
+    IsolatedCodexHome,
 };
+use device_auth::{
+    request_device_code, wait_for_device_tokens, DeviceLoginFlow, DEFAULT_POLL_INTERVAL_SECONDS,
+};
+use failover::{FailoverConfig, MIN_FAILOVER_INTERVAL_SECONDS};
+use guards::SwitchGuardPolicy;
 use models::{
-    Account, CodexCliStatus, QuotaDashboardItem, QuotaRefreshPolicy, QuotaSnapshot,
-    RuntimeDiagnostics, SimpleStatus, SwitchHistory, SwitchResult,
+    Account, AccountEvent, AccountHealth, AccountHomesStatus, AccountPool, BulkImportReport,
+    CodexBinaryInfo, CodexCliStatus, ControlApiStatus, ControlToken, CreatedControlToken,
+    DeviceAuthConfig, DeviceLoginChallenge, FailoverEvent, HealthCheckConfig, ImporterInfo,
+    IntegrityReport, PoolDecision, PoolSwitchResult, ProjectAccountResolution, ProjectBinding,
+    QuotaDashboardItem, QuotaRefreshPolicy, QuotaSnapshot, RuntimeDiagnostics, SimpleStatus,
+    SnapshotPruneReport, SnapshotRetentionPolicy, SwitchHistory, SwitchHistoryPage,
+    SwitchHistoryQuery, SwitchResult, TrashedAccount, UsageReport, VaultExportSummary,
+    VaultImportReport,
 };
-use quota::{ensure_access_token, probe_quota};
+use pools::AccountPoolInput;
 use serde_json::Value;
-use std::{collections::HashMap, fs, path::PathBuf, time::Duration};
-use tauri::State;
+use service::SwitchOptions;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+use tauri::{AppHandle, Manager, State};
+use vault_bundle::{ConflictStrategy, VaultExportFilter};
 use zeroize::Zeroize;
 
 type CmdResult<T> = Result<T, String>;
 const LOGIN_AUTH_POLL_MAX_ATTEMPTS: usize = 8;
 const LOGIN_AUTH_POLL_INTERVAL_MS: u64 = 500;
+const CODEX_LOGIN_TIMEOUT_SECONDS: u64 = 900;
 
 fn map_error<T>(result: anyhow::Result<T>) -> CmdResult<T> {
     result.map_err(|error| error.to_string())
 }
 
-fn ensure_name(name: &str, auth_json: &Value) -> String {
-    if !name.trim().is_empty() {
-        return name.trim().to_string();
-    }
-    if let Some(email) = auth_json
-        .get("email")
-        .and_then(Value::as_str)
-        .map(str::trim)
-        .filter(|value| !value.is_empty())
-    {
-        return email.to_string();
-    }
-
-    if let Some(account_id) = auth_json
-        .get("account_id")
-        .and_then(Value::as_str)
-        .map(str::trim)
-        .filter(|value| !value.is_empty())
-    {
-        return account_id.to_string();
-    }
-
-    let suffix = compute_fingerprint(auth_json)
-        .ok()
-        .and_then(|fingerprint| fingerprint.split(':').nth(1).map(|hash| hash.to_string()))
-        .map(|hash| hash.chars().take(4).collect::<String>())
-        .filter(|hash| hash.len() == 4)
-        .unwrap_or_else(|| "0000".to_string());
-    format!("未命名账号-{suffix}")
-}
-
-fn unique_tags(tags: Vec<String>) -> Vec<String> {
-    let mut seen = std::collections::BTreeSet::new();
-    tags.into_iter()
-        .map(|tag| tag.trim().to_string())
-        .filter(|tag| !tag.is_empty())
-        .filter(|tag| seen.insert(tag.clone()))
-        .collect()
-}
-
-fn import_account_from_auth_json(
-    state: &AppState,
-    name: &str,
-    tags: Vec<String>,
-    previous_fingerprint: Option<&str>,
-    auth_json: Value,
-) -> anyhow::Result<Account> {
-    let mut key = state.get_vault_key()?;
-    let auth_text = serde_json::to_string_pretty(&auth_json)?;
-    let fingerprint = compute_fingerprint(&auth_json)?;
-
-    if let Some(previous) = previous_fingerprint {
-        if previous == fingerprint {
-            key.zeroize();
-            return Err(anyhow::anyhow!(
-                "登录完成，但检测到仍是当前账号。请在浏览器切换到新账号后重试。"
-            ));
-        }
-    }
-
-    if state
-        .store
-        .find_account_by_fingerprint(&fingerprint)?
-        .is_some()
-    {
-        key.zeroize();
-        return Err(anyhow::anyhow!("该账号已存在，已跳过重复导入。"));
-    }
-
-    let encrypted = crypto::encrypt_to_base64(&key, auth_text.as_bytes())?;
-    key.zeroize();
-    state.store.create_account(
-        &ensure_name(name, &auth_json),
-        &unique_tags(tags),
-        &encrypted,
-        &fingerprint,
-    )
-}
-
 fn import_account_from_current_auth(
     state: &AppState,
     name: &str,
     tags: Vec<String>,
-    previous_fingerprint: Option<&str>,
 ) -> anyhow::Result<Account> {
     let auth_path = codex_auth_path()?;
     let auth_json = read_and_validate_auth_json(&auth_path)?;
-    import_account_from_auth_json(state, name, tags, previous_fingerprint, auth_json)
+    import_account_from_auth_json(state, name, tags, auth_json)
 }
 
-async fn wait_for_login_auth_json(previous_auth_text: Option<&str>) -> anyhow::Result<Value> {
-    let auth_path = codex_auth_path()?;
-    let previous_auth_text = previous_auth_text.map(str::to_owned);
-
+async fn wait_for_login_auth_json(auth_path: &Path) -> anyhow::Result<Value> {
     for _ in 0..LOGIN_AUTH_POLL_MAX_ATTEMPTS {
-        if let Ok(current_text) = fs::read_to_string(&auth_path) {
-            let updated = match previous_auth_text.as_ref() {
-                Some(previous) => previous != &current_text,
-                None => true,
-            };
-            if updated {
-                if let Ok(json) = validate_auth_json(&current_text) {
-                    return Ok(json);
-                }
+        if let Ok(current_text) = fs::read_to_string(auth_path) {
+            if let Ok(json) = validate_auth_json(&current_text) {
+                return Ok(json);
             }
         }
         tokio::time::sleep(Duration::from_millis(LOGIN_AUTH_POLL_INTERVAL_MS)).await;
     }
 
     Err(anyhow::anyhow!(
-        "登录已结束，但 ~/.codex/auth.json 未在预期时间内更新。请确认浏览器授权已完成后重试。"
+        "登录已结束，但临时登录目录中的 auth.json 未在预期时间内生成。请确认浏览器授权已完成后重试。"
     ))
 }
 
@@ -169,6 +124,9 @@ fn init_vault(state: State<'_, AppState>, master_password: String) -> CmdResult<
 fn unlock_vault(state: State<'_, AppState>, master_password: String) -> CmdResult<SimpleStatus> {
     map_error((|| {
         state.unlock_vault(master_password.trim())?;
+        if state.store.get_account_homes_config()?.0 {
+            let _ = homes::sync_account_homes(&state);
+        }
         Ok(SimpleStatus {
             ok: true,
             message: "保险库已解锁".to_string(),
@@ -179,6 +137,10 @@ fn unlock_vault(state: State<'_, AppState>, master_password: String) -> CmdResul
 #[tauri::command]
 fn lock_vault(state: State<'_, AppState>) -> CmdResult<SimpleStatus> {
     map_error((|| {
+        // 已锁定时独立目录无法回写令牌，保留到下次解锁后同步
+        if state.is_vault_unlocked()? {
+            homes::clear_account_homes(&state)?;
+        }
         state.lock_vault()?;
         Ok(SimpleStatus {
             ok: true,
@@ -208,7 +170,7 @@ fn import_current_codex_auth(
     name: String,
     tags: Vec<String>,
 ) -> CmdResult<Account> {
-    map_error(import_account_from_current_auth(&state, &name, tags, None))
+    map_error(import_account_from_current_auth(&state, &name, tags))
 }
 
 #[tauri::command]
@@ -228,20 +190,77 @@ fn create_account_from_auth_file(
     tags: Vec<String>,
 ) -> CmdResult<Account> {
     map_error((|| {
-        let trimmed = path.trim();
-        if trimmed.is_empty() {
-            return Err(anyhow::anyhow!("认证文件路径不能为空"));
-        }
-
-        let file_path = PathBuf::from(trimmed);
-        let auth_text = fs::read_to_string(&file_path)
-            .with_context(|| format!("读取认证文件失败: {}", file_path.display()))?;
-        let auth_json =
-            validate_auth_json(&auth_text).with_context(|| "认证文件格式校验失败".to_string())?;
-        import_account_from_auth_json(&state, &name, tags, None, auth_json)
+        let auth_json = read_auth_file(&path)?;
+        import_account_from_auth_json(&state, &name, tags, auth_json)
     })())
 }
 
+fn read_auth_file(path: &str) -> anyhow::Result<Value> {
+    let trimmed = path.trim();
+    if trimmed.is_empty() {
+        return Err(anyhow::anyhow!("认证文件路径不能为空"));
+    }
+
+    let file_path = PathBuf::from(trimmed);
+    let auth_text = fs::read_to_string(&file_path)
+        .with_context(|| format!("读取认证文件失败: {}", file_path.display()))?;
+    validate_auth_json(&auth_text).with_context(|| "认证文件格式校验失败".to_string())
+}
+
+// 在独立的临时 CODEX_HOME 中完成浏览器登录，返回新生成的 auth.json。
+async fn login_in_isolated_home(state: &AppState) -> anyhow::Result<Value> {
+    let home = IsolatedCodexHome::create(&state.store.login_homes_dir)?;
+    let home_path = home.path().to_path_buf();
+    let (binary, compat) = state.resolve_codex_compat_async().await?;
+    tauri::async_runtime::spawn_blocking(move || {
+        run_codex_login(
+            CODEX_LOGIN_TIMEOUT_SECONDS,
+            &home_path,
+            binary.as_ref().map(|binary| binary.path.as_path()),
+            compat.profile.login_args,
+        )
+    })
+    .await
+    .map_err(|error| anyhow::anyhow!("等待登录任务失败: {error}"))??;
+
+    wait_for_login_auth_json(&home.auth_path()).await
+}
+
+#[tauri::command]
+fn bulk_import_auth_files(
+    state: State<'_, AppState>,
+    source: String,
+    tags: Vec<String>,
+    name_source: Option<NameSource>,
+) -> CmdResult<BulkImportReport> {
+    map_error(account_import::bulk_import_auth_files(
+        &state,
+        &source,
+        tags,
+        name_source.unwrap_or_default(),
+    ))
+}
+
+#[tauri::command]
+fn list_account_importers() -> CmdResult<Vec<ImporterInfo>> {
+    Ok(importers::list_importers())
+}
+
+#[tauri::command]
+fn import_accounts_from(
+    state: State<'_, AppState>,
+    importer: Option<String>,
+    source: String,
+    tags: Vec<String>,
+) -> CmdResult<BulkImportReport> {
+    map_error(importers::import_from(
+        &state,
+        importer.as_deref(),
+        Path::new(source.trim()),
+        tags,
+    ))
+}
+
 #[tauri::command]
 async fn create_account_from_login(
     state: State<'_, AppState>,
@@ -254,30 +273,227 @@ async fn create_account_from_login(
                 return Err(anyhow::anyhow!("请先解锁保险库，再进行登录添加"));
             }
 
-            let auth_path = codex_auth_path()?;
-            let previous_auth_text = fs::read_to_string(&auth_path).ok();
-            let previous_fingerprint = previous_auth_text
-                .as_deref()
-                .and_then(|text| validate_auth_json(text).ok())
-                .and_then(|json| compute_fingerprint(&json).ok());
+            let latest_auth_json = login_in_isolated_home(&state).await?;
+            import_account_from_auth_json(&state, &name, tags, latest_auth_json)
+        }
+        .await,
+    )
+}
+
+#[tauri::command]
+async fn reauthenticate_account_from_login(
+    state: State<'_, AppState>,
+    account_id: String,
+) -> CmdResult<Account> {
+    map_error(
+        async move {
+            if !state.is_vault_unlocked()? {
+                return Err(anyhow::anyhow!("请先解锁保险库，再重新登录"));
+            }
+            if state.store.get_account(account_id.trim())?.is_none() {
+                return Err(anyhow::anyhow!("目标账户不存在"));
+            }
+
+            let latest_auth_json = login_in_isolated_home(&state).await?;
+            account_import::reauthenticate_account(&state, &account_id, latest_auth_json, "login")
+        }
+        .await,
+    )
+}
+
+#[tauri::command]
+fn reauthenticate_account_from_file(
+    state: State<'_, AppState>,
+    account_id: String,
+    path: String,
+) -> CmdResult<Account> {
+    map_error((|| {
+        let auth_json = read_auth_file(&path)?;
+        account_import::reauthenticate_account(
+            &state,
+            &account_id,
+            auth_json,
+            &format!("file:{}", path.trim()),
+        )
+    })())
+}
 
-            tauri::async_runtime::spawn_blocking(|| run_codex_login(900))
+#[tauri::command]
+fn list_account_events(
+    state: State<'_, AppState>,
+    account_id: String,
+) -> CmdResult<Vec<AccountEvent>> {
+    map_error(state.store.list_account_events(account_id.trim(), 50))
+}
+
+#[tauri::command]
+fn get_device_auth_config(state: State<'_, AppState>) -> CmdResult<DeviceAuthConfig> {
+    map_error(state.store.get_device_auth_config())
+}
+
+#[tauri::command]
+fn set_device_auth_config(
+    state: State<'_, AppState>,
+    config: DeviceAuthConfig,
+) -> CmdResult<SimpleStatus> {
+    map_error((|| {
+        let config = DeviceAuthConfig {
+            device_authorization_url: config.device_authorization_url.trim().to_string(),
+            token_url: config.token_url.trim().to_string(),
+            client_id: config.client_id.trim().to_string(),
+            scope: config.scope.trim().to_string(),
+        };
+        for url in [&config.device_authorization_url, &config.token_url] {
+            if !url.starts_with("https://") && !url.starts_with("http://") {
+                return Err(anyhow::anyhow!(
+                    "设备码登录地址必须以 http:// 或 https:// 开头"
+                ));
+            }
+        }
+        if config.client_id.is_empty() {
+            return Err(anyhow::anyhow!("设备码登录 client_id 不能为空"));
+        }
+        state.store.set_device_auth_config(&config)?;
+        Ok(SimpleStatus {
+            ok: true,
+            message: "设备码登录配置已更新".to_string(),
+        })
+    })())
+}
+
+#[tauri::command]
+async fn start_device_login(
+    state: State<'_, AppState>,
+    prefer_native: Option<bool>,
+) -> CmdResult<DeviceLoginChallenge> {
+    map_error(
+        async move {
+            if !state.is_vault_unlocked()? {
+                return Err(anyhow::anyhow!("请先解锁保险库，再进行设备码登录"));
+            }
+
+            let mut cli_error = None;
+            if !prefer_native.unwrap_or(false) {
+                let home = IsolatedCodexHome::create(&state.store.login_homes_dir)?;
+                let home_path = home.path().to_path_buf();
+                let (binary, compat) = state.resolve_codex_compat_async().await?;
+                let started = tauri::async_runtime::spawn_blocking(move || {
+                    let version = binary.as_ref().and_then(|binary| binary.version.clone());
+                    let Some(args) = compat.profile.device_login_args else {
+                        return Err(format!(
+                            "Codex CLI {} 不支持设备码登录",
+                            version.as_deref().unwrap_or("(未知版本)")
+                        ));
+                    };
+                    start_codex_device_login(
+                        &home_path,
+                        binary.as_ref().map(|binary| binary.path.as_path()),
+                        args,
+                    )
+                })
                 .await
-                .map_err(|error| anyhow::anyhow!("等待登录任务失败: {error}"))??;
-
-            let latest_auth_json = wait_for_login_auth_json(previous_auth_text.as_deref()).await?;
-            import_account_from_auth_json(
-                &state,
-                &name,
-                tags,
-                previous_fingerprint.as_deref(),
-                latest_auth_json,
-            )
+                .map_err(|error| anyhow::anyhow!("等待设备码登录任务失败: {error}"))?;
+                match started {
+                    Ok(login) => {
+                        let user_code = login.user_code.clone();
+                        let verification_uri = login.verification_uri.clone();
+                        let expires_at = chrono::Utc::now()
+                            + chrono::Duration::seconds(CODEX_LOGIN_TIMEOUT_SECONDS as i64);
+                        let session_id = state.register_device_login(
+                            DeviceLoginFlow::Cli { login, home },
+                            expires_at,
+                        )?;
+                        return Ok(DeviceLoginChallenge {
+                            session_id,
+                            mode: "cli".to_string(),
+                            user_code,
+                            verification_uri,
+                            verification_uri_complete: None,
+                            expires_at: expires_at.to_rfc3339(),
+                            interval_seconds: DEFAULT_POLL_INTERVAL_SECONDS,
+                        });
+                    }
+                    Err(error) => cli_error = Some(error),
+                }
+            }
+
+            let config = state.store.get_device_auth_config()?;
+            let grant = request_device_code(&config)
+                .await
+                .map_err(|error| match &cli_error {
+                    Some(cli_error) => anyhow::anyhow!(
+                        "Codex CLI 设备码登录不可用（{cli_error}），原生设备码登录也失败：{error}"
+                    ),
+                    None => error,
+                })?;
+            let session_id = state.register_device_login(
+                DeviceLoginFlow::Native {
+                    config,
+                    grant: grant.clone(),
+                },
+                grant.expires_at,
+            )?;
+            Ok(DeviceLoginChallenge {
+                session_id,
+                mode: "native".to_string(),
+                user_code: grant.user_code,
+                verification_uri: grant.verification_uri,
+                verification_uri_complete: grant.verification_uri_complete,
+                expires_at: grant.expires_at.to_rfc3339(),
+                interval_seconds: grant.interval_seconds,
+            })
         }
         .await,
     )
 }
 
+#[tauri::command]
+async fn complete_device_login(
+    state: State<'_, AppState>,
+    session_id: String,
+    name: String,
+    tags: Vec<String>,
+) -> CmdResult<Account> {
+    let session_id = session_id.trim().to_string();
+    let (flow, cancelled) = map_error(state.begin_device_login(&session_id))?;
+    let result = async {
+        match flow {
+            DeviceLoginFlow::Cli { mut login, home } => {
+                tauri::async_runtime::spawn_blocking(move || {
+                    wait_for_codex_device_login(&mut login, CODEX_LOGIN_TIMEOUT_SECONDS, &cancelled)
+                })
+                .await
+                .map_err(|error| anyhow::anyhow!("等待设备码登录任务失败: {error}"))??;
+                let latest_auth_json = wait_for_login_auth_json(&home.auth_path()).await?;
+                import_account_from_auth_json(&state, &name, tags, latest_auth_json)
+            }
+            DeviceLoginFlow::Native { config, grant } => {
+                let auth_json = wait_for_device_tokens(&config, &grant, &cancelled).await?;
+                validate_auth_json(&auth_json.to_string())?;
+                import_account_from_auth_json(&state, &name, tags, auth_json)
+            }
+        }
+    }
+    .await;
+    state.finish_device_login(&session_id).ok();
+    map_error(result)
+}
+
+#[tauri::command]
+fn cancel_device_login(state: State<'_, AppState>, session_id: String) -> CmdResult<SimpleStatus> {
+    map_error((|| {
+        let cancelled = state.cancel_device_login(session_id.trim())?;
+        Ok(SimpleStatus {
+            ok: cancelled,
+            message: if cancelled {
+                "设备码登录已取消".to_string()
+            } else {
+                "设备码登录会话不存在或已结束".to_string()
+            },
+        })
+    })())
+}
+
 #[tauri::command]
 fn list_accounts(state: State<'_, AppState>) -> CmdResult<Vec<Account>> {
     map_error(state.store.list_accounts())
@@ -304,10 +520,53 @@ fn update_account_meta(
 #[tauri::command]
 fn delete_account(state: State<'_, AppState>, id: String) -> CmdResult<SimpleStatus> {
     map_error((|| {
-        state.store.delete_account(id.trim())?;
+        trash::trash_account(&state, id.trim())?;
         Ok(SimpleStatus {
             ok: true,
-            message: "账户已删除".to_string(),
+            message: "账户已移入回收站".to_string(),
+        })
+    })())
+}
+
+#[tauri::command]
+fn list_trashed_accounts(state: State<'_, AppState>) -> CmdResult<Vec<TrashedAccount>> {
+    map_error(trash::list_trash(&state))
+}
+
+#[tauri::command]
+fn restore_account(state: State<'_, AppState>, id: String) -> CmdResult<Account> {
+    map_error(trash::restore_account(&state, id.trim()))
+}
+
+#[tauri::command]
+fn purge_account(state: State<'_, AppState>, id: String) -> CmdResult<SimpleStatus> {
+    map_error((|| {
+        trash::purge_account(&state, id.trim())?;
+        Ok(SimpleStatus {
+            ok: true,
+            message: "账户已永久删除".to_string(),
+        })
+    })())
+}
+
+#[tauri::command]
+fn get_trash_retention_days(state: State<'_, AppState>) -> CmdResult<u32> {
+    map_error(state.store.get_trash_retention_days())
+}
+
+#[tauri::command]
+fn set_trash_retention_days(state: State<'_, AppState>, days: u32) -> CmdResult<SimpleStatus> {
+    map_error((|| {
+        if !(1..=trash::MAX_TRASH_RETENTION_DAYS).contains(&days) {
+            return Err(anyhow::anyhow!(
+                "保留天数需在 1 到 {} 之间",
+                trash::MAX_TRASH_RETENTION_DAYS
+            ));
+        }
+        state.store.set_trash_retention_days(days)?;
+        Ok(SimpleStatus {
+            ok: true,
+            message: format!("回收站中的账户将在 {days} 天后永久删除"),
         })
     })())
 }
@@ -317,110 +576,138 @@ fn switch_account(
     state: State<'_, AppState>,
     id: String,
     force_restart: bool,
+    override_guards: Option<bool>,
 ) -> CmdResult<SwitchResult> {
-    map_error((|| {
-        let account_secret = state
-            .store
-            .get_account_secret(id.trim())?
-            .ok_or_else(|| anyhow::anyhow!("目标账户不存在"))?;
-
-        let from_account = state.store.get_current_account_id()?;
-        let mut key = state.get_vault_key()?;
-        let decrypted = crypto::decrypt_from_base64(&key, &account_secret.encrypted_auth_blob)?;
-        key.zeroize();
-        let auth_text = String::from_utf8(decrypted)?;
-        validate_auth_json(&auth_text)?;
-
-        let auth_path = codex_auth_path()?;
-        let snapshot_path = create_snapshot(&auth_path, &state.store.snapshots_dir)?;
-
-        let write_result = atomic_write(&auth_path, &auth_text);
-        if let Err(error) = write_result {
-            let history_id = state.store.create_switch_history(
-                from_account.as_deref(),
-                account_secret.account.id.as_str(),
-                snapshot_path.as_deref(),
-                "failed",
-                Some(&error.to_string()),
-            )?;
-            return Ok(SwitchResult {
-                success: false,
-                history_id,
-                snapshot_path: snapshot_path.map(|path| path.display().to_string()),
-                message: format!("切换失败：{error}"),
-            });
-        }
+    map_error(service::switch_to_account(
+        &state,
+        &id,
+        SwitchOptions {
+            force_restart,
+            override_guards: override_guards.unwrap_or(false),
+            ..SwitchOptions::default()
+        },
+    ))
+}
 
-        let mut killed_count = 0usize;
-        if force_restart {
-            killed_count = kill_codex_processes();
-            let _ = restart_codex();
-        }
-        state
-            .store
-            .mark_account_used(account_secret.account.id.as_str())?;
-        let history_id = state.store.create_switch_history(
-            from_account.as_deref(),
-            account_secret.account.id.as_str(),
-            snapshot_path.as_deref(),
-            "success",
-            None,
-        )?;
-        Ok(SwitchResult {
-            success: true,
-            history_id,
-            snapshot_path: snapshot_path.map(|path| path.display().to_string()),
-            message: if force_restart {
-                format!("切换完成，已处理 {killed_count} 个 Codex 进程")
-            } else {
-                "切换完成".to_string()
-            },
+#[tauri::command]
+fn get_switch_guard_policy(state: State<'_, AppState>) -> CmdResult<SwitchGuardPolicy> {
+    map_error(state.store.get_switch_guard_policy())
+}
+
+#[tauri::command]
+fn set_switch_guard_policy(
+    state: State<'_, AppState>,
+    policy: SwitchGuardPolicy,
+) -> CmdResult<SimpleStatus> {
+    map_error((|| {
+        state.store.set_switch_guard_policy(&policy)?;
+        Ok(SimpleStatus {
+            ok: true,
+            message: "切换守卫设置已更新".to_string(),
         })
     })())
 }
 
 #[tauri::command]
-fn rollback_to_history(state: State<'_, AppState>, history_id: String) -> CmdResult<SwitchResult> {
+fn get_failover_config(state: State<'_, AppState>) -> CmdResult<FailoverConfig> {
+    map_error(state.store.get_failover_config())
+}
+
+#[tauri::command]
+fn set_failover_config(
+    state: State<'_, AppState>,
+    config: FailoverConfig,
+) -> CmdResult<SimpleStatus> {
     map_error((|| {
-        let history = state
-            .store
-            .get_switch_history(history_id.trim())?
-            .ok_or_else(|| anyhow::anyhow!("历史记录不存在"))?;
-        let snapshot_path = history
-            .snapshot_path
-            .as_ref()
-            .map(PathBuf::from)
-            .ok_or_else(|| anyhow::anyhow!("该历史记录没有可回滚快照"))?;
-        if !snapshot_path.exists() {
+        if config.interval_seconds < MIN_FAILOVER_INTERVAL_SECONDS {
             return Err(anyhow::anyhow!(
-                "快照文件不存在: {}",
-                snapshot_path.display()
+                "探测间隔不能少于 {MIN_FAILOVER_INTERVAL_SECONDS} 秒"
             ));
         }
-        let snapshot_content = std::fs::read_to_string(&snapshot_path)?;
-        validate_auth_json(&snapshot_content)?;
-        let auth_path = codex_auth_path()?;
-        let current_snapshot = create_snapshot(&auth_path, &state.store.snapshots_dir)?;
-        atomic_write(&auth_path, &snapshot_content)?;
-        let killed_count = kill_codex_processes();
-        let _ = restart_codex();
-        let created_history_id = state.store.create_switch_history(
-            history.from_account_id.as_deref(),
-            history.to_account_id.as_str(),
-            current_snapshot.as_deref(),
-            "rolled_back",
-            None,
-        )?;
+        if config
+            .threshold_percent
+            .is_some_and(|threshold| !(0.0..=100.0).contains(&threshold))
+        {
+            return Err(anyhow::anyhow!("切换阈值必须在 0 到 100 之间"));
+        }
+        let config = FailoverConfig {
+            preferred_tags: unique_tags(config.preferred_tags),
+            ..config
+        };
+        state.store.set_failover_config(&config)?;
+        Ok(SimpleStatus {
+            ok: true,
+            message: "自动切换设置已更新".to_string(),
+        })
+    })())
+}
+
+#[tauri::command]
+async fn run_failover_check(state: State<'_, AppState>) -> CmdResult<Option<FailoverEvent>> {
+    map_error(
+        async move {
+            let config = state.store.get_failover_config()?;
+            failover::check_and_failover(&state, &config).await
+        }
+        .await,
+    )
+}
+
+#[tauri::command]
+fn list_account_pools(state: State<'_, AppState>) -> CmdResult<Vec<AccountPool>> {
+    map_error(state.store.list_account_pools())
+}
+
+#[tauri::command]
+fn save_account_pool(state: State<'_, AppState>, pool: AccountPoolInput) -> CmdResult<AccountPool> {
+    map_error(pools::save_pool(&state, &pool))
+}
 
-        Ok(SwitchResult {
-            success: true,
-            history_id: created_history_id,
-            snapshot_path: Some(snapshot_path.display().to_string()),
-            message: format!("回滚完成，已处理 {killed_count} 个 Codex 进程"),
+#[tauri::command]
+fn delete_account_pool(state: State<'_, AppState>, id: String) -> CmdResult<SimpleStatus> {
+    map_error((|| {
+        state.store.delete_account_pool(id.trim())?;
+        Ok(SimpleStatus {
+            ok: true,
+            message: "账号池已删除".to_string(),
         })
     })())
 }
 
+#[tauri::command]
+fn preview_pool_rotation(state: State<'_, AppState>, pool_id: String) -> CmdResult<PoolDecision> {
+    map_error((|| {
+        let pool = state
+            .store
+            .get_account_pool(pool_id.trim())?
+            .ok_or_else(|| anyhow::anyhow!("账号池不存在"))?;
+        pools::plan_rotation(&state, &pool)
+    })())
+}
+
+#[tauri::command]
+fn switch_to_next_in_pool(
+    state: State<'_, AppState>,
+    pool_id: String,
+    force_restart: bool,
+    override_guards: Option<bool>,
+) -> CmdResult<PoolSwitchResult> {
+    map_error(pools::switch_to_next_in_pool(
+        &state,
+        &pool_id,
+        SwitchOptions {
+            force_restart,
+            override_guards: override_guards.unwrap_or(false),
+            ..SwitchOptions::default()
+        },
+    ))
+}
+
+#[tauri::command]
+fn rollback_to_history(state: State<'_, AppState>, history_id: String) -> CmdResult<SwitchResult> {
+    map_error(service::rollback_to_history(&state, &history_id))
+}
+
 #[tauri::command]
 fn list_switch_history(
     state: State<'_, AppState>,
@@ -429,114 +716,63 @@ fn list_switch_history(
     map_error(state.store.list_switch_history(limit.unwrap_or(100)))
 }
 
+#[tauri::command]
+fn query_switch_history(
+    state: State<'_, AppState>,
+    query: Option<SwitchHistoryQuery>,
+) -> CmdResult<SwitchHistoryPage> {
+    map_error(state.store.query_switch_history(&query.unwrap_or_default()))
+}
+
 #[tauri::command]
 async fn refresh_quota(
     state: State<'_, AppState>,
     account_id: Option<String>,
     force: Option<bool>,
 ) -> CmdResult<Vec<QuotaSnapshot>> {
-    map_error(
-        async move {
-            let mut key = state.get_vault_key()?;
-            let accounts = if let Some(account_id) = account_id {
-                let account = state
-                    .store
-                    .get_account_secret(account_id.trim())?
-                    .ok_or_else(|| anyhow::anyhow!("账户不存在"))?;
-                vec![account]
-            } else {
-                let account_list = state.store.list_accounts()?;
-                let mut result = Vec::new();
-                for account in account_list {
-                    if let Some(secret) = state.store.get_account_secret(&account.id)? {
-                        result.push(secret);
-                    }
-                }
-                result
-            };
-
-            let (timeout_ms, ttl_seconds, _max_concurrency) = state.store.get_quota_policy()?;
-            let force_refresh = force.unwrap_or(false);
-            let mut snapshots = Vec::new();
-
-            for account in accounts {
-                if !force_refresh {
-                    if let Some(existing) =
-                        state.store.latest_quota_by_account(&account.account.id)?
-                    {
-                        let age = chrono::DateTime::parse_from_rfc3339(&existing.created_at)
-                            .map(|time| {
-                                chrono::Utc::now()
-                                    .signed_duration_since(time.with_timezone(&chrono::Utc))
-                                    .num_seconds()
-                            })
-                            .unwrap_or(i64::MAX);
-                        if age >= 0 && age as u64 <= ttl_seconds {
-                            snapshots.push(existing);
-                            continue;
-                        }
-                    }
-                }
+    map_error(service::refresh_quota(&state, account_id.as_deref(), force.unwrap_or(false)).await)
+}
 
-                let decrypted = crypto::decrypt_from_base64(&key, &account.encrypted_auth_blob)?;
-                let auth_text = String::from_utf8(decrypted)?;
-                let auth_json: Value = serde_json::from_str(&auth_text)?;
-                let access_token = ensure_access_token(&auth_json)?;
-                let chatgpt_account_id = auth_json
-                    .get("account_id")
-                    .and_then(Value::as_str)
-                    .map(str::trim)
-                    .filter(|value| !value.is_empty());
-                let probe = probe_quota(&access_token, chatgpt_account_id, timeout_ms).await;
-                let saved = state.store.save_quota_snapshot(
-                    &account.account.id,
-                    &probe.mode,
-                    probe.remaining_value,
-                    probe.remaining_unit.as_deref(),
-                    &probe.quota_state,
-                    probe.reset_at.as_deref(),
-                    &probe.source,
-                    probe.confidence,
-                    probe.reason.as_deref(),
-                )?;
-                snapshots.push(saved);
-            }
-            key.zeroize();
-            Ok(snapshots)
-        }
-        .await,
-    )
+#[tauri::command]
+async fn check_accounts_health(
+    state: State<'_, AppState>,
+    account_id: Option<String>,
+    remote: Option<bool>,
+) -> CmdResult<Vec<AccountHealth>> {
+    map_error(health::check_accounts_health(&state, account_id.as_deref(), remote).await)
 }
 
 #[tauri::command]
-fn get_quota_dashboard(state: State<'_, AppState>) -> CmdResult<Vec<QuotaDashboardItem>> {
+fn get_health_check_config(state: State<'_, AppState>) -> CmdResult<HealthCheckConfig> {
+    map_error(state.store.get_health_check_config())
+}
+
+#[tauri::command]
+fn set_health_check_config(
+    state: State<'_, AppState>,
+    config: HealthCheckConfig,
+) -> CmdResult<SimpleStatus> {
     map_error((|| {
-        let accounts = state.store.list_accounts()?;
-        let snapshots = state.store.list_latest_quota_snapshots()?;
-        let snapshot_map: HashMap<String, QuotaSnapshot> = snapshots
-            .into_iter()
-            .map(|snapshot| (snapshot.account_id.clone(), snapshot))
-            .collect();
-
-        let mut dashboard = accounts
-            .into_iter()
-            .map(|account| {
-                let snapshot = snapshot_map.get(&account.id).cloned();
-                QuotaDashboardItem { account, snapshot }
-            })
-            .collect::<Vec<_>>();
-
-        dashboard.sort_by_key(|item| {
-            state_rank(
-                item.snapshot
-                    .as_ref()
-                    .map(|snapshot| snapshot.quota_state.as_str()),
-            )
-        });
-        Ok(dashboard)
+        let config = HealthCheckConfig {
+            remote_enabled: config.remote_enabled,
+            endpoint: config.endpoint.trim().to_string(),
+        };
+        if !config.endpoint.starts_with("https://") && !config.endpoint.starts_with("http://") {
+            return Err(anyhow::anyhow!("校验地址必须以 http:// 或 https:// 开头"));
+        }
+        state.store.set_health_check_config(&config)?;
+        Ok(SimpleStatus {
+            ok: true,
+            message: "凭据健康检查设置已更新".to_string(),
+        })
     })())
 }
 
+#[tauri::command]
+fn get_quota_dashboard(state: State<'_, AppState>) -> CmdResult<Vec<QuotaDashboardItem>> {
+    map_error(service::quota_dashboard(&state))
+}
+
 #[tauri::command]
 fn list_quota_snapshots(
     state: State<'_, AppState>,
@@ -569,26 +805,266 @@ fn set_quota_refresh_policy(
     })())
 }
 
+#[tauri::command]
+fn get_snapshot_retention(state: State<'_, AppState>) -> CmdResult<SnapshotRetentionPolicy> {
+    map_error(state.store.get_snapshot_retention())
+}
+
+#[tauri::command]
+fn set_snapshot_retention(
+    state: State<'_, AppState>,
+    policy: SnapshotRetentionPolicy,
+) -> CmdResult<SimpleStatus> {
+    map_error((|| {
+        state
+            .store
+            .set_snapshot_retention(&SnapshotRetentionPolicy {
+                keep_count: policy.keep_count.clamp(1, 1000),
+                max_age_days: policy.max_age_days.clamp(1, 3650),
+                protect_recent: policy.protect_recent.min(100),
+            })?;
+        Ok(SimpleStatus {
+            ok: true,
+            message: "快照保留策略已更新".to_string(),
+        })
+    })())
+}
+
+#[tauri::command]
+fn prune_snapshots(state: State<'_, AppState>, dry_run: bool) -> CmdResult<SnapshotPruneReport> {
+    map_error(snapshots::prune_snapshots(&state.store, dry_run))
+}
+
+#[tauri::command]
+fn get_usage_analytics(
+    state: State<'_, AppState>,
+    query: Option<UsageQuery>,
+) -> CmdResult<UsageReport> {
+    map_error(analytics::usage_report(
+        &state.store,
+        &query.unwrap_or_default(),
+    ))
+}
+
+#[tauri::command]
+fn export_usage_csv(
+    state: State<'_, AppState>,
+    query: Option<UsageQuery>,
+    table: UsageCsvTable,
+    path: String,
+) -> CmdResult<SimpleStatus> {
+    map_error((|| {
+        let report = analytics::usage_report(&state.store, &query.unwrap_or_default())?;
+        let path = PathBuf::from(path.trim());
+        fs::write(&path, analytics::report_to_csv(&report, table))
+            .with_context(|| format!("写入 CSV 失败: {}", path.display()))?;
+        Ok(SimpleStatus {
+            ok: true,
+            message: format!("已导出到 {}", path.display()),
+        })
+    })())
+}
+
 #[tauri::command]
 fn get_runtime_diagnostics(state: State<'_, AppState>) -> CmdResult<RuntimeDiagnostics> {
+    map_error(service::runtime_diagnostics(&state))
+}
+
+#[tauri::command]
+fn export_vault(
+    state: State<'_, AppState>,
+    path: String,
+    passphrase: String,
+    filter: Option<VaultExportFilter>,
+) -> CmdResult<VaultExportSummary> {
+    let mut passphrase = passphrase;
+    let result = vault_bundle::export_vault(
+        &state,
+        Path::new(path.trim()),
+        &passphrase,
+        &filter.unwrap_or_default(),
+    );
+    passphrase.zeroize();
+    map_error(result)
+}
+
+#[tauri::command]
+fn import_vault(
+    state: State<'_, AppState>,
+    path: String,
+    passphrase: String,
+    strategy: Option<ConflictStrategy>,
+) -> CmdResult<VaultImportReport> {
+    let mut passphrase = passphrase;
+    let result = vault_bundle::import_vault(
+        &state,
+        Path::new(path.trim()),
+        &passphrase,
+        strategy.unwrap_or_default(),
+    );
+    passphrase.zeroize();
+    map_error(result)
+}
+
+#[tauri::command]
+fn check_data_integrity(state: State<'_, AppState>, repair: bool) -> CmdResult<IntegrityReport> {
+    map_error(integrity::check_integrity(&state.store, repair))
+}
+
+#[tauri::command]
+async fn discover_codex_binaries(state: State<'_, AppState>) -> CmdResult<Vec<CodexBinaryInfo>> {
+    map_error(
+        async move {
+            let pinned = state.store.get_codex_binary_path()?;
+            let binaries = tauri::async_runtime::spawn_blocking(move || {
+                discovery::discover_codex_binaries(pinned.as_deref())
+            })
+            .await
+            .map_err(|error| anyhow::anyhow!("等待 Codex CLI 探测任务失败: {error}"))?;
+            // 重新扫描通常意味着刚安装或升级了 Codex，丢弃旧的探测结果。
+            state.invalidate_codex_binary()?;
+            Ok(binaries
+                .into_iter()
+                .map(|binary| CodexBinaryInfo {
+                    path: binary.path.display().to_string(),
+                    pinned: binary.source == "pinned",
+                    version: binary.version,
+                    source: binary.source,
+                })
+                .collect())
+        }
+        .await,
+    )
+}
+
+#[tauri::command]
+fn set_codex_binary_path(
+    state: State<'_, AppState>,
+    path: Option<String>,
+) -> CmdResult<SimpleStatus> {
     map_error((|| {
-        let auth_path = codex_auth_path()?;
-        let codex_auth_exists = auth_path.exists();
-        let schema_ok = if codex_auth_exists {
-            read_and_validate_auth_json(&auth_path).is_ok()
-        } else {
-            false
+        let path = path
+            .as_deref()
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .map(PathBuf::from);
+        let Some(path) = path else {
+            state.store.set_codex_binary_path(None)?;
+            state.invalidate_codex_binary()?;
+            return Ok(SimpleStatus {
+                ok: true,
+                message: "已取消固定 Codex CLI 路径，将自动探测".to_string(),
+            });
         };
 
-        let process_count = count_codex_processes();
+        if !path.is_file() {
+            return Err(anyhow::anyhow!("Codex CLI 文件不存在: {}", path.display()));
+        }
+        let version = discovery::probe_codex_version(&path);
+        state.store.set_codex_binary_path(Some(&path))?;
+        state.invalidate_codex_binary()?;
+        Ok(SimpleStatus {
+            ok: version.is_some(),
+            message: match version {
+                Some(version) => format!("已固定 Codex CLI：{}（版本 {version}）", path.display()),
+                None => format!(
+                    "已固定 Codex CLI：{}，但未能读取版本号，请确认该文件可执行",
+                    path.display()
+                ),
+            },
+        })
+    })())
+}
 
-        Ok(RuntimeDiagnostics {
-            codex_auth_path: auth_path.display().to_string(),
-            codex_auth_exists,
-            app_data_dir: state.store.base_dir.display().to_string(),
-            db_path: state.store.db_path.display().to_string(),
-            schema_ok,
-            process_count,
+#[tauri::command]
+fn list_project_bindings(state: State<'_, AppState>) -> CmdResult<Vec<ProjectBinding>> {
+    map_error(state.store.list_project_bindings())
+}
+
+#[tauri::command]
+fn set_project_binding(
+    state: State<'_, AppState>,
+    path: String,
+    account_id: Option<String>,
+    tag: Option<String>,
+) -> CmdResult<ProjectBinding> {
+    map_error((|| {
+        let trimmed = path.trim();
+        if trimmed.is_empty() {
+            return Err(anyhow::anyhow!("项目目录不能为空"));
+        }
+        let dir = projects::normalize_project_path(Path::new(trimmed));
+        if !dir.is_dir() {
+            return Err(anyhow::anyhow!("项目目录不存在: {}", dir.display()));
+        }
+
+        let account_id = account_id
+            .as_deref()
+            .map(str::trim)
+            .filter(|value| !value.is_empty());
+        let tag = tag
+            .as_deref()
+            .map(str::trim)
+            .filter(|value| !value.is_empty());
+        match (account_id, tag) {
+            (Some(_), Some(_)) | (None, None) => {
+                return Err(anyhow::anyhow!("项目绑定需要且只能指定账号或标签其中之一"))
+            }
+            (Some(account_id), None) => {
+                state
+                    .store
+                    .get_account(account_id)?
+                    .ok_or_else(|| anyhow::anyhow!("目标账户不存在"))?;
+            }
+            (None, Some(_)) => {}
+        }
+        state
+            .store
+            .upsert_project_binding(&dir.display().to_string(), account_id, tag)
+    })())
+}
+
+#[tauri::command]
+fn delete_project_binding(state: State<'_, AppState>, id: String) -> CmdResult<SimpleStatus> {
+    map_error((|| {
+        state.store.delete_project_binding(id.trim())?;
+        Ok(SimpleStatus {
+            ok: true,
+            message: "项目绑定已删除".to_string(),
+        })
+    })())
+}
+
+#[tauri::command]
+fn resolve_project_account(
+    state: State<'_, AppState>,
+    path: String,
+) -> CmdResult<ProjectAccountResolution> {
+    map_error((|| {
+        let trimmed = path.trim();
+        if trimmed.is_empty() {
+            return Err(anyhow::anyhow!("项目路径不能为空"));
+        }
+        projects::resolve_for_path(&state.store, Path::new(trimmed))
+    })())
+}
+
+#[tauri::command]
+fn get_project_auto_switch(state: State<'_, AppState>) -> CmdResult<bool> {
+    map_error(state.store.get_project_auto_switch())
+}
+
+#[tauri::command]
+fn set_project_auto_switch(state: State<'_, AppState>, enabled: bool) -> CmdResult<SimpleStatus> {
+    map_error((|| {
+        state.store.set_project_auto_switch(enabled)?;
+        Ok(SimpleStatus {
+            ok: true,
+            message: if enabled {
+                "已开启项目自动切换，新启动的 Codex 进程将按工作目录切换账号".to_string()
+            } else {
+                "已关闭项目自动切换".to_string()
+            },
         })
     })())
 }
@@ -605,28 +1081,152 @@ fn get_codex_cli_status() -> CmdResult<CodexCliStatus> {
     })())
 }
 
-fn state_rank(state: Option<&str>) -> u8 {
-    match state.unwrap_or("unknown") {
-        "available" => 0,
-        "near_limit" => 1,
-        "exhausted" => 2,
-        _ => 3,
-    }
+#[tauri::command]
+fn get_control_api_status(state: State<'_, AppState>) -> CmdResult<ControlApiStatus> {
+    map_error(rpc::control_status(&state))
+}
+
+#[tauri::command]
+fn set_control_api_enabled(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    enabled: bool,
+) -> CmdResult<ControlApiStatus> {
+    map_error((|| {
+        state.store.set_control_api_enabled(enabled)?;
+        if enabled {
+            rpc::start_control_server(&app)?;
+        } else {
+            rpc::stop_control_server(&state)?;
+        }
+        rpc::control_status(&state)
+    })())
+}
+
+#[tauri::command]
+fn list_control_tokens(state: State<'_, AppState>) -> CmdResult<Vec<ControlToken>> {
+    map_error(state.store.list_control_tokens())
+}
+
+#[tauri::command]
+fn create_control_token(
+    state: State<'_, AppState>,
+    name: String,
+) -> CmdResult<CreatedControlToken> {
+    map_error((|| {
+        if name.trim().is_empty() {
+            return Err(anyhow::anyhow!("请填写客户端名称"));
+        }
+        let secret = crypto::generate_token();
+        let token = state
+            .store
+            .create_control_token(name.trim(), &crypto::hash_token(&secret))?;
+        Ok(CreatedControlToken { token, secret })
+    })())
+}
+
+#[tauri::command]
+fn revoke_control_token(state: State<'_, AppState>, id: String) -> CmdResult<SimpleStatus> {
+    map_error((|| {
+        state.store.delete_control_token(id.trim())?;
+        Ok(SimpleStatus {
+            ok: true,
+            message: "客户端令牌已撤销".to_string(),
+        })
+    })())
+}
+
+#[tauri::command]
+fn get_account_homes(state: State<'_, AppState>) -> CmdResult<AccountHomesStatus> {
+    map_error(homes::homes_status(&state))
+}
+
+#[tauri::command]
+fn set_account_homes_config(
+    state: State<'_, AppState>,
+    enabled: bool,
+    share_config: bool,
+) -> CmdResult<AccountHomesStatus> {
+    map_error((|| {
+        // 先清理再保存设置，清理失败（如未解锁）时保持启用状态
+        if !enabled {
+            homes::clear_account_homes(&state)?;
+        }
+        state
+            .store
+            .set_account_homes_config(enabled, share_config)?;
+        if enabled && state.is_vault_unlocked()? {
+            homes::sync_account_homes(&state)?;
+        }
+        homes::homes_status(&state)
+    })())
+}
+
+#[tauri::command]
+fn sync_account_homes(state: State<'_, AppState>) -> CmdResult<SimpleStatus> {
+    map_error((|| {
+        if !state.store.get_account_homes_config()?.0 {
+            return Err(anyhow::anyhow!("请先启用账号独立目录"));
+        }
+        let (synced, pulled) = homes::sync_account_homes(&state)?;
+        Ok(SimpleStatus {
+            ok: true,
+            message: format!("已同步 {synced} 个账号目录，回写 {pulled} 份刷新后的登录数据"),
+        })
+    })())
+}
+
+#[tauri::command]
+fn launch_codex_with_account(
+    state: State<'_, AppState>,
+    account_id: String,
+    cwd: Option<String>,
+) -> CmdResult<SimpleStatus> {
+    map_error((|| {
+        let home = homes::materialize_account_home(&state, account_id.trim())?;
+        let cwd = cwd
+            .map(|cwd| PathBuf::from(cwd.trim()))
+            .filter(|cwd| !cwd.as_os_str().is_empty());
+        if let Some(cwd) = cwd.as_deref().filter(|cwd| !cwd.is_dir()) {
+            return Err(anyhow::anyhow!("工作目录不存在: {}", cwd.display()));
+        }
+        let binary = state.resolve_codex_binary()?;
+        launch_codex(
+            binary.as_ref().map(|binary| binary.path.as_path()),
+            Some(&home),
+            cwd.as_deref(),
+        )?;
+        Ok(SimpleStatus {
+            ok: true,
+            message: format!("已使用独立目录启动 Codex：{}", home.display()),
+        })
+    })())
 }
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
-    let base_dir = dirs::data_local_dir()
-        .or_else(dirs::home_dir)
-        .unwrap_or_else(|| std::env::current_dir().unwrap_or_else(|_| PathBuf::from(".")))
-        .join("codex-switch");
-    let store = store::AppStore::new(base_dir);
+    let store = store::AppStore::new(service::default_base_dir());
     let state = AppState::initialize(store).expect("初始化应用状态失败");
 
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_dialog::init())
         .manage(state)
+        .setup(|app| {
+            watcher::spawn_project_watcher(app.handle().clone());
+            trash::spawn_trash_purger(app.handle().clone());
+            failover::spawn_failover_monitor(app.handle().clone());
+            device_auth::spawn_device_login_reaper(app.handle().clone());
+            if app
+                .state::<AppState>()
+                .store
+                .get_control_api_enabled()
+                .unwrap_or(false)
+            {
+                let _ = rpc::start_control_server(app.handle());
+            }
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
             init_vault,
             unlock_vault,
@@ -635,20 +1235,84 @@ pub fn run() {
             import_current_codex_auth,
             create_account_from_import,
             create_account_from_auth_file,
+            bulk_import_auth_files,
+            list_account_importers,
+            import_accounts_from,
             create_account_from_login,
+            reauthenticate_account_from_login,
+            reauthenticate_account_from_file,
+            list_account_events,
+            get_device_auth_config,
+            set_device_auth_config,
+            start_device_login,
+            complete_device_login,
+            cancel_device_login,
             list_accounts,
             update_account_meta,
             delete_account,
+            list_trashed_accounts,
+            restore_account,
+            purge_account,
+            get_trash_retention_days,
+            set_trash_retention_days,
             switch_account,
+            get_switch_guard_policy,
+            set_switch_guard_policy,
+            get_failover_config,
+            set_failover_config,
+            run_failover_check,
+            list_account_pools,
+            save_account_pool,
+            delete_account_pool,
+            preview_pool_rotation,
+            switch_to_next_in_pool,
             rollback_to_history,
             list_switch_history,
+            query_switch_history,
+            get_usage_analytics,
+            export_usage_csv,
+            export_vault,
+            import_vault,
             refresh_quota,
+            check_accounts_health,
+            get_health_check_config,
+            set_health_check_config,
             get_quota_dashboard,
             list_quota_snapshots,
             set_quota_refresh_policy,
+            get_snapshot_retention,
+            set_snapshot_retention,
+            prune_snapshots,
             get_runtime_diagnostics,
+            check_data_integrity,
             get_codex_cli_status,
+            discover_codex_binaries,
+            set_codex_binary_path,
+            list_project_bindings,
+            set_project_binding,
+            delete_project_binding,
+            resolve_project_account,
+            get_project_auto_switch,
+            set_project_auto_switch,
+            get_control_api_status,
+            set_control_api_enabled,
+            list_control_tokens,
+            create_control_token,
+            revoke_control_token,
+            get_account_homes,
+            set_account_homes_config,
+            sync_account_homes,
+            launch_codex_with_account,
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while running tauri application")
+        .run(|app, event| {
+            // 退出时密钥仍在内存中，回写轮换过的令牌后删除解密的独立目录
+            if let tauri::RunEvent::Exit = event {
+                let state = app.state::<AppState>();
+                if state.is_vault_unlocked().unwrap_or(false) {
+                    let _ = homes::clear_account_homes(&state);
+                }
+            }
+        });
 }
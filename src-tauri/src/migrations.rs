@@ -0,0 +1,660 @@
+use crate::codex::create_private_dir;
+use anyhow::{anyhow, Context, Result};
+use chrono::Utc;
+use rusqlite::{params, Connection, Transaction};
+use std::path::{Path, PathBuf};
+
+struct Migration {
+    version: i64,
+    name: &'static str,
+    apply: fn(&Transaction<'_>) -> Result<()>,
+}
+
+// 只允许在末尾追加；v1-v6 需兼容引入版本号之前通过 ensure_column 升级过的数据库，因此保持幂等。
+static MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        name: "initial_schema",
+        apply: initial_schema,
+    },
+    Migration {
+        version: 2,
+        name: "device_auth_settings",
+        apply: device_auth_settings,
+    },
+    Migration {
+        version: 3,
+        name: "codex_binary_path",
+        apply: codex_binary_path,
+    },
+    Migration {
+        version: 4,
+        name: "project_bindings",
+        apply: project_bindings,
+    },
+    Migration {
+        version: 5,
+        name: "control_api",
+        apply: control_api,
+    },
+    Migration {
+        version: 6,
+        name: "account_homes",
+        apply: account_homes,
+    },
+    Migration {
+        version: 7,
+        name: "foreign_keys",
+        apply: foreign_keys,
+    },
+    Migration {
+        version: 8,
+        name: "account_trash",
+        apply: account_trash,
+    },
+    Migration {
+        version: 9,
+        name: "snapshot_retention",
+        apply: snapshot_retention,
+    },
+    Migration {
+        version: 10,
+        name: "snapshot_hash",
+        apply: snapshot_hash,
+    },
+    Migration {
+        version: 11,
+        name: "account_events",
+        apply: account_events,
+    },
+    Migration {
+        version: 12,
+        name: "account_health",
+        apply: account_health,
+    },
+    Migration {
+        version: 13,
+        name: "switch_guards",
+        apply: switch_guards,
+    },
+    Migration {
+        version: 14,
+        name: "auto_failover",
+        apply: auto_failover,
+    },
+    Migration {
+        version: 15,
+        name: "account_pools",
+        apply: account_pools,
+    },
+    Migration {
+        version: 16,
+        name: "failover_restart_mode",
+        apply: failover_restart_mode,
+    },
+];
+
+const INITIAL_SCHEMA: &str = r#"
+    CREATE TABLE IF NOT EXISTS app_settings (
+      id INTEGER PRIMARY KEY CHECK (id = 1),
+      vault_salt TEXT,
+      default_account_id TEXT,
+      cli_restart_mode TEXT NOT NULL DEFAULT 'force',
+      quota_timeout_ms INTEGER NOT NULL DEFAULT 5000,
+      quota_cache_ttl_seconds INTEGER NOT NULL DEFAULT 180,
+      quota_max_concurrency INTEGER NOT NULL DEFAULT 3,
+      updated_at TEXT NOT NULL
+    );
+
+    CREATE TABLE IF NOT EXISTS accounts (
+      id TEXT PRIMARY KEY,
+      name TEXT NOT NULL,
+      tags_json TEXT NOT NULL,
+      encrypted_auth_blob TEXT NOT NULL,
+      auth_fingerprint TEXT NOT NULL,
+      created_at TEXT NOT NULL,
+      updated_at TEXT NOT NULL,
+      last_used_at TEXT
+    );
+
+    CREATE TABLE IF NOT EXISTS switch_history (
+      id TEXT PRIMARY KEY,
+      from_account_id TEXT,
+      to_account_id TEXT NOT NULL,
+      snapshot_path TEXT,
+      result TEXT NOT NULL,
+      error_message TEXT,
+      created_at TEXT NOT NULL
+    );
+
+    CREATE TABLE IF NOT EXISTS quota_snapshots (
+      id TEXT PRIMARY KEY,
+      account_id TEXT NOT NULL,
+      mode TEXT NOT NULL,
+      remaining_value REAL,
+      remaining_unit TEXT,
+      quota_state TEXT NOT NULL,
+      reset_at TEXT,
+      source TEXT NOT NULL,
+      confidence INTEGER NOT NULL,
+      reason TEXT,
+      created_at TEXT NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS idx_quota_snapshots_account_created_at
+      ON quota_snapshots(account_id, created_at DESC);
+"#;
+
+pub fn latest_version() -> i64 {
+    MIGRATIONS
+        .last()
+        .map(|migration| migration.version)
+        .unwrap_or(0)
+}
+
+pub fn schema_version(conn: &Connection) -> Result<i64> {
+    conn.pragma_query_value(None, "user_version", |row| row.get(0))
+        .context("读取数据库版本失败")
+}
+
+pub fn migrate(conn: &mut Connection, backup_dir: &Path) -> Result<Option<PathBuf>> {
+    migrate_to(conn, backup_dir, latest_version())
+}
+
+fn migrate_to(conn: &mut Connection, backup_dir: &Path, target: i64) -> Result<Option<PathBuf>> {
+    let current = schema_version(conn)?;
+    if current > latest_version() {
+        return Err(anyhow!(
+            "数据库版本 v{current} 高于当前应用支持的 v{}，请升级 Codex Switch",
+            latest_version()
+        ));
+    }
+    let pending = MIGRATIONS
+        .iter()
+        .filter(|migration| migration.version > current && migration.version <= target)
+        .collect::<Vec<_>>();
+    if pending.is_empty() {
+        return Ok(None);
+    }
+
+    let backup = if has_tables(conn)? {
+        Some(backup_database(conn, backup_dir, current)?)
+    } else {
+        None
+    };
+    for migration in pending {
+        let tx = conn.transaction()?;
+        (migration.apply)(&tx).with_context(|| {
+            format!("数据库迁移失败: v{} {}", migration.version, migration.name)
+        })?;
+        tx.pragma_update(None, "user_version", migration.version)?;
+        tx.commit()
+            .with_context(|| format!("提交数据库迁移失败: v{}", migration.version))?;
+    }
+    Ok(backup)
+}
+
+fn has_tables(conn: &Connection) -> Result<bool> {
+    let count: i64 = conn.query_row(
+        "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name NOT LIKE 'sqlite_%'",
+        [],
+        |row| row.get(0),
+    )?;
+    Ok(count > 0)
+}
+
+fn backup_database(conn: &Connection, backup_dir: &Path, version: i64) -> Result<PathBuf> {
+    create_private_dir(backup_dir)?;
+    let backup_path = backup_dir.join(format!(
+        "codex-switch-v{version}-{}.db",
+        Utc::now().format("%Y%m%d%H%M%S%.3f")
+    ));
+    conn.execute("VACUUM INTO ?1", params![backup_path.display().to_string()])
+        .with_context(|| format!("迁移前备份数据库失败: {}", backup_path.display()))?;
+    Ok(backup_path)
+}
+
+fn add_column(conn: &Connection, table: &str, column: &str, definition: &str) -> Result<()> {
+    let mut stmt = conn.prepare(&format!("PRAGMA table_info({table})"))?;
+    let exists = stmt
+        .query_map([], |row| row.get::<_, String>(1))?
+        .filter_map(Result::ok)
+        .any(|name| name == column);
+    if !exists {
+        conn.execute_batch(&format!(
+            "ALTER TABLE {table} ADD COLUMN {column} {definition}"
+        ))
+        .with_context(|| format!("升级数据表失败: {table}.{column}"))?;
+    }
+    Ok(())
+}
+
+fn initial_schema(tx: &Transaction<'_>) -> Result<()> {
+    tx.execute_batch(INITIAL_SCHEMA)?;
+    Ok(())
+}
+
+fn device_auth_settings(tx: &Transaction<'_>) -> Result<()> {
+    add_column(tx, "app_settings", "device_authorization_url", "TEXT")?;
+    add_column(tx, "app_settings", "device_token_url", "TEXT")?;
+    add_column(tx, "app_settings", "device_client_id", "TEXT")?;
+    add_column(tx, "app_settings", "device_scope", "TEXT")
+}
+
+fn codex_binary_path(tx: &Transaction<'_>) -> Result<()> {
+    add_column(tx, "app_settings", "codex_binary_path", "TEXT")
+}
+
+fn project_bindings(tx: &Transaction<'_>) -> Result<()> {
+    tx.execute_batch(
+        r#"
+        CREATE TABLE IF NOT EXISTS project_bindings (
+          id TEXT PRIMARY KEY,
+          path TEXT NOT NULL UNIQUE,
+          account_id TEXT,
+          tag TEXT,
+          created_at TEXT NOT NULL,
+          updated_at TEXT NOT NULL
+        );
+        "#,
+    )?;
+    add_column(
+        tx,
+        "app_settings",
+        "project_auto_switch",
+        "INTEGER NOT NULL DEFAULT 0",
+    )
+}
+
+fn control_api(tx: &Transaction<'_>) -> Result<()> {
+    tx.execute_batch(
+        r#"
+        CREATE TABLE IF NOT EXISTS control_tokens (
+          id TEXT PRIMARY KEY,
+          name TEXT NOT NULL,
+          token_hash TEXT NOT NULL UNIQUE,
+          created_at TEXT NOT NULL,
+          last_used_at TEXT
+        );
+        "#,
+    )?;
+    add_column(
+        tx,
+        "app_settings",
+        "control_api_enabled",
+        "INTEGER NOT NULL DEFAULT 0",
+    )
+}
+
+fn account_homes(tx: &Transaction<'_>) -> Result<()> {
+    add_column(
+        tx,
+        "app_settings",
+        "account_homes_enabled",
+        "INTEGER NOT NULL DEFAULT 0",
+    )?;
+    add_column(
+        tx,
+        "app_settings",
+        "account_homes_share_config",
+        "INTEGER NOT NULL DEFAULT 1",
+    )
+}
+
+// SQLite 不支持给已有表追加外键，只能重建；重建前清理孤儿数据，否则开启外键后无法写入。
+// 历史记录保留但去除已删除账号的引用，配额快照与账号级项目绑定随账号删除。
+fn foreign_keys(tx: &Transaction<'_>) -> Result<()> {
+    tx.execute_batch(
+        r#"
+        CREATE TABLE switch_history_new (
+          id TEXT PRIMARY KEY,
+          from_account_id TEXT REFERENCES accounts(id) ON DELETE SET NULL,
+          to_account_id TEXT REFERENCES accounts(id) ON DELETE SET NULL,
+          snapshot_path TEXT,
+          result TEXT NOT NULL,
+          error_message TEXT,
+          created_at TEXT NOT NULL
+        );
+        INSERT INTO switch_history_new
+        SELECT
+          id,
+          CASE WHEN from_account_id IN (SELECT id FROM accounts) THEN from_account_id END,
+          CASE WHEN to_account_id IN (SELECT id FROM accounts) THEN to_account_id END,
+          snapshot_path, result, error_message, created_at
+        FROM switch_history;
+        DROP TABLE switch_history;
+        ALTER TABLE switch_history_new RENAME TO switch_history;
+        CREATE INDEX idx_switch_history_created_at ON switch_history(created_at DESC);
+
+        CREATE TABLE quota_snapshots_new (
+          id TEXT PRIMARY KEY,
+          account_id TEXT NOT NULL REFERENCES accounts(id) ON DELETE CASCADE,
+          mode TEXT NOT NULL,
+          remaining_value REAL,
+          remaining_unit TEXT,
+          quota_state TEXT NOT NULL,
+          reset_at TEXT,
+          source TEXT NOT NULL,
+          confidence INTEGER NOT NULL,
+          reason TEXT,
+          created_at TEXT NOT NULL
+        );
+        INSERT INTO quota_snapshots_new
+        SELECT
+          id, account_id, mode, remaining_value, remaining_unit, quota_state, reset_at,
+          source, confidence, reason, created_at
+        FROM quota_snapshots
+        WHERE account_id IN (SELECT id FROM accounts);
+        DROP TABLE quota_snapshots;
+        ALTER TABLE quota_snapshots_new RENAME TO quota_snapshots;
+        CREATE INDEX idx_quota_snapshots_account_created_at
+          ON quota_snapshots(account_id, created_at DESC);
+
+        CREATE TABLE project_bindings_new (
+          id TEXT PRIMARY KEY,
+          path TEXT NOT NULL UNIQUE,
+          account_id TEXT REFERENCES accounts(id) ON DELETE CASCADE,
+          tag TEXT,
+          created_at TEXT NOT NULL,
+          updated_at TEXT NOT NULL
+        );
+        INSERT INTO project_bindings_new
+        SELECT id, path, account_id, tag, created_at, updated_at
+        FROM project_bindings
+        WHERE account_id IS NULL OR account_id IN (SELECT id FROM accounts);
+        DROP TABLE project_bindings;
+        ALTER TABLE project_bindings_new RENAME TO project_bindings;
+
+        UPDATE app_settings SET default_account_id = NULL
+        WHERE default_account_id NOT IN (SELECT id FROM accounts);
+        "#,
+    )?;
+    Ok(())
+}
+
+fn account_trash(tx: &Transaction<'_>) -> Result<()> {
+    tx.execute_batch(
+        r#"
+        ALTER TABLE accounts ADD COLUMN deleted_at TEXT;
+        ALTER TABLE app_settings ADD COLUMN trash_retention_days INTEGER NOT NULL DEFAULT 30;
+        "#,
+    )?;
+    Ok(())
+}
+
+fn snapshot_retention(tx: &Transaction<'_>) -> Result<()> {
+    tx.execute_batch(
+        r#"
+        ALTER TABLE switch_history ADD COLUMN snapshot_pruned_at TEXT;
+        ALTER TABLE app_settings ADD COLUMN snapshot_keep_count INTEGER NOT NULL DEFAULT 20;
+        ALTER TABLE app_settings ADD COLUMN snapshot_max_age_days INTEGER NOT NULL DEFAULT 30;
+        ALTER TABLE app_settings ADD COLUMN snapshot_protect_recent INTEGER NOT NULL DEFAULT 5;
+        "#,
+    )?;
+    Ok(())
+}
+
+// 旧快照文件的哈希需读取文件计算，由 snapshots::adopt_legacy_snapshots 在启动时补齐。
+fn snapshot_hash(tx: &Transaction<'_>) -> Result<()> {
+    tx.execute_batch(
+        r#"
+        ALTER TABLE switch_history ADD COLUMN snapshot_hash TEXT;
+        CREATE INDEX idx_switch_history_snapshot_hash ON switch_history(snapshot_hash);
+        "#,
+    )?;
+    Ok(())
+}
+
+fn account_events(tx: &Transaction<'_>) -> Result<()> {
+    tx.execute_batch(
+        r#"
+        CREATE TABLE account_events (
+          id TEXT PRIMARY KEY,
+          account_id TEXT NOT NULL REFERENCES accounts(id) ON DELETE CASCADE,
+          kind TEXT NOT NULL,
+          detail TEXT,
+          created_at TEXT NOT NULL
+        );
+        CREATE INDEX idx_account_events_account_created_at
+          ON account_events(account_id, created_at DESC);
+        "#,
+    )?;
+    Ok(())
+}
+
+fn account_health(tx: &Transaction<'_>) -> Result<()> {
+    tx.execute_batch(
+        r#"
+        CREATE TABLE account_health (
+          account_id TEXT PRIMARY KEY REFERENCES accounts(id) ON DELETE CASCADE,
+          status TEXT NOT NULL,
+          expires_at TEXT,
+          reason TEXT,
+          remote_checked INTEGER NOT NULL DEFAULT 0,
+          checked_at TEXT NOT NULL
+        );
+        ALTER TABLE app_settings ADD COLUMN health_remote_check INTEGER NOT NULL DEFAULT 0;
+        ALTER TABLE app_settings ADD COLUMN health_check_url TEXT;
+        "#,
+    )?;
+    Ok(())
+}
+
+fn switch_guards(tx: &Transaction<'_>) -> Result<()> {
+    tx.execute_batch(
+        r#"
+        ALTER TABLE app_settings ADD COLUMN guard_token_expiry TEXT NOT NULL DEFAULT 'block';
+        ALTER TABLE app_settings ADD COLUMN guard_health TEXT NOT NULL DEFAULT 'block';
+        ALTER TABLE app_settings ADD COLUMN guard_quota TEXT NOT NULL DEFAULT 'warn';
+        ALTER TABLE app_settings ADD COLUMN guard_running_processes TEXT NOT NULL DEFAULT 'warn';
+        "#,
+    )?;
+    Ok(())
+}
+
+// 重启策略起初沿用初始表结构中预留的 cli_restart_mode，v16 起改为独立列。
+fn auto_failover(tx: &Transaction<'_>) -> Result<()> {
+    tx.execute_batch(
+        r#"
+        ALTER TABLE app_settings ADD COLUMN failover_enabled INTEGER NOT NULL DEFAULT 0;
+        ALTER TABLE app_settings ADD COLUMN failover_interval_seconds INTEGER NOT NULL DEFAULT 300;
+        ALTER TABLE app_settings ADD COLUMN failover_threshold_percent REAL;
+        ALTER TABLE app_settings ADD COLUMN failover_cooldown_seconds INTEGER NOT NULL DEFAULT 1800;
+        ALTER TABLE app_settings ADD COLUMN failover_preferred_tags TEXT NOT NULL DEFAULT '[]';
+        "#,
+    )?;
+    Ok(())
+}
+
+// 成员可以显式列出（顺序即轮询顺序），也可以按标签动态匹配；last_account_id 为轮询游标。
+fn account_pools(tx: &Transaction<'_>) -> Result<()> {
+    tx.execute_batch(
+        r#"
+        CREATE TABLE account_pools (
+          id TEXT PRIMARY KEY,
+          name TEXT NOT NULL UNIQUE,
+          tag TEXT,
+          account_ids_json TEXT NOT NULL DEFAULT '[]',
+          strategy TEXT NOT NULL DEFAULT 'round_robin',
+          last_account_id TEXT,
+          created_at TEXT NOT NULL,
+          updated_at TEXT NOT NULL
+        );
+        "#,
+    )?;
+    Ok(())
+}
+
+// 自动切换改用独立列并默认不结束运行中的 Codex；已启用自动切换的设置保留原有选择。
+fn failover_restart_mode(tx: &Transaction<'_>) -> Result<()> {
+    tx.execute_batch(
+        r#"
+        ALTER TABLE app_settings ADD COLUMN failover_restart_mode TEXT NOT NULL DEFAULT 'never';
+        UPDATE app_settings SET failover_restart_mode = cli_restart_mode WHERE failover_enabled = 1;
+        "#,
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{latest_version, migrate, migrate_to, schema_version, INITIAL_SCHEMA};
+    use rusqlite::Connection;
+    use std::path::{Path, PathBuf};
+
+    // 引入版本号之前各发布版本在基础表结构上依次追加的变更（user_version 均为 0）。
+    const LEGACY_STEPS: &[&str] = &[
+        r#"
+        ALTER TABLE app_settings ADD COLUMN device_authorization_url TEXT;
+        ALTER TABLE app_settings ADD COLUMN device_token_url TEXT;
+        ALTER TABLE app_settings ADD COLUMN device_client_id TEXT;
+        ALTER TABLE app_settings ADD COLUMN device_scope TEXT;
+        "#,
+        "ALTER TABLE app_settings ADD COLUMN codex_binary_path TEXT;",
+        r#"
+        CREATE TABLE project_bindings (
+          id TEXT PRIMARY KEY,
+          path TEXT NOT NULL UNIQUE,
+          account_id TEXT,
+          tag TEXT,
+          created_at TEXT NOT NULL,
+          updated_at TEXT NOT NULL
+        );
+        ALTER TABLE app_settings ADD COLUMN project_auto_switch INTEGER NOT NULL DEFAULT 0;
+        "#,
+        r#"
+        CREATE TABLE control_tokens (
+          id TEXT PRIMARY KEY,
+          name TEXT NOT NULL,
+          token_hash TEXT NOT NULL UNIQUE,
+          created_at TEXT NOT NULL,
+          last_used_at TEXT
+        );
+        ALTER TABLE app_settings ADD COLUMN control_api_enabled INTEGER NOT NULL DEFAULT 0;
+        "#,
+        r#"
+        ALTER TABLE app_settings ADD COLUMN account_homes_enabled INTEGER NOT NULL DEFAULT 0;
+        ALTER TABLE app_settings ADD COLUMN account_homes_share_config INTEGER NOT NULL DEFAULT 1;
+        "#,
+    ];
+
+    fn temp_dir() -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("codex-switch-migrate-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).expect("应创建目录");
+        dir
+    }
+
+    fn schema_signature(conn: &Connection) -> Vec<String> {
+        let mut stmt = conn
+            .prepare(
+                r#"
+                SELECT m.name, p.name, p.type, p."notnull", COALESCE(p.dflt_value, ''), p.pk
+                FROM sqlite_master m JOIN pragma_table_info(m.name) p
+                WHERE m.type = 'table'
+                ORDER BY m.name, p.name
+                "#,
+            )
+            .expect("应查询表结构");
+        stmt.query_map([], |row| {
+            Ok(format!(
+                "{}.{} {} {} {} {}",
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, i64>(3)?,
+                row.get::<_, String>(4)?,
+                row.get::<_, i64>(5)?
+            ))
+        })
+        .expect("应读取表结构")
+        .filter_map(Result::ok)
+        .collect()
+    }
+
+    fn latest_signature(dir: &Path) -> Vec<String> {
+        let mut conn = Connection::open(dir.join("fresh.db")).expect("应打开数据库");
+        assert!(migrate(&mut conn, &dir.join("backups"))
+            .expect("应迁移")
+            .is_none());
+        schema_signature(&conn)
+    }
+
+    #[test]
+    fn upgrades_every_historical_schema() {
+        let dir = temp_dir();
+        let expected = latest_signature(&dir);
+
+        for step in 0..=LEGACY_STEPS.len() {
+            let mut conn =
+                Connection::open(dir.join(format!("legacy-{step}.db"))).expect("应打开数据库");
+            conn.execute_batch(INITIAL_SCHEMA).expect("应创建基础表");
+            for sql in &LEGACY_STEPS[..step] {
+                conn.execute_batch(sql).expect("应应用历史变更");
+            }
+            conn.execute_batch(
+                r#"
+                INSERT INTO accounts VALUES ('a', 'work', '[]', 'blob', 'account:1', 't', 't', NULL);
+                INSERT INTO switch_history VALUES ('h', NULL, 'a', NULL, 'success', NULL, 't');
+                INSERT INTO switch_history VALUES ('h2', 'a', 'gone', NULL, 'success', NULL, 't');
+                INSERT INTO quota_snapshots
+                  VALUES ('q', 'gone', 'exact', 1, '%', 'available', NULL, 'usage_api', 90, NULL, 't');
+                "#,
+            )
+            .expect("应写入历史数据");
+            conn.pragma_update(None, "foreign_keys", true)
+                .expect("应开启外键");
+
+            let backup = migrate(&mut conn, &dir.join("backups"))
+                .expect("应完成迁移")
+                .expect("非空数据库迁移前应备份");
+            assert!(backup.is_file());
+            assert_eq!(schema_version(&conn).expect("应读取版本"), latest_version());
+            assert_eq!(
+                schema_signature(&conn),
+                expected,
+                "历史版本 {step} 升级后结构不一致"
+            );
+            let name: String = conn
+                .query_row("SELECT name FROM accounts WHERE id = 'a'", [], |row| {
+                    row.get(0)
+                })
+                .expect("升级后应保留账号");
+            assert_eq!(name, "work");
+            let orphan: (Option<String>, Option<String>, i64) = conn
+                .query_row(
+                    r#"
+                    SELECT from_account_id, to_account_id, (SELECT COUNT(*) FROM quota_snapshots)
+                    FROM switch_history WHERE id = 'h2'
+                    "#,
+                    [],
+                    |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+                )
+                .expect("孤儿历史应保留");
+            assert_eq!(orphan, (Some("a".to_string()), None, 0));
+        }
+
+        for version in 1..latest_version() {
+            let mut conn = Connection::open(dir.join(format!("versioned-{version}.db")))
+                .expect("应打开数据库");
+            migrate_to(&mut conn, &dir.join("backups"), version).expect("应迁移到中间版本");
+            assert_eq!(schema_version(&conn).expect("应读取版本"), version);
+            migrate(&mut conn, &dir.join("backups")).expect("应继续迁移");
+            assert_eq!(
+                schema_signature(&conn),
+                expected,
+                "v{version} 升级后结构不一致"
+            );
+        }
+        std::fs::remove_dir_all(&dir).expect("应清理目录");
+    }
+
+    #[test]
+    fn refuses_database_from_newer_release() {
+        let dir = temp_dir();
+        let mut conn = Connection::open(dir.join("future.db")).expect("应打开数据库");
+        conn.pragma_update(None, "user_version", latest_version() + 1)
+            .expect("应写入版本");
+        assert!(migrate(&mut conn, &dir.join("backups")).is_err());
+        std::fs::remove_dir_all(&dir).expect("应清理目录");
+    }
+}
@@ -11,23 +11,221 @@ pub struct Account {
     pub last_used_at: Option<String>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrashedAccount {
+    pub account: Account,
+    pub deleted_at: String,
+    pub purge_at: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SwitchHistory {
     pub id: String,
     pub from_account_id: Option<String>,
-    pub to_account_id: String,
+    pub to_account_id: Option<String>,
     pub snapshot_path: Option<String>,
+    pub snapshot_hash: Option<String>,
+    pub snapshot_pruned_at: Option<String>,
     pub result: String,
     pub error_message: Option<String>,
     pub created_at: String,
 }
 
+// 账号生命周期中值得追溯的事件，例如重新登录替换凭据。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountEvent {
+    pub id: String,
+    pub account_id: String,
+    pub kind: String,
+    pub detail: Option<String>,
+    pub created_at: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SwitchHistoryQuery {
+    // 匹配切换来源或目标账号
+    pub account_id: Option<String>,
+    pub result: Option<String>,
+    pub since: Option<String>,
+    pub until: Option<String>,
+    pub cursor: Option<String>,
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SwitchHistoryEntry {
+    #[serde(flatten)]
+    pub history: SwitchHistory,
+    pub from_account_name: Option<String>,
+    pub to_account_name: Option<String>,
+    pub from_account_trashed: bool,
+    pub to_account_trashed: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SwitchHistoryPage {
+    pub items: Vec<SwitchHistoryEntry>,
+    pub total: usize,
+    pub next_cursor: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageBucket {
+    pub period_start: String,
+    pub account_id: Option<String>,
+    pub account_name: Option<String>,
+    pub active_seconds: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountUsage {
+    pub account_id: Option<String>,
+    pub account_name: Option<String>,
+    pub active_seconds: i64,
+    pub switches_in: usize,
+    pub failed_switches: usize,
+    pub failure_rate: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SwitchPairCount {
+    pub from_account_id: Option<String>,
+    pub from_account_name: Option<String>,
+    pub to_account_id: Option<String>,
+    pub to_account_name: Option<String>,
+    pub count: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageReport {
+    pub granularity: String,
+    pub since: String,
+    pub until: String,
+    pub buckets: Vec<UsageBucket>,
+    pub accounts: Vec<AccountUsage>,
+    pub top_pairs: Vec<SwitchPairCount>,
+    pub total_switches: usize,
+    pub failed_switches: usize,
+    pub rollbacks: usize,
+    pub failure_rate: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VaultExportSummary {
+    pub path: String,
+    pub account_count: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VaultImportItem {
+    pub name: String,
+    pub auth_fingerprint: String,
+    // imported / overwritten / renamed / skipped / failed
+    pub action: String,
+    pub account_id: Option<String>,
+    pub message: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VaultImportReport {
+    pub total: usize,
+    pub imported: usize,
+    pub overwritten: usize,
+    pub renamed: usize,
+    pub skipped: usize,
+    pub failed: usize,
+    pub items: Vec<VaultImportItem>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImporterInfo {
+    pub id: String,
+    pub label: String,
+    pub description: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BulkImportItem {
+    pub path: String,
+    pub name: Option<String>,
+    // imported / skipped / failed
+    pub action: String,
+    pub account_id: Option<String>,
+    pub message: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BulkImportReport {
+    pub total: usize,
+    pub imported: usize,
+    pub skipped: usize,
+    pub failed: usize,
+    pub items: Vec<BulkImportItem>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SwitchResult {
     pub success: bool,
     pub history_id: String,
     pub snapshot_path: Option<String>,
     pub message: String,
+    // 被阻止级别的守卫拦下时为 true，此时未写入 auth.json 也不记录历史
+    #[serde(default)]
+    pub blocked: bool,
+    #[serde(default)]
+    pub guards: Vec<GuardResult>,
+}
+
+// 切换前单个守卫的检查结果；action 为 block / warn。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GuardResult {
+    pub guard: String,
+    pub action: String,
+    pub passed: bool,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotRetentionPolicy {
+    pub keep_count: usize,
+    pub max_age_days: u32,
+    pub protect_recent: usize,
+}
+
+impl Default for SnapshotRetentionPolicy {
+    fn default() -> Self {
+        Self {
+            keep_count: 20,
+            max_age_days: 30,
+            protect_recent: 5,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PrunedSnapshot {
+    pub history_id: String,
+    pub snapshot_path: String,
+    pub created_at: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotPruneReport {
+    pub dry_run: bool,
+    pub kept: usize,
+    pub pruned: Vec<PrunedSnapshot>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IntegrityReport {
+    pub orphan_quota_snapshots: usize,
+    pub orphan_history_refs: usize,
+    pub orphan_project_bindings: usize,
+    pub missing_snapshot_files: usize,
+    pub unreferenced_snapshot_files: usize,
+    pub repaired: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -36,8 +234,22 @@ pub struct RuntimeDiagnostics {
     pub codex_auth_exists: bool,
     pub app_data_dir: String,
     pub db_path: String,
+    pub db_schema_version: i64,
     pub schema_ok: bool,
     pub process_count: usize,
+    pub codex_binary_path: Option<String>,
+    pub codex_binary_source: Option<String>,
+    pub codex_version: Option<String>,
+    pub codex_compat_profile: String,
+    pub codex_compat_warning: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CodexBinaryInfo {
+    pub path: String,
+    pub version: Option<String>,
+    pub source: String,
+    pub pinned: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -66,6 +278,34 @@ pub struct QuotaSnapshot {
 pub struct QuotaDashboardItem {
     pub account: Account,
     pub snapshot: Option<QuotaSnapshot>,
+    pub health: Option<AccountHealth>,
+}
+
+// 最近一次凭据健康检查结果：valid / expiring / expired / revoked / malformed。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountHealth {
+    pub account_id: String,
+    pub status: String,
+    pub expires_at: Option<String>,
+    pub reason: Option<String>,
+    pub remote_checked: bool,
+    pub checked_at: String,
+}
+
+// 远程校验默认关闭；地址可改为本地模拟服务以便离线测试。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HealthCheckConfig {
+    pub remote_enabled: bool,
+    pub endpoint: String,
+}
+
+impl Default for HealthCheckConfig {
+    fn default() -> Self {
+        Self {
+            remote_enabled: false,
+            endpoint: "https://chatgpt.com/backend-api/me".to_string(),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -90,3 +330,172 @@ impl Default for QuotaRefreshPolicy {
         }
     }
 }
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeviceAuthConfig {
+    pub device_authorization_url: String,
+    pub token_url: String,
+    pub client_id: String,
+    pub scope: String,
+}
+
+impl Default for DeviceAuthConfig {
+    fn default() -> Self {
+        Self {
+            device_authorization_url: "https://auth.openai.com/oauth/device/code".to_string(),
+            token_url: "https://auth.openai.com/oauth/token".to_string(),
+            client_id: "app_EMoamEEZ73f0CkXaXp7hrann".to_string(),
+            scope: "openid profile email offline_access".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeviceLoginChallenge {
+    pub session_id: String,
+    pub mode: String,
+    pub user_code: String,
+    pub verification_uri: String,
+    pub verification_uri_complete: Option<String>,
+    pub expires_at: String,
+    pub interval_seconds: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectBinding {
+    pub id: String,
+    pub path: String,
+    pub account_id: Option<String>,
+    pub tag: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectAccountResolution {
+    pub path: String,
+    pub binding_path: Option<String>,
+    pub source: Option<String>,
+    pub account_id: Option<String>,
+    pub tag: Option<String>,
+    pub account: Option<Account>,
+    pub message: String,
+}
+
+// 自动切换的候选账号及其入选理由，按排名先后排列。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FailoverCandidate {
+    pub account_id: String,
+    pub account_name: String,
+    pub quota_state: String,
+    pub remaining_value: Option<f64>,
+    pub remaining_unit: Option<String>,
+    pub reset_at: Option<String>,
+    pub tag_match: bool,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FailoverEvent {
+    pub from_account_id: String,
+    pub from_account_name: String,
+    pub trigger: String,
+    pub switched_to: Option<FailoverCandidate>,
+    pub result: Option<SwitchResult>,
+    pub candidates: Vec<FailoverCandidate>,
+    pub message: String,
+    pub created_at: String,
+}
+
+// 账号池：account_ids 为显式成员，tag 不为空时同时纳入带该标签的账号。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountPool {
+    pub id: String,
+    pub name: String,
+    pub tag: Option<String>,
+    pub account_ids: Vec<String>,
+    pub strategy: String,
+    pub last_account_id: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PoolMember {
+    pub account_id: String,
+    pub account_name: String,
+    pub eligible: bool,
+    pub note: String,
+}
+
+// 按池策略选出的下一个账号，explanation 说明入选原因，members 列出各成员是否可选。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PoolDecision {
+    pub pool_id: String,
+    pub pool_name: String,
+    pub strategy: String,
+    pub account_id: String,
+    pub account_name: String,
+    pub explanation: String,
+    pub members: Vec<PoolMember>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PoolSwitchResult {
+    pub decision: PoolDecision,
+    pub result: SwitchResult,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectAutoSwitchEvent {
+    pub pid: u32,
+    pub cwd: String,
+    pub resolution: ProjectAccountResolution,
+    pub result: SwitchResult,
+    pub restarted: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ControlToken {
+    pub id: String,
+    pub name: String,
+    pub created_at: String,
+    pub last_used_at: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreatedControlToken {
+    pub token: ControlToken,
+    pub secret: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ControlApiStatus {
+    pub enabled: bool,
+    pub running: bool,
+    pub endpoint: String,
+    pub token_count: usize,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppEvent {
+    pub event: String,
+    pub payload: serde_json::Value,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountHome {
+    pub account_id: String,
+    pub account_name: String,
+    pub path: String,
+    pub materialized: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountHomesStatus {
+    pub enabled: bool,
+    pub share_config: bool,
+    pub homes_dir: String,
+    pub homes: Vec<AccountHome>,
+}
@@ -0,0 +1,498 @@
+use crate::{
+    app_state::AppState,
+    failover::{exclusion_reason, remaining_percent},
+    models::{AccountPool, PoolDecision, PoolMember, PoolSwitchResult, QuotaDashboardItem},
+    service::{self, SwitchOptions},
+};
+use anyhow::{anyhow, Result};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "snake_case")]
+pub enum RotationStrategy {
+    #[default]
+    RoundRobin,
+    MostRemaining,
+    EarliestReset,
+    LeastRecentlyUsed,
+}
+
+impl RotationStrategy {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::RoundRobin => "round_robin",
+            Self::MostRemaining => "most_remaining",
+            Self::EarliestReset => "earliest_reset",
+            Self::LeastRecentlyUsed => "least_recently_used",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "round_robin" => Some(Self::RoundRobin),
+            "most_remaining" => Some(Self::MostRemaining),
+            "earliest_reset" => Some(Self::EarliestReset),
+            "least_recently_used" => Some(Self::LeastRecentlyUsed),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountPoolInput {
+    #[serde(default)]
+    pub id: Option<String>,
+    pub name: String,
+    #[serde(default)]
+    pub tag: Option<String>,
+    #[serde(default)]
+    pub account_ids: Vec<String>,
+    #[serde(default)]
+    pub strategy: RotationStrategy,
+}
+
+pub fn save_pool(state: &AppState, input: &AccountPoolInput) -> Result<AccountPool> {
+    let name = input.name.trim();
+    if name.is_empty() {
+        return Err(anyhow!("账号池名称不能为空"));
+    }
+    let tag = input
+        .tag
+        .as_deref()
+        .map(str::trim)
+        .filter(|tag| !tag.is_empty());
+    let known = state
+        .store
+        .list_accounts()?
+        .into_iter()
+        .map(|account| account.id)
+        .collect::<Vec<_>>();
+    let mut account_ids: Vec<String> = Vec::new();
+    for id in input.account_ids.iter().map(|id| id.trim()) {
+        if id.is_empty() || account_ids.iter().any(|existing| existing == id) {
+            continue;
+        }
+        if !known.iter().any(|known| known == id) {
+            return Err(anyhow!("账号不存在：{id}"));
+        }
+        account_ids.push(id.to_string());
+    }
+    if tag.is_none() && account_ids.is_empty() {
+        return Err(anyhow!("账号池至少需要指定成员账号或标签"));
+    }
+    state.store.save_account_pool(
+        input.id.as_deref().filter(|id| !id.is_empty()),
+        name,
+        tag,
+        &account_ids,
+        input.strategy.as_str(),
+    )
+}
+
+pub fn find_pool(state: &AppState, key: &str) -> Result<AccountPool> {
+    let key = key.trim();
+    state
+        .store
+        .list_account_pools()?
+        .into_iter()
+        .find(|pool| pool.id == key || pool.name.eq_ignore_ascii_case(key))
+        .ok_or_else(|| anyhow!("未找到账号池：{key}"))
+}
+
+pub fn plan_rotation(state: &AppState, pool: &AccountPool) -> Result<PoolDecision> {
+    let items = service::quota_dashboard(state)?;
+    let current = state.store.get_current_account_id()?;
+    decide(pool, &items, current.as_deref())
+}
+
+// 只有切换成功才推进轮询游标；被守卫阻止时原样返回结果，由调用方决定是否强制。
+pub fn switch_to_next_in_pool(
+    state: &AppState,
+    pool_id: &str,
+    options: SwitchOptions,
+) -> Result<PoolSwitchResult> {
+    let pool = state
+        .store
+        .get_account_pool(pool_id.trim())?
+        .ok_or_else(|| anyhow!("账号池不存在"))?;
+    let decision = plan_rotation(state, &pool)?;
+    let result = service::switch_to_account(state, &decision.account_id, options)?;
+    if result.success {
+        state
+            .store
+            .set_account_pool_cursor(&pool.id, &decision.account_id)?;
+    }
+    Ok(PoolSwitchResult { decision, result })
+}
+
+// 显式成员按列出的顺序在前，标签成员按创建时间追加；回收站中的账号不在 items 里，自然被略过。
+fn pool_members<'a>(
+    pool: &AccountPool,
+    items: &'a [QuotaDashboardItem],
+) -> Vec<&'a QuotaDashboardItem> {
+    let mut members = pool
+        .account_ids
+        .iter()
+        .filter_map(|id| items.iter().find(|item| &item.account.id == id))
+        .collect::<Vec<_>>();
+    if let Some(tag) = pool.tag.as_deref() {
+        let mut tagged = items
+            .iter()
+            .filter(|item| item.account.tags.iter().any(|value| value == tag))
+            .filter(|item| !pool.account_ids.contains(&item.account.id))
+            .collect::<Vec<_>>();
+        tagged.sort_by(|a, b| {
+            a.account
+                .created_at
+                .cmp(&b.account.created_at)
+                .then_with(|| a.account.name.cmp(&b.account.name))
+        });
+        members.extend(tagged);
+    }
+    members
+}
+
+fn reset_at(item: &QuotaDashboardItem) -> Option<&str> {
+    item.snapshot
+        .as_ref()
+        .and_then(|snapshot| snapshot.reset_at.as_deref())
+}
+
+fn summary(item: &QuotaDashboardItem) -> String {
+    let mut parts = vec![item
+        .snapshot
+        .as_ref()
+        .map(
+            |snapshot| match (snapshot.remaining_value, snapshot.remaining_unit.as_deref()) {
+                (Some(value), Some(unit)) => format!("剩余 {value:.0}{unit}"),
+                _ => format!("配额状态 {}", snapshot.quota_state),
+            },
+        )
+        .unwrap_or_else(|| "暂无配额记录".to_string())];
+    if let Some(reset_at) = reset_at(item) {
+        parts.push(format!("{reset_at} 重置"));
+    }
+    parts.push(match item.account.last_used_at.as_deref() {
+        Some(last_used_at) => format!("最近使用于 {last_used_at}"),
+        None => "从未使用".to_string(),
+    });
+    parts.join("，")
+}
+
+fn decide(
+    pool: &AccountPool,
+    items: &[QuotaDashboardItem],
+    current: Option<&str>,
+) -> Result<PoolDecision> {
+    let strategy = RotationStrategy::parse(&pool.strategy).unwrap_or_default();
+    let members = pool_members(pool, items);
+    if members.is_empty() {
+        return Err(anyhow!("账号池「{}」中没有账号", pool.name));
+    }
+    let exclusions = members
+        .iter()
+        .map(|item| exclusion_reason(item, current))
+        .collect::<Vec<_>>();
+    let eligible = (0..members.len())
+        .filter(|&index| exclusions[index].is_none())
+        .collect::<Vec<_>>();
+    if eligible.is_empty() {
+        let reasons = members
+            .iter()
+            .zip(&exclusions)
+            .map(|(item, reason)| {
+                format!(
+                    "{}：{}",
+                    item.account.name,
+                    reason.as_deref().unwrap_or("-")
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("；");
+        return Err(anyhow!(
+            "账号池「{}」中没有可切换的账号（{reasons}）",
+            pool.name
+        ));
+    }
+
+    let (chosen, explanation) = match strategy {
+        RotationStrategy::RoundRobin => {
+            // 从上次选中的账号之后按池内顺序找；游标已不在池中时从当前账号之后开始
+            let position = |id: &str| members.iter().position(|item| item.account.id == id);
+            let anchor = pool
+                .last_account_id
+                .as_deref()
+                .and_then(position)
+                .or_else(|| current.and_then(position));
+            let start = anchor.map_or(0, |index| index + 1);
+            let chosen = (0..members.len())
+                .map(|offset| (start + offset) % members.len())
+                .find(|index| eligible.contains(index))
+                .unwrap_or(eligible[0]);
+            let explanation = match anchor {
+                Some(anchor) => format!(
+                    "轮询：上一次轮到「{}」，按池内顺序下一个可用的是「{}」",
+                    members[anchor].account.name, members[chosen].account.name
+                ),
+                None => format!(
+                    "轮询：从池内第一个可用账号「{}」开始",
+                    members[chosen].account.name
+                ),
+            };
+            (chosen, explanation)
+        }
+        RotationStrategy::MostRemaining => {
+            let chosen = eligible
+                .iter()
+                .copied()
+                .min_by(|&a, &b| {
+                    let percent = |index: usize| {
+                        members[index]
+                            .snapshot
+                            .as_ref()
+                            .and_then(remaining_percent)
+                            .unwrap_or(-1.0)
+                    };
+                    percent(b)
+                        .partial_cmp(&percent(a))
+                        .unwrap_or(Ordering::Equal)
+                        .then_with(|| state_rank(members[a]).cmp(&state_rank(members[b])))
+                })
+                .unwrap_or(eligible[0]);
+            let item = members[chosen];
+            let explanation = match item.snapshot.as_ref().and_then(remaining_percent) {
+                Some(percent) => format!(
+                    "剩余配额最多：「{}」剩余 {percent:.0}%，在 {} 个可用账号中最高",
+                    item.account.name,
+                    eligible.len()
+                ),
+                None => format!(
+                    "可用账号都没有百分比配额数据，按配额状态选择了「{}」",
+                    item.account.name
+                ),
+            };
+            (chosen, explanation)
+        }
+        RotationStrategy::EarliestReset => {
+            let earliest = eligible
+                .iter()
+                .copied()
+                .filter(|&index| reset_at(members[index]).is_some())
+                .min_by(|&a, &b| reset_at(members[a]).cmp(&reset_at(members[b])));
+            match earliest {
+                Some(chosen) => (
+                    chosen,
+                    format!(
+                        "重置时间最早：「{}」预计 {} 重置，先用掉即将重置的额度",
+                        members[chosen].account.name,
+                        reset_at(members[chosen]).unwrap_or("-")
+                    ),
+                ),
+                None => (
+                    eligible[0],
+                    format!(
+                        "可用账号都没有重置时间记录，按池内顺序选择了「{}」",
+                        members[eligible[0]].account.name
+                    ),
+                ),
+            }
+        }
+        RotationStrategy::LeastRecentlyUsed => {
+            // None 排在最前，从未使用过的账号优先
+            let chosen = eligible
+                .iter()
+                .copied()
+                .min_by(|&a, &b| {
+                    members[a]
+                        .account
+                        .last_used_at
+                        .cmp(&members[b].account.last_used_at)
+                })
+                .unwrap_or(eligible[0]);
+            let item = members[chosen];
+            let explanation = match item.account.last_used_at.as_deref() {
+                Some(last_used_at) => format!(
+                    "最久未使用：「{}」最近一次使用于 {last_used_at}，早于其他可用账号",
+                    item.account.name
+                ),
+                None => format!("最久未使用：「{}」从未被切换使用过", item.account.name),
+            };
+            (chosen, explanation)
+        }
+    };
+
+    Ok(PoolDecision {
+        pool_id: pool.id.clone(),
+        pool_name: pool.name.clone(),
+        strategy: strategy.as_str().to_string(),
+        account_id: members[chosen].account.id.clone(),
+        account_name: members[chosen].account.name.clone(),
+        explanation,
+        members: members
+            .iter()
+            .zip(exclusions)
+            .map(|(item, reason)| PoolMember {
+                account_id: item.account.id.clone(),
+                account_name: item.account.name.clone(),
+                eligible: reason.is_none(),
+                note: reason.unwrap_or_else(|| summary(item)),
+            })
+            .collect(),
+    })
+}
+
+fn state_rank(item: &QuotaDashboardItem) -> u8 {
+    match item
+        .snapshot
+        .as_ref()
+        .map(|snapshot| snapshot.quota_state.as_str())
+    {
+        Some("available") => 0,
+        Some("near_limit") => 1,
+        _ => 2,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decide, RotationStrategy};
+    use crate::{models::QuotaDashboardItem, store::AppStore, test_support::dashboard_item};
+
+    // 创建时间随 id 长度递增，决定按标签纳入成员的顺序。
+    fn item(
+        id: &str,
+        tags: &[&str],
+        percent: f64,
+        reset_at: Option<&str>,
+        last_used_at: Option<&str>,
+    ) -> QuotaDashboardItem {
+        let quota_state = if percent > 0.0 {
+            "available"
+        } else {
+            "exhausted"
+        };
+        let mut item = dashboard_item(id, tags, quota_state, Some(percent));
+        item.account.created_at = format!("2026-01-01T00:00:0{}Z", id.len());
+        item.account.last_used_at = last_used_at.map(ToString::to_string);
+        if let Some(snapshot) = item.snapshot.as_mut() {
+            snapshot.reset_at = reset_at.map(ToString::to_string);
+        }
+        item
+    }
+
+    #[test]
+    fn persists_pools_and_explains_each_strategy() {
+        let root =
+            std::env::temp_dir().join(format!("codex-switch-pools-{}", uuid::Uuid::new_v4()));
+        let store = AppStore::new(root.clone());
+        store.init().expect("应初始化数据库");
+        let mut pool = store
+            .save_account_pool(
+                None,
+                "团队",
+                Some("team"),
+                &["b".to_string()],
+                "round_robin",
+            )
+            .expect("应保存账号池");
+        assert!(store
+            .save_account_pool(None, "团队", None, &[], "round_robin")
+            .is_err());
+
+        let items = vec![
+            item(
+                "a",
+                &["team"],
+                30.0,
+                Some("2026-01-03T00:00:00Z"),
+                Some("2026-01-02T00:00:00Z"),
+            ),
+            item(
+                "b",
+                &[],
+                60.0,
+                Some("2026-01-05T00:00:00Z"),
+                Some("2026-01-01T00:00:00Z"),
+            ),
+            item("cc", &["team"], 90.0, None, None),
+            item("ddd", &["team"], 0.0, Some("2026-01-02T00:00:00Z"), None),
+            item("other", &[], 100.0, None, None),
+        ];
+
+        // 成员顺序：显式的 b 在前，随后是带 team 标签的 a、cc、ddd
+        let decision = decide(&pool, &items, Some("b")).expect("应选出账号");
+        assert_eq!(decision.account_id, "a");
+        assert!(decision.explanation.contains("上一次轮到「b」"));
+        assert_eq!(decision.members.len(), 4);
+        assert!(!decision.members[0].eligible);
+        assert_eq!(decision.members[3].note, "配额已耗尽");
+
+        store
+            .set_account_pool_cursor(&pool.id, "cc")
+            .expect("应更新游标");
+        pool = store
+            .get_account_pool(&pool.id)
+            .expect("应读取")
+            .expect("应存在");
+        assert_eq!(
+            decide(&pool, &items, Some("a"))
+                .expect("应选出账号")
+                .account_id,
+            "b"
+        );
+
+        for (strategy, expected) in [
+            (RotationStrategy::MostRemaining, "cc"),
+            (RotationStrategy::EarliestReset, "a"),
+            (RotationStrategy::LeastRecentlyUsed, "cc"),
+        ] {
+            pool = store
+                .save_account_pool(
+                    Some(&pool.id),
+                    &pool.name,
+                    pool.tag.as_deref(),
+                    &pool.account_ids,
+                    strategy.as_str(),
+                )
+                .expect("应更新策略");
+            assert_eq!(pool.last_account_id.as_deref(), Some("cc"));
+            let decision = decide(&pool, &items, Some("b")).expect("应选出账号");
+            assert_eq!(decision.strategy, strategy.as_str());
+            assert_eq!(decision.account_id, expected, "{}", decision.explanation);
+        }
+
+        let exhausted = vec![item("b", &[], 0.0, None, None)];
+        assert!(decide(&pool, &exhausted, None).is_err());
+        std::fs::remove_dir_all(&root).expect("应清理目录");
+    }
+    #[test]
+    fn round_robin_falls_back_to_current_when_cursor_left_pool() {
+        let root =
+            std::env::temp_dir().join(format!("codex-switch-pools-{}", uuid::Uuid::new_v4()));
+        let store = AppStore::new(root.clone());
+        store.init().expect("应初始化数据库");
+        let pool = store
+            .save_account_pool(None, "团队", Some("team"), &[], "round_robin")
+            .expect("应保存账号池");
+        store
+            .set_account_pool_cursor(&pool.id, "other")
+            .expect("应更新游标");
+        let pool = store
+            .get_account_pool(&pool.id)
+            .expect("应读取")
+            .expect("应存在");
+        let items = vec![
+            item("a", &["team"], 50.0, None, None),
+            item("bb", &["team"], 50.0, None, None),
+            item("ccc", &["team"], 50.0, None, None),
+            item("other", &[], 100.0, None, None),
+        ];
+
+        let decision = decide(&pool, &items, Some("bb")).expect("应选出账号");
+        assert_eq!(decision.account_id, "ccc", "{}", decision.explanation);
+        assert!(decision.explanation.contains("上一次轮到「bb」"));
+        std::fs::remove_dir_all(&root).expect("应清理目录");
+    }
+}
@@ -0,0 +1,265 @@
+use crate::{
+    models::{Account, ProjectAccountResolution, ProjectBinding},
+    store::AppStore,
+};
+use anyhow::{anyhow, Context, Result};
+use serde::Deserialize;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+pub const PROJECT_BINDING_FILE: &str = ".codex-switch.toml";
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct BindingTarget {
+    pub account: Option<String>,
+    pub tag: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct BindingMatch {
+    pub dir: PathBuf,
+    pub source: &'static str,
+    pub target: BindingTarget,
+}
+
+pub fn normalize_project_path(path: &Path) -> PathBuf {
+    let path = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    if path.is_file() {
+        return path.parent().map(Path::to_path_buf).unwrap_or(path);
+    }
+    path
+}
+
+pub fn parse_binding_file(text: &str) -> Result<BindingTarget> {
+    let target: BindingTarget =
+        toml::from_str(text).with_context(|| format!("解析 {PROJECT_BINDING_FILE} 失败"))?;
+    let target = BindingTarget {
+        account: trim_field(target.account),
+        tag: trim_field(target.tag),
+    };
+    if target.account.is_none() && target.tag.is_none() {
+        return Err(anyhow!("{PROJECT_BINDING_FILE} 需要提供 account 或 tag"));
+    }
+    Ok(target)
+}
+
+pub fn find_binding(path: &Path, bindings: &[ProjectBinding]) -> Result<Option<BindingMatch>> {
+    let start = normalize_project_path(path);
+    for dir in start.ancestors() {
+        let file = dir.join(PROJECT_BINDING_FILE);
+        if file.is_file() {
+            let text = fs::read_to_string(&file)
+                .with_context(|| format!("读取项目绑定文件失败: {}", file.display()))?;
+            let target = parse_binding_file(&text)
+                .with_context(|| format!("项目绑定文件无效: {}", file.display()))?;
+            return Ok(Some(BindingMatch {
+                dir: dir.to_path_buf(),
+                source: "dotfile",
+                target,
+            }));
+        }
+
+        if let Some(binding) = bindings
+            .iter()
+            .find(|binding| Path::new(&binding.path) == dir)
+        {
+            return Ok(Some(BindingMatch {
+                dir: dir.to_path_buf(),
+                source: "store",
+                target: BindingTarget {
+                    account: binding.account_id.clone(),
+                    tag: binding.tag.clone(),
+                },
+            }));
+        }
+    }
+    Ok(None)
+}
+
+pub fn select_account<'a>(
+    target: &BindingTarget,
+    accounts: &'a [Account],
+    current_account_id: Option<&str>,
+) -> Option<&'a Account> {
+    if let Some(key) = target.account.as_deref() {
+        return accounts
+            .iter()
+            .find(|account| account.id == key)
+            .or_else(|| {
+                accounts
+                    .iter()
+                    .find(|account| account.name.eq_ignore_ascii_case(key))
+            });
+    }
+
+    let tag = target.tag.as_deref()?;
+    let tagged = accounts
+        .iter()
+        .filter(|account| {
+            account
+                .tags
+                .iter()
+                .any(|item| item.eq_ignore_ascii_case(tag))
+        })
+        .collect::<Vec<_>>();
+    tagged
+        .iter()
+        .find(|account| Some(account.id.as_str()) == current_account_id)
+        .or_else(|| {
+            tagged
+                .iter()
+                .max_by(|left, right| left.last_used_at.cmp(&right.last_used_at))
+        })
+        .copied()
+}
+
+pub fn resolve_for_path(store: &AppStore, path: &Path) -> Result<ProjectAccountResolution> {
+    let normalized = normalize_project_path(path);
+    let bindings = store.list_project_bindings()?;
+    let Some(matched) = find_binding(&normalized, &bindings)? else {
+        return Ok(ProjectAccountResolution {
+            path: normalized.display().to_string(),
+            binding_path: None,
+            source: None,
+            account_id: None,
+            tag: None,
+            account: None,
+            message: "未找到项目绑定，保持当前账号".to_string(),
+        });
+    };
+
+    let accounts = store.list_accounts()?;
+    let current_account_id = store.get_current_account_id()?;
+    let account =
+        select_account(&matched.target, &accounts, current_account_id.as_deref()).cloned();
+    let wanted = matched
+        .target
+        .account
+        .as_deref()
+        .map(|account| format!("账号 {account}"))
+        .or_else(|| {
+            matched
+                .target
+                .tag
+                .as_deref()
+                .map(|tag| format!("标签 {tag}"))
+        })
+        .unwrap_or_default();
+    let message = match &account {
+        Some(account) => format!(
+            "已匹配 {} 中的绑定（{wanted}）→ {}",
+            matched.dir.display(),
+            account.name
+        ),
+        None => format!(
+            "已找到 {} 中的绑定，但没有符合{wanted}的账号",
+            matched.dir.display()
+        ),
+    };
+    Ok(ProjectAccountResolution {
+        path: normalized.display().to_string(),
+        binding_path: Some(matched.dir.display().to_string()),
+        source: Some(matched.source.to_string()),
+        account_id: matched.target.account,
+        tag: matched.target.tag,
+        account,
+        message,
+    })
+}
+
+fn trim_field(value: Option<String>) -> Option<String> {
+    value
+        .map(|field| field.trim().to_string())
+        .filter(|field| !field.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        find_binding, parse_binding_file, select_account, BindingTarget, PROJECT_BINDING_FILE,
+    };
+    use crate::models::{Account, ProjectBinding};
+    use std::fs;
+
+    fn account(id: &str, tags: &[&str], last_used_at: Option<&str>) -> Account {
+        Account {
+            id: id.to_string(),
+            name: format!("name-{id}"),
+            tags: tags.iter().map(|tag| tag.to_string()).collect(),
+            auth_fingerprint: format!("account:{id}"),
+            created_at: "2026-01-01T00:00:00Z".to_string(),
+            updated_at: "2026-01-01T00:00:00Z".to_string(),
+            last_used_at: last_used_at.map(ToString::to_string),
+        }
+    }
+
+    #[test]
+    fn parses_binding_file_and_rejects_empty_target() {
+        let target = parse_binding_file("account = \" client-a \"\n").expect("应解析绑定文件");
+        assert_eq!(target.account.as_deref(), Some("client-a"));
+        assert!(parse_binding_file("tag = \"\"\n").is_err());
+    }
+
+    #[test]
+    fn nearest_dotfile_or_store_binding_wins() {
+        let root =
+            std::env::temp_dir().join(format!("codex-switch-project-{}", uuid::Uuid::new_v4()));
+        let nested = root.join("client").join("service");
+        fs::create_dir_all(&nested).expect("应创建目录");
+        fs::write(root.join(PROJECT_BINDING_FILE), "tag = \"work\"\n").expect("应写入绑定文件");
+        let client_dir = fs::canonicalize(root.join("client")).expect("应解析路径");
+        let bindings = vec![ProjectBinding {
+            id: "binding-1".to_string(),
+            path: client_dir.display().to_string(),
+            account_id: Some("acc-client".to_string()),
+            tag: None,
+            created_at: "2026-01-01T00:00:00Z".to_string(),
+            updated_at: "2026-01-01T00:00:00Z".to_string(),
+        }];
+
+        let matched = find_binding(&nested, &bindings)
+            .expect("应查找绑定")
+            .expect("应命中绑定");
+        assert_eq!(matched.source, "store");
+        assert_eq!(matched.target.account.as_deref(), Some("acc-client"));
+
+        let matched = find_binding(&root, &bindings)
+            .expect("应查找绑定")
+            .expect("应命中绑定");
+        assert_eq!(matched.source, "dotfile");
+        assert_eq!(matched.target.tag.as_deref(), Some("work"));
+        fs::remove_dir_all(&root).expect("应清理目录");
+    }
+
+    #[test]
+    fn tag_binding_prefers_current_then_recent_account() {
+        let accounts = vec![
+            account("a", &["work"], Some("2026-01-02T00:00:00Z")),
+            account("b", &["Work"], Some("2026-01-03T00:00:00Z")),
+            account("c", &["home"], None),
+        ];
+        let target = BindingTarget {
+            account: None,
+            tag: Some("work".to_string()),
+        };
+        assert_eq!(
+            select_account(&target, &accounts, Some("a")).map(|item| item.id.as_str()),
+            Some("a")
+        );
+        assert_eq!(
+            select_account(&target, &accounts, Some("c")).map(|item| item.id.as_str()),
+            Some("b")
+        );
+
+        let by_name = BindingTarget {
+            account: Some("NAME-C".to_string()),
+            tag: None,
+        };
+        assert_eq!(
+            select_account(&by_name, &accounts, None).map(|item| item.id.as_str()),
+            Some("c")
+        );
+    }
+}
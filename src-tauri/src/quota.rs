@@ -1,3 +1,4 @@
+use crate::compat::{auth_text_field, CodexCompat};
 use anyhow::{anyhow, Context, Result};
 use regex::Regex;
 use reqwest::{header, Client, RequestBuilder, StatusCode};
@@ -5,10 +6,6 @@ use serde_json::Value;
 use std::time::Duration;
 use uuid::Uuid;
 
-const CODEX_CLIENT_VERSION: &str = "0.98.0";
-const CODEX_OPENAI_BETA: &str = "responses=experimental";
-const CODEX_USER_AGENT: &str = "codex_cli_rs/0.98.0 (Windows NT 10.0; x86_64) codex-switch";
-const CODEX_ORIGINATOR: &str = "codex_cli_rs";
 const API_ENDPOINTS: [(&str, &str); 8] = [
     ("https://chatgpt.com", "/backend-api/api/codex/usage"),
     ("https://chatgpt.com", "/backend-api/wham/usage"),
@@ -55,26 +52,71 @@ pub async fn probe_quota(
     access_token: &str,
     account_id: Option<&str>,
     timeout_ms: u64,
+    compat: &CodexCompat,
 ) -> QuotaProbeResult {
     let (api_result, web_result) = tokio::join!(
-        probe_via_api(access_token, account_id, timeout_ms),
-        probe_via_web(access_token, account_id, timeout_ms)
+        probe_via_api(access_token, account_id, timeout_ms, compat),
+        probe_via_web(access_token, account_id, timeout_ms, compat)
     );
     merge_probe_results(api_result, web_result)
 }
 
+// 轻量已认证请求的结果：只关心令牌是否被服务端接受，不解析配额。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AuthProbe {
+    Accepted,
+    Rejected(String),
+    Unreachable(String),
+}
+
+pub async fn probe_auth(
+    endpoint: &str,
+    access_token: &str,
+    account_id: Option<&str>,
+    timeout_ms: u64,
+    compat: &CodexCompat,
+) -> AuthProbe {
+    let client = match build_client(timeout_ms, compat) {
+        Ok(client) => client,
+        Err(error) => return AuthProbe::Unreachable(short_error(&error)),
+    };
+    let request = apply_codex_headers(
+        client.get(endpoint),
+        compat,
+        access_token,
+        account_id,
+        "application/json",
+    );
+    match request.send().await {
+        Ok(response) => {
+            let status = response.status();
+            // 429 说明令牌有效，只是被限流
+            if status.is_success() || status == StatusCode::TOO_MANY_REQUESTS {
+                AuthProbe::Accepted
+            } else if status == StatusCode::UNAUTHORIZED || status == StatusCode::FORBIDDEN {
+                AuthProbe::Rejected(reason_from_http_status(status, endpoint))
+            } else {
+                AuthProbe::Unreachable(reason_from_http_status(status, endpoint))
+            }
+        }
+        Err(error) => AuthProbe::Unreachable(reason_from_request_error(&error, endpoint)),
+    }
+}
+
 async fn probe_via_api(
     access_token: &str,
     account_id: Option<&str>,
     timeout_ms: u64,
+    compat: &CodexCompat,
 ) -> Result<QuotaProbeResult> {
-    let client = build_client(timeout_ms)?;
+    let client = build_client(timeout_ms, compat)?;
     let mut last_reason = "source_unavailable".to_string();
 
     for (domain, path) in API_ENDPOINTS {
         let endpoint = format!("{domain}{path}");
         let request = apply_codex_headers(
             client.get(&endpoint),
+            compat,
             access_token,
             account_id,
             "application/json",
@@ -143,14 +185,16 @@ async fn probe_via_web(
     access_token: &str,
     account_id: Option<&str>,
     timeout_ms: u64,
+    compat: &CodexCompat,
 ) -> Result<QuotaProbeResult> {
-    let client = build_client(timeout_ms)?;
+    let client = build_client(timeout_ms, compat)?;
     let mut last_reason = "source_unavailable".to_string();
 
     for (domain, path) in WEB_ENDPOINTS {
         let endpoint = format!("{domain}{path}");
         let request = apply_codex_headers(
             client.get(&endpoint),
+            compat,
             access_token,
             account_id,
             "text/html,application/xhtml+xml",
@@ -229,17 +273,18 @@ fn merge_probe_results(
 
 fn apply_codex_headers(
     request: RequestBuilder,
+    compat: &CodexCompat,
     access_token: &str,
     account_id: Option<&str>,
     accept: &'static str,
 ) -> RequestBuilder {
     let mut request = request
         .bearer_auth(access_token)
-        .header("Version", CODEX_CLIENT_VERSION)
-        .header("Openai-Beta", CODEX_OPENAI_BETA)
+        .header("Version", compat.client_version.as_str())
+        .header("Openai-Beta", compat.profile.openai_beta)
         .header("Session_id", Uuid::new_v4().to_string())
-        .header(header::USER_AGENT, CODEX_USER_AGENT)
-        .header("Originator", CODEX_ORIGINATOR)
+        .header(header::USER_AGENT, compat.user_agent())
+        .header("Originator", compat.originator())
         .header(header::ACCEPT, accept)
         .header(header::CONNECTION, "Keep-Alive");
 
@@ -287,15 +332,15 @@ fn reason_from_request_error(error: &reqwest::Error, endpoint: &str) -> String {
 
 fn short_error(error: &impl std::fmt::Display) -> String {
     let message = error.to_string();
-    let compact = message.replace('\n', " ").replace('\r', " ");
+    let compact = message.replace(['\n', '\r'], " ");
     compact.chars().take(120).collect()
 }
 
-fn build_client(timeout_ms: u64) -> Result<Client> {
+fn build_client(timeout_ms: u64, compat: &CodexCompat) -> Result<Client> {
     let mut headers = header::HeaderMap::new();
     headers.insert(
         header::USER_AGENT,
-        header::HeaderValue::from_static(CODEX_USER_AGENT),
+        header::HeaderValue::from_str(&compat.user_agent()).context("Codex User-Agent 格式无效")?,
     );
     headers.insert(
         header::ACCEPT,
@@ -594,11 +639,7 @@ fn state_from_value(value: f64) -> &'static str {
 }
 
 pub fn ensure_access_token(auth_json: &Value) -> Result<String> {
-    auth_json
-        .get("access_token")
-        .and_then(Value::as_str)
-        .map(str::trim)
-        .filter(|value| !value.is_empty())
+    auth_text_field(auth_json, "access_token")
         .map(ToString::to_string)
         .ok_or_else(|| anyhow!("该账号缺少 access_token 字段，无法查询配额"))
 }
@@ -0,0 +1,720 @@
+use crate::{
+    app_state::AppState,
+    crypto,
+    models::{AppEvent, ControlApiStatus},
+    service,
+    store::AppStore,
+};
+use anyhow::{anyhow, Result};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{json, Value};
+use tauri::{AppHandle, Manager};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader},
+    sync::{broadcast, oneshot},
+};
+
+const JSONRPC_VERSION: &str = "2.0";
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+const COMMAND_FAILED: i64 = -32000;
+const UNAUTHORIZED: i64 = -32001;
+// 未配置客户端令牌时，同一用户下的任意进程都能连上套接字，只开放不涉及凭据的只读查询。
+const TOKENLESS_METHODS: &[&str] = &[
+    "subscribe",
+    "unsubscribe",
+    "get_active_account",
+    "vault_status",
+    "list_accounts",
+    "get_quota_dashboard",
+    "resolve_project_account",
+    "get_codex_cli_status",
+];
+
+#[cfg(unix)]
+const CONTROL_SOCKET_FILE: &str = "control.sock";
+
+#[derive(Debug, Default)]
+pub struct ControlServerState {
+    shutdown: Option<oneshot::Sender<()>>,
+    error: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RpcRequest {
+    jsonrpc: Option<String>,
+    id: Option<Value>,
+    method: String,
+    #[serde(default)]
+    params: Value,
+}
+
+#[derive(Debug)]
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+impl RpcError {
+    fn new(code: i64, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct Connection {
+    authenticated: bool,
+    subscription: Option<Subscription>,
+}
+
+#[derive(Debug)]
+struct Subscription {
+    receiver: broadcast::Receiver<AppEvent>,
+    events: Vec<String>,
+}
+
+pub fn control_endpoint(store: &AppStore) -> String {
+    #[cfg(unix)]
+    {
+        store
+            .base_dir
+            .join("run")
+            .join(CONTROL_SOCKET_FILE)
+            .display()
+            .to_string()
+    }
+    #[cfg(windows)]
+    {
+        let _ = store;
+        let user = std::env::var("USERNAME").unwrap_or_else(|_| "default".to_string());
+        format!(r"\\.\pipe\codex-switch-{user}")
+    }
+}
+
+pub fn control_status(state: &AppState) -> Result<ControlApiStatus> {
+    let server = state
+        .control_server
+        .lock()
+        .map_err(|_| anyhow!("控制接口状态锁失败"))?;
+    Ok(ControlApiStatus {
+        enabled: state.store.get_control_api_enabled()?,
+        running: server.shutdown.is_some(),
+        endpoint: control_endpoint(&state.store),
+        token_count: state.store.list_control_tokens()?.len(),
+        error: server.error.clone(),
+    })
+}
+
+pub fn start_control_server(app: &AppHandle) -> Result<()> {
+    let state = app.state::<AppState>();
+    let mut server = state
+        .control_server
+        .lock()
+        .map_err(|_| anyhow!("控制接口状态锁失败"))?;
+    if server.shutdown.is_some() {
+        return Ok(());
+    }
+
+    let endpoint = control_endpoint(&state.store);
+    let listener = match bind_endpoint(&endpoint) {
+        Ok(listener) => listener,
+        Err(error) => {
+            server.error = Some(error.to_string());
+            return Err(error);
+        }
+    };
+    let (shutdown_tx, shutdown_rx) = oneshot::channel();
+    server.shutdown = Some(shutdown_tx);
+    server.error = None;
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let result = accept_loop(app.clone(), listener, shutdown_rx).await;
+        let state = app.state::<AppState>();
+        if let Ok(mut server) = state.control_server.lock() {
+            server.shutdown = None;
+            server.error = result.err().map(|error| error.to_string());
+        };
+    });
+    Ok(())
+}
+
+pub fn stop_control_server(state: &AppState) -> Result<()> {
+    let shutdown = state
+        .control_server
+        .lock()
+        .map_err(|_| anyhow!("控制接口状态锁失败"))?
+        .shutdown
+        .take();
+    if let Some(shutdown) = shutdown {
+        let _ = shutdown.send(());
+    }
+    #[cfg(unix)]
+    {
+        let _ = std::fs::remove_file(control_endpoint(&state.store));
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+fn bind_endpoint(endpoint: &str) -> Result<std::os::unix::net::UnixListener> {
+    use anyhow::Context;
+    use std::{
+        fs,
+        os::unix::{
+            fs::PermissionsExt,
+            net::{UnixListener, UnixStream},
+        },
+        path::Path,
+    };
+
+    let path = Path::new(endpoint);
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)
+            .with_context(|| format!("创建控制接口目录失败: {}", dir.display()))?;
+        fs::set_permissions(dir, fs::Permissions::from_mode(0o700))
+            .with_context(|| format!("设置控制接口目录权限失败: {}", dir.display()))?;
+    }
+    if path.exists() {
+        if UnixStream::connect(path).is_ok() {
+            return Err(anyhow!("控制接口已被其他实例占用: {endpoint}"));
+        }
+        fs::remove_file(path).with_context(|| format!("清理旧控制接口失败: {endpoint}"))?;
+    }
+    let listener =
+        UnixListener::bind(path).with_context(|| format!("监听控制接口失败: {endpoint}"))?;
+    fs::set_permissions(path, fs::Permissions::from_mode(0o600))
+        .with_context(|| format!("设置控制接口权限失败: {endpoint}"))?;
+    listener.set_nonblocking(true)?;
+    Ok(listener)
+}
+
+#[cfg(windows)]
+fn bind_endpoint(endpoint: &str) -> Result<String> {
+    Ok(endpoint.to_string())
+}
+
+#[cfg(unix)]
+async fn accept_loop(
+    app: AppHandle,
+    listener: std::os::unix::net::UnixListener,
+    mut shutdown: oneshot::Receiver<()>,
+) -> Result<()> {
+    let listener = tokio::net::UnixListener::from_std(listener)?;
+    loop {
+        tokio::select! {
+            _ = &mut shutdown => return Ok(()),
+            accepted = listener.accept() => {
+                let (stream, _) = accepted?;
+                tauri::async_runtime::spawn(serve_connection(app.clone(), stream));
+            }
+        }
+    }
+}
+
+// 命名管道默认 DACL 仅允许创建者、SYSTEM 与管理员写入，其他用户无法发送请求。
+#[cfg(windows)]
+async fn accept_loop(
+    app: AppHandle,
+    endpoint: String,
+    mut shutdown: oneshot::Receiver<()>,
+) -> Result<()> {
+    use tokio::net::windows::named_pipe::ServerOptions;
+
+    let mut server = ServerOptions::new()
+        .first_pipe_instance(true)
+        .reject_remote_clients(true)
+        .create(&endpoint)?;
+    loop {
+        tokio::select! {
+            _ = &mut shutdown => return Ok(()),
+            connected = server.connect() => {
+                connected?;
+                let client = std::mem::replace(
+                    &mut server,
+                    ServerOptions::new()
+                        .reject_remote_clients(true)
+                        .create(&endpoint)?,
+                );
+                tauri::async_runtime::spawn(serve_connection(app.clone(), client));
+            }
+        }
+    }
+}
+
+async fn serve_connection<S>(app: AppHandle, stream: S)
+where
+    S: AsyncRead + AsyncWrite + Send + 'static,
+{
+    let (reader, mut writer) = tokio::io::split(stream);
+    let mut lines = BufReader::new(reader).lines();
+    let mut connection = Connection::default();
+
+    loop {
+        let message = tokio::select! {
+            line = lines.next_line() => match line {
+                Ok(Some(line)) if line.trim().is_empty() => continue,
+                Ok(Some(line)) => handle_line(&app, &mut connection, &line).await,
+                _ => break,
+            },
+            event = next_event(&mut connection.subscription) => event.map(|event| {
+                json!({ "jsonrpc": JSONRPC_VERSION, "method": "event", "params": event })
+            }),
+        };
+        let Some(message) = message else {
+            continue;
+        };
+        let mut line = message.to_string();
+        line.push('\n');
+        if writer.write_all(line.as_bytes()).await.is_err() {
+            break;
+        }
+    }
+}
+
+async fn next_event(subscription: &mut Option<Subscription>) -> Option<AppEvent> {
+    let Some(subscription) = subscription else {
+        return std::future::pending().await;
+    };
+    match subscription.receiver.recv().await {
+        Ok(event) if subscription.wants(&event.event) => Some(event),
+        Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) => None,
+        Err(broadcast::error::RecvError::Closed) => std::future::pending().await,
+    }
+}
+
+impl Subscription {
+    fn wants(&self, event: &str) -> bool {
+        self.events.is_empty() || self.events.iter().any(|item| item == event)
+    }
+}
+
+async fn handle_line(app: &AppHandle, connection: &mut Connection, line: &str) -> Option<Value> {
+    let request = match parse_request(line) {
+        Ok(request) => request,
+        Err(error) => return Some(error_response(Value::Null, error)),
+    };
+    let id = request.id.clone();
+    let result = handle_request(app, connection, request).await;
+    let id = id?;
+    Some(match result {
+        Ok(result) => json!({ "jsonrpc": JSONRPC_VERSION, "id": id, "result": result }),
+        Err(error) => error_response(id, error),
+    })
+}
+
+fn parse_request(line: &str) -> Result<RpcRequest, RpcError> {
+    let value: Value = serde_json::from_str(line)
+        .map_err(|error| RpcError::new(PARSE_ERROR, format!("请求不是合法 JSON：{error}")))?;
+    let request: RpcRequest = serde_json::from_value(value)
+        .map_err(|error| RpcError::new(INVALID_REQUEST, format!("请求格式无效：{error}")))?;
+    if request.jsonrpc.as_deref() != Some(JSONRPC_VERSION) {
+        return Err(RpcError::new(INVALID_REQUEST, "仅支持 JSON-RPC 2.0"));
+    }
+    Ok(request)
+}
+
+fn allowed_without_token(method: &str) -> bool {
+    TOKENLESS_METHODS.contains(&method)
+}
+
+fn error_response(id: Value, error: RpcError) -> Value {
+    json!({
+        "jsonrpc": JSONRPC_VERSION,
+        "id": id,
+        "error": { "code": error.code, "message": error.message },
+    })
+}
+
+async fn handle_request(
+    app: &AppHandle,
+    connection: &mut Connection,
+    request: RpcRequest,
+) -> Result<Value, RpcError> {
+    let state = app.state::<AppState>();
+    let params = &request.params;
+    if request.method == "authenticate" {
+        let token: String = param(params, "token")?;
+        let token = state
+            .store
+            .authenticate_control_token(&crypto::hash_token(&token))
+            .map_err(command_error)?
+            .ok_or_else(|| RpcError::new(UNAUTHORIZED, "令牌无效"))?;
+        connection.authenticated = true;
+        return Ok(json!({ "ok": true, "client": token.name }));
+    }
+
+    if !connection.authenticated {
+        let has_tokens = !state
+            .store
+            .list_control_tokens()
+            .map_err(command_error)?
+            .is_empty();
+        if has_tokens {
+            return Err(RpcError::new(
+                UNAUTHORIZED,
+                "已配置客户端令牌，请先调用 authenticate",
+            ));
+        }
+        if !allowed_without_token(&request.method) {
+            return Err(RpcError::new(
+                UNAUTHORIZED,
+                "该方法需要客户端令牌，请先在桌面端创建令牌并调用 authenticate",
+            ));
+        }
+    }
+
+    match request.method.as_str() {
+        "subscribe" => {
+            let events: Option<Vec<String>> = param(params, "events")?;
+            let events = events.unwrap_or_default();
+            connection.subscription = Some(Subscription {
+                receiver: state.subscribe_events(),
+                events: events.clone(),
+            });
+            Ok(json!({ "ok": true, "events": events }))
+        }
+        "unsubscribe" => {
+            connection.subscription = None;
+            Ok(json!({ "ok": true }))
+        }
+        "get_active_account" => {
+            to_value(service::active_account(&state).map_err(|error| error.to_string()))
+        }
+        method => dispatch(app, method, params).await,
+    }
+}
+
+async fn dispatch(app: &AppHandle, method: &str, params: &Value) -> Result<Value, RpcError> {
+    let state = || app.state::<AppState>();
+    match method {
+        "init_vault" => to_value(crate::init_vault(state(), param(params, "masterPassword")?)),
+        "unlock_vault" => to_value(crate::unlock_vault(
+            state(),
+            param(params, "masterPassword")?,
+        )),
+        "lock_vault" => to_value(crate::lock_vault(state())),
+        "vault_status" => to_value(crate::vault_status(state())),
+        "import_current_codex_auth" => to_value(crate::import_current_codex_auth(
+            state(),
+            param(params, "name")?,
+            param(params, "tags")?,
+        )),
+        "create_account_from_import" => to_value(crate::create_account_from_import(
+            state(),
+            param(params, "name")?,
+            param(params, "tags")?,
+        )),
+        "create_account_from_auth_file" => to_value(crate::create_account_from_auth_file(
+            state(),
+            param(params, "path")?,
+            param(params, "name")?,
+            param(params, "tags")?,
+        )),
+        "bulk_import_auth_files" => to_value(crate::bulk_import_auth_files(
+            state(),
+            param(params, "source")?,
+            param(params, "tags")?,
+            param(params, "nameSource")?,
+        )),
+        "list_account_importers" => to_value(crate::list_account_importers()),
+        "import_accounts_from" => to_value(crate::import_accounts_from(
+            state(),
+            param(params, "importer")?,
+            param(params, "source")?,
+            param(params, "tags")?,
+        )),
+        "create_account_from_login" => to_value(
+            crate::create_account_from_login(
+                state(),
+                param(params, "name")?,
+                param(params, "tags")?,
+            )
+            .await,
+        ),
+        "reauthenticate_account_from_login" => to_value(
+            crate::reauthenticate_account_from_login(state(), param(params, "accountId")?).await,
+        ),
+        "reauthenticate_account_from_file" => to_value(crate::reauthenticate_account_from_file(
+            state(),
+            param(params, "accountId")?,
+            param(params, "path")?,
+        )),
+        "list_account_events" => to_value(crate::list_account_events(
+            state(),
+            param(params, "accountId")?,
+        )),
+        "get_device_auth_config" => to_value(crate::get_device_auth_config(state())),
+        "set_device_auth_config" => to_value(crate::set_device_auth_config(
+            state(),
+            param(params, "config")?,
+        )),
+        "start_device_login" => {
+            to_value(crate::start_device_login(state(), param(params, "preferNative")?).await)
+        }
+        "complete_device_login" => to_value(
+            crate::complete_device_login(
+                state(),
+                param(params, "sessionId")?,
+                param(params, "name")?,
+                param(params, "tags")?,
+            )
+            .await,
+        ),
+        "cancel_device_login" => to_value(crate::cancel_device_login(
+            state(),
+            param(params, "sessionId")?,
+        )),
+        "list_accounts" => to_value(crate::list_accounts(state())),
+        "update_account_meta" => to_value(crate::update_account_meta(
+            state(),
+            param(params, "id")?,
+            param(params, "name")?,
+            param(params, "tags")?,
+        )),
+        "delete_account" => to_value(crate::delete_account(state(), param(params, "id")?)),
+        "list_trashed_accounts" => to_value(crate::list_trashed_accounts(state())),
+        "restore_account" => to_value(crate::restore_account(state(), param(params, "id")?)),
+        "purge_account" => to_value(crate::purge_account(state(), param(params, "id")?)),
+        "get_trash_retention_days" => to_value(crate::get_trash_retention_days(state())),
+        "set_trash_retention_days" => to_value(crate::set_trash_retention_days(
+            state(),
+            param(params, "days")?,
+        )),
+        "switch_account" => to_value(crate::switch_account(
+            state(),
+            param(params, "id")?,
+            param(params, "forceRestart")?,
+            param(params, "overrideGuards")?,
+        )),
+        "get_switch_guard_policy" => to_value(crate::get_switch_guard_policy(state())),
+        "set_switch_guard_policy" => to_value(crate::set_switch_guard_policy(
+            state(),
+            param(params, "policy")?,
+        )),
+        "get_failover_config" => to_value(crate::get_failover_config(state())),
+        "set_failover_config" => to_value(crate::set_failover_config(
+            state(),
+            param(params, "config")?,
+        )),
+        "run_failover_check" => to_value(crate::run_failover_check(state()).await),
+        "list_account_pools" => to_value(crate::list_account_pools(state())),
+        "save_account_pool" => to_value(crate::save_account_pool(state(), param(params, "pool")?)),
+        "delete_account_pool" => {
+            to_value(crate::delete_account_pool(state(), param(params, "id")?))
+        }
+        "preview_pool_rotation" => to_value(crate::preview_pool_rotation(
+            state(),
+            param(params, "poolId")?,
+        )),
+        "switch_to_next_in_pool" => to_value(crate::switch_to_next_in_pool(
+            state(),
+            param(params, "poolId")?,
+            param(params, "forceRestart")?,
+            param(params, "overrideGuards")?,
+        )),
+        "rollback_to_history" => to_value(crate::rollback_to_history(
+            state(),
+            param(params, "historyId")?,
+        )),
+        "list_switch_history" => {
+            to_value(crate::list_switch_history(state(), param(params, "limit")?))
+        }
+        "get_usage_analytics" => {
+            to_value(crate::get_usage_analytics(state(), param(params, "query")?))
+        }
+        "export_usage_csv" => to_value(crate::export_usage_csv(
+            state(),
+            param(params, "query")?,
+            param(params, "table")?,
+            param(params, "path")?,
+        )),
+        "export_vault" => to_value(crate::export_vault(
+            state(),
+            param(params, "path")?,
+            param(params, "passphrase")?,
+            param(params, "filter")?,
+        )),
+        "import_vault" => to_value(crate::import_vault(
+            state(),
+            param(params, "path")?,
+            param(params, "passphrase")?,
+            param(params, "strategy")?,
+        )),
+        "query_switch_history" => to_value(crate::query_switch_history(
+            state(),
+            param(params, "query")?,
+        )),
+        "refresh_quota" => to_value(
+            crate::refresh_quota(
+                state(),
+                param(params, "accountId")?,
+                param(params, "force")?,
+            )
+            .await,
+        ),
+        "check_accounts_health" => to_value(
+            crate::check_accounts_health(
+                state(),
+                param(params, "accountId")?,
+                param(params, "remote")?,
+            )
+            .await,
+        ),
+        "get_health_check_config" => to_value(crate::get_health_check_config(state())),
+        "set_health_check_config" => to_value(crate::set_health_check_config(
+            state(),
+            param(params, "config")?,
+        )),
+        "get_quota_dashboard" => to_value(crate::get_quota_dashboard(state())),
+        "list_quota_snapshots" => to_value(crate::list_quota_snapshots(
+            state(),
+            param(params, "accountId")?,
+            param(params, "limit")?,
+        )),
+        "set_quota_refresh_policy" => to_value(crate::set_quota_refresh_policy(
+            state(),
+            param(params, "policy")?,
+        )),
+        "get_snapshot_retention" => to_value(crate::get_snapshot_retention(state())),
+        "set_snapshot_retention" => to_value(crate::set_snapshot_retention(
+            state(),
+            param(params, "policy")?,
+        )),
+        "prune_snapshots" => to_value(crate::prune_snapshots(state(), param(params, "dryRun")?)),
+        "get_runtime_diagnostics" => to_value(crate::get_runtime_diagnostics(state())),
+        "check_data_integrity" => to_value(crate::check_data_integrity(
+            state(),
+            param(params, "repair")?,
+        )),
+        "get_codex_cli_status" => to_value(crate::get_codex_cli_status()),
+        "discover_codex_binaries" => to_value(crate::discover_codex_binaries(state()).await),
+        "set_codex_binary_path" => to_value(crate::set_codex_binary_path(
+            state(),
+            param(params, "path")?,
+        )),
+        "list_project_bindings" => to_value(crate::list_project_bindings(state())),
+        "set_project_binding" => to_value(crate::set_project_binding(
+            state(),
+            param(params, "path")?,
+            param(params, "accountId")?,
+            param(params, "tag")?,
+        )),
+        "delete_project_binding" => {
+            to_value(crate::delete_project_binding(state(), param(params, "id")?))
+        }
+        "resolve_project_account" => to_value(crate::resolve_project_account(
+            state(),
+            param(params, "path")?,
+        )),
+        "get_project_auto_switch" => to_value(crate::get_project_auto_switch(state())),
+        "set_project_auto_switch" => to_value(crate::set_project_auto_switch(
+            state(),
+            param(params, "enabled")?,
+        )),
+        _ => Err(RpcError::new(
+            METHOD_NOT_FOUND,
+            format!("未知方法：{method}"),
+        )),
+    }
+}
+
+fn param<T: DeserializeOwned>(params: &Value, key: &str) -> Result<T, RpcError> {
+    let value = params.get(key).cloned().unwrap_or(Value::Null);
+    serde_json::from_value(value)
+        .map_err(|error| RpcError::new(INVALID_PARAMS, format!("参数 {key} 无效：{error}")))
+}
+
+fn to_value<T: Serialize>(result: Result<T, String>) -> Result<Value, RpcError> {
+    let value = result.map_err(|message| RpcError::new(COMMAND_FAILED, message))?;
+    serde_json::to_value(value).map_err(|error| RpcError::new(COMMAND_FAILED, error.to_string()))
+}
+
+fn command_error(error: anyhow::Error) -> RpcError {
+    RpcError::new(COMMAND_FAILED, error.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        allowed_without_token, param, parse_request, Subscription, INVALID_PARAMS, INVALID_REQUEST,
+    };
+    use serde_json::json;
+    use tokio::sync::broadcast;
+
+    #[test]
+    fn parses_requests_and_params() {
+        let request = parse_request(
+            r#"{"jsonrpc":"2.0","id":7,"method":"switch_account","params":{"id":"a","forceRestart":true}}"#,
+        )
+        .expect("应解析请求");
+        assert_eq!(request.method, "switch_account");
+        let force: bool = param(&request.params, "forceRestart").expect("应读取参数");
+        assert!(force);
+        let limit: Option<usize> = param(&request.params, "limit").expect("可选参数可缺省");
+        assert!(limit.is_none());
+        let missing = param::<String>(&json!({}), "id").expect_err("必填参数缺失应报错");
+        assert_eq!(missing.code, INVALID_PARAMS);
+
+        let legacy = parse_request(r#"{"id":1,"method":"list_accounts"}"#).expect_err("应拒绝");
+        assert_eq!(legacy.code, INVALID_REQUEST);
+    }
+
+    #[test]
+    fn tokenless_connections_only_reach_read_only_methods() {
+        assert!(allowed_without_token("get_active_account"));
+        assert!(allowed_without_token("subscribe"));
+        for method in [
+            "export_vault",
+            "import_vault",
+            "unlock_vault",
+            "switch_account",
+        ] {
+            assert!(!allowed_without_token(method), "{method} 应要求令牌");
+        }
+    }
+
+    #[test]
+    fn subscription_filters_events() {
+        let (sender, _) = broadcast::channel(4);
+        let all = Subscription {
+            receiver: sender.subscribe(),
+            events: Vec::new(),
+        };
+        assert!(all.wants("quota-updated"));
+        let switches = Subscription {
+            receiver: sender.subscribe(),
+            events: vec!["account-switched".to_string()],
+        };
+        assert!(switches.wants("account-switched"));
+        assert!(!switches.wants("quota-updated"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn socket_is_owner_only() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let root = std::env::temp_dir().join(format!("codex-switch-rpc-{}", uuid::Uuid::new_v4()));
+        let endpoint = root.join("run").join("control.sock");
+        let listener = super::bind_endpoint(&endpoint.display().to_string()).expect("应监听");
+        let mode = std::fs::metadata(&endpoint)
+            .expect("应存在")
+            .permissions()
+            .mode();
+        assert_eq!(mode & 0o777, 0o600);
+        let dir_mode = std::fs::metadata(root.join("run"))
+            .expect("应存在")
+            .permissions()
+            .mode();
+        assert_eq!(dir_mode & 0o777, 0o700);
+        assert!(super::bind_endpoint(&endpoint.display().to_string()).is_err());
+        drop(listener);
+        std::fs::remove_dir_all(&root).expect("应清理目录");
+    }
+}
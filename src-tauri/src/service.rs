@@ -0,0 +1,394 @@
+use crate::{
+    app_state::AppState,
+    codex::{
+        atomic_write, codex_auth_path, count_codex_processes, kill_codex_processes,
+        read_and_validate_auth_json, restart_codex, validate_auth_json,
+    },
+    compat::{auth_text_field, render_auth_json, CodexCompat},
+    crypto,
+    guards::{evaluate_guards, failed_guards, GuardAction},
+    models::{
+        AccountHealth, GuardResult, QuotaDashboardItem, QuotaSnapshot, RuntimeDiagnostics,
+        SwitchResult,
+    },
+    quota::{ensure_access_token, probe_quota},
+    snapshots::{prune_snapshots, read_verified_snapshot, store_snapshot},
+};
+use anyhow::{anyhow, Result};
+use serde_json::{json, Value};
+use std::{collections::HashMap, path::PathBuf};
+use zeroize::Zeroize;
+
+pub const ACCOUNT_SWITCHED_EVENT: &str = "account-switched";
+pub const QUOTA_UPDATED_EVENT: &str = "quota-updated";
+
+pub fn default_base_dir() -> PathBuf {
+    dirs::data_local_dir()
+        .or_else(dirs::home_dir)
+        .unwrap_or_else(|| std::env::current_dir().unwrap_or_else(|_| PathBuf::from(".")))
+        .join("codex-switch")
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SwitchOptions {
+    pub force_restart: bool,
+    // 忽略阻止级别的守卫继续切换，守卫结果仍随 SwitchResult 返回
+    pub override_guards: bool,
+    // 由新启动的 Codex 进程触发（项目自动切换），不检查运行中进程
+    pub triggered_by_process: bool,
+}
+
+pub fn switch_to_account(
+    state: &AppState,
+    id: &str,
+    options: SwitchOptions,
+) -> Result<SwitchResult> {
+    let force_restart = options.force_restart;
+    let account_secret = state
+        .store
+        .get_account_secret(id.trim())?
+        .ok_or_else(|| anyhow!("目标账户不存在"))?;
+
+    let from_account = state.store.get_current_account_id()?;
+    let mut key = state.get_vault_key()?;
+    let decrypted = crypto::decrypt_from_base64(&key, &account_secret.encrypted_auth_blob)?;
+    key.zeroize();
+    let auth_text = String::from_utf8(decrypted)?;
+    let auth_json = validate_auth_json(&auth_text)?;
+
+    let policy = state.store.get_switch_guard_policy()?;
+    let guards = evaluate_guards(
+        state,
+        &policy,
+        &account_secret.account.id,
+        &auth_json,
+        !force_restart && !options.triggered_by_process,
+    )?;
+    let blocking = failed_guards(&guards, GuardAction::Block);
+    if !blocking.is_empty() && !options.override_guards {
+        let reasons = guard_messages(&blocking);
+        return Ok(SwitchResult {
+            success: false,
+            history_id: String::new(),
+            snapshot_path: None,
+            message: format!("切换已被阻止：{reasons}。确认无误后可选择仍然切换。"),
+            blocked: true,
+            guards,
+        });
+    }
+    let warnings = guard_messages(
+        &guards
+            .iter()
+            .filter(|guard| !guard.passed)
+            .collect::<Vec<_>>(),
+    );
+
+    let (binary, compat) = state.resolve_codex_compat()?;
+    let auth_text = render_live_auth(&auth_json, &compat)?;
+
+    let auth_path = codex_auth_path()?;
+    let snapshot = store_snapshot(&state.store, &auth_path)?;
+
+    let write_result = atomic_write(&auth_path, &auth_text);
+    if let Err(error) = write_result {
+        let history_id = state.store.create_switch_history(
+            from_account.as_deref(),
+            Some(account_secret.account.id.as_str()),
+            snapshot.as_ref(),
+            "failed",
+            Some(&error.to_string()),
+        )?;
+        return Ok(SwitchResult {
+            success: false,
+            history_id,
+            snapshot_path: snapshot.map(|snapshot| snapshot.path.display().to_string()),
+            message: format!("切换失败：{error}"),
+            blocked: false,
+            guards,
+        });
+    }
+
+    let mut killed_count = 0usize;
+    if force_restart {
+        killed_count = kill_codex_processes();
+        let _ = restart_codex(binary.as_ref().map(|binary| binary.path.as_path()));
+    }
+    state
+        .store
+        .mark_account_used(account_secret.account.id.as_str())?;
+    let history_id = state.store.create_switch_history(
+        from_account.as_deref(),
+        Some(account_secret.account.id.as_str()),
+        snapshot.as_ref(),
+        "success",
+        None,
+    )?;
+    let _ = prune_snapshots(&state.store, false);
+    let mut message = if force_restart {
+        format!("切换完成，已处理 {killed_count} 个 Codex 进程")
+    } else {
+        "切换完成".to_string()
+    };
+    if !warnings.is_empty() {
+        message.push_str(&format!("（注意：{warnings}）"));
+    }
+    let result = SwitchResult {
+        success: true,
+        history_id,
+        snapshot_path: snapshot.map(|snapshot| snapshot.path.display().to_string()),
+        message,
+        blocked: false,
+        guards,
+    };
+    state.publish_event(
+        ACCOUNT_SWITCHED_EVENT,
+        &json!({
+            "account_id": account_secret.account.id,
+            "account_name": account_secret.account.name,
+            "result": result,
+        }),
+    );
+    Ok(result)
+}
+
+// 当前账户的凭据被替换后，按切换时的格式改写 ~/.codex/auth.json，避免 Codex 继续使用旧令牌。
+pub fn rewrite_live_auth_if_current(
+    state: &AppState,
+    account_id: &str,
+    auth_json: &Value,
+) -> Result<bool> {
+    if state.store.get_current_account_id()?.as_deref() != Some(account_id) {
+        return Ok(false);
+    }
+    let (_, compat) = state.resolve_codex_compat()?;
+    atomic_write(&codex_auth_path()?, &render_live_auth(auth_json, &compat)?)?;
+    Ok(true)
+}
+
+fn render_live_auth(auth_json: &Value, compat: &CodexCompat) -> Result<String> {
+    Ok(serde_json::to_string_pretty(&render_auth_json(
+        auth_json,
+        compat.profile.auth_layout,
+    ))?)
+}
+
+pub fn rollback_to_history(state: &AppState, history_id: &str) -> Result<SwitchResult> {
+    let history = state
+        .store
+        .get_switch_history(history_id.trim())?
+        .ok_or_else(|| anyhow!("历史记录不存在"))?;
+    let snapshot_content = read_verified_snapshot(&history)?;
+    validate_auth_json(&snapshot_content)?;
+    let auth_path = codex_auth_path()?;
+    let current_snapshot = store_snapshot(&state.store, &auth_path)?;
+    atomic_write(&auth_path, &snapshot_content)?;
+    let killed_count = kill_codex_processes();
+    let binary = state.resolve_codex_binary()?;
+    let _ = restart_codex(binary.as_ref().map(|binary| binary.path.as_path()));
+    let created_history_id = state.store.create_switch_history(
+        history.from_account_id.as_deref(),
+        history.to_account_id.as_deref(),
+        current_snapshot.as_ref(),
+        "rolled_back",
+        None,
+    )?;
+    let _ = prune_snapshots(&state.store, false);
+
+    let result = SwitchResult {
+        success: true,
+        history_id: created_history_id,
+        snapshot_path: history.snapshot_path.clone(),
+        message: format!("回滚完成，已处理 {killed_count} 个 Codex 进程"),
+        blocked: false,
+        guards: Vec::new(),
+    };
+    let account_name = history
+        .from_account_id
+        .as_deref()
+        .and_then(|id| state.store.get_account(id).ok().flatten())
+        .map(|account| account.name);
+    state.publish_event(
+        ACCOUNT_SWITCHED_EVENT,
+        &json!({
+            "account_id": history.from_account_id,
+            "account_name": account_name,
+            "result": result,
+        }),
+    );
+    Ok(result)
+}
+
+fn guard_messages(guards: &[&GuardResult]) -> String {
+    guards
+        .iter()
+        .map(|guard| guard.message.as_str())
+        .collect::<Vec<_>>()
+        .join("；")
+}
+
+pub async fn refresh_quota(
+    state: &AppState,
+    account_id: Option<&str>,
+    force: bool,
+) -> Result<Vec<QuotaSnapshot>> {
+    let mut key = state.get_vault_key()?;
+    let accounts = if let Some(account_id) = account_id {
+        let account = state
+            .store
+            .get_account_secret(account_id.trim())?
+            .ok_or_else(|| anyhow!("账户不存在"))?;
+        vec![account]
+    } else {
+        let account_list = state.store.list_accounts()?;
+        let mut result = Vec::new();
+        for account in account_list {
+            if let Some(secret) = state.store.get_account_secret(&account.id)? {
+                result.push(secret);
+            }
+        }
+        result
+    };
+
+    let (timeout_ms, ttl_seconds, _max_concurrency) = state.store.get_quota_policy()?;
+    let (_, compat) = state.resolve_codex_compat_async().await?;
+    let mut snapshots = Vec::new();
+
+    for account in accounts {
+        if !force {
+            if let Some(existing) = state.store.latest_quota_by_account(&account.account.id)? {
+                let age = chrono::DateTime::parse_from_rfc3339(&existing.created_at)
+                    .map(|time| {
+                        chrono::Utc::now()
+                            .signed_duration_since(time.with_timezone(&chrono::Utc))
+                            .num_seconds()
+                    })
+                    .unwrap_or(i64::MAX);
+                if age >= 0 && age as u64 <= ttl_seconds {
+                    snapshots.push(existing);
+                    continue;
+                }
+            }
+        }
+
+        let decrypted = crypto::decrypt_from_base64(&key, &account.encrypted_auth_blob)?;
+        let auth_text = String::from_utf8(decrypted)?;
+        let auth_json: Value = serde_json::from_str(&auth_text)?;
+        let access_token = ensure_access_token(&auth_json)?;
+        let chatgpt_account_id = auth_text_field(&auth_json, "account_id");
+        let probe = probe_quota(&access_token, chatgpt_account_id, timeout_ms, &compat).await;
+        let saved = state.store.save_quota_snapshot(
+            &account.account.id,
+            &probe.mode,
+            probe.remaining_value,
+            probe.remaining_unit.as_deref(),
+            &probe.quota_state,
+            probe.reset_at.as_deref(),
+            &probe.source,
+            probe.confidence,
+            probe.reason.as_deref(),
+        )?;
+        snapshots.push(saved);
+    }
+    key.zeroize();
+    state.publish_event(QUOTA_UPDATED_EVENT, &snapshots);
+    Ok(snapshots)
+}
+
+pub fn active_account(state: &AppState) -> Result<Option<QuotaDashboardItem>> {
+    match state.store.get_current_account_id()? {
+        Some(account_id) => account_with_quota(state, &account_id),
+        None => Ok(None),
+    }
+}
+
+pub fn account_with_quota(
+    state: &AppState,
+    account_id: &str,
+) -> Result<Option<QuotaDashboardItem>> {
+    let Some(account) = state.store.get_account(account_id)? else {
+        return Ok(None);
+    };
+    let snapshot = state.store.latest_quota_by_account(&account.id)?;
+    let health = state.store.get_account_health(&account.id)?;
+    Ok(Some(QuotaDashboardItem {
+        account,
+        snapshot,
+        health,
+    }))
+}
+
+pub fn quota_dashboard(state: &AppState) -> Result<Vec<QuotaDashboardItem>> {
+    let accounts = state.store.list_accounts()?;
+    let snapshots = state.store.list_latest_quota_snapshots()?;
+    let snapshot_map: HashMap<String, QuotaSnapshot> = snapshots
+        .into_iter()
+        .map(|snapshot| (snapshot.account_id.clone(), snapshot))
+        .collect();
+    let mut health_map: HashMap<String, AccountHealth> = state
+        .store
+        .list_account_health()?
+        .into_iter()
+        .map(|health| (health.account_id.clone(), health))
+        .collect();
+
+    let mut dashboard = accounts
+        .into_iter()
+        .map(|account| {
+            let snapshot = snapshot_map.get(&account.id).cloned();
+            let health = health_map.remove(&account.id);
+            QuotaDashboardItem {
+                account,
+                snapshot,
+                health,
+            }
+        })
+        .collect::<Vec<_>>();
+
+    dashboard.sort_by_key(|item| {
+        state_rank(
+            item.snapshot
+                .as_ref()
+                .map(|snapshot| snapshot.quota_state.as_str()),
+        )
+    });
+    Ok(dashboard)
+}
+
+pub fn runtime_diagnostics(state: &AppState) -> Result<RuntimeDiagnostics> {
+    let auth_path = codex_auth_path()?;
+    let codex_auth_exists = auth_path.exists();
+    let schema_ok = if codex_auth_exists {
+        read_and_validate_auth_json(&auth_path).is_ok()
+    } else {
+        false
+    };
+
+    let process_count = count_codex_processes();
+    let (binary, compat) = state.resolve_codex_compat()?;
+
+    Ok(RuntimeDiagnostics {
+        codex_auth_path: auth_path.display().to_string(),
+        codex_auth_exists,
+        app_data_dir: state.store.base_dir.display().to_string(),
+        db_path: state.store.db_path.display().to_string(),
+        db_schema_version: state.store.schema_version()?,
+        schema_ok,
+        process_count,
+        codex_binary_path: binary
+            .as_ref()
+            .map(|binary| binary.path.display().to_string()),
+        codex_binary_source: binary.as_ref().map(|binary| binary.source.clone()),
+        codex_version: binary.and_then(|binary| binary.version),
+        codex_compat_profile: compat.profile.name.to_string(),
+        codex_compat_warning: compat.warning,
+    })
+}
+
+fn state_rank(state: Option<&str>) -> u8 {
+    match state.unwrap_or("unknown") {
+        "available" => 0,
+        "near_limit" => 1,
+        "exhausted" => 2,
+        _ => 3,
+    }
+}
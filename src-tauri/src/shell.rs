@@ -0,0 +1,163 @@
+use crate::models::QuotaDashboardItem;
+use clap::ValueEnum;
+
+pub const ACCOUNT_ENV: &str = "CODEX_SWITCH_ACCOUNT";
+pub const DEFAULT_PROMPT_FORMAT: &str = "[{name}|{state}]";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ShellKind {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+pub fn init_script(shell: ShellKind, exe: &str) -> String {
+    let exe = quote(shell, exe);
+    match shell {
+        ShellKind::Bash => format!(
+            r#"codex_switch_prompt() {{
+  local segment
+  segment="$({exe} prompt 2>/dev/null)"
+  [ -n "$segment" ] && printf '%s ' "$segment"
+}}
+codex-use() {{
+  local script
+  script="$({exe} env --shell bash "$@")" && eval "$script"
+}}
+case "$PS1" in
+  *codex_switch_prompt*) ;;
+  *) PS1='$(codex_switch_prompt)'"$PS1" ;;
+esac
+"#
+        ),
+        ShellKind::Zsh => format!(
+            r#"codex_switch_prompt() {{
+  local segment
+  segment="$({exe} prompt 2>/dev/null)"
+  [[ -n "$segment" ]] && print -rn -- "$segment "
+}}
+codex-use() {{
+  local script
+  script="$({exe} env --shell zsh "$@")" && eval "$script"
+}}
+setopt prompt_subst
+[[ "$RPROMPT" == *codex_switch_prompt* ]] || RPROMPT='$(codex_switch_prompt)'"$RPROMPT"
+"#
+        ),
+        ShellKind::Fish => format!(
+            r#"function codex_switch_prompt
+    set -l segment ({exe} prompt 2>/dev/null)
+    test -n "$segment"; and printf '%s ' $segment
+end
+function codex-use
+    {exe} env --shell fish $argv | source
+end
+if not functions -q fish_right_prompt
+    function fish_right_prompt
+        codex_switch_prompt
+    end
+end
+"#
+        ),
+    }
+}
+
+pub fn env_script(shell: ShellKind, vars: &[(&str, Option<&str>)]) -> String {
+    vars.iter()
+        .map(|(name, value)| match (shell, value) {
+            (ShellKind::Fish, Some(value)) => format!("set -gx {name} {}", quote(shell, value)),
+            (ShellKind::Fish, None) => format!("set -e {name}"),
+            (_, Some(value)) => format!("export {name}={}", quote(shell, value)),
+            (_, None) => format!("unset {name}"),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+pub fn render_prompt(format: &str, item: &QuotaDashboardItem) -> String {
+    let snapshot = item.snapshot.as_ref();
+    let remaining = snapshot
+        .and_then(|snapshot| {
+            let value = snapshot.remaining_value?;
+            Some(match snapshot.remaining_unit.as_deref() {
+                Some("%") => format!("{value}%"),
+                Some(unit) => format!("{value} {unit}"),
+                None => value.to_string(),
+            })
+        })
+        .unwrap_or_else(|| "-".to_string());
+    format
+        .replace("{name}", &item.account.name)
+        .replace(
+            "{state}",
+            snapshot
+                .map(|snapshot| snapshot.quota_state.as_str())
+                .unwrap_or("unknown"),
+        )
+        .replace("{remaining}", &remaining)
+}
+
+fn quote(shell: ShellKind, value: &str) -> String {
+    match shell {
+        ShellKind::Fish => format!("'{}'", value.replace('\\', "\\\\").replace('\'', "\\'")),
+        _ => format!("'{}'", value.replace('\'', r"'\''")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{env_script, render_prompt, ShellKind};
+    use crate::models::{Account, QuotaDashboardItem, QuotaSnapshot};
+
+    #[test]
+    fn quotes_env_values_per_shell() {
+        let vars = [
+            ("CODEX_HOME", Some("/tmp/it's")),
+            ("CODEX_SWITCH_ACCOUNT", None),
+        ];
+        assert_eq!(
+            env_script(ShellKind::Bash, &vars),
+            "export CODEX_HOME='/tmp/it'\\''s'\nunset CODEX_SWITCH_ACCOUNT"
+        );
+        assert_eq!(
+            env_script(ShellKind::Fish, &vars),
+            "set -gx CODEX_HOME '/tmp/it\\'s'\nset -e CODEX_SWITCH_ACCOUNT"
+        );
+    }
+
+    #[test]
+    fn renders_prompt_from_latest_snapshot() {
+        let mut item = QuotaDashboardItem {
+            account: Account {
+                id: "a".to_string(),
+                name: "work".to_string(),
+                tags: Vec::new(),
+                auth_fingerprint: "account:a".to_string(),
+                created_at: "2026-01-01T00:00:00Z".to_string(),
+                updated_at: "2026-01-01T00:00:00Z".to_string(),
+                last_used_at: None,
+            },
+            snapshot: None,
+            health: None,
+        };
+        assert_eq!(render_prompt("[{name}|{state}]", &item), "[work|unknown]");
+
+        item.snapshot = Some(QuotaSnapshot {
+            id: "s".to_string(),
+            account_id: "a".to_string(),
+            mode: "exact".to_string(),
+            remaining_value: Some(42.0),
+            remaining_unit: Some("%".to_string()),
+            quota_state: "available".to_string(),
+            reset_at: None,
+            source: "usage_api".to_string(),
+            confidence: 90,
+            reason: None,
+            created_at: "2026-01-01T00:00:00Z".to_string(),
+        });
+        assert_eq!(
+            render_prompt("{name} {state} {remaining}", &item),
+            "work available 42%"
+        );
+    }
+}
@@ -0,0 +1,289 @@
+use crate::{
+    codex::{atomic_write, secure_remove_file},
+    crypto,
+    models::{PrunedSnapshot, SnapshotPruneReport, SwitchHistory},
+    store::AppStore,
+};
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use std::{
+    collections::HashSet,
+    fs,
+    path::{Path, PathBuf},
+};
+
+// 快照按 auth.json 内容的 SHA-256 命名，相同登录状态只保存一份，由切换历史按哈希引用。
+#[derive(Debug, Clone)]
+pub struct Snapshot {
+    pub path: PathBuf,
+    pub hash: String,
+}
+
+pub fn store_snapshot(store: &AppStore, auth_path: &Path) -> Result<Option<Snapshot>> {
+    if !auth_path.exists() {
+        return Ok(None);
+    }
+    let content = fs::read(auth_path)
+        .with_context(|| format!("备份登录文件失败: {}", auth_path.display()))?;
+    write_snapshot(store, &content).map(Some)
+}
+
+pub fn read_verified_snapshot(history: &SwitchHistory) -> Result<String> {
+    let path = history
+        .snapshot_path
+        .as_ref()
+        .map(PathBuf::from)
+        .ok_or_else(|| match history.snapshot_pruned_at {
+            Some(_) => anyhow!("该历史记录的快照已按保留策略清理，无法回滚"),
+            None => anyhow!("该历史记录没有可回滚快照"),
+        })?;
+    if !path.exists() {
+        return Err(anyhow!("快照文件不存在: {}", path.display()));
+    }
+    let content = fs::read(&path).with_context(|| format!("读取快照失败: {}", path.display()))?;
+    if let Some(hash) = history.snapshot_hash.as_deref() {
+        if crypto::sha256_hex(&content) != hash {
+            return Err(anyhow!(
+                "快照内容校验失败，文件可能已损坏或被修改: {}",
+                path.display()
+            ));
+        }
+    }
+    String::from_utf8(content).context("快照内容不是有效的 UTF-8")
+}
+
+// 仅删除已无历史引用的快照文件；同一内容可能被多条历史共享。
+pub fn release_snapshots<P: AsRef<Path>>(store: &AppStore, paths: &[P]) -> Result<usize> {
+    let mut removed = 0;
+    for path in paths {
+        let path = path.as_ref();
+        let hash = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .unwrap_or_default();
+        if path.is_file() && store.count_snapshot_references(hash)? == 0 {
+            secure_remove_file(path)?;
+            removed += 1;
+        }
+    }
+    Ok(removed)
+}
+
+pub fn adopt_legacy_snapshots(store: &AppStore) -> Result<usize> {
+    let mut adopted = 0;
+    for (history_id, legacy_path) in store.list_unhashed_snapshots()? {
+        let legacy_path = PathBuf::from(legacy_path);
+        let Ok(content) = fs::read(&legacy_path) else {
+            continue;
+        };
+        let snapshot = write_snapshot(store, &content)?;
+        store.set_history_snapshot(&history_id, &snapshot)?;
+        if legacy_path != snapshot.path {
+            secure_remove_file(&legacy_path)?;
+        }
+        adopted += 1;
+    }
+    Ok(adopted)
+}
+
+fn write_snapshot(store: &AppStore, content: &[u8]) -> Result<Snapshot> {
+    let hash = crypto::sha256_hex(content);
+    let path = store.snapshots_dir.join(format!("{hash}.json"));
+    let intact = fs::read(&path)
+        .map(|existing| crypto::sha256_hex(&existing) == hash)
+        .unwrap_or(false);
+    if !intact {
+        let text = std::str::from_utf8(content).context("登录文件不是有效的 UTF-8")?;
+        atomic_write(&path, text)?;
+    }
+    Ok(Snapshot { path, hash })
+}
+
+// 最近 protect_recent 条历史引用的快照始终保留；其余超出数量或超过天数的快照会被清理。
+pub fn prune_snapshots(store: &AppStore, dry_run: bool) -> Result<SnapshotPruneReport> {
+    let policy = store.get_snapshot_retention()?;
+    let protected = store
+        .list_switch_history(policy.protect_recent)?
+        .into_iter()
+        .map(|item| item.id)
+        .collect::<HashSet<_>>();
+    let cutoff = Utc::now() - Duration::days(policy.max_age_days as i64);
+
+    let mut kept = 0;
+    let mut pruned = Vec::new();
+    for (index, item) in store.list_snapshot_history()?.into_iter().enumerate() {
+        let Some(snapshot_path) = item.snapshot_path else {
+            continue;
+        };
+        let expired = DateTime::parse_from_rfc3339(&item.created_at)
+            .map(|time| time.with_timezone(&Utc) < cutoff)
+            .unwrap_or(false);
+        let reason = if protected.contains(&item.id) {
+            None
+        } else if index >= policy.keep_count {
+            Some(format!("超出保留数量 {}", policy.keep_count))
+        } else if expired {
+            Some(format!("超过保留天数 {}", policy.max_age_days))
+        } else {
+            None
+        };
+        match reason {
+            Some(reason) => pruned.push(PrunedSnapshot {
+                history_id: item.id,
+                snapshot_path,
+                created_at: item.created_at,
+                reason,
+            }),
+            None => kept += 1,
+        }
+    }
+
+    if !dry_run && !pruned.is_empty() {
+        let ids = pruned
+            .iter()
+            .map(|item| item.history_id.clone())
+            .collect::<Vec<_>>();
+        store.mark_snapshots_pruned(&ids)?;
+        let paths = pruned
+            .iter()
+            .map(|item| item.snapshot_path.as_str())
+            .collect::<Vec<_>>();
+        release_snapshots(store, &paths)?;
+    }
+    Ok(SnapshotPruneReport {
+        dry_run,
+        kept,
+        pruned,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{prune_snapshots, read_verified_snapshot, release_snapshots, store_snapshot};
+    use crate::{models::SnapshotRetentionPolicy, store::AppStore};
+    use rusqlite::params;
+    use std::fs;
+
+    #[test]
+    fn prunes_by_count_and_age_but_keeps_recent_history() {
+        let root =
+            std::env::temp_dir().join(format!("codex-switch-prune-{}", uuid::Uuid::new_v4()));
+        let store = AppStore::new(root.clone());
+        store.init().expect("应初始化");
+        store
+            .set_snapshot_retention(&SnapshotRetentionPolicy {
+                keep_count: 3,
+                max_age_days: 7,
+                protect_recent: 1,
+            })
+            .expect("应写入策略");
+
+        // 由新到旧：0-2 在数量内（2 已过期），3-4 超出数量；最新一条无快照但占用保护名额。
+        let ages = [1, 2, 8, 9, 10];
+        for (index, days) in ages.iter().enumerate() {
+            let path = store.snapshots_dir.join(format!("snapshot-{index}.json"));
+            fs::write(&path, "{}").expect("应写入快照");
+            let created_at = (chrono::Utc::now() - chrono::Duration::days(*days)).to_rfc3339();
+            store
+                .conn()
+                .expect("应获取连接")
+                .execute(
+                    "INSERT INTO switch_history(id, to_account_id, snapshot_path, result, created_at) VALUES (?1, NULL, ?2, 'success', ?3)",
+                    params![format!("h{index}"), path.display().to_string(), created_at],
+                )
+                .expect("应写入历史");
+        }
+        store
+            .create_switch_history(None, None, None, "failed", None)
+            .expect("应写入历史");
+
+        let report = prune_snapshots(&store, true).expect("应预览");
+        let mut ids = report
+            .pruned
+            .iter()
+            .map(|item| item.history_id.as_str())
+            .collect::<Vec<_>>();
+        ids.sort();
+        assert_eq!(ids, ["h2", "h3", "h4"]);
+        assert_eq!(report.kept, 2);
+        assert!(store.snapshots_dir.join("snapshot-4.json").is_file());
+
+        prune_snapshots(&store, false).expect("应清理");
+        assert!(!store.snapshots_dir.join("snapshot-4.json").exists());
+        assert!(store.snapshots_dir.join("snapshot-0.json").is_file());
+        let history = store
+            .get_switch_history("h4")
+            .expect("应读取")
+            .expect("历史应保留");
+        assert!(history.snapshot_path.is_none());
+        assert!(history.snapshot_pruned_at.is_some());
+        assert!(prune_snapshots(&store, false)
+            .expect("应清理")
+            .pruned
+            .is_empty());
+        fs::remove_dir_all(&root).expect("应清理目录");
+    }
+
+    #[test]
+    fn identical_snapshots_share_one_file_until_unreferenced() {
+        let root =
+            std::env::temp_dir().join(format!("codex-switch-dedup-{}", uuid::Uuid::new_v4()));
+        let store = AppStore::new(root.clone());
+        store.init().expect("应初始化");
+        let auth_file = root.join("auth.json");
+        fs::write(&auth_file, r#"{"tokens":{}}"#).expect("应写入登录文件");
+
+        let first = store_snapshot(&store, &auth_file)
+            .expect("应备份")
+            .expect("应有快照");
+        let second = store_snapshot(&store, &auth_file)
+            .expect("应备份")
+            .expect("应有快照");
+        assert_eq!(first.path, second.path);
+        assert_eq!(
+            fs::read_dir(&store.snapshots_dir).expect("应读取").count(),
+            1
+        );
+
+        let kept = store
+            .create_switch_history(None, None, Some(&first), "success", None)
+            .expect("应写入历史");
+        let kept = store
+            .get_switch_history(&kept)
+            .expect("应读取")
+            .expect("历史应存在");
+        let history = store
+            .create_switch_history(None, None, Some(&second), "success", None)
+            .expect("应写入历史");
+        let history = store
+            .get_switch_history(&history)
+            .expect("应读取")
+            .expect("历史应存在");
+        assert_eq!(
+            read_verified_snapshot(&history).expect("应通过校验"),
+            r#"{"tokens":{}}"#
+        );
+
+        store
+            .mark_snapshots_pruned(std::slice::from_ref(&history.id))
+            .expect("应标记清理");
+        assert_eq!(
+            release_snapshots(&store, &[&first.path]).expect("应释放"),
+            0
+        );
+        assert!(first.path.is_file());
+
+        fs::write(&first.path, r#"{"tokens":{"x":1}}"#).expect("应篡改快照");
+        assert!(read_verified_snapshot(&kept).is_err());
+
+        store
+            .mark_snapshots_pruned(std::slice::from_ref(&kept.id))
+            .expect("应标记清理");
+        assert_eq!(
+            release_snapshots(&store, &[&first.path]).expect("应释放"),
+            1
+        );
+        assert!(!first.path.exists());
+        fs::remove_dir_all(&root).expect("应清理目录");
+    }
+}
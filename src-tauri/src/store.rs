@@ -1,17 +1,81 @@
-use crate::models::{Account, QuotaSnapshot, SwitchHistory};
+use crate::{
+    failover::{FailoverConfig, RestartMode},
+    guards::{GuardAction, SwitchGuardPolicy},
+    migrations,
+    models::{
+        Account, AccountEvent, AccountHealth, AccountPool, ControlToken, DeviceAuthConfig,
+        HealthCheckConfig, ProjectBinding, QuotaSnapshot, SnapshotRetentionPolicy, SwitchHistory,
+        SwitchHistoryEntry, SwitchHistoryPage, SwitchHistoryQuery,
+    },
+    snapshots::Snapshot,
+};
 use anyhow::{anyhow, Context, Result};
-use chrono::Utc;
-use rusqlite::{params, Connection, OptionalExtension};
-use std::path::{Path, PathBuf};
+use chrono::{DateTime, Utc};
+use rusqlite::{
+    params, params_from_iter, types::Value, CachedStatement, Connection, OpenFlags,
+    OptionalExtension, Params, Row, Transaction,
+};
+use std::{
+    collections::HashMap,
+    ops::{Deref, DerefMut},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, MutexGuard},
+    time::Duration,
+};
 use uuid::Uuid;
 
 const SETTINGS_SINGLETON_ID: i64 = 1;
+// 桌面端与命令行共用同一数据库，WAL 下写锁冲突时等待而非立即报 SQLITE_BUSY。
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+const STATEMENT_CACHE_CAPACITY: usize = 64;
+const HISTORY_PAGE_DEFAULT: usize = 50;
+const HISTORY_PAGE_MAX: usize = 500;
+const HISTORY_RESULTS: [&str; 3] = ["success", "failed", "rolled_back"];
 
 #[derive(Debug, Clone)]
 pub struct AppStore {
     pub base_dir: PathBuf,
     pub db_path: PathBuf,
     pub snapshots_dir: PathBuf,
+    pub login_homes_dir: PathBuf,
+    pub homes_dir: PathBuf,
+    pub backups_dir: PathBuf,
+    conn: Arc<Mutex<Option<Connection>>>,
+}
+
+// 共享连接的独占访问；execute / query_row 走预编译语句缓存。
+pub struct StoreConn<'a>(MutexGuard<'a, Option<Connection>>);
+
+impl Deref for StoreConn<'_> {
+    type Target = Connection;
+
+    fn deref(&self) -> &Connection {
+        self.0.as_ref().expect("数据库连接已初始化")
+    }
+}
+
+impl DerefMut for StoreConn<'_> {
+    fn deref_mut(&mut self) -> &mut Connection {
+        self.0.as_mut().expect("数据库连接已初始化")
+    }
+}
+
+impl StoreConn<'_> {
+    pub fn execute<P: Params>(&self, sql: &str, params: P) -> rusqlite::Result<usize> {
+        self.statement(sql)?.execute(params)
+    }
+
+    pub fn query_row<T, P, F>(&self, sql: &str, params: P, f: F) -> rusqlite::Result<T>
+    where
+        P: Params,
+        F: FnOnce(&Row<'_>) -> rusqlite::Result<T>,
+    {
+        self.statement(sql)?.query_row(params, f)
+    }
+
+    fn statement(&self, sql: &str) -> rusqlite::Result<CachedStatement<'_>> {
+        Deref::deref(self).prepare_cached(sql)
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -29,10 +93,17 @@ impl AppStore {
     pub fn new(base_dir: PathBuf) -> Self {
         let db_path = base_dir.join("codex-switch.db");
         let snapshots_dir = base_dir.join("snapshots");
+        let login_homes_dir = base_dir.join("login-homes");
+        let homes_dir = base_dir.join("homes");
+        let backups_dir = base_dir.join("backups");
         Self {
             base_dir,
             db_path,
             snapshots_dir,
+            login_homes_dir,
+            homes_dir,
+            backups_dir,
+            conn: Arc::new(Mutex::new(None)),
         }
     }
 
@@ -41,60 +112,11 @@ impl AppStore {
             .with_context(|| format!("创建数据目录失败: {}", self.base_dir.display()))?;
         std::fs::create_dir_all(&self.snapshots_dir)
             .with_context(|| format!("创建快照目录失败: {}", self.snapshots_dir.display()))?;
-        let conn = self.open_conn()?;
-        conn.execute_batch(
-            r#"
-            PRAGMA journal_mode = WAL;
-            CREATE TABLE IF NOT EXISTS app_settings (
-              id INTEGER PRIMARY KEY CHECK (id = 1),
-              vault_salt TEXT,
-              default_account_id TEXT,
-              cli_restart_mode TEXT NOT NULL DEFAULT 'force',
-              quota_timeout_ms INTEGER NOT NULL DEFAULT 5000,
-              quota_cache_ttl_seconds INTEGER NOT NULL DEFAULT 180,
-              quota_max_concurrency INTEGER NOT NULL DEFAULT 3,
-              updated_at TEXT NOT NULL
-            );
-
-            CREATE TABLE IF NOT EXISTS accounts (
-              id TEXT PRIMARY KEY,
-              name TEXT NOT NULL,
-              tags_json TEXT NOT NULL,
-              encrypted_auth_blob TEXT NOT NULL,
-              auth_fingerprint TEXT NOT NULL,
-              created_at TEXT NOT NULL,
-              updated_at TEXT NOT NULL,
-              last_used_at TEXT
-            );
-
-            CREATE TABLE IF NOT EXISTS switch_history (
-              id TEXT PRIMARY KEY,
-              from_account_id TEXT,
-              to_account_id TEXT NOT NULL,
-              snapshot_path TEXT,
-              result TEXT NOT NULL,
-              error_message TEXT,
-              created_at TEXT NOT NULL
-            );
-
-            CREATE TABLE IF NOT EXISTS quota_snapshots (
-              id TEXT PRIMARY KEY,
-              account_id TEXT NOT NULL,
-              mode TEXT NOT NULL,
-              remaining_value REAL,
-              remaining_unit TEXT,
-              quota_state TEXT NOT NULL,
-              reset_at TEXT,
-              source TEXT NOT NULL,
-              confidence INTEGER NOT NULL,
-              reason TEXT,
-              created_at TEXT NOT NULL
-            );
-            CREATE INDEX IF NOT EXISTS idx_quota_snapshots_account_created_at
-              ON quota_snapshots(account_id, created_at DESC);
-        "#,
-        )
-        .context("初始化数据库失败")?;
+        let mut conn = self.open_conn()?;
+        conn.execute_batch("PRAGMA journal_mode = WAL;")
+            .context("初始化数据库失败")?;
+        migrations::migrate(&mut conn, &self.backups_dir)?;
+        conn.set_prepared_statement_cache_capacity(STATEMENT_CACHE_CAPACITY);
         conn.execute(
             r#"
             INSERT INTO app_settings(id, updated_at)
@@ -104,16 +126,67 @@ impl AppStore {
             params![SETTINGS_SINGLETON_ID, now()],
         )
         .context("初始化设置失败")?;
+        *self.lock_conn()? = Some(conn);
+        Ok(())
+    }
+
+    // 只读打开已是最新版本的数据库，不建目录、不迁移也不写入设置；供高频只读调用（如 Shell 提示符）使用。
+    pub fn open_read_only(&self) -> Result<()> {
+        let conn = Connection::open_with_flags(
+            &self.db_path,
+            OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+        )
+        .with_context(|| format!("打开数据库失败: {}", self.db_path.display()))?;
+        conn.busy_timeout(BUSY_TIMEOUT)
+            .context("设置数据库等待超时失败")?;
+        if migrations::schema_version(&conn)? != migrations::latest_version() {
+            return Err(anyhow!("数据库需要升级，无法以只读方式打开"));
+        }
+        conn.set_prepared_statement_cache_capacity(STATEMENT_CACHE_CAPACITY);
+        *self.lock_conn()? = Some(conn);
         Ok(())
     }
 
+    // 单独打开的新连接，仅用于迁移与需要独立连接状态的场景；常规读写走 conn()。
     pub fn open_conn(&self) -> Result<Connection> {
-        Connection::open(&self.db_path)
-            .with_context(|| format!("打开数据库失败: {}", self.db_path.display()))
+        let conn = Connection::open(&self.db_path)
+            .with_context(|| format!("打开数据库失败: {}", self.db_path.display()))?;
+        conn.busy_timeout(BUSY_TIMEOUT)
+            .context("设置数据库等待超时失败")?;
+        conn.pragma_update(None, "foreign_keys", true)
+            .context("开启外键约束失败")?;
+        Ok(conn)
+    }
+
+    pub fn conn(&self) -> Result<StoreConn<'_>> {
+        let mut guard = self.lock_conn()?;
+        if guard.is_none() {
+            let conn = self.open_conn()?;
+            conn.set_prepared_statement_cache_capacity(STATEMENT_CACHE_CAPACITY);
+            *guard = Some(conn);
+        }
+        Ok(StoreConn(guard))
+    }
+
+    // 闭包返回错误时事务自动回滚；闭包内不可再调用 AppStore 的其他方法，否则会在连接锁上死锁。
+    pub fn with_transaction<T>(&self, f: impl FnOnce(&Transaction<'_>) -> Result<T>) -> Result<T> {
+        let mut conn = self.conn()?;
+        let tx = conn.transaction()?;
+        let value = f(&tx)?;
+        tx.commit().context("提交数据库事务失败")?;
+        Ok(value)
+    }
+
+    fn lock_conn(&self) -> Result<MutexGuard<'_, Option<Connection>>> {
+        self.conn.lock().map_err(|_| anyhow!("数据库连接锁失败"))
+    }
+
+    pub fn schema_version(&self) -> Result<i64> {
+        migrations::schema_version(&*self.conn()?)
     }
 
     pub fn get_vault_settings(&self) -> Result<VaultSettings> {
-        let conn = self.open_conn()?;
+        let conn = self.conn()?;
         let salt: Option<String> = conn
             .query_row(
                 "SELECT vault_salt FROM app_settings WHERE id = ?1",
@@ -127,7 +200,7 @@ impl AppStore {
     }
 
     pub fn set_vault_salt(&self, salt: &str) -> Result<()> {
-        let conn = self.open_conn()?;
+        let conn = self.conn()?;
         conn.execute(
             r#"
             UPDATE app_settings
@@ -141,7 +214,7 @@ impl AppStore {
     }
 
     pub fn get_quota_policy(&self) -> Result<(u64, u64, usize)> {
-        let conn = self.open_conn()?;
+        let conn = self.conn()?;
         let tuple = conn
             .query_row(
                 r#"
@@ -167,7 +240,7 @@ impl AppStore {
         cache_ttl_seconds: u64,
         max_concurrency: usize,
     ) -> Result<()> {
-        let conn = self.open_conn()?;
+        let conn = self.conn()?;
         conn.execute(
             r#"
             UPDATE app_settings
@@ -186,6 +259,611 @@ impl AppStore {
         Ok(())
     }
 
+    pub fn get_snapshot_retention(&self) -> Result<SnapshotRetentionPolicy> {
+        let conn = self.conn()?;
+        conn.query_row(
+            r#"
+            SELECT snapshot_keep_count, snapshot_max_age_days, snapshot_protect_recent
+            FROM app_settings WHERE id = ?1
+            "#,
+            params![SETTINGS_SINGLETON_ID],
+            |row| {
+                Ok(SnapshotRetentionPolicy {
+                    keep_count: row.get::<_, i64>(0)? as usize,
+                    max_age_days: row.get::<_, i64>(1)? as u32,
+                    protect_recent: row.get::<_, i64>(2)? as usize,
+                })
+            },
+        )
+        .context("读取快照保留策略失败")
+    }
+
+    pub fn set_snapshot_retention(&self, policy: &SnapshotRetentionPolicy) -> Result<()> {
+        let conn = self.conn()?;
+        conn.execute(
+            r#"
+            UPDATE app_settings
+            SET snapshot_keep_count = ?1, snapshot_max_age_days = ?2, snapshot_protect_recent = ?3, updated_at = ?4
+            WHERE id = ?5
+            "#,
+            params![
+                policy.keep_count as i64,
+                policy.max_age_days as i64,
+                policy.protect_recent as i64,
+                now(),
+                SETTINGS_SINGLETON_ID
+            ],
+        )
+        .context("更新快照保留策略失败")?;
+        Ok(())
+    }
+
+    pub fn get_device_auth_config(&self) -> Result<DeviceAuthConfig> {
+        let conn = self.conn()?;
+        let (device_authorization_url, token_url, client_id, scope) = conn
+            .query_row(
+                r#"
+            SELECT device_authorization_url, device_token_url, device_client_id, device_scope
+            FROM app_settings WHERE id = ?1
+            "#,
+                params![SETTINGS_SINGLETON_ID],
+                |row| {
+                    Ok((
+                        row.get::<_, Option<String>>(0)?,
+                        row.get::<_, Option<String>>(1)?,
+                        row.get::<_, Option<String>>(2)?,
+                        row.get::<_, Option<String>>(3)?,
+                    ))
+                },
+            )
+            .context("读取设备码登录配置失败")?;
+        let defaults = DeviceAuthConfig::default();
+        Ok(DeviceAuthConfig {
+            device_authorization_url: device_authorization_url
+                .unwrap_or(defaults.device_authorization_url),
+            token_url: token_url.unwrap_or(defaults.token_url),
+            client_id: client_id.unwrap_or(defaults.client_id),
+            scope: scope.unwrap_or(defaults.scope),
+        })
+    }
+
+    pub fn set_device_auth_config(&self, config: &DeviceAuthConfig) -> Result<()> {
+        let conn = self.conn()?;
+        conn.execute(
+            r#"
+            UPDATE app_settings
+            SET device_authorization_url = ?1, device_token_url = ?2, device_client_id = ?3, device_scope = ?4, updated_at = ?5
+            WHERE id = ?6
+            "#,
+            params![
+                config.device_authorization_url,
+                config.token_url,
+                config.client_id,
+                config.scope,
+                now(),
+                SETTINGS_SINGLETON_ID
+            ],
+        )
+        .context("更新设备码登录配置失败")?;
+        Ok(())
+    }
+
+    pub fn get_health_check_config(&self) -> Result<HealthCheckConfig> {
+        let conn = self.conn()?;
+        let (remote_enabled, endpoint): (i64, Option<String>) = conn
+            .query_row(
+                "SELECT health_remote_check, health_check_url FROM app_settings WHERE id = ?1",
+                params![SETTINGS_SINGLETON_ID],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .context("读取凭据健康检查设置失败")?;
+        Ok(HealthCheckConfig {
+            remote_enabled: remote_enabled != 0,
+            endpoint: endpoint.unwrap_or(HealthCheckConfig::default().endpoint),
+        })
+    }
+
+    pub fn set_health_check_config(&self, config: &HealthCheckConfig) -> Result<()> {
+        let conn = self.conn()?;
+        conn.execute(
+            r#"
+            UPDATE app_settings
+            SET health_remote_check = ?1, health_check_url = ?2, updated_at = ?3
+            WHERE id = ?4
+            "#,
+            params![
+                config.remote_enabled as i64,
+                config.endpoint,
+                now(),
+                SETTINGS_SINGLETON_ID
+            ],
+        )
+        .context("写入凭据健康检查设置失败")?;
+        Ok(())
+    }
+
+    pub fn get_switch_guard_policy(&self) -> Result<SwitchGuardPolicy> {
+        let conn = self.conn()?;
+        let actions: [String; 4] = conn
+            .query_row(
+                r#"
+                SELECT guard_token_expiry, guard_health, guard_quota, guard_running_processes
+                FROM app_settings WHERE id = ?1
+                "#,
+                params![SETTINGS_SINGLETON_ID],
+                |row| Ok([row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?]),
+            )
+            .context("读取切换守卫设置失败")?;
+        let defaults = SwitchGuardPolicy::default();
+        let parse = |value: &str, fallback| GuardAction::parse(value).unwrap_or(fallback);
+        Ok(SwitchGuardPolicy {
+            token_expiry: parse(&actions[0], defaults.token_expiry),
+            health: parse(&actions[1], defaults.health),
+            quota: parse(&actions[2], defaults.quota),
+            running_processes: parse(&actions[3], defaults.running_processes),
+        })
+    }
+
+    pub fn set_switch_guard_policy(&self, policy: &SwitchGuardPolicy) -> Result<()> {
+        let conn = self.conn()?;
+        conn.execute(
+            r#"
+            UPDATE app_settings
+            SET guard_token_expiry = ?1, guard_health = ?2, guard_quota = ?3,
+                guard_running_processes = ?4, updated_at = ?5
+            WHERE id = ?6
+            "#,
+            params![
+                policy.token_expiry.as_str(),
+                policy.health.as_str(),
+                policy.quota.as_str(),
+                policy.running_processes.as_str(),
+                now(),
+                SETTINGS_SINGLETON_ID
+            ],
+        )
+        .context("写入切换守卫设置失败")?;
+        Ok(())
+    }
+
+    pub fn get_failover_config(&self) -> Result<FailoverConfig> {
+        let conn = self.conn()?;
+        let (
+            enabled,
+            interval_seconds,
+            threshold_percent,
+            cooldown_seconds,
+            tags_json,
+            restart_mode,
+        ) = conn
+            .query_row(
+                r#"
+                SELECT failover_enabled, failover_interval_seconds, failover_threshold_percent,
+                       failover_cooldown_seconds, failover_preferred_tags, failover_restart_mode
+                FROM app_settings WHERE id = ?1
+                "#,
+                params![SETTINGS_SINGLETON_ID],
+                |row| {
+                    Ok((
+                        row.get::<_, i64>(0)?,
+                        row.get::<_, i64>(1)?,
+                        row.get::<_, Option<f64>>(2)?,
+                        row.get::<_, i64>(3)?,
+                        row.get::<_, String>(4)?,
+                        row.get::<_, String>(5)?,
+                    ))
+                },
+            )
+            .context("读取自动切换设置失败")?;
+        Ok(FailoverConfig {
+            enabled: enabled != 0,
+            interval_seconds: interval_seconds.max(0) as u64,
+            threshold_percent,
+            cooldown_seconds: cooldown_seconds.max(0) as u64,
+            preferred_tags: serde_json::from_str::<Vec<String>>(&tags_json).unwrap_or_default(),
+            restart_mode: RestartMode::parse(&restart_mode).unwrap_or_default(),
+        })
+    }
+
+    pub fn set_failover_config(&self, config: &FailoverConfig) -> Result<()> {
+        let conn = self.conn()?;
+        conn.execute(
+            r#"
+            UPDATE app_settings
+            SET failover_enabled = ?1, failover_interval_seconds = ?2,
+                failover_threshold_percent = ?3, failover_cooldown_seconds = ?4,
+                failover_preferred_tags = ?5, failover_restart_mode = ?6, updated_at = ?7
+            WHERE id = ?8
+            "#,
+            params![
+                config.enabled as i64,
+                config.interval_seconds as i64,
+                config.threshold_percent,
+                config.cooldown_seconds as i64,
+                serde_json::to_string(&config.preferred_tags).context("序列化偏好标签失败")?,
+                config.restart_mode.as_str(),
+                now(),
+                SETTINGS_SINGLETON_ID
+            ],
+        )
+        .context("写入自动切换设置失败")?;
+        Ok(())
+    }
+
+    pub fn get_codex_binary_path(&self) -> Result<Option<PathBuf>> {
+        let conn = self.conn()?;
+        let path: Option<String> = conn
+            .query_row(
+                "SELECT codex_binary_path FROM app_settings WHERE id = ?1",
+                params![SETTINGS_SINGLETON_ID],
+                |row| row.get(0),
+            )
+            .optional()
+            .context("读取 Codex CLI 路径设置失败")?
+            .flatten();
+        Ok(path
+            .filter(|value| !value.trim().is_empty())
+            .map(PathBuf::from))
+    }
+
+    pub fn set_codex_binary_path(&self, path: Option<&Path>) -> Result<()> {
+        let conn = self.conn()?;
+        conn.execute(
+            r#"
+            UPDATE app_settings
+            SET codex_binary_path = ?1, updated_at = ?2
+            WHERE id = ?3
+            "#,
+            params![
+                path.map(|value| value.display().to_string()),
+                now(),
+                SETTINGS_SINGLETON_ID
+            ],
+        )
+        .context("写入 Codex CLI 路径设置失败")?;
+        Ok(())
+    }
+
+    pub fn get_project_auto_switch(&self) -> Result<bool> {
+        let conn = self.conn()?;
+        let enabled: i64 = conn
+            .query_row(
+                "SELECT project_auto_switch FROM app_settings WHERE id = ?1",
+                params![SETTINGS_SINGLETON_ID],
+                |row| row.get(0),
+            )
+            .context("读取项目自动切换设置失败")?;
+        Ok(enabled != 0)
+    }
+
+    pub fn set_project_auto_switch(&self, enabled: bool) -> Result<()> {
+        let conn = self.conn()?;
+        conn.execute(
+            r#"
+            UPDATE app_settings
+            SET project_auto_switch = ?1, updated_at = ?2
+            WHERE id = ?3
+            "#,
+            params![enabled as i64, now(), SETTINGS_SINGLETON_ID],
+        )
+        .context("写入项目自动切换设置失败")?;
+        Ok(())
+    }
+
+    pub fn get_trash_retention_days(&self) -> Result<u32> {
+        let conn = self.conn()?;
+        let days: i64 = conn
+            .query_row(
+                "SELECT trash_retention_days FROM app_settings WHERE id = ?1",
+                params![SETTINGS_SINGLETON_ID],
+                |row| row.get(0),
+            )
+            .context("读取回收站保留天数失败")?;
+        Ok(days.max(1) as u32)
+    }
+
+    pub fn set_trash_retention_days(&self, days: u32) -> Result<()> {
+        let conn = self.conn()?;
+        conn.execute(
+            r#"
+            UPDATE app_settings
+            SET trash_retention_days = ?1, updated_at = ?2
+            WHERE id = ?3
+            "#,
+            params![days as i64, now(), SETTINGS_SINGLETON_ID],
+        )
+        .context("写入回收站保留天数失败")?;
+        Ok(())
+    }
+
+    pub fn get_control_api_enabled(&self) -> Result<bool> {
+        let conn = self.conn()?;
+        let enabled: i64 = conn
+            .query_row(
+                "SELECT control_api_enabled FROM app_settings WHERE id = ?1",
+                params![SETTINGS_SINGLETON_ID],
+                |row| row.get(0),
+            )
+            .context("读取本地控制接口设置失败")?;
+        Ok(enabled != 0)
+    }
+
+    pub fn set_control_api_enabled(&self, enabled: bool) -> Result<()> {
+        let conn = self.conn()?;
+        conn.execute(
+            r#"
+            UPDATE app_settings
+            SET control_api_enabled = ?1, updated_at = ?2
+            WHERE id = ?3
+            "#,
+            params![enabled as i64, now(), SETTINGS_SINGLETON_ID],
+        )
+        .context("写入本地控制接口设置失败")?;
+        Ok(())
+    }
+
+    pub fn get_account_homes_config(&self) -> Result<(bool, bool)> {
+        let conn = self.conn()?;
+        let (enabled, share_config): (i64, i64) = conn
+            .query_row(
+                r#"
+                SELECT account_homes_enabled, account_homes_share_config
+                FROM app_settings WHERE id = ?1
+                "#,
+                params![SETTINGS_SINGLETON_ID],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .context("读取账号独立目录设置失败")?;
+        Ok((enabled != 0, share_config != 0))
+    }
+
+    pub fn set_account_homes_config(&self, enabled: bool, share_config: bool) -> Result<()> {
+        let conn = self.conn()?;
+        conn.execute(
+            r#"
+            UPDATE app_settings
+            SET account_homes_enabled = ?1, account_homes_share_config = ?2, updated_at = ?3
+            WHERE id = ?4
+            "#,
+            params![
+                enabled as i64,
+                share_config as i64,
+                now(),
+                SETTINGS_SINGLETON_ID
+            ],
+        )
+        .context("写入账号独立目录设置失败")?;
+        Ok(())
+    }
+
+    pub fn list_control_tokens(&self) -> Result<Vec<ControlToken>> {
+        let conn = self.conn()?;
+        let mut stmt = conn.prepare_cached(
+            r#"
+            SELECT id, name, created_at, last_used_at
+            FROM control_tokens
+            ORDER BY created_at ASC
+            "#,
+        )?;
+        let rows = stmt.query_map([], map_control_token)?;
+        Ok(rows.filter_map(Result::ok).collect())
+    }
+
+    pub fn create_control_token(&self, name: &str, token_hash: &str) -> Result<ControlToken> {
+        let conn = self.conn()?;
+        let token = ControlToken {
+            id: Uuid::new_v4().to_string(),
+            name: name.trim().to_string(),
+            created_at: now(),
+            last_used_at: None,
+        };
+        conn.execute(
+            r#"
+            INSERT INTO control_tokens(id, name, token_hash, created_at)
+            VALUES (?1, ?2, ?3, ?4)
+            "#,
+            params![token.id, token.name, token_hash, token.created_at],
+        )
+        .context("写入控制接口令牌失败")?;
+        Ok(token)
+    }
+
+    pub fn delete_control_token(&self, id: &str) -> Result<()> {
+        let conn = self.conn()?;
+        conn.execute("DELETE FROM control_tokens WHERE id = ?1", params![id])
+            .context("删除控制接口令牌失败")?;
+        Ok(())
+    }
+
+    pub fn authenticate_control_token(&self, token_hash: &str) -> Result<Option<ControlToken>> {
+        let conn = self.conn()?;
+        conn.execute(
+            "UPDATE control_tokens SET last_used_at = ?1 WHERE token_hash = ?2",
+            params![now(), token_hash],
+        )
+        .context("更新控制接口令牌失败")?;
+        conn.query_row(
+            r#"
+            SELECT id, name, created_at, last_used_at
+            FROM control_tokens WHERE token_hash = ?1
+            "#,
+            params![token_hash],
+            map_control_token,
+        )
+        .optional()
+        .context("读取控制接口令牌失败")
+    }
+
+    pub fn list_project_bindings(&self) -> Result<Vec<ProjectBinding>> {
+        let conn = self.conn()?;
+        let mut stmt = conn.prepare_cached(
+            r#"
+            SELECT id, path, account_id, tag, created_at, updated_at
+            FROM project_bindings
+            ORDER BY path ASC
+            "#,
+        )?;
+        let rows = stmt.query_map([], map_project_binding)?;
+        Ok(rows.filter_map(Result::ok).collect())
+    }
+
+    pub fn upsert_project_binding(
+        &self,
+        path: &str,
+        account_id: Option<&str>,
+        tag: Option<&str>,
+    ) -> Result<ProjectBinding> {
+        let conn = self.conn()?;
+        let timestamp = now();
+        conn.execute(
+            r#"
+            INSERT INTO project_bindings(id, path, account_id, tag, created_at, updated_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+            ON CONFLICT(path) DO UPDATE SET
+              account_id = excluded.account_id,
+              tag = excluded.tag,
+              updated_at = excluded.updated_at
+            "#,
+            params![
+                Uuid::new_v4().to_string(),
+                path,
+                account_id,
+                tag,
+                timestamp,
+                timestamp
+            ],
+        )
+        .context("写入项目绑定失败")?;
+        conn.query_row(
+            r#"
+            SELECT id, path, account_id, tag, created_at, updated_at
+            FROM project_bindings WHERE path = ?1
+            "#,
+            params![path],
+            map_project_binding,
+        )
+        .context("读取项目绑定失败")
+    }
+
+    pub fn delete_project_binding(&self, id: &str) -> Result<()> {
+        let conn = self.conn()?;
+        conn.execute("DELETE FROM project_bindings WHERE id = ?1", params![id])
+            .context("删除项目绑定失败")?;
+        Ok(())
+    }
+
+    pub fn list_account_pools(&self) -> Result<Vec<AccountPool>> {
+        let conn = self.conn()?;
+        let mut stmt = conn.prepare_cached(
+            r#"
+            SELECT id, name, tag, account_ids_json, strategy, last_account_id, created_at, updated_at
+            FROM account_pools
+            ORDER BY name ASC
+            "#,
+        )?;
+        let rows = stmt.query_map([], map_account_pool)?;
+        Ok(rows.filter_map(Result::ok).collect())
+    }
+
+    pub fn get_account_pool(&self, id: &str) -> Result<Option<AccountPool>> {
+        let conn = self.conn()?;
+        conn.query_row(
+            r#"
+            SELECT id, name, tag, account_ids_json, strategy, last_account_id, created_at, updated_at
+            FROM account_pools WHERE id = ?1
+            "#,
+            params![id],
+            map_account_pool,
+        )
+        .optional()
+        .context("读取账号池失败")
+    }
+
+    // id 为空时新建；修改成员或策略不会重置轮询游标。
+    pub fn save_account_pool(
+        &self,
+        id: Option<&str>,
+        name: &str,
+        tag: Option<&str>,
+        account_ids: &[String],
+        strategy: &str,
+    ) -> Result<AccountPool> {
+        let id = {
+            let conn = self.conn()?;
+            let existing: Option<String> = conn
+                .query_row(
+                    "SELECT id FROM account_pools WHERE name = ?1",
+                    params![name],
+                    |row| row.get(0),
+                )
+                .optional()?;
+            if existing.is_some() && existing.as_deref() != id {
+                return Err(anyhow!("已存在同名账号池：{name}"));
+            }
+            let account_ids_json =
+                serde_json::to_string(account_ids).context("序列化账号池成员失败")?;
+            let timestamp = now();
+            match id {
+                Some(id) => {
+                    let updated = conn
+                        .execute(
+                            r#"
+                            UPDATE account_pools
+                            SET name = ?1, tag = ?2, account_ids_json = ?3, strategy = ?4, updated_at = ?5
+                            WHERE id = ?6
+                            "#,
+                            params![name, tag, account_ids_json, strategy, timestamp, id],
+                        )
+                        .context("更新账号池失败")?;
+                    if updated == 0 {
+                        return Err(anyhow!("账号池不存在"));
+                    }
+                    id.to_string()
+                }
+                None => {
+                    let id = Uuid::new_v4().to_string();
+                    conn.execute(
+                        r#"
+                        INSERT INTO account_pools(
+                            id, name, tag, account_ids_json, strategy, created_at, updated_at
+                        ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+                        "#,
+                        params![
+                            id,
+                            name,
+                            tag,
+                            account_ids_json,
+                            strategy,
+                            timestamp,
+                            timestamp
+                        ],
+                    )
+                    .context("写入账号池失败")?;
+                    id
+                }
+            }
+        };
+        self.get_account_pool(&id)?
+            .ok_or_else(|| anyhow!("账号池不存在"))
+    }
+
+    pub fn set_account_pool_cursor(&self, id: &str, account_id: &str) -> Result<()> {
+        let conn = self.conn()?;
+        conn.execute(
+            "UPDATE account_pools SET last_account_id = ?1 WHERE id = ?2",
+            params![account_id, id],
+        )
+        .context("更新账号池轮询位置失败")?;
+        Ok(())
+    }
+
+    pub fn delete_account_pool(&self, id: &str) -> Result<()> {
+        let conn = self.conn()?;
+        conn.execute("DELETE FROM account_pools WHERE id = ?1", params![id])
+            .context("删除账号池失败")?;
+        Ok(())
+    }
+
     pub fn create_account(
         &self,
         name: &str,
@@ -200,7 +878,7 @@ impl AppStore {
                 existing.auth_fingerprint
             ));
         }
-        let conn = self.open_conn()?;
+        let conn = self.conn()?;
         let id = Uuid::new_v4().to_string();
         let timestamp = now();
         conn.execute(
@@ -220,69 +898,48 @@ impl AppStore {
             ],
         )
         .context("写入账户失败")?;
+        drop(conn);
         self.get_account(&id)?
             .ok_or_else(|| anyhow!("账户写入后未找到"))
     }
 
     pub fn find_account_by_fingerprint(&self, fingerprint: &str) -> Result<Option<Account>> {
-        let conn = self.open_conn()?;
+        let conn = self.conn()?;
         conn.query_row(
             r#"
             SELECT id, name, tags_json, auth_fingerprint, created_at, updated_at, last_used_at
             FROM accounts
-            WHERE auth_fingerprint = ?1
+            WHERE auth_fingerprint = ?1 AND deleted_at IS NULL
             "#,
             params![fingerprint],
-            |row| {
-                Ok(Account {
-                    id: row.get(0)?,
-                    name: row.get(1)?,
-                    tags: serde_json::from_str::<Vec<String>>(&row.get::<_, String>(2)?)
-                        .unwrap_or_default(),
-                    auth_fingerprint: row.get(3)?,
-                    created_at: row.get(4)?,
-                    updated_at: row.get(5)?,
-                    last_used_at: row.get(6)?,
-                })
-            },
+            map_account,
         )
         .optional()
         .context("按指纹读取账户失败")
     }
 
     pub fn get_account(&self, id: &str) -> Result<Option<Account>> {
-        let conn = self.open_conn()?;
+        let conn = self.conn()?;
         conn.query_row(
             r#"
             SELECT id, name, tags_json, auth_fingerprint, created_at, updated_at, last_used_at
             FROM accounts
-            WHERE id = ?1
+            WHERE id = ?1 AND deleted_at IS NULL
             "#,
             params![id],
-            |row| {
-                Ok(Account {
-                    id: row.get(0)?,
-                    name: row.get(1)?,
-                    tags: serde_json::from_str::<Vec<String>>(&row.get::<_, String>(2)?)
-                        .unwrap_or_default(),
-                    auth_fingerprint: row.get(3)?,
-                    created_at: row.get(4)?,
-                    updated_at: row.get(5)?,
-                    last_used_at: row.get(6)?,
-                })
-            },
+            map_account,
         )
         .optional()
         .context("读取账户失败")
     }
 
     pub fn get_account_secret(&self, id: &str) -> Result<Option<AccountSecret>> {
-        let conn = self.open_conn()?;
+        let conn = self.conn()?;
         conn.query_row(
             r#"
             SELECT id, name, tags_json, encrypted_auth_blob, auth_fingerprint, created_at, updated_at, last_used_at
             FROM accounts
-            WHERE id = ?1
+            WHERE id = ?1 AND deleted_at IS NULL
             "#,
             params![id],
             |row| {
@@ -306,31 +963,21 @@ impl AppStore {
     }
 
     pub fn list_accounts(&self) -> Result<Vec<Account>> {
-        let conn = self.open_conn()?;
-        let mut stmt = conn.prepare(
+        let conn = self.conn()?;
+        let mut stmt = conn.prepare_cached(
             r#"
             SELECT id, name, tags_json, auth_fingerprint, created_at, updated_at, last_used_at
             FROM accounts
+            WHERE deleted_at IS NULL
             ORDER BY updated_at DESC
         "#,
         )?;
-        let rows = stmt.query_map([], |row| {
-            Ok(Account {
-                id: row.get(0)?,
-                name: row.get(1)?,
-                tags: serde_json::from_str::<Vec<String>>(&row.get::<_, String>(2)?)
-                    .unwrap_or_default(),
-                auth_fingerprint: row.get(3)?,
-                created_at: row.get(4)?,
-                updated_at: row.get(5)?,
-                last_used_at: row.get(6)?,
-            })
-        })?;
+        let rows = stmt.query_map([], map_account)?;
         Ok(rows.filter_map(Result::ok).collect())
     }
 
     pub fn update_account_meta(&self, id: &str, name: &str, tags: &[String]) -> Result<()> {
-        let conn = self.open_conn()?;
+        let conn = self.conn()?;
         conn.execute(
             r#"
             UPDATE accounts
@@ -343,15 +990,227 @@ impl AppStore {
         Ok(())
     }
 
-    pub fn delete_account(&self, id: &str) -> Result<()> {
-        let conn = self.open_conn()?;
-        conn.execute("DELETE FROM accounts WHERE id = ?1", params![id])
-            .context("删除账户失败")?;
-        Ok(())
+    // 凭据换新后旧的健康检查结果不再可信，一并清除，等待下次检查。
+    pub fn update_account_auth_blob(&self, id: &str, encrypted_auth_blob: &str) -> Result<()> {
+        self.with_transaction(|tx| {
+            tx.execute(
+                "UPDATE accounts SET encrypted_auth_blob = ?1, updated_at = ?2 WHERE id = ?3",
+                params![encrypted_auth_blob, now(), id],
+            )
+            .context("更新账户登录数据失败")?;
+            tx.execute(
+                "DELETE FROM account_health WHERE account_id = ?1",
+                params![id],
+            )?;
+            Ok(())
+        })
+    }
+
+    // 替换凭据与记录事件放在同一事务，避免出现凭据已更新却无从追溯的情况。
+    pub fn replace_account_auth(
+        &self,
+        id: &str,
+        encrypted_auth_blob: &str,
+        event_kind: &str,
+        event_detail: Option<&str>,
+    ) -> Result<()> {
+        self.with_transaction(|tx| {
+            let timestamp = now();
+            let updated = tx
+                .execute(
+                    r#"
+                    UPDATE accounts SET encrypted_auth_blob = ?1, updated_at = ?2
+                    WHERE id = ?3 AND deleted_at IS NULL
+                    "#,
+                    params![encrypted_auth_blob, timestamp, id],
+                )
+                .context("更新账户登录数据失败")?;
+            if updated == 0 {
+                return Err(anyhow!("目标账户不存在"));
+            }
+            tx.execute(
+                "DELETE FROM account_health WHERE account_id = ?1",
+                params![id],
+            )?;
+            tx.execute(
+                r#"
+                INSERT INTO account_events(id, account_id, kind, detail, created_at)
+                VALUES (?1, ?2, ?3, ?4, ?5)
+                "#,
+                params![
+                    Uuid::new_v4().to_string(),
+                    id,
+                    event_kind,
+                    event_detail,
+                    timestamp
+                ],
+            )
+            .context("写入账户事件失败")?;
+            Ok(())
+        })
+    }
+
+    pub fn list_account_events(&self, account_id: &str, limit: usize) -> Result<Vec<AccountEvent>> {
+        let conn = self.conn()?;
+        let mut stmt = conn.prepare_cached(
+            r#"
+            SELECT id, account_id, kind, detail, created_at
+            FROM account_events
+            WHERE account_id = ?1
+            ORDER BY created_at DESC, id DESC
+            LIMIT ?2
+        "#,
+        )?;
+        let rows = stmt.query_map(params![account_id, limit as i64], |row| {
+            Ok(AccountEvent {
+                id: row.get(0)?,
+                account_id: row.get(1)?,
+                kind: row.get(2)?,
+                detail: row.get(3)?,
+                created_at: row.get(4)?,
+            })
+        })?;
+        Ok(rows.filter_map(Result::ok).collect())
+    }
+
+    pub fn save_account_health(
+        &self,
+        account_id: &str,
+        status: &str,
+        expires_at: Option<&str>,
+        reason: Option<&str>,
+        remote_checked: bool,
+    ) -> Result<AccountHealth> {
+        let conn = self.conn()?;
+        let checked_at = now();
+        conn.execute(
+            r#"
+            INSERT INTO account_health(account_id, status, expires_at, reason, remote_checked, checked_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+            ON CONFLICT(account_id) DO UPDATE SET
+              status = excluded.status,
+              expires_at = excluded.expires_at,
+              reason = excluded.reason,
+              remote_checked = excluded.remote_checked,
+              checked_at = excluded.checked_at
+            "#,
+            params![
+                account_id,
+                status,
+                expires_at,
+                reason,
+                remote_checked as i64,
+                checked_at
+            ],
+        )
+        .context("写入账户健康状态失败")?;
+        Ok(AccountHealth {
+            account_id: account_id.to_string(),
+            status: status.to_string(),
+            expires_at: expires_at.map(ToString::to_string),
+            reason: reason.map(ToString::to_string),
+            remote_checked,
+            checked_at,
+        })
+    }
+
+    pub fn get_account_health(&self, account_id: &str) -> Result<Option<AccountHealth>> {
+        let conn = self.conn()?;
+        conn.query_row(
+            r#"
+            SELECT account_id, status, expires_at, reason, remote_checked, checked_at
+            FROM account_health WHERE account_id = ?1
+            "#,
+            params![account_id],
+            map_account_health,
+        )
+        .optional()
+        .context("读取账户健康状态失败")
+    }
+
+    pub fn list_account_health(&self) -> Result<Vec<AccountHealth>> {
+        let conn = self.conn()?;
+        let mut stmt = conn.prepare_cached(
+            r#"
+            SELECT account_id, status, expires_at, reason, remote_checked, checked_at
+            FROM account_health
+        "#,
+        )?;
+        let rows = stmt.query_map([], map_account_health)?;
+        Ok(rows.filter_map(Result::ok).collect())
+    }
+
+    pub fn trash_account(&self, id: &str) -> Result<bool> {
+        let conn = self.conn()?;
+        let changed = conn
+            .execute(
+                "UPDATE accounts SET deleted_at = ?1 WHERE id = ?2 AND deleted_at IS NULL",
+                params![now(), id],
+            )
+            .context("移入回收站失败")?;
+        Ok(changed > 0)
+    }
+
+    pub fn restore_account(&self, id: &str) -> Result<bool> {
+        let conn = self.conn()?;
+        let changed = conn
+            .execute(
+                "UPDATE accounts SET deleted_at = NULL, updated_at = ?1 WHERE id = ?2 AND deleted_at IS NOT NULL",
+                params![now(), id],
+            )
+            .context("恢复账户失败")?;
+        Ok(changed > 0)
+    }
+
+    // 包含回收站中的账号，用于为历史记录补全名称。
+    pub fn list_account_names(&self) -> Result<HashMap<String, String>> {
+        let conn = self.conn()?;
+        let mut stmt = conn.prepare_cached("SELECT id, name FROM accounts")?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        rows.collect::<rusqlite::Result<HashMap<_, _>>>()
+            .context("读取账号名称失败")
+    }
+
+    // 返回 (账户, 移入回收站时间)，按删除时间倒序。
+    pub fn list_trashed_accounts(&self) -> Result<Vec<(Account, String)>> {
+        let conn = self.conn()?;
+        let mut stmt = conn.prepare_cached(
+            r#"
+            SELECT id, name, tags_json, auth_fingerprint, created_at, updated_at, last_used_at, deleted_at
+            FROM accounts
+            WHERE deleted_at IS NOT NULL
+            ORDER BY deleted_at DESC
+            "#,
+        )?;
+        let rows = stmt.query_map([], |row| Ok((map_account(row)?, row.get(7)?)))?;
+        Ok(rows.filter_map(Result::ok).collect())
+    }
+
+    // 返回从历史记录中摘除的快照路径：切换前快照保存的是来源账号的登录信息，需随账号一并清除。
+    pub fn delete_account(&self, id: &str) -> Result<Vec<String>> {
+        self.with_transaction(|tx| {
+            let snapshot_paths = {
+                let mut stmt = tx.prepare_cached(
+                    r#"
+                    SELECT snapshot_path FROM switch_history
+                    WHERE from_account_id = ?1 AND snapshot_path IS NOT NULL
+                    "#,
+                )?;
+                let rows = stmt.query_map(params![id], |row| row.get::<_, String>(0))?;
+                rows.filter_map(Result::ok).collect::<Vec<_>>()
+            };
+            tx.execute(
+                "UPDATE switch_history SET snapshot_path = NULL, snapshot_hash = NULL WHERE from_account_id = ?1",
+                params![id],
+            )?;
+            tx.execute("DELETE FROM accounts WHERE id = ?1", params![id])
+                .context("删除账户失败")?;
+            Ok(snapshot_paths)
+        })
     }
 
     pub fn mark_account_used(&self, id: &str) -> Result<()> {
-        let conn = self.open_conn()?;
+        let conn = self.conn()?;
         conn.execute(
             "UPDATE accounts SET last_used_at = ?1, updated_at = ?2 WHERE id = ?3",
             params![now(), now(), id],
@@ -363,24 +1222,25 @@ impl AppStore {
     pub fn create_switch_history(
         &self,
         from_account_id: Option<&str>,
-        to_account_id: &str,
-        snapshot_path: Option<&Path>,
+        to_account_id: Option<&str>,
+        snapshot: Option<&Snapshot>,
         result: &str,
         error_message: Option<&str>,
     ) -> Result<String> {
-        let conn = self.open_conn()?;
+        let conn = self.conn()?;
         let id = Uuid::new_v4().to_string();
         conn.execute(
             r#"
             INSERT INTO switch_history(
-              id, from_account_id, to_account_id, snapshot_path, result, error_message, created_at
-            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+              id, from_account_id, to_account_id, snapshot_path, snapshot_hash, result, error_message, created_at
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
             "#,
             params![
                 id,
                 from_account_id,
                 to_account_id,
-                snapshot_path.map(|p| p.display().to_string()),
+                snapshot.map(|snapshot| snapshot.path.display().to_string()),
+                snapshot.map(|snapshot| snapshot.hash.as_str()),
                 result,
                 error_message,
                 now()
@@ -391,56 +1251,294 @@ impl AppStore {
     }
 
     pub fn list_switch_history(&self, limit: usize) -> Result<Vec<SwitchHistory>> {
-        let conn = self.open_conn()?;
-        let mut stmt = conn.prepare(
+        let conn = self.conn()?;
+        let mut stmt = conn.prepare_cached(
             r#"
-            SELECT id, from_account_id, to_account_id, snapshot_path, result, error_message, created_at
+            SELECT id, from_account_id, to_account_id, snapshot_path, snapshot_hash, snapshot_pruned_at, result, error_message, created_at
             FROM switch_history
-            ORDER BY created_at DESC
+            ORDER BY created_at DESC, id DESC
             LIMIT ?1
             "#,
         )?;
-        let rows = stmt.query_map(params![limit as i64], |row| {
-            Ok(SwitchHistory {
-                id: row.get(0)?,
-                from_account_id: row.get(1)?,
-                to_account_id: row.get(2)?,
-                snapshot_path: row.get(3)?,
-                result: row.get(4)?,
-                error_message: row.get(5)?,
-                created_at: row.get(6)?,
-            })
-        })?;
+        let rows = stmt.query_map(params![limit as i64], map_switch_history)?;
         Ok(rows.filter_map(Result::ok).collect())
     }
 
+    pub fn list_switch_history_chronological(&self) -> Result<Vec<SwitchHistory>> {
+        let conn = self.conn()?;
+        let mut stmt = conn.prepare_cached(
+            r#"
+            SELECT id, from_account_id, to_account_id, snapshot_path, snapshot_hash, snapshot_pruned_at, result, error_message, created_at
+            FROM switch_history
+            ORDER BY created_at ASC, id ASC
+            "#,
+        )?;
+        let rows = stmt.query_map([], map_switch_history)?;
+        rows.collect::<rusqlite::Result<Vec<_>>>()
+            .context("读取切换历史失败")
+    }
+
     pub fn get_switch_history(&self, history_id: &str) -> Result<Option<SwitchHistory>> {
-        let conn = self.open_conn()?;
+        let conn = self.conn()?;
         conn.query_row(
             r#"
-            SELECT id, from_account_id, to_account_id, snapshot_path, result, error_message, created_at
+            SELECT id, from_account_id, to_account_id, snapshot_path, snapshot_hash, snapshot_pruned_at, result, error_message, created_at
             FROM switch_history WHERE id = ?1
             "#,
             params![history_id],
-            |row| {
-                Ok(SwitchHistory {
-                    id: row.get(0)?,
-                    from_account_id: row.get(1)?,
-                    to_account_id: row.get(2)?,
-                    snapshot_path: row.get(3)?,
-                    result: row.get(4)?,
-                    error_message: row.get(5)?,
-                    created_at: row.get(6)?,
-                })
-            },
+            map_switch_history,
         )
         .optional()
         .context("读取切换历史失败")
     }
 
+    // 按 (created_at, id) 倒序做游标分页；账号名不过滤回收站，已永久删除的账号引用为空。
+    pub fn query_switch_history(&self, query: &SwitchHistoryQuery) -> Result<SwitchHistoryPage> {
+        let mut filters = Vec::new();
+        let mut values = Vec::<Value>::new();
+        if let Some(account_id) = query.account_id.as_deref().filter(|id| !id.is_empty()) {
+            values.push(Value::Text(account_id.to_string()));
+            filters.push(format!(
+                "(h.from_account_id = ?{0} OR h.to_account_id = ?{0})",
+                values.len()
+            ));
+        }
+        if let Some(result) = query.result.as_deref().filter(|result| !result.is_empty()) {
+            if !HISTORY_RESULTS.contains(&result) {
+                return Err(anyhow!("不支持的切换结果筛选: {result}"));
+            }
+            values.push(Value::Text(result.to_string()));
+            filters.push(format!("h.result = ?{}", values.len()));
+        }
+        if let Some(since) = query.since.as_deref().filter(|value| !value.is_empty()) {
+            values.push(Value::Text(normalize_time(since)?));
+            filters.push(format!("h.created_at >= ?{}", values.len()));
+        }
+        if let Some(until) = query.until.as_deref().filter(|value| !value.is_empty()) {
+            values.push(Value::Text(normalize_time(until)?));
+            filters.push(format!("h.created_at < ?{}", values.len()));
+        }
+        let where_clause = |filters: &[String]| {
+            if filters.is_empty() {
+                String::new()
+            } else {
+                format!("WHERE {}", filters.join(" AND "))
+            }
+        };
+
+        let conn = self.conn()?;
+        let total: i64 = conn
+            .query_row(
+                &format!(
+                    "SELECT COUNT(*) FROM switch_history h {}",
+                    where_clause(&filters)
+                ),
+                params_from_iter(values.iter()),
+                |row| row.get(0),
+            )
+            .context("统计切换历史失败")?;
+
+        if let Some(cursor) = query.cursor.as_deref().filter(|cursor| !cursor.is_empty()) {
+            let (created_at, id) = cursor
+                .split_once('|')
+                .ok_or_else(|| anyhow!("分页游标无效"))?;
+            values.push(Value::Text(created_at.to_string()));
+            values.push(Value::Text(id.to_string()));
+            filters.push(format!(
+                "(h.created_at, h.id) < (?{}, ?{})",
+                values.len() - 1,
+                values.len()
+            ));
+        }
+        let limit = query
+            .limit
+            .unwrap_or(HISTORY_PAGE_DEFAULT)
+            .clamp(1, HISTORY_PAGE_MAX);
+        // 多取一条用于判断是否还有下一页
+        values.push(Value::Integer(limit as i64 + 1));
+        let sql = format!(
+            r#"
+            SELECT h.id, h.from_account_id, h.to_account_id, h.snapshot_path, h.snapshot_hash, h.snapshot_pruned_at, h.result, h.error_message, h.created_at,
+                   fa.name, ta.name, fa.deleted_at IS NOT NULL, ta.deleted_at IS NOT NULL
+            FROM switch_history h
+            LEFT JOIN accounts fa ON fa.id = h.from_account_id
+            LEFT JOIN accounts ta ON ta.id = h.to_account_id
+            {}
+            ORDER BY h.created_at DESC, h.id DESC
+            LIMIT ?{}
+            "#,
+            where_clause(&filters),
+            values.len()
+        );
+        let mut stmt = conn.prepare_cached(&sql)?;
+        let mut items = stmt
+            .query_map(params_from_iter(values.iter()), |row| {
+                Ok(SwitchHistoryEntry {
+                    history: map_switch_history(row)?,
+                    from_account_name: row.get(9)?,
+                    to_account_name: row.get(10)?,
+                    from_account_trashed: row.get(11)?,
+                    to_account_trashed: row.get(12)?,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()
+            .context("读取切换历史失败")?;
+        let next_cursor = if items.len() > limit {
+            items.truncate(limit);
+            items
+                .last()
+                .map(|entry| format!("{}|{}", entry.history.created_at, entry.history.id))
+        } else {
+            None
+        };
+        Ok(SwitchHistoryPage {
+            items,
+            total: total as usize,
+            next_cursor,
+        })
+    }
+
+    // 返回 (孤儿配额快照, 引用了不存在账号的历史, 孤儿项目绑定) 数量；外键开启后仅外部修改或旧数据会产生。
+    pub fn count_orphan_rows(&self) -> Result<(usize, usize, usize)> {
+        let conn = self.conn()?;
+        conn.query_row(
+            r#"
+            SELECT
+              (SELECT COUNT(*) FROM quota_snapshots
+               WHERE account_id NOT IN (SELECT id FROM accounts)),
+              (SELECT COUNT(*) FROM switch_history
+               WHERE from_account_id NOT IN (SELECT id FROM accounts)
+                  OR to_account_id NOT IN (SELECT id FROM accounts)),
+              (SELECT COUNT(*) FROM project_bindings
+               WHERE account_id NOT IN (SELECT id FROM accounts))
+            "#,
+            [],
+            |row| {
+                Ok((
+                    row.get::<_, i64>(0)? as usize,
+                    row.get::<_, i64>(1)? as usize,
+                    row.get::<_, i64>(2)? as usize,
+                ))
+            },
+        )
+        .context("检查孤儿数据失败")
+    }
+
+    pub fn repair_orphan_rows(&self) -> Result<()> {
+        self.with_transaction(|tx| {
+            tx.execute_batch(
+                r#"
+                DELETE FROM quota_snapshots WHERE account_id NOT IN (SELECT id FROM accounts);
+                UPDATE switch_history SET from_account_id = NULL
+                  WHERE from_account_id NOT IN (SELECT id FROM accounts);
+                UPDATE switch_history SET to_account_id = NULL
+                  WHERE to_account_id NOT IN (SELECT id FROM accounts);
+                DELETE FROM project_bindings WHERE account_id NOT IN (SELECT id FROM accounts);
+                "#,
+            )
+            .context("修复孤儿数据失败")
+        })
+    }
+
+    pub fn list_history_snapshot_paths(&self) -> Result<Vec<(String, String)>> {
+        let conn = self.conn()?;
+        let mut stmt = conn.prepare_cached(
+            "SELECT id, snapshot_path FROM switch_history WHERE snapshot_path IS NOT NULL",
+        )?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        Ok(rows.filter_map(Result::ok).collect())
+    }
+
+    pub fn clear_history_snapshot_paths(&self, history_ids: &[String]) -> Result<()> {
+        self.with_transaction(|tx| {
+            let mut stmt =
+                tx.prepare_cached("UPDATE switch_history SET snapshot_path = NULL, snapshot_hash = NULL WHERE id = ?1")?;
+            for id in history_ids {
+                stmt.execute(params![id]).context("更新切换历史失败")?;
+            }
+            Ok(())
+        })
+    }
+
+    // 仅返回仍引用快照文件的历史记录，按时间倒序。
+    pub fn list_snapshot_history(&self) -> Result<Vec<SwitchHistory>> {
+        let conn = self.conn()?;
+        let mut stmt = conn.prepare_cached(
+            r#"
+            SELECT id, from_account_id, to_account_id, snapshot_path, snapshot_hash, snapshot_pruned_at, result, error_message, created_at
+            FROM switch_history
+            WHERE snapshot_path IS NOT NULL
+            ORDER BY created_at DESC
+            "#,
+        )?;
+        let rows = stmt.query_map([], map_switch_history)?;
+        Ok(rows.filter_map(Result::ok).collect())
+    }
+
+    pub fn mark_snapshots_pruned(&self, history_ids: &[String]) -> Result<()> {
+        let pruned_at = now();
+        self.with_transaction(|tx| {
+            let mut stmt = tx.prepare_cached(
+                "UPDATE switch_history SET snapshot_path = NULL, snapshot_hash = NULL, snapshot_pruned_at = ?1 WHERE id = ?2",
+            )?;
+            for id in history_ids {
+                stmt.execute(params![pruned_at, id])
+                    .context("标记快照已清理失败")?;
+            }
+            Ok(())
+        })
+    }
+
+    pub fn count_snapshot_references(&self, hash: &str) -> Result<usize> {
+        let conn = self.conn()?;
+        let count: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM switch_history WHERE snapshot_hash = ?1",
+                params![hash],
+                |row| row.get(0),
+            )
+            .context("统计快照引用失败")?;
+        Ok(count as usize)
+    }
+
+    // 引入内容寻址前写入的历史：有快照路径但没有哈希。
+    pub fn list_unhashed_snapshots(&self) -> Result<Vec<(String, String)>> {
+        let conn = self.conn()?;
+        let mut stmt = conn.prepare_cached(
+            r#"
+            SELECT id, snapshot_path FROM switch_history
+            WHERE snapshot_path IS NOT NULL AND snapshot_hash IS NULL
+            "#,
+        )?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        Ok(rows.filter_map(Result::ok).collect())
+    }
+
+    pub fn set_history_snapshot(&self, history_id: &str, snapshot: &Snapshot) -> Result<()> {
+        let conn = self.conn()?;
+        conn.execute(
+            "UPDATE switch_history SET snapshot_path = ?1, snapshot_hash = ?2 WHERE id = ?3",
+            params![
+                snapshot.path.display().to_string(),
+                snapshot.hash,
+                history_id
+            ],
+        )
+        .context("更新历史快照失败")?;
+        Ok(())
+    }
+
     pub fn get_current_account_id(&self) -> Result<Option<String>> {
-        let history = self.list_switch_history(1)?;
-        Ok(history.first().map(|item| item.to_account_id.clone()))
+        let conn = self.conn()?;
+        let current: Option<Option<String>> = conn
+            .query_row(
+                "SELECT to_account_id FROM switch_history ORDER BY created_at DESC, id DESC LIMIT 1",
+                [],
+                |row| row.get(0),
+            )
+            .optional()
+            .context("读取当前账号失败")?;
+        Ok(current.flatten())
     }
 
     pub fn save_quota_snapshot(
@@ -455,7 +1553,7 @@ impl AppStore {
         confidence: i64,
         reason: Option<&str>,
     ) -> Result<QuotaSnapshot> {
-        let conn = self.open_conn()?;
+        let conn = self.conn()?;
         let id = Uuid::new_v4().to_string();
         let created_at = now();
         conn.execute(
@@ -479,12 +1577,13 @@ impl AppStore {
             ],
         )
         .context("写入配额快照失败")?;
+        drop(conn);
         self.get_quota_snapshot(&id)?
             .ok_or_else(|| anyhow!("写入配额快照后未找到"))
     }
 
     pub fn get_quota_snapshot(&self, id: &str) -> Result<Option<QuotaSnapshot>> {
-        let conn = self.open_conn()?;
+        let conn = self.conn()?;
         conn.query_row(
             r#"
             SELECT id, account_id, mode, remaining_value, remaining_unit, quota_state, reset_at, source, confidence, reason, created_at
@@ -502,8 +1601,8 @@ impl AppStore {
         account_id: &str,
         limit: usize,
     ) -> Result<Vec<QuotaSnapshot>> {
-        let conn = self.open_conn()?;
-        let mut stmt = conn.prepare(
+        let conn = self.conn()?;
+        let mut stmt = conn.prepare_cached(
             r#"
             SELECT id, account_id, mode, remaining_value, remaining_unit, quota_state, reset_at, source, confidence, reason, created_at
             FROM quota_snapshots
@@ -517,8 +1616,8 @@ impl AppStore {
     }
 
     pub fn list_latest_quota_snapshots(&self) -> Result<Vec<QuotaSnapshot>> {
-        let conn = self.open_conn()?;
-        let mut stmt = conn.prepare(
+        let conn = self.conn()?;
+        let mut stmt = conn.prepare_cached(
             r#"
             SELECT q.id, q.account_id, q.mode, q.remaining_value, q.remaining_unit, q.quota_state, q.reset_at, q.source, q.confidence, q.reason, q.created_at
             FROM quota_snapshots q
@@ -535,7 +1634,7 @@ impl AppStore {
     }
 
     pub fn latest_quota_by_account(&self, account_id: &str) -> Result<Option<QuotaSnapshot>> {
-        let conn = self.open_conn()?;
+        let conn = self.conn()?;
         conn.query_row(
             r#"
             SELECT id, account_id, mode, remaining_value, remaining_unit, quota_state, reset_at, source, confidence, reason, created_at
@@ -568,6 +1667,235 @@ fn map_quota_snapshot(row: &rusqlite::Row<'_>) -> rusqlite::Result<QuotaSnapshot
     })
 }
 
+fn map_account_health(row: &rusqlite::Row<'_>) -> rusqlite::Result<AccountHealth> {
+    Ok(AccountHealth {
+        account_id: row.get(0)?,
+        status: row.get(1)?,
+        expires_at: row.get(2)?,
+        reason: row.get(3)?,
+        remote_checked: row.get::<_, i64>(4)? != 0,
+        checked_at: row.get(5)?,
+    })
+}
+
+fn map_account(row: &rusqlite::Row<'_>) -> rusqlite::Result<Account> {
+    Ok(Account {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        tags: serde_json::from_str::<Vec<String>>(&row.get::<_, String>(2)?).unwrap_or_default(),
+        auth_fingerprint: row.get(3)?,
+        created_at: row.get(4)?,
+        updated_at: row.get(5)?,
+        last_used_at: row.get(6)?,
+    })
+}
+
+fn map_switch_history(row: &rusqlite::Row<'_>) -> rusqlite::Result<SwitchHistory> {
+    Ok(SwitchHistory {
+        id: row.get(0)?,
+        from_account_id: row.get(1)?,
+        to_account_id: row.get(2)?,
+        snapshot_path: row.get(3)?,
+        snapshot_hash: row.get(4)?,
+        snapshot_pruned_at: row.get(5)?,
+        result: row.get(6)?,
+        error_message: row.get(7)?,
+        created_at: row.get(8)?,
+    })
+}
+
+fn map_project_binding(row: &rusqlite::Row<'_>) -> rusqlite::Result<ProjectBinding> {
+    Ok(ProjectBinding {
+        id: row.get(0)?,
+        path: row.get(1)?,
+        account_id: row.get(2)?,
+        tag: row.get(3)?,
+        created_at: row.get(4)?,
+        updated_at: row.get(5)?,
+    })
+}
+
+fn map_account_pool(row: &rusqlite::Row<'_>) -> rusqlite::Result<AccountPool> {
+    Ok(AccountPool {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        tag: row.get(2)?,
+        account_ids: serde_json::from_str::<Vec<String>>(&row.get::<_, String>(3)?)
+            .unwrap_or_default(),
+        strategy: row.get(4)?,
+        last_account_id: row.get(5)?,
+        created_at: row.get(6)?,
+        updated_at: row.get(7)?,
+    })
+}
+
+fn map_control_token(row: &rusqlite::Row<'_>) -> rusqlite::Result<ControlToken> {
+    Ok(ControlToken {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        created_at: row.get(2)?,
+        last_used_at: row.get(3)?,
+    })
+}
+
 pub fn now() -> String {
     Utc::now().to_rfc3339()
 }
+
+// 历史时间以 UTC RFC 3339 字符串存储，筛选值统一换算后才能按字典序比较。
+fn normalize_time(value: &str) -> Result<String> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|time| time.with_timezone(&Utc).to_rfc3339())
+        .map_err(|_| anyhow!("时间格式无效，应为 RFC 3339: {value}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{now, AppStore};
+    use crate::{failover::RestartMode, models::SwitchHistoryQuery};
+    use rusqlite::params;
+
+    #[test]
+    fn store_calls_share_one_connection() {
+        let root = std::env::temp_dir().join(format!("codex-switch-conn-{}", uuid::Uuid::new_v4()));
+        let store = AppStore::new(root.clone());
+        store.init().expect("应初始化");
+
+        // 临时表只对创建它的连接可见，后续调用能读到即说明复用了同一连接。
+        let first = {
+            let conn = store.conn().expect("应获取连接");
+            conn.execute_batch(
+                "CREATE TEMP TABLE probe(id INTEGER); INSERT INTO probe VALUES (1);",
+            )
+            .expect("应创建临时表");
+            &*conn as *const rusqlite::Connection
+        };
+        store.list_accounts().expect("应读取账号");
+        let conn = store.conn().expect("应获取连接");
+        let probe: i64 = conn
+            .query_row("SELECT id FROM probe", [], |row| row.get(0))
+            .expect("应读到临时表");
+
+        assert_eq!(probe, 1);
+        assert_eq!(&*conn as *const rusqlite::Connection, first);
+        drop(conn);
+        std::fs::remove_dir_all(&root).expect("应清理目录");
+    }
+
+    #[test]
+    fn history_query_pages_filters_and_names_trashed_accounts() {
+        let root =
+            std::env::temp_dir().join(format!("codex-switch-history-{}", uuid::Uuid::new_v4()));
+        let store = AppStore::new(root.clone());
+        store.init().expect("应初始化");
+        for id in ["a", "b"] {
+            store
+                .conn()
+                .expect("应获取连接")
+                .execute(
+                    r#"
+                    INSERT INTO accounts(id, name, tags_json, encrypted_auth_blob, auth_fingerprint, created_at, updated_at)
+                    VALUES (?1, ?2, '[]', 'blob', ?1, ?3, ?3)
+                    "#,
+                    params![id, format!("账号{id}"), now()],
+                )
+                .expect("应写入账号");
+        }
+        for index in 0..5 {
+            let (from, to, result) = match index % 2 {
+                0 => ("a", "b", "success"),
+                _ => ("b", "a", "failed"),
+            };
+            store
+                .conn()
+                .expect("应获取连接")
+                .execute(
+                    "INSERT INTO switch_history(id, from_account_id, to_account_id, result, created_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+                    params![
+                        format!("h{index}"),
+                        from,
+                        to,
+                        result,
+                        format!("2026-01-0{}T00:00:00+00:00", index + 1)
+                    ],
+                )
+                .expect("应写入历史");
+        }
+        assert!(store.trash_account("b").expect("应移入回收站"));
+        assert_eq!(
+            store.get_current_account_id().expect("应读取"),
+            Some("b".into())
+        );
+
+        let mut query = SwitchHistoryQuery {
+            limit: Some(2),
+            ..Default::default()
+        };
+        let mut ids = Vec::new();
+        loop {
+            let page = store.query_switch_history(&query).expect("应查询");
+            assert_eq!(page.total, 5);
+            ids.extend(page.items.iter().map(|entry| entry.history.id.clone()));
+            match page.next_cursor {
+                Some(cursor) => query.cursor = Some(cursor),
+                None => break,
+            }
+        }
+        assert_eq!(ids, ["h4", "h3", "h2", "h1", "h0"]);
+
+        let page = store
+            .query_switch_history(&SwitchHistoryQuery {
+                result: Some("success".into()),
+                since: Some("2026-01-02T08:00:00+08:00".into()),
+                ..Default::default()
+            })
+            .expect("应查询");
+        assert_eq!(page.total, 2);
+        let entry = &page.items[0];
+        assert_eq!(entry.history.id, "h4");
+        assert_eq!(entry.to_account_name.as_deref(), Some("账号b"));
+        assert!(entry.to_account_trashed && !entry.from_account_trashed);
+
+        assert!(store
+            .query_switch_history(&SwitchHistoryQuery {
+                result: Some("unknown".into()),
+                ..Default::default()
+            })
+            .is_err());
+        std::fs::remove_dir_all(&root).expect("应清理目录");
+    }
+
+    #[test]
+    fn failover_does_not_restart_codex_by_default() {
+        let root =
+            std::env::temp_dir().join(format!("codex-switch-failover-{}", uuid::Uuid::new_v4()));
+        let store = AppStore::new(root.clone());
+        store.init().expect("应初始化");
+        let mut config = store.get_failover_config().expect("应读取");
+        assert_eq!(config.restart_mode, RestartMode::Never);
+
+        config.enabled = true;
+        config.restart_mode = RestartMode::Force;
+        store.set_failover_config(&config).expect("应保存");
+        assert_eq!(
+            store.get_failover_config().expect("应读取").restart_mode,
+            RestartMode::Force
+        );
+        std::fs::remove_dir_all(&root).expect("应清理目录");
+    }
+
+    #[test]
+    fn read_only_store_reads_without_initializing() {
+        let root =
+            std::env::temp_dir().join(format!("codex-switch-readonly-{}", uuid::Uuid::new_v4()));
+        assert!(AppStore::new(root.clone()).open_read_only().is_err());
+        assert!(!root.exists());
+
+        AppStore::new(root.clone()).init().expect("应初始化");
+        let store = AppStore::new(root.clone());
+        store.open_read_only().expect("应只读打开");
+        assert!(store.get_current_account_id().expect("应读取").is_none());
+        assert!(store.set_trash_retention_days(7).is_err());
+        std::fs::remove_dir_all(&root).expect("应清理目录");
+    }
+}
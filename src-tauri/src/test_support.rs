@@ -0,0 +1,94 @@
+use crate::{
+    app_state::AppState,
+    models::{Account, QuotaDashboardItem, QuotaSnapshot},
+    store::AppStore,
+};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::{Duration, Utc};
+use std::{ops::Deref, path::PathBuf};
+
+pub const TEST_VAULT_KEY: [u8; 32] = [7_u8; 32];
+
+// 临时数据目录中的 AppState，析构时删除目录。
+pub struct TestState {
+    pub root: PathBuf,
+    pub state: AppState,
+}
+
+impl TestState {
+    pub fn locked(name: &str) -> Self {
+        let root =
+            std::env::temp_dir().join(format!("codex-switch-{name}-{}", uuid::Uuid::new_v4()));
+        let state = AppState::open(AppStore::new(root.clone())).expect("应初始化状态");
+        Self { root, state }
+    }
+
+    pub fn unlocked(name: &str) -> Self {
+        let fixture = Self::locked(name);
+        fixture
+            .state
+            .vault
+            .lock()
+            .expect("应获取锁")
+            .set_key(TEST_VAULT_KEY.to_vec());
+        fixture
+    }
+}
+
+impl Deref for TestState {
+    type Target = AppState;
+
+    fn deref(&self) -> &AppState {
+        &self.state
+    }
+}
+
+impl Drop for TestState {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.root);
+    }
+}
+
+// 只带 exp 声明的 JWT，签名部分不参与校验。
+pub fn access_token_expiring_in(offset: Duration) -> String {
+    let claims = format!(r#"{{"exp":{}}}"#, (Utc::now() + offset).timestamp());
+    format!("e30.{}.sig", URL_SAFE_NO_PAD.encode(claims))
+}
+
+pub fn auth_file_text(email: &str) -> String {
+    format!(r#"{{"type":"codex","access_token":"token-{email}","email":"{email}"}}"#)
+}
+
+// 配额看板中的一项；percent 为空时只记录配额状态，时间字段统一为 2026-01-01。
+pub fn dashboard_item(
+    id: &str,
+    tags: &[&str],
+    quota_state: &str,
+    percent: Option<f64>,
+) -> QuotaDashboardItem {
+    QuotaDashboardItem {
+        account: Account {
+            id: id.to_string(),
+            name: id.to_string(),
+            tags: tags.iter().map(ToString::to_string).collect(),
+            auth_fingerprint: format!("account:{id}"),
+            created_at: "2026-01-01T00:00:00Z".to_string(),
+            updated_at: "2026-01-01T00:00:00Z".to_string(),
+            last_used_at: None,
+        },
+        snapshot: Some(QuotaSnapshot {
+            id: format!("q-{id}"),
+            account_id: id.to_string(),
+            mode: if percent.is_some() { "exact" } else { "state" }.to_string(),
+            remaining_value: percent,
+            remaining_unit: percent.map(|_| "%".to_string()),
+            quota_state: quota_state.to_string(),
+            reset_at: None,
+            source: "api".to_string(),
+            confidence: 90,
+            reason: None,
+            created_at: "2026-01-01T00:00:00Z".to_string(),
+        }),
+        health: None,
+    }
+}
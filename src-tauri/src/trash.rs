@@ -0,0 +1,155 @@
+use crate::{
+    app_state::AppState, homes, integrity, models::Account, models::TrashedAccount, snapshots,
+};
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use std::{thread, time::Duration};
+use tauri::{AppHandle, Manager};
+
+const TRASH_PURGE_INTERVAL: Duration = Duration::from_secs(10 * 60);
+pub const MAX_TRASH_RETENTION_DAYS: u32 = 365;
+
+pub fn spawn_trash_purger(app: AppHandle) {
+    thread::spawn(move || loop {
+        let state = app.state::<AppState>();
+        let _ = purge_expired(&state);
+        thread::sleep(TRASH_PURGE_INTERVAL);
+    });
+}
+
+pub fn trash_account(state: &AppState, id: &str) -> Result<()> {
+    if !state.store.trash_account(id)? {
+        return Err(anyhow!("账户不存在"));
+    }
+    homes::remove_account_home(state, id)
+}
+
+pub fn restore_account(state: &AppState, id: &str) -> Result<Account> {
+    if !state.store.restore_account(id)? {
+        return Err(anyhow!("回收站中没有该账户"));
+    }
+    let account = state
+        .store
+        .get_account(id)?
+        .ok_or_else(|| anyhow!("账户恢复后未找到"))?;
+    let _ = homes::sync_account_home(state, id);
+    Ok(account)
+}
+
+pub fn list_trash(state: &AppState) -> Result<Vec<TrashedAccount>> {
+    let retention = ChronoDuration::days(state.store.get_trash_retention_days()? as i64);
+    Ok(state
+        .store
+        .list_trashed_accounts()?
+        .into_iter()
+        .map(|(account, deleted_at)| TrashedAccount {
+            purge_at: parse_time(&deleted_at)
+                .map(|time| (time + retention).to_rfc3339())
+                .unwrap_or_default(),
+            account,
+            deleted_at,
+        })
+        .collect())
+}
+
+// 永久删除会销毁加密的登录信息，要求保险库已解锁以确认操作者持有主密码。
+pub fn purge_account(state: &AppState, id: &str) -> Result<()> {
+    if !state.is_vault_unlocked()? {
+        return Err(anyhow!("请先解锁保险库后再永久删除账户"));
+    }
+    if !state
+        .store
+        .list_trashed_accounts()?
+        .iter()
+        .any(|(account, _)| account.id == id)
+    {
+        return Err(anyhow!("只能永久删除回收站中的账户"));
+    }
+    let detached_snapshots = state.store.delete_account(id)?;
+    snapshots::release_snapshots(&state.store, &detached_snapshots)?;
+    integrity::remove_unreferenced_snapshots(&state.store)?;
+    homes::remove_account_home(state, id)
+}
+
+pub fn purge_expired(state: &AppState) -> Result<usize> {
+    if !state.is_vault_unlocked()? {
+        return Ok(0);
+    }
+    let now = Utc::now();
+    let mut purged = 0;
+    for item in list_trash(state)? {
+        if parse_time(&item.purge_at).is_some_and(|purge_at| purge_at <= now) {
+            purge_account(state, &item.account.id)?;
+            purged += 1;
+        }
+    }
+    Ok(purged)
+}
+
+fn parse_time(value: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value)
+        .ok()
+        .map(|time| time.with_timezone(&Utc))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{list_trash, purge_account, purge_expired, restore_account, trash_account};
+    use crate::{models::Account, test_support::TestState};
+
+    fn trashed_account(state: &TestState) -> Account {
+        let account = state
+            .store
+            .create_account("work", &[], "blob", "account:a")
+            .expect("应创建账号");
+        trash_account(state, &account.id).expect("应移入回收站");
+        account
+    }
+
+    #[test]
+    fn trashed_accounts_are_hidden_until_restored() {
+        let state = TestState::locked("trash");
+        let account = trashed_account(&state);
+        assert!(state.store.list_accounts().expect("应读取").is_empty());
+        assert!(state
+            .store
+            .get_account_secret(&account.id)
+            .expect("应读取")
+            .is_none());
+        assert_eq!(list_trash(&state).expect("应读取").len(), 1);
+
+        restore_account(&state, &account.id).expect("应恢复");
+        assert_eq!(state.store.list_accounts().expect("应读取").len(), 1);
+        assert!(list_trash(&state).expect("应读取").is_empty());
+    }
+
+    #[test]
+    fn purging_requires_an_unlocked_vault() {
+        let state = TestState::locked("trash");
+        let account = trashed_account(&state);
+        assert!(
+            purge_account(&state, &account.id).is_err(),
+            "未解锁时不应永久删除"
+        );
+        assert_eq!(list_trash(&state).expect("应读取").len(), 1);
+    }
+
+    #[test]
+    fn purges_only_accounts_past_retention() {
+        let state = TestState::unlocked("trash");
+        trashed_account(&state);
+        assert_eq!(purge_expired(&state).expect("应清理"), 0);
+
+        state
+            .store
+            .conn()
+            .expect("应获取数据库连接")
+            .execute(
+                "UPDATE accounts SET deleted_at = '2000-01-01T00:00:00+00:00'",
+                [],
+            )
+            .expect("应修改删除时间");
+        assert_eq!(purge_expired(&state).expect("应清理"), 1);
+        assert!(list_trash(&state).expect("应读取").is_empty());
+    }
+}
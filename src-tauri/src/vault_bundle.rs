@@ -0,0 +1,455 @@
+use crate::{
+    app_state::AppState,
+    codex::{atomic_write, compute_fingerprint, validate_auth_json},
+    crypto, homes,
+    models::{Account, VaultExportSummary, VaultImportItem, VaultImportReport},
+    service::rewrite_live_auth_if_current,
+    store::now,
+};
+use anyhow::{anyhow, Context, Result};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+    collections::{BTreeSet, HashSet},
+    fs,
+    path::Path,
+};
+use zeroize::Zeroize;
+
+const BUNDLE_FORMAT: &str = "codex-switch-vault";
+const BUNDLE_VERSION: u32 = 1;
+const MIN_PASSPHRASE_LEN: usize = 8;
+
+// 导出文件外层为明文 JSON，仅记录格式与派生参数；账号数据整体以导出口令派生的密钥加密，
+// 与本机主密码、盐值无关，可在任意设备上导入。
+#[derive(Debug, Serialize, Deserialize)]
+struct BundleEnvelope {
+    format: String,
+    version: u32,
+    kdf: String,
+    salt: String,
+    created_at: String,
+    account_count: usize,
+    payload: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct BundleAccount {
+    name: String,
+    tags: Vec<String>,
+    auth_fingerprint: String,
+    created_at: String,
+    auth: Value,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct BundlePayload {
+    accounts: Vec<BundleAccount>,
+}
+
+// 冲突按 auth_fingerprint 判定；rename 用于指纹不同但名称已被本机账号占用的情况。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictStrategy {
+    #[default]
+    Skip,
+    Overwrite,
+    Rename,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VaultExportFilter {
+    pub account_ids: Vec<String>,
+    pub tags: Vec<String>,
+}
+
+impl VaultExportFilter {
+    fn matches(&self, account: &Account) -> bool {
+        if self.account_ids.is_empty() && self.tags.is_empty() {
+            return true;
+        }
+        self.account_ids.contains(&account.id)
+            || account.tags.iter().any(|tag| self.tags.contains(tag))
+    }
+}
+
+pub fn export_vault(
+    state: &AppState,
+    path: &Path,
+    passphrase: &str,
+    filter: &VaultExportFilter,
+) -> Result<VaultExportSummary> {
+    check_passphrase(passphrase)?;
+    let accounts = state
+        .store
+        .list_accounts()?
+        .into_iter()
+        .filter(|account| filter.matches(account))
+        .collect::<Vec<_>>();
+    if accounts.is_empty() {
+        return Err(anyhow!("没有符合条件的账号可导出"));
+    }
+
+    let mut key = state.get_vault_key()?;
+    let entries = (|| {
+        accounts
+            .iter()
+            .map(|account| {
+                let secret = state
+                    .store
+                    .get_account_secret(&account.id)?
+                    .ok_or_else(|| anyhow!("账号不存在: {}", account.name))?;
+                let mut decrypted = crypto::decrypt_from_base64(&key, &secret.encrypted_auth_blob)?;
+                let auth = serde_json::from_slice::<Value>(&decrypted)
+                    .with_context(|| format!("账号登录数据损坏: {}", account.name));
+                decrypted.zeroize();
+                Ok(BundleAccount {
+                    name: account.name.clone(),
+                    tags: account.tags.clone(),
+                    auth_fingerprint: account.auth_fingerprint.clone(),
+                    created_at: account.created_at.clone(),
+                    auth: auth?,
+                })
+            })
+            .collect::<Result<Vec<_>>>()
+    })();
+    key.zeroize();
+    let entries = entries?;
+
+    let account_count = entries.len();
+    let mut plaintext = serde_json::to_string(&BundlePayload { accounts: entries })?;
+    let salt = crypto::generate_salt();
+    let mut export_key = crypto::derive_key(passphrase, &salt)?;
+    let payload = crypto::encrypt_to_base64(&export_key, plaintext.as_bytes());
+    export_key.zeroize();
+    plaintext.zeroize();
+
+    let envelope = BundleEnvelope {
+        format: BUNDLE_FORMAT.to_string(),
+        version: BUNDLE_VERSION,
+        kdf: "argon2id".to_string(),
+        salt,
+        created_at: now(),
+        account_count,
+        payload: payload?,
+    };
+    atomic_write(path, &serde_json::to_string_pretty(&envelope)?)?;
+    Ok(VaultExportSummary {
+        path: path.display().to_string(),
+        account_count,
+    })
+}
+
+pub fn import_vault(
+    state: &AppState,
+    path: &Path,
+    passphrase: &str,
+    strategy: ConflictStrategy,
+) -> Result<VaultImportReport> {
+    let text = fs::read_to_string(path)
+        .with_context(|| format!("读取导出文件失败: {}", path.display()))?;
+    let envelope = serde_json::from_str::<BundleEnvelope>(&text)
+        .ok()
+        .filter(|envelope| envelope.format == BUNDLE_FORMAT)
+        .ok_or_else(|| anyhow!("不是 Codex Switch 导出文件: {}", path.display()))?;
+    if envelope.version > BUNDLE_VERSION {
+        return Err(anyhow!(
+            "导出文件版本 v{} 高于当前应用支持的 v{BUNDLE_VERSION}，请先升级应用",
+            envelope.version
+        ));
+    }
+
+    let mut export_key = crypto::derive_key(passphrase, &envelope.salt)?;
+    let decrypted = crypto::decrypt_from_base64(&export_key, &envelope.payload);
+    export_key.zeroize();
+    let mut decrypted = decrypted.map_err(|_| anyhow!("导出口令错误或文件已损坏"))?;
+    let payload = serde_json::from_slice::<BundlePayload>(&decrypted);
+    decrypted.zeroize();
+    let payload = payload.context("导出文件内容格式不正确")?;
+
+    let mut key = state.get_vault_key()?;
+    let mut names = state
+        .store
+        .list_accounts()?
+        .into_iter()
+        .map(|account| account.name)
+        .collect::<HashSet<_>>();
+    let trashed = state
+        .store
+        .list_trashed_accounts()?
+        .into_iter()
+        .map(|(account, _)| account.auth_fingerprint)
+        .collect::<HashSet<_>>();
+
+    let mut report = VaultImportReport {
+        total: payload.accounts.len(),
+        imported: 0,
+        overwritten: 0,
+        renamed: 0,
+        skipped: 0,
+        failed: 0,
+        items: Vec::new(),
+    };
+    for entry in &payload.accounts {
+        let item = import_entry(state, &key, entry, strategy, &mut names, &trashed).unwrap_or_else(
+            |error| VaultImportItem {
+                name: entry.name.clone(),
+                auth_fingerprint: entry.auth_fingerprint.clone(),
+                action: "failed".to_string(),
+                account_id: None,
+                message: Some(error.to_string()),
+            },
+        );
+        match item.action.as_str() {
+            "imported" => report.imported += 1,
+            "overwritten" => report.overwritten += 1,
+            "renamed" => report.renamed += 1,
+            "skipped" => report.skipped += 1,
+            _ => report.failed += 1,
+        }
+        report.items.push(item);
+    }
+    key.zeroize();
+    Ok(report)
+}
+
+fn import_entry(
+    state: &AppState,
+    key: &[u8],
+    entry: &BundleAccount,
+    strategy: ConflictStrategy,
+    names: &mut HashSet<String>,
+    trashed: &HashSet<String>,
+) -> Result<VaultImportItem> {
+    let auth = validate_auth_json(&entry.auth.to_string())?;
+    let fingerprint = compute_fingerprint(&auth)?;
+    let mut name = entry.name.trim().to_string();
+    if name.is_empty() {
+        name = fingerprint.clone();
+    }
+    let tags = entry
+        .tags
+        .iter()
+        .map(|tag| tag.trim().to_string())
+        .filter(|tag| !tag.is_empty())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect::<Vec<_>>();
+    let item =
+        |action: &str, account_id: Option<String>, message: Option<String>| VaultImportItem {
+            name: name.clone(),
+            auth_fingerprint: fingerprint.clone(),
+            action: action.to_string(),
+            account_id,
+            message,
+        };
+
+    if trashed.contains(&fingerprint) {
+        return Ok(item(
+            "skipped",
+            None,
+            Some("回收站中存在同一账号，请先恢复".to_string()),
+        ));
+    }
+    if let Some(existing) = state.store.find_account_by_fingerprint(&fingerprint)? {
+        if strategy != ConflictStrategy::Overwrite {
+            return Ok(item(
+                "skipped",
+                Some(existing.id),
+                Some(format!("已存在同一账号：{}", existing.name)),
+            ));
+        }
+        let encrypted = encrypt_auth(key, &auth)?;
+        state
+            .store
+            .update_account_auth_blob(&existing.id, &encrypted)?;
+        // 保留本机名称：导入文件中的名称可能已被本机其他账号占用
+        state
+            .store
+            .update_account_meta(&existing.id, &existing.name, &tags)?;
+        let _ = homes::sync_account_home(state, &existing.id);
+        rewrite_live_auth_if_current(state, &existing.id, &auth)
+            .context("凭据已更新，但改写当前 Codex 登录文件失败")?;
+        return Ok(item(
+            "overwritten",
+            Some(existing.id),
+            Some(format!("已覆盖本机账号：{}", existing.name)),
+        ));
+    }
+
+    let mut action = "imported";
+    let mut message = None;
+    if names.contains(&name) {
+        match strategy {
+            ConflictStrategy::Skip => {
+                return Ok(item(
+                    "skipped",
+                    None,
+                    Some("名称已被本机其他账号使用".to_string()),
+                ))
+            }
+            ConflictStrategy::Rename => {
+                let renamed = unique_name(&name, names);
+                message = Some(format!("名称冲突，已重命名为 {renamed}"));
+                name = renamed;
+                action = "renamed";
+            }
+            // 名称不是账号标识，覆盖策略下指纹不同的同名账号直接导入
+            ConflictStrategy::Overwrite => {}
+        }
+    }
+    let encrypted = encrypt_auth(key, &auth)?;
+    let account = state
+        .store
+        .create_account(&name, &tags, &encrypted, &fingerprint)?;
+    names.insert(name.clone());
+    let _ = homes::sync_account_home(state, &account.id);
+    Ok(VaultImportItem {
+        name,
+        auth_fingerprint: fingerprint,
+        action: action.to_string(),
+        account_id: Some(account.id),
+        message,
+    })
+}
+
+fn encrypt_auth(key: &[u8], auth: &Value) -> Result<String> {
+    let mut auth_text = serde_json::to_string_pretty(auth)?;
+    let encrypted = crypto::encrypt_to_base64(key, auth_text.as_bytes());
+    auth_text.zeroize();
+    encrypted
+}
+
+fn unique_name(name: &str, names: &HashSet<String>) -> String {
+    (2..)
+        .map(|index| format!("{name} ({index})"))
+        .find(|candidate| !names.contains(candidate))
+        .unwrap_or_else(|| name.to_string())
+}
+
+fn check_passphrase(passphrase: &str) -> Result<()> {
+    if passphrase.trim().chars().count() < MIN_PASSPHRASE_LEN {
+        return Err(anyhow!("导出口令至少需要 {MIN_PASSPHRASE_LEN} 位"));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{export_vault, import_vault, ConflictStrategy, VaultExportFilter};
+    use crate::{app_state::AppState, codex::compute_fingerprint, crypto, test_support::TestState};
+    use serde_json::json;
+    use std::path::PathBuf;
+
+    const PASSPHRASE: &str = "export-passphrase";
+
+    fn add_account(state: &AppState, name: &str, email: &str, tags: &[&str]) {
+        let auth =
+            json!({ "type": "codex", "access_token": format!("token-{email}"), "email": email });
+        let key = state.get_vault_key().expect("应已解锁");
+        let encrypted =
+            crypto::encrypt_to_base64(&key, auth.to_string().as_bytes()).expect("应加密");
+        let tags = tags.iter().map(|tag| tag.to_string()).collect::<Vec<_>>();
+        state
+            .store
+            .create_account(
+                name,
+                &tags,
+                &encrypted,
+                &compute_fingerprint(&auth).expect("应生成指纹"),
+            )
+            .expect("应创建账号");
+    }
+
+    fn team_filter() -> VaultExportFilter {
+        VaultExportFilter {
+            account_ids: Vec::new(),
+            tags: vec!["team".to_string()],
+        }
+    }
+
+    // 导出带 team 标签的 work、shared 两个账号；返回的夹具需活到导入完成。
+    fn team_bundle() -> (TestState, PathBuf) {
+        let source = TestState::unlocked("export");
+        add_account(&source, "work", "work@example.com", &["team"]);
+        add_account(&source, "shared", "shared@example.com", &["team"]);
+        add_account(&source, "private", "me@example.com", &[]);
+        let bundle = source.root.join("accounts.csvault");
+        export_vault(&source, &bundle, PASSPHRASE, &team_filter()).expect("应导出");
+        (source, bundle)
+    }
+
+    // 目标库中 work 同名不同人，shared 同人不同名。
+    fn conflicting_target() -> TestState {
+        let target = TestState::unlocked("import");
+        add_account(&target, "work", "other@example.com", &[]);
+        add_account(&target, "shared-local", "shared@example.com", &[]);
+        target
+    }
+
+    #[test]
+    fn exports_only_filtered_accounts_encrypted() {
+        let source = TestState::unlocked("export");
+        add_account(&source, "work", "work@example.com", &["team"]);
+        add_account(&source, "private", "me@example.com", &[]);
+        let bundle = source.root.join("accounts.csvault");
+        assert!(export_vault(&source, &bundle, "short", &team_filter()).is_err());
+
+        let summary = export_vault(&source, &bundle, PASSPHRASE, &team_filter()).expect("应导出");
+        assert_eq!(summary.account_count, 1);
+        assert!(!std::fs::read_to_string(&bundle)
+            .expect("应读取")
+            .contains("work@example.com"));
+    }
+
+    #[test]
+    fn import_rejects_wrong_passphrase() {
+        let (_source, bundle) = team_bundle();
+        let target = conflicting_target();
+        assert!(
+            import_vault(&target, &bundle, "wrong-passphrase", ConflictStrategy::Skip).is_err()
+        );
+        assert_eq!(target.store.list_accounts().expect("应读取").len(), 2);
+    }
+
+    #[test]
+    fn import_renames_name_conflicts_and_skips_known_identities() {
+        let (_source, bundle) = team_bundle();
+        let target = conflicting_target();
+        let report =
+            import_vault(&target, &bundle, PASSPHRASE, ConflictStrategy::Rename).expect("应导入");
+        assert_eq!((report.total, report.renamed, report.skipped), (2, 1, 1));
+        let mut names = target
+            .store
+            .list_accounts()
+            .expect("应读取")
+            .into_iter()
+            .map(|account| account.name)
+            .collect::<Vec<_>>();
+        names.sort();
+        assert_eq!(names, ["shared-local", "work", "work (2)"]);
+    }
+
+    #[test]
+    fn import_overwrites_known_identities_only() {
+        let (_source, bundle) = team_bundle();
+        let target = conflicting_target();
+        let report = import_vault(&target, &bundle, PASSPHRASE, ConflictStrategy::Overwrite)
+            .expect("应导入");
+        assert_eq!((report.overwritten, report.imported), (1, 1));
+        let accounts = target.store.list_accounts().expect("应读取");
+        assert!(accounts
+            .iter()
+            .any(|account| account.name == "shared-local" && account.tags == ["team"]));
+        assert!(accounts.iter().all(|account| account.name != "shared"));
+        assert_eq!(
+            accounts
+                .iter()
+                .filter(|account| account.name == "work")
+                .count(),
+            2
+        );
+    }
+}
@@ -0,0 +1,87 @@
+use crate::{
+    app_state::AppState,
+    codex::{kill_codex_process, launch_codex, list_codex_process_cwds},
+    models::ProjectAutoSwitchEvent,
+    projects::resolve_for_path,
+    service::{switch_to_account, SwitchOptions},
+};
+use anyhow::Result;
+use std::{collections::HashSet, path::Path, thread, time::Duration};
+use tauri::{AppHandle, Emitter, Manager};
+
+const PROJECT_WATCH_INTERVAL_MS: u64 = 2000;
+pub const PROJECT_AUTO_SWITCH_EVENT: &str = "project-auto-switch";
+
+pub fn spawn_project_watcher(app: AppHandle) {
+    thread::spawn(move || {
+        let mut known_pids: Option<HashSet<u32>> = None;
+        loop {
+            thread::sleep(Duration::from_millis(PROJECT_WATCH_INTERVAL_MS));
+            let state = app.state::<AppState>();
+            if !state.store.get_project_auto_switch().unwrap_or(false) {
+                known_pids = None;
+                continue;
+            }
+
+            let processes = list_codex_process_cwds();
+            let current = processes
+                .iter()
+                .map(|(pid, _)| *pid)
+                .collect::<HashSet<_>>();
+            let Some(previous) = known_pids.replace(current) else {
+                continue;
+            };
+            for (pid, cwd) in processes {
+                let Some(cwd) = cwd.filter(|_| !previous.contains(&pid)) else {
+                    continue;
+                };
+                if let Ok(Some(event)) = auto_switch_for_process(&state, pid, &cwd) {
+                    let _ = app.emit(PROJECT_AUTO_SWITCH_EVENT, event);
+                }
+            }
+        }
+    });
+}
+
+fn auto_switch_for_process(
+    state: &AppState,
+    pid: u32,
+    cwd: &Path,
+) -> Result<Option<ProjectAutoSwitchEvent>> {
+    if !state.is_vault_unlocked()? {
+        return Ok(None);
+    }
+    let resolution = resolve_for_path(&state.store, cwd)?;
+    let Some(account) = resolution.account.as_ref() else {
+        return Ok(None);
+    };
+    if state.store.get_current_account_id()?.as_deref() == Some(account.id.as_str()) {
+        return Ok(None);
+    }
+
+    let result = switch_to_account(
+        state,
+        &account.id,
+        SwitchOptions {
+            triggered_by_process: true,
+            ..SwitchOptions::default()
+        },
+    )?;
+    // 触发切换的进程启动时已读入旧凭据，只重启它本身，其他正在运行的 Codex 不受影响。
+    let restarted = result.success && kill_codex_process(pid) && {
+        let binary = state.resolve_codex_binary()?;
+        launch_codex(
+            binary.as_ref().map(|binary| binary.path.as_path()),
+            None,
+            Some(cwd),
+        )
+        .is_ok()
+    };
+    Ok(Some(ProjectAutoSwitchEvent {
+        pid,
+        cwd: cwd.display().to_string(),
+        resolution,
+        result,
+        restarted,
+    }))
+}
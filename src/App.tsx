@@ -1,41 +1,124 @@
 ﻿
-import { open } from "@tauri-apps/plugin-dialog";
+import { listen } from "@tauri-apps/api/event";
+import { open, save } from "@tauri-apps/plugin-dialog";
 import { useCallback, useEffect, useMemo, useRef, useState } from "react";
 import {
+  cancelDeviceLogin,
+  checkDataIntegrity,
+  completeDeviceLogin,
   createAccountFromAuthFile,
+  bulkImportAuthFiles,
+  checkAccountsHealth,
+  getHealthCheckConfig,
+  getSwitchGuardPolicy,
+  setSwitchGuardPolicy,
+  getFailoverConfig,
+  listAccountPools,
+  saveAccountPool,
+  deleteAccountPool,
+  previewPoolRotation,
+  switchToNextInPool,
+  setFailoverConfig,
+  runFailoverCheck,
+  setHealthCheckConfig,
+  importAccountsFrom,
+  listAccountImporters,
   createAccountFromLogin,
+  reauthenticateAccountFromFile,
+  reauthenticateAccountFromLogin,
+  createControlToken,
   deleteAccount,
+  deleteProjectBinding,
+  getAccountHomes,
   getCodexCliStatus,
+  getControlApiStatus,
+  getProjectAutoSwitch,
   getQuotaDashboard,
   getRuntimeDiagnostics,
+  getSnapshotRetention,
+  getTrashRetentionDays,
   getVaultStatus,
   initVault,
+  launchCodexWithAccount,
   listAccounts,
-  listSwitchHistory,
+  listControlTokens,
+  listProjectBindings,
+  querySwitchHistory,
+  getUsageAnalytics,
+  exportUsageCsv,
+  exportVault,
+  importVault,
+  listTrashedAccounts,
   lockVault,
+  pruneSnapshots,
+  purgeAccount,
   refreshQuota,
+  resolveProjectAccount,
+  restoreAccount,
+  revokeControlToken,
   rollbackToHistory,
+  setAccountHomesConfig,
+  setCodexBinaryPath,
+  setControlApiEnabled,
+  setProjectAutoSwitch,
+  setProjectBinding,
+  setSnapshotRetention,
+  setTrashRetentionDays,
+  startDeviceLogin,
   switchAccount,
+  syncAccountHomes,
   unlockVault,
   updateAccountMeta,
 } from "./api";
 import type {
   Account,
   AccountDraft,
+  AccountHomesStatus,
   CodexCliStatus,
+  ControlApiStatus,
+  ControlToken,
+  DeviceLoginChallenge,
+  IntegrityReport,
+  ProjectAccountResolution,
+  ProjectAutoSwitchEvent,
+  ProjectBinding,
   QuotaDashboardItem,
   QuotaSnapshot,
   RuntimeDiagnostics,
   SimpleStatus,
+  SnapshotPruneReport,
+  SnapshotRetentionPolicy,
   SwitchHistory,
+  SwitchHistoryEntry,
+  SwitchHistoryQuery,
+  SwitchHistoryResult,
+  TrashedAccount,
   UiNotice,
+  UsageCsvTable,
+  UsageGranularity,
+  UsageQuery,
+  UsageReport,
+  BulkImportReport,
+  ImportNameSource,
+  ImporterInfo,
+  HealthCheckConfig,
+  GuardAction,
+  SwitchGuardPolicy,
+  FailoverConfig,
+  FailoverEvent,
+  RestartMode,
+  AccountPool,
+  PoolDecision,
+  RotationStrategy,
+  VaultConflictStrategy,
+  VaultImportReport,
 } from "./types";
 import "./App.css";
 
-const HISTORY_LIMIT = 30;
+const HISTORY_PAGE_SIZE = 30;
 const CLI_STATUS_POLL_MS = 6000;
 
-type WorkspaceView = "overview" | "vault" | "accounts" | "quota" | "history" | "diagnostics";
+type WorkspaceView = "overview" | "vault" | "accounts" | "quota" | "history" | "projects" | "diagnostics";
 
 interface NavItem {
   id: WorkspaceView;
@@ -61,6 +144,7 @@ const navGroups: NavGroup[] = [
       { id: "accounts", label: "账号", hint: "登录导入、编辑与切换" },
       { id: "quota", label: "配额", hint: "刷新与状态追踪" },
       { id: "history", label: "历史", hint: "回滚与审计记录" },
+      { id: "projects", label: "项目", hint: "目录与账号绑定" },
     ],
   },
   {
@@ -78,6 +162,7 @@ const viewMeta: Record<WorkspaceView, { title: string; description: string }> =
   accounts: { title: "账号管理", description: "通过登录或认证文件新增账号，并维护标签和元数据。" },
   quota: { title: "配额中心", description: "按账号刷新配额，查看来源、置信度与异常原因。" },
   history: { title: "切换历史", description: "审计每次切换结果，可按记录执行回滚。" },
+  projects: { title: "项目绑定", description: "按目录绑定账号或标签，新启动的 Codex 进程可自动切换。" },
   diagnostics: { title: "运行诊断", description: "检查认证文件、数据库路径和 Codex 进程状态。" },
 };
 
@@ -88,6 +173,40 @@ const quotaStateText: Record<string, string> = {
   unknown: "状态未知",
 };
 
+const healthStatusText: Record<string, string> = {
+  valid: "凭据有效",
+  expiring: "即将过期",
+  refreshable: "待续期",
+  expired: "已过期",
+  revoked: "已吊销",
+  malformed: "数据损坏",
+};
+
+const guardLabels: Record<keyof SwitchGuardPolicy, string> = {
+  token_expiry: "令牌过期",
+  health: "凭据健康",
+  quota: "配额耗尽",
+  running_processes: "运行中的 Codex 进程",
+};
+
+const guardActionText: Record<GuardAction, string> = {
+  block: "阻止切换",
+  warn: "仅提示",
+  ignore: "不检查",
+};
+
+const restartModeText: Record<RestartMode, string> = {
+  never: "不重启，下次启动生效",
+  force: "重启运行中的 Codex 进程（会中断进行中的会话）",
+};
+
+const rotationStrategyText: Record<RotationStrategy, string> = {
+  round_robin: "轮询",
+  most_remaining: "剩余配额最多",
+  earliest_reset: "最早重置",
+  least_recently_used: "最久未使用",
+};
+
 const historyResultText: Record<string, string> = {
   success: "切换成功",
   failed: "切换失败",
@@ -128,6 +247,12 @@ function quotaStateClassName(state: string): string {
   return "state-unknown";
 }
 
+function healthStatusClassName(status: string): string {
+  if (status === "valid") return "state-available";
+  if (status === "expiring" || status === "refreshable") return "state-near-limit";
+  return "state-exhausted";
+}
+
 function historyResultClassName(result: string): string {
   if (result === "success") return "history-success";
   if (result === "failed") return "history-failed";
@@ -135,6 +260,72 @@ function historyResultClassName(result: string): string {
   return "history-unknown";
 }
 
+interface HistoryFilter {
+  accountId: string;
+  result: SwitchHistoryResult | "";
+  since: string;
+  until: string;
+}
+
+const EMPTY_HISTORY_FILTER: HistoryFilter = { accountId: "", result: "", since: "", until: "" };
+
+// 日期输入按本地时区的整天换算为 RFC 3339，截止日期取次日零点（不含）。
+function localDayStart(value: string, offsetDays = 0): string {
+  const date = new Date(`${value}T00:00:00`);
+  date.setDate(date.getDate() + offsetDays);
+  return date.toISOString();
+}
+
+function buildHistoryQuery(filter: HistoryFilter, cursor?: string | null): SwitchHistoryQuery {
+  return {
+    account_id: filter.accountId || null,
+    result: filter.result || null,
+    since: filter.since ? localDayStart(filter.since) : null,
+    until: filter.until ? localDayStart(filter.until, 1) : null,
+    cursor: cursor ?? null,
+    limit: HISTORY_PAGE_SIZE,
+  };
+}
+
+const bulkImportActionText: Record<string, string> = {
+  imported: "已导入",
+  skipped: "已跳过",
+  failed: "失败",
+};
+
+const vaultImportActionText: Record<string, string> = {
+  imported: "已导入",
+  overwritten: "已覆盖",
+  renamed: "已重命名",
+  skipped: "已跳过",
+  failed: "失败",
+};
+
+const usageCsvTableText: Record<UsageCsvTable, string> = {
+  active_time: "生效时长",
+  accounts: "账号汇总",
+  pairs: "常见切换",
+};
+
+function buildUsageQuery(granularity: UsageGranularity, since: string, until: string): UsageQuery {
+  return {
+    granularity,
+    since: since ? localDayStart(since) : null,
+    until: until ? localDayStart(until, 1) : null,
+    utc_offset_minutes: -new Date().getTimezoneOffset(),
+  };
+}
+
+function formatHours(seconds: number): string {
+  return `${(seconds / 3600).toFixed(1)} 小时`;
+}
+
+function historyAccountLabel(id: string | null, name: string | null, trashed: boolean, missing: string): string {
+  if (!id) return missing;
+  if (!name) return `未知账号(${id.slice(0, 8)})`;
+  return trashed ? `${name}（回收站）` : name;
+}
+
 function buildDraft(account: Account): AccountDraft {
   return { name: account.name, tagsText: account.tags.join(", ") };
 }
@@ -144,8 +335,39 @@ function App() {
   const [vaultStatus, setVaultStatus] = useState<SimpleStatus | null>(null);
   const [accounts, setAccounts] = useState<Account[]>([]);
   const [quotaDashboard, setQuotaDashboard] = useState<QuotaDashboardItem[]>([]);
-  const [historyItems, setHistoryItems] = useState<SwitchHistory[]>([]);
+  const [historyItems, setHistoryItems] = useState<SwitchHistoryEntry[]>([]);
+  const [historyTotal, setHistoryTotal] = useState(0);
+  const [historyCursor, setHistoryCursor] = useState<string | null>(null);
+  const [historyFilter, setHistoryFilter] = useState<HistoryFilter>(EMPTY_HISTORY_FILTER);
+  const [projectBindings, setProjectBindings] = useState<ProjectBinding[]>([]);
+  const [accountPools, setAccountPools] = useState<AccountPool[]>([]);
+  const [poolName, setPoolName] = useState("");
+  const [poolTag, setPoolTag] = useState("");
+  const [poolAccountIds, setPoolAccountIds] = useState<string[]>([]);
+  const [poolStrategy, setPoolStrategy] = useState<RotationStrategy>("round_robin");
+  const [poolDecision, setPoolDecision] = useState<PoolDecision | null>(null);
+  const [projectAutoSwitch, setProjectAutoSwitchState] = useState(false);
   const [diagnostics, setDiagnostics] = useState<RuntimeDiagnostics | null>(null);
+  const [integrityReport, setIntegrityReport] = useState<IntegrityReport | null>(null);
+  const [accountHomes, setAccountHomes] = useState<AccountHomesStatus | null>(null);
+  const [trashedAccounts, setTrashedAccounts] = useState<TrashedAccount[]>([]);
+  const [trashRetentionDays, setTrashRetentionDaysState] = useState(30);
+  const [snapshotRetention, setSnapshotRetentionState] = useState<SnapshotRetentionPolicy>({ keep_count: 20, max_age_days: 30, protect_recent: 5 });
+  const [pruneReport, setPruneReport] = useState<SnapshotPruneReport | null>(null);
+  const [usageGranularity, setUsageGranularity] = useState<UsageGranularity>("day");
+  const [usageSince, setUsageSince] = useState("");
+  const [usageUntil, setUsageUntil] = useState("");
+  const [usageReport, setUsageReport] = useState<UsageReport | null>(null);
+  const [transferPassphrase, setTransferPassphrase] = useState("");
+  const [exportTags, setExportTags] = useState("");
+  const [exportAccountIds, setExportAccountIds] = useState<string[]>([]);
+  const [importStrategy, setImportStrategy] = useState<VaultConflictStrategy>("skip");
+  const [importReport, setImportReport] = useState<VaultImportReport | null>(null);
+  const [launchCwd, setLaunchCwd] = useState("");
+  const [controlStatus, setControlStatus] = useState<ControlApiStatus | null>(null);
+  const [controlTokens, setControlTokens] = useState<ControlToken[]>([]);
+  const [controlTokenName, setControlTokenName] = useState("");
+  const [createdControlSecret, setCreatedControlSecret] = useState<string | null>(null);
   const [codexCliStatus, setCodexCliStatus] = useState<CodexCliStatus | null>(null);
   const [notice, setNotice] = useState<UiNotice | null>(null);
 
@@ -153,6 +375,24 @@ function App() {
   const [newAccountName, setNewAccountName] = useState("");
   const [newAccountTags, setNewAccountTags] = useState("");
   const [authFilePath, setAuthFilePath] = useState("");
+  const [bulkSource, setBulkSource] = useState("");
+  const [bulkTags, setBulkTags] = useState("");
+  const [bulkNameSource, setBulkNameSource] = useState<ImportNameSource>("filename");
+  // files 为按文件批量导入，auto 自动识别其他工具的存储格式，其余为指定导入器
+  const [bulkMode, setBulkMode] = useState("files");
+  const [importerOptions, setImporterOptions] = useState<ImporterInfo[]>([]);
+  const [healthConfig, setHealthConfig] = useState<HealthCheckConfig>({ remote_enabled: false, endpoint: "" });
+  const [guardPolicy, setGuardPolicy] = useState<SwitchGuardPolicy | null>(null);
+  const [failoverConfig, setFailoverConfigDraft] = useState<FailoverConfig | null>(null);
+  const [failoverThreshold, setFailoverThreshold] = useState("");
+  const [failoverTags, setFailoverTags] = useState("");
+  const [bulkReport, setBulkReport] = useState<BulkImportReport | null>(null);
+  const [deviceChallenge, setDeviceChallenge] = useState<DeviceLoginChallenge | null>(null);
+  const [codexBinaryPath, setCodexBinaryPathInput] = useState("");
+  const [bindingPath, setBindingPath] = useState("");
+  const [bindingAccountId, setBindingAccountId] = useState("");
+  const [bindingTag, setBindingTag] = useState("");
+  const [projectResolution, setProjectResolution] = useState<ProjectAccountResolution | null>(null);
   const [selectedAccountId, setSelectedAccountId] = useState("");
   const [forceRestart, setForceRestart] = useState(true);
   const [accountDrafts, setAccountDrafts] = useState<Record<string, AccountDraft>>({});
@@ -160,6 +400,7 @@ function App() {
   const [actionLoading, setActionLoading] = useState<Record<string, boolean>>({});
 
   const latestCliRef = useRef<CodexCliStatus | null>(null);
+  const historyFilterRef = useRef<HistoryFilter>(EMPTY_HISTORY_FILTER);
 
   const vaultUnlocked = vaultStatus?.ok ?? false;
   const codexCliRunning = Boolean(codexCliStatus?.is_running);
@@ -177,9 +418,9 @@ function App() {
       accountCount: accounts.length,
       availableQuotaCount: quotaDashboard.filter((item) => item.snapshot?.quota_state === "available").length,
       warningQuotaCount: quotaDashboard.filter((item) => ["near_limit", "exhausted"].includes(item.snapshot?.quota_state ?? "")).length,
-      historyCount: historyItems.length,
+      historyCount: historyTotal,
     }),
-    [accounts.length, historyItems.length, quotaDashboard],
+    [accounts.length, historyTotal, quotaDashboard],
   );
 
   const resolveAccountName = useCallback(
@@ -215,18 +456,38 @@ function App() {
   const refreshAllData = useCallback(async (showLoading = false): Promise<boolean> => {
     if (showLoading) setLoadingPage(true);
     try {
-      const [status, diagnosticsData, accountList, dashboardData, historyData] = await Promise.all([
+      const [status, diagnosticsData, accountList, dashboardData, historyData, bindingList, autoSwitch, controlData, tokenList, homesData, trashList, retentionDays, snapshotPolicy, poolList] = await Promise.all([
         getVaultStatus(),
         getRuntimeDiagnostics(),
         listAccounts(),
         getQuotaDashboard(),
-        listSwitchHistory(HISTORY_LIMIT),
+        querySwitchHistory(buildHistoryQuery(historyFilterRef.current)),
+        listProjectBindings(),
+        getProjectAutoSwitch(),
+        getControlApiStatus(),
+        listControlTokens(),
+        getAccountHomes(),
+        listTrashedAccounts(),
+        getTrashRetentionDays(),
+        getSnapshotRetention(),
+        listAccountPools(),
       ]);
       setVaultStatus(status);
       setDiagnostics(diagnosticsData);
       setAccounts(accountList);
       setQuotaDashboard(dashboardData);
-      setHistoryItems(historyData);
+      setHistoryItems(historyData.items);
+      setHistoryTotal(historyData.total);
+      setHistoryCursor(historyData.next_cursor);
+      setProjectBindings(bindingList);
+      setProjectAutoSwitchState(autoSwitch);
+      setControlStatus(controlData);
+      setControlTokens(tokenList);
+      setAccountHomes(homesData);
+      setTrashedAccounts(trashList);
+      setTrashRetentionDaysState(retentionDays);
+      setSnapshotRetentionState(snapshotPolicy);
+      setAccountPools(poolList);
       return true;
     } catch (error) {
       setNotice({ kind: "error", text: `加载数据失败：${normalizeError(error)}` });
@@ -271,6 +532,19 @@ function App() {
     void refreshCodexCliStatus(false);
   }, [refreshAllData, refreshCodexCliStatus]);
 
+  useEffect(() => {
+    listAccountImporters().then(setImporterOptions).catch(() => setImporterOptions([]));
+    getHealthCheckConfig().then(setHealthConfig).catch(() => undefined);
+    getSwitchGuardPolicy().then(setGuardPolicy).catch(() => setGuardPolicy(null));
+    getFailoverConfig()
+      .then((config) => {
+        setFailoverConfigDraft(config);
+        setFailoverThreshold(config.threshold_percent === null ? "" : String(config.threshold_percent));
+        setFailoverTags(config.preferred_tags.join(", "));
+      })
+      .catch(() => setFailoverConfigDraft(null));
+  }, []);
+
   useEffect(() => {
     const timer = window.setInterval(() => {
       void refreshCodexCliStatus();
@@ -278,6 +552,30 @@ function App() {
     return () => window.clearInterval(timer);
   }, [refreshCodexCliStatus]);
 
+  useEffect(() => {
+    const unlisten = listen<ProjectAutoSwitchEvent>("project-auto-switch", (event) => {
+      const { resolution, result, restarted } = event.payload;
+      setNotice({
+        kind: result.success ? "success" : "error",
+        text: `检测到 Codex 在 ${event.payload.cwd} 启动，已按项目绑定切换到 ${resolution.account?.name ?? "--"}：${result.message}${restarted ? "；已重启该 Codex 进程使新账号生效" : ""}`,
+      });
+      void refreshAllData();
+    });
+    return () => {
+      void unlisten.then((dispose) => dispose());
+    };
+  }, [refreshAllData]);
+
+  useEffect(() => {
+    const unlisten = listen<FailoverEvent>("auto-failover", (event) => {
+      setNotice({ kind: event.payload.switched_to ? "success" : "error", text: event.payload.message });
+      void refreshAllData();
+    });
+    return () => {
+      void unlisten.then((dispose) => dispose());
+    };
+  }, [refreshAllData]);
+
   useEffect(() => {
     setAccountDrafts((previous) => {
       const next: Record<string, AccountDraft> = {};
@@ -353,6 +651,175 @@ function App() {
     setNotice({ kind: "success", text: "运行诊断已刷新" });
   };
 
+  const handleSaveCodexBinaryPath = async (path: string | null) => {
+    const result = await runAction("save-codex-binary", () => setCodexBinaryPath(path));
+    if (!result) return;
+    setNotice({ kind: result.ok ? "success" : "info", text: result.message });
+    if (!path) setCodexBinaryPathInput("");
+    await handleRefreshDiagnostics();
+  };
+
+  const handleCheckIntegrity = async (repair: boolean) => {
+    const report = await runAction(repair ? "repair-integrity" : "check-integrity", () => checkDataIntegrity(repair));
+    if (!report) return;
+    setIntegrityReport(report);
+    if (repair) {
+      setNotice({ kind: "success", text: "数据完整性问题已修复" });
+      await refreshAllData();
+    }
+  };
+
+  const handleChooseBindingDir = async () => {
+    try {
+      const selected = await open({ directory: true, multiple: false });
+      const pickedPath = Array.isArray(selected) ? selected[0] : selected;
+      if (!pickedPath) return;
+      setBindingPath(pickedPath);
+    } catch (error) {
+      setNotice({ kind: "error", text: `选择项目目录失败：${normalizeError(error)}` });
+    }
+  };
+
+  const handleSaveProjectBinding = async () => {
+    const result = await runAction("save-project-binding", () =>
+      setProjectBinding(bindingPath.trim(), bindingAccountId || null, bindingAccountId ? null : bindingTag.trim() || null),
+    );
+    if (!result) return;
+    setNotice({ kind: "success", text: `项目绑定已保存：${result.path}` });
+    setBindingPath("");
+    setBindingTag("");
+    await refreshAllData();
+  };
+
+  const handleDeleteProjectBinding = async (binding: ProjectBinding) => {
+    const result = await runAction(`delete-binding-${binding.id}`, () => deleteProjectBinding(binding.id));
+    if (!result) return;
+    setNotice({ kind: result.ok ? "success" : "info", text: result.message });
+    await refreshAllData();
+  };
+
+  const handleSaveAccountPool = async () => {
+    const result = await runAction("save-account-pool", () =>
+      saveAccountPool({ name: poolName.trim(), tag: poolTag.trim() || null, account_ids: poolAccountIds, strategy: poolStrategy }),
+    );
+    if (!result) return;
+    setNotice({ kind: "success", text: `账号池已保存：${result.name}` });
+    setPoolName("");
+    setPoolTag("");
+    setPoolAccountIds([]);
+    await refreshAllData();
+  };
+
+  const handleChangePoolStrategy = async (pool: AccountPool, strategy: RotationStrategy) => {
+    const result = await runAction(`pool-strategy-${pool.id}`, () =>
+      saveAccountPool({ id: pool.id, name: pool.name, tag: pool.tag, account_ids: pool.account_ids, strategy }),
+    );
+    if (!result) return;
+    setAccountPools((prev) => prev.map((item) => (item.id === result.id ? result : item)));
+    setPoolDecision(null);
+  };
+
+  const handleDeleteAccountPool = async (pool: AccountPool) => {
+    const result = await runAction(`delete-pool-${pool.id}`, () => deleteAccountPool(pool.id));
+    if (!result) return;
+    setNotice({ kind: result.ok ? "success" : "info", text: result.message });
+    if (poolDecision?.pool_id === pool.id) setPoolDecision(null);
+    await refreshAllData();
+  };
+
+  const handlePreviewPool = async (pool: AccountPool) => {
+    const decision = await runAction(`preview-pool-${pool.id}`, () => previewPoolRotation(pool.id));
+    if (!decision) return;
+    setPoolDecision(decision);
+  };
+
+  const handleSwitchNextInPool = async (pool: AccountPool) => {
+    if (!vaultUnlocked) {
+      setNotice({ kind: "error", text: "请先解锁保险库，再切换账号" });
+      return;
+    }
+    const actionKey = `next-pool-${pool.id}`;
+    let outcome = await runAction(actionKey, () => switchToNextInPool(pool.id, forceRestart));
+    if (!outcome) return;
+    if (outcome.result.blocked) {
+      const confirmed = window.confirm(`${outcome.result.message}\n\n仍然切换到「${outcome.decision.account_name}」吗？`);
+      if (!confirmed) {
+        setPoolDecision(outcome.decision);
+        setNotice({ kind: "info", text: `已取消切换：${outcome.decision.account_name}：${outcome.result.message}` });
+        return;
+      }
+      outcome = await runAction(actionKey, () => switchToNextInPool(pool.id, forceRestart, true));
+      if (!outcome) return;
+    }
+    setPoolDecision(outcome.decision);
+    setNotice({
+      kind: outcome.result.success ? "success" : "error",
+      text: `${outcome.decision.explanation}。${outcome.result.message}`,
+    });
+    await refreshAllData();
+    await refreshCodexCliStatus(false);
+  };
+
+  const handleResolveProject = async () => {
+    const result = await runAction("resolve-project", () => resolveProjectAccount(bindingPath.trim()));
+    if (!result) return;
+    setProjectResolution(result);
+  };
+
+  const handleToggleProjectAutoSwitch = async (enabled: boolean) => {
+    const result = await runAction("toggle-project-auto-switch", () => setProjectAutoSwitch(enabled));
+    if (!result) return;
+    setProjectAutoSwitchState(enabled);
+    setNotice({ kind: result.ok ? "success" : "info", text: result.message });
+  };
+
+  const handleSaveAccountHomesConfig = async (enabled: boolean, shareConfig: boolean) => {
+    const result = await runAction("account-homes-config", () => setAccountHomesConfig(enabled, shareConfig));
+    if (!result) return;
+    setAccountHomes(result);
+    setNotice({ kind: "success", text: enabled ? "账号独立目录已启用" : "账号独立目录已关闭并清理" });
+  };
+
+  const handleSyncAccountHomes = async () => {
+    const result = await runAction("sync-account-homes", () => syncAccountHomes());
+    if (!result) return;
+    setNotice({ kind: result.ok ? "success" : "info", text: result.message });
+    await refreshAllData();
+  };
+
+  const handleLaunchCodex = async (accountId: string) => {
+    const result = await runAction(`launch-codex-${accountId}`, () => launchCodexWithAccount(accountId, launchCwd.trim() || null));
+    if (!result) return;
+    setNotice({ kind: result.ok ? "success" : "info", text: result.message });
+    await refreshAllData();
+  };
+
+  const handleToggleControlApi = async (enabled: boolean) => {
+    const result = await runAction("toggle-control-api", () => setControlApiEnabled(enabled));
+    if (!result) return;
+    setControlStatus(result);
+    setNotice({
+      kind: result.error ? "error" : "success",
+      text: result.error ?? (result.running ? `本地控制接口已启动：${result.endpoint}` : "本地控制接口已关闭"),
+    });
+  };
+
+  const handleCreateControlToken = async () => {
+    const result = await runAction("create-control-token", () => createControlToken(controlTokenName.trim()));
+    if (!result) return;
+    setCreatedControlSecret(result.secret);
+    setControlTokenName("");
+    setNotice({ kind: "success", text: `已为 ${result.token.name} 创建令牌，请立即复制保存` });
+    await refreshAllData();
+  };
+
+  const handleRevokeControlToken = async (token: ControlToken) => {
+    const result = await runAction(`revoke-control-token-${token.id}`, () => revokeControlToken(token.id));
+    if (!result) return;
+    setNotice({ kind: result.ok ? "success" : "info", text: result.message });
+    await refreshAllData();
+  };
+
   const handleImportAccountByLogin = async () => {
     if (!vaultUnlocked) {
       setNotice({ kind: "error", text: "请先解锁保险库，再执行登录添加" });
@@ -379,6 +846,43 @@ function App() {
     await refreshCodexCliStatus(false);
   };
 
+  const handleImportAccountByDevice = async () => {
+    if (!vaultUnlocked) {
+      setNotice({ kind: "error", text: "请先解锁保险库，再执行设备码登录" });
+      return;
+    }
+
+    const challenge = await runAction("start-device-login", () => startDeviceLogin());
+    if (!challenge) return;
+    setDeviceChallenge(challenge);
+    setNotice({ kind: "info", text: `设备码登录已启动：请在任意设备打开 ${challenge.verification_uri} 并输入 ${challenge.user_code}。` });
+
+    const result = await runAction(
+      "import-account-device",
+      () => completeDeviceLogin(challenge.session_id, newAccountName.trim(), parseTags(newAccountTags)),
+      {
+        suppressNotice: true,
+        onError: (message) => {
+          setNotice({ kind: "error", text: `设备码登录失败：${message}` });
+        },
+      },
+    );
+    setDeviceChallenge(null);
+    if (!result) return;
+
+    setNotice({ kind: "success", text: `设备码登录成功：账号 ${result.name} 已保存到保险库。` });
+    clearCreateFields();
+    await refreshAllData();
+    await refreshCodexCliStatus(false);
+  };
+
+  const handleCancelDeviceLogin = async () => {
+    if (!deviceChallenge) return;
+    const result = await runAction("cancel-device-login", () => cancelDeviceLogin(deviceChallenge.session_id));
+    if (!result) return;
+    setNotice({ kind: "info", text: result.message });
+  };
+
   const handleImportAccountByFile = async () => {
     if (!vaultUnlocked) {
       setNotice({ kind: "error", text: "请先解锁保险库，再导入认证文件" });
@@ -398,6 +902,33 @@ function App() {
     await refreshCodexCliStatus(false);
   };
 
+  const handleChooseBulkDirectory = async () => {
+    try {
+      const selected = await open({ directory: true, multiple: false });
+      const pickedPath = Array.isArray(selected) ? selected[0] : selected;
+      if (pickedPath) setBulkSource(pickedPath);
+    } catch (error) {
+      setNotice({ kind: "error", text: `选择目录失败：${normalizeError(error)}` });
+    }
+  };
+
+  const handleBulkImport = async () => {
+    const source = bulkSource.trim();
+    const tags = parseTags(bulkTags);
+    const report = await runAction("bulk-import", () =>
+      bulkMode === "files"
+        ? bulkImportAuthFiles(source, tags, bulkNameSource)
+        : importAccountsFrom(bulkMode === "auto" ? null : bulkMode, source, tags),
+    );
+    if (!report) return;
+    setBulkReport(report);
+    setNotice({
+      kind: report.failed > 0 ? "error" : "success",
+      text: `批量导入完成：新增 ${report.imported}，跳过 ${report.skipped}，失败 ${report.failed}`,
+    });
+    await refreshAllData();
+  };
+
   const updateDraftField = (accountId: string, field: keyof AccountDraft, value: string) => {
     setAccountDrafts((previous) => ({
       ...previous,
@@ -422,8 +953,42 @@ function App() {
     await refreshAllData();
   };
 
+  const handleReauthByLogin = async (account: Account) => {
+    setNotice({ kind: "info", text: `正在为「${account.name}」重新登录：请在浏览器中使用同一账号完成授权。` });
+    const result = await runAction(`reauth-${account.id}`, () => reauthenticateAccountFromLogin(account.id), {
+      suppressNotice: true,
+      onError: (message) => {
+        setNotice({ kind: "error", text: `重新登录失败：${message}` });
+      },
+    });
+    if (!result) return;
+    setNotice({ kind: "success", text: `已更新「${result.name}」的登录凭据，标签与历史保持不变。` });
+    await refreshAllData();
+  };
+
+  const handleReauthByFile = async (account: Account) => {
+    let pickedPath: string | null;
+    try {
+      const selected = await open({
+        directory: false,
+        multiple: false,
+        filters: [{ name: "JSON 认证文件", extensions: ["json"] }],
+      });
+      pickedPath = Array.isArray(selected) ? selected[0] : selected;
+    } catch (error) {
+      setNotice({ kind: "error", text: `选择认证文件失败：${normalizeError(error)}` });
+      return;
+    }
+    if (!pickedPath) return;
+    const path = pickedPath;
+    const result = await runAction(`reauth-${account.id}`, () => reauthenticateAccountFromFile(account.id, path));
+    if (!result) return;
+    setNotice({ kind: "success", text: `已用 ${path} 更新「${result.name}」的登录凭据。` });
+    await refreshAllData();
+  };
+
   const handleDeleteAccount = async (account: Account) => {
-    const confirmed = window.confirm(`确认删除账号「${account.name}」吗？该操作不可恢复。`);
+    const confirmed = window.confirm(`确认删除账号「${account.name}」吗？账号将移入回收站，${trashRetentionDays} 天后永久删除。`);
     if (!confirmed) return;
     const result = await runAction(`delete-${account.id}`, () => deleteAccount(account.id));
     if (!result) return;
@@ -431,19 +996,101 @@ function App() {
     await refreshAllData();
   };
 
+  const handleExportVault = async () => {
+    if (transferPassphrase.trim().length < 8) {
+      setNotice({ kind: "error", text: "导出口令至少需要 8 位" });
+      return;
+    }
+    let path: string | null;
+    try {
+      path = await save({
+        defaultPath: "codex-switch-accounts.csvault",
+        filters: [{ name: "Codex Switch 导出文件", extensions: ["csvault"] }],
+      });
+    } catch (error) {
+      setNotice({ kind: "error", text: `选择导出位置失败：${normalizeError(error)}` });
+      return;
+    }
+    if (!path) return;
+    const target = path;
+    const summary = await runAction("export-vault", () =>
+      exportVault(target, transferPassphrase, { account_ids: exportAccountIds, tags: parseTags(exportTags) }),
+    );
+    if (!summary) return;
+    setNotice({ kind: "success", text: `已导出 ${summary.account_count} 个账号到 ${summary.path}` });
+  };
+
+  const handleImportVault = async () => {
+    let selected: string | string[] | null;
+    try {
+      selected = await open({
+        directory: false,
+        multiple: false,
+        filters: [{ name: "Codex Switch 导出文件", extensions: ["csvault", "json"] }],
+      });
+    } catch (error) {
+      setNotice({ kind: "error", text: `选择导入文件失败：${normalizeError(error)}` });
+      return;
+    }
+    const pickedPath = Array.isArray(selected) ? selected[0] : selected;
+    if (!pickedPath) return;
+    const report = await runAction("import-vault", () => importVault(pickedPath, transferPassphrase, importStrategy));
+    if (!report) return;
+    setImportReport(report);
+    setNotice({
+      kind: report.failed > 0 ? "error" : "success",
+      text: `导入完成：新增 ${report.imported}，覆盖 ${report.overwritten}，重命名 ${report.renamed}，跳过 ${report.skipped}，失败 ${report.failed}`,
+    });
+    await refreshAllData();
+  };
+
+  const handleRestoreAccount = async (item: TrashedAccount) => {
+    const restored = await runAction(`restore-${item.account.id}`, () => restoreAccount(item.account.id));
+    if (!restored) return;
+    setNotice({ kind: "success", text: `已恢复账号：${restored.name}` });
+    await refreshAllData();
+  };
+
+  const handlePurgeAccount = async (item: TrashedAccount) => {
+    if (!vaultUnlocked) {
+      setNotice({ kind: "error", text: "请先解锁保险库，再永久删除账号" });
+      return;
+    }
+    const confirmed = window.confirm(`确认永久删除账号「${item.account.name}」吗？该操作不可恢复。`);
+    if (!confirmed) return;
+    const result = await runAction(`purge-${item.account.id}`, () => purgeAccount(item.account.id));
+    if (!result) return;
+    setNotice({ kind: result.ok ? "success" : "info", text: result.message });
+    await refreshAllData();
+  };
+
+  const handleSaveTrashRetention = async () => {
+    const result = await runAction("save-trash-retention", () => setTrashRetentionDays(trashRetentionDays));
+    if (!result) return;
+    setNotice({ kind: result.ok ? "success" : "info", text: result.message });
+    await refreshAllData();
+  };
+
   const executeSwitch = async (accountId: string, actionKey: string) => {
     if (!vaultUnlocked) {
       setNotice({ kind: "error", text: "请先解锁保险库，再切换账号" });
       return;
     }
     const accountName = resolveAccountName(accountId);
-    const result = await runAction(actionKey, () => switchAccount(accountId, forceRestart), {
-      suppressNotice: true,
-      onError: (message) => {
-        setNotice({ kind: "error", text: `账号切换失败：${accountName}：${message}` });
-      },
-    });
+    const onError = (message: string) => {
+      setNotice({ kind: "error", text: `账号切换失败：${accountName}：${message}` });
+    };
+    let result = await runAction(actionKey, () => switchAccount(accountId, forceRestart), { suppressNotice: true, onError });
     if (!result) return;
+    if (result.blocked) {
+      const confirmed = window.confirm(`${result.message}\n\n仍然切换到「${accountName}」吗？`);
+      if (!confirmed) {
+        setNotice({ kind: "info", text: `已取消切换：${accountName}：${result.message}` });
+        return;
+      }
+      result = await runAction(actionKey, () => switchAccount(accountId, forceRestart, true), { suppressNotice: true, onError });
+      if (!result) return;
+    }
 
     setNotice({
       kind: result.success ? "success" : "error",
@@ -498,6 +1145,61 @@ function App() {
     await executeRefreshQuota(undefined, "refresh-quota-all");
   };
 
+  const handleCheckHealth = async () => {
+    const results = await runAction("check-health", () => checkAccountsHealth(undefined, healthConfig.remote_enabled));
+    if (!results) return;
+    const unhealthy = results.filter((item) => item.status !== "valid").length;
+    setNotice({
+      kind: unhealthy > 0 ? "info" : "success",
+      text: `已检查 ${results.length} 个账号，${unhealthy > 0 ? `${unhealthy} 个需要关注` : "凭据均有效"}。`,
+    });
+    await refreshAllData();
+  };
+
+  const handleSaveGuardPolicy = async (next: SwitchGuardPolicy) => {
+    setGuardPolicy(next);
+    const result = await runAction("guard-policy", () => setSwitchGuardPolicy(next));
+    if (!result) return;
+    setNotice({ kind: "success", text: result.message });
+  };
+
+  const handleSaveFailoverConfig = async () => {
+    if (!failoverConfig) return;
+    const threshold = failoverThreshold.trim();
+    const next: FailoverConfig = {
+      ...failoverConfig,
+      threshold_percent: threshold ? Number(threshold) : null,
+      preferred_tags: parseTags(failoverTags),
+    };
+    if (next.threshold_percent !== null && Number.isNaN(next.threshold_percent)) {
+      setNotice({ kind: "error", text: "切换阈值必须是数字。" });
+      return;
+    }
+    const result = await runAction("failover-config", () => setFailoverConfig(next));
+    if (!result) return;
+    setFailoverConfigDraft(next);
+    setNotice({ kind: "success", text: result.message });
+  };
+
+  const handleRunFailoverCheck = async () => {
+    // runAction 出错时也返回 null，包一层以区分“未触发”
+    const outcome = await runAction("failover-check", async () => ({ event: await runFailoverCheck() }));
+    if (!outcome) return;
+    const { event } = outcome;
+    if (!event) {
+      setNotice({ kind: "info", text: "当前账号未达到自动切换条件。" });
+      return;
+    }
+    setNotice({ kind: event.switched_to ? "success" : "error", text: event.message });
+    await refreshAllData();
+  };
+
+  const handleSaveHealthConfig = async () => {
+    const result = await runAction("health-config", () => setHealthCheckConfig(healthConfig));
+    if (!result) return;
+    setNotice({ kind: "success", text: result.message });
+  };
+
   const handleRefreshQuotaFromRow = async (accountId: string) => {
     await executeRefreshQuota(accountId, `refresh-quota-${accountId}`);
   };
@@ -515,13 +1217,73 @@ function App() {
     await refreshAllData();
   };
 
-  const handleReloadAll = async () => {
-    const ok = await refreshAllData(true);
-    await refreshCodexCliStatus(false);
-    if (ok) setNotice({ kind: "success", text: "页面数据已刷新" });
+  const handleApplyHistoryFilter = async (filter: HistoryFilter) => {
+    const page = await runAction("query-history", () => querySwitchHistory(buildHistoryQuery(filter)));
+    if (!page) return;
+    historyFilterRef.current = filter;
+    setHistoryFilter(filter);
+    setHistoryItems(page.items);
+    setHistoryTotal(page.total);
+    setHistoryCursor(page.next_cursor);
   };
 
-  const overviewView = (
+  const handleLoadMoreHistory = async () => {
+    if (!historyCursor) return;
+    const page = await runAction("load-more-history", () => querySwitchHistory(buildHistoryQuery(historyFilterRef.current, historyCursor)));
+    if (!page) return;
+    setHistoryItems((prev) => [...prev, ...page.items]);
+    setHistoryTotal(page.total);
+    setHistoryCursor(page.next_cursor);
+  };
+
+  const handleLoadUsage = async () => {
+    const report = await runAction("usage-analytics", () => getUsageAnalytics(buildUsageQuery(usageGranularity, usageSince, usageUntil)));
+    if (report) setUsageReport(report);
+  };
+
+  const handleExportUsage = async (table: UsageCsvTable) => {
+    let path: string | null;
+    try {
+      path = await save({
+        defaultPath: `codex-switch-${table}.csv`,
+        filters: [{ name: "CSV", extensions: ["csv"] }],
+      });
+    } catch (error) {
+      setNotice({ kind: "error", text: `选择导出位置失败：${normalizeError(error)}` });
+      return;
+    }
+    if (!path) return;
+    const target = path;
+    const result = await runAction(`export-usage-${table}`, () => exportUsageCsv(buildUsageQuery(usageGranularity, usageSince, usageUntil), table, target));
+    if (!result) return;
+    setNotice({ kind: result.ok ? "success" : "error", text: result.message });
+  };
+
+  const handleSaveSnapshotRetention = async () => {
+    const result = await runAction("save-snapshot-retention", () => setSnapshotRetention(snapshotRetention));
+    if (!result) return;
+    setNotice({ kind: result.ok ? "success" : "info", text: result.message });
+    await refreshAllData();
+  };
+
+  const handlePruneSnapshots = async (dryRun: boolean) => {
+    if (!dryRun && !window.confirm("确认按保留策略清理快照吗？被清理的历史记录将无法回滚。")) return;
+    const report = await runAction(dryRun ? "preview-prune" : "prune-snapshots", () => pruneSnapshots(dryRun));
+    if (!report) return;
+    setPruneReport(report);
+    if (!dryRun) {
+      setNotice({ kind: "success", text: `已清理 ${report.pruned.length} 个快照` });
+      await refreshAllData();
+    }
+  };
+
+  const handleReloadAll = async () => {
+    const ok = await refreshAllData(true);
+    await refreshCodexCliStatus(false);
+    if (ok) setNotice({ kind: "success", text: "页面数据已刷新" });
+  };
+
+  const overviewView = (
     <div className="view-stack">
       <section className="view-card">
         <div className="card-head">
@@ -607,7 +1369,7 @@ function App() {
             {historyItems.slice(0, 6).map((item) => (
               <article key={item.id} className="history-stream-item">
                 <div>
-                  <strong>{resolveAccountName(item.to_account_id)}</strong>
+                  <strong>{historyAccountLabel(item.to_account_id, item.to_account_name, item.to_account_trashed, "已删除账号")}</strong>
                   <p>{formatDateTime(item.created_at)}</p>
                 </div>
                 <span className={`history-pill ${historyResultClassName(item.result)}`}>
@@ -703,6 +1465,14 @@ function App() {
           >
             {isActionLoading("import-account-login") ? "登录处理中..." : "登录并添加"}
           </button>
+          <button
+            type="button"
+            className="btn btn-secondary"
+            onClick={handleImportAccountByDevice}
+            disabled={!vaultUnlocked || isActionLoading("start-device-login") || isActionLoading("import-account-device")}
+          >
+            {isActionLoading("start-device-login") || isActionLoading("import-account-device") ? "等待设备码授权..." : "设备码登录"}
+          </button>
           <button
             type="button"
             className="btn btn-secondary"
@@ -712,6 +1482,78 @@ function App() {
             {isActionLoading("import-account-file") ? "导入中..." : "导入认证文件"}
           </button>
         </div>
+        {deviceChallenge && (
+          <div className="tips-list">
+            <p>验证地址：<code>{deviceChallenge.verification_uri_complete ?? deviceChallenge.verification_uri}</code></p>
+            <p>设备码：<code>{deviceChallenge.user_code}</code>（{deviceChallenge.mode === "cli" ? "Codex CLI" : "原生"}流程，{formatDateTime(deviceChallenge.expires_at)} 前有效）</p>
+            <div className="button-row">
+              <button type="button" className="btn btn-danger btn-small" onClick={handleCancelDeviceLogin} disabled={isActionLoading("cancel-device-login")}>取消设备码登录</button>
+            </div>
+          </div>
+        )}
+      </section>
+
+      <section className="view-card">
+        <div className="card-head"><h3>批量导入</h3></div>
+        <div className="form-grid">
+          <label className="field-label field-span-2">
+            目录或通配符
+            <div className="picker-row">
+              <input type="text" value={bulkSource} onChange={(event) => setBulkSource(event.currentTarget.value)} placeholder="例如：/Users/me/backups、/Users/me/backups/auth-*.json 或 accounts.json" />
+              <button type="button" className="btn btn-secondary" onClick={handleChooseBulkDirectory}>选择目录</button>
+            </div>
+          </label>
+          <label className="field-label">
+            导入方式
+            <select value={bulkMode} onChange={(event) => setBulkMode(event.currentTarget.value)}>
+              <option value="files">按文件批量导入</option>
+              <option value="auto">其他切换工具（自动识别）</option>
+              {importerOptions.map((importer) => (
+                <option key={importer.id} value={importer.id} title={importer.description}>{importer.label}</option>
+              ))}
+            </select>
+          </label>
+          <label className="field-label">
+            账号命名
+            <select disabled={bulkMode !== "files"} value={bulkNameSource} onChange={(event) => setBulkNameSource(event.currentTarget.value as ImportNameSource)}>
+              <option value="filename">按文件名（auth-work.json → work）</option>
+              <option value="email">按登录邮箱</option>
+            </select>
+          </label>
+          <label className="field-label">
+            标签
+            <input type="text" value={bulkTags} onChange={(event) => setBulkTags(event.currentTarget.value)} placeholder="为本次导入的账号统一添加" />
+          </label>
+        </div>
+        <div className="button-row">
+          <button type="button" className="btn btn-secondary" onClick={handleBulkImport} disabled={!vaultUnlocked || !bulkSource.trim() || isActionLoading("bulk-import")}>
+            {isActionLoading("bulk-import") ? "导入中..." : "批量导入"}
+          </button>
+        </div>
+        {bulkReport && (
+          <div className="table-wrap">
+            <table className="data-table">
+              <thead>
+                <tr>
+                  <th>文件</th>
+                  <th>账号</th>
+                  <th>结果</th>
+                  <th>说明</th>
+                </tr>
+              </thead>
+              <tbody>
+                {bulkReport.items.map((item) => (
+                  <tr key={item.path}>
+                    <td><code>{item.path}</code></td>
+                    <td>{item.name ?? "--"}</td>
+                    <td>{bulkImportActionText[item.action] ?? item.action}</td>
+                    <td className="error-cell">{item.message ?? "--"}</td>
+                  </tr>
+                ))}
+              </tbody>
+            </table>
+          </div>
+        )}
       </section>
 
       <section className="view-card">
@@ -756,6 +1598,8 @@ function App() {
                         <button type="button" className="btn btn-secondary btn-small" onClick={() => handleSaveAccountMeta(account.id)} disabled={isActionLoading(`save-${account.id}`)}>保存</button>
                         <button type="button" className="btn btn-primary btn-small" onClick={() => handleSwitchFromRow(account.id)} disabled={!vaultUnlocked || isActionLoading(`switch-${account.id}`)}>切换</button>
                         <button type="button" className="btn btn-secondary btn-small" onClick={() => handleRefreshQuotaFromRow(account.id)} disabled={!vaultUnlocked || isActionLoading(`refresh-quota-${account.id}`)}>刷配额</button>
+                        <button type="button" className="btn btn-secondary btn-small" onClick={() => handleReauthByLogin(account)} disabled={!vaultUnlocked || isActionLoading(`reauth-${account.id}`)} title="凭据失效时使用同一账号重新登录，保留名称、标签与历史">重新登录</button>
+                        <button type="button" className="btn btn-secondary btn-small" onClick={() => handleReauthByFile(account)} disabled={!vaultUnlocked || isActionLoading(`reauth-${account.id}`)}>从文件更新</button>
                         <button type="button" className="btn btn-danger btn-small" onClick={() => handleDeleteAccount(account)} disabled={isActionLoading(`delete-${account.id}`)}>删除</button>
                       </div>
                     </td>
@@ -766,6 +1610,281 @@ function App() {
           </table>
         </div>
       </section>
+
+      <section className="view-card">
+        <div className="card-head">
+          <h3>账号独立目录</h3>
+          <button type="button" className="btn btn-ghost" onClick={handleSyncAccountHomes} disabled={!vaultUnlocked || !accountHomes?.enabled || isActionLoading("sync-account-homes")}>
+            {isActionLoading("sync-account-homes") ? "同步中..." : "立即同步"}
+          </button>
+        </div>
+        <label className="checkbox-label">
+          <input
+            type="checkbox"
+            checked={accountHomes?.enabled ?? false}
+            onChange={(event) => handleSaveAccountHomesConfig(event.currentTarget.checked, accountHomes?.share_config ?? true)}
+            disabled={isActionLoading("account-homes-config")}
+          />
+          为每个账号生成独立 CODEX_HOME，多个终端可同时使用不同账号
+        </label>
+        <label className="checkbox-label">
+          <input
+            type="checkbox"
+            checked={accountHomes?.share_config ?? true}
+            onChange={(event) => handleSaveAccountHomesConfig(accountHomes?.enabled ?? false, event.currentTarget.checked)}
+            disabled={!accountHomes?.enabled || isActionLoading("account-homes-config")}
+          />
+          链接共享 ~/.codex/config.toml
+        </label>
+        <ul className="tips-list">
+          <li>目录位于 <code>{accountHomes?.homes_dir ?? "--"}</code>，解锁时同步，锁定保险库时回写刷新后的令牌并清除。</li>
+          <li>终端中可使用 <code>codex-switch run &lt;账号&gt;</code> 或 <code>codex-use &lt;账号&gt;</code>。</li>
+        </ul>
+        <label className="field-label">
+          启动工作目录（可选）
+          <input type="text" value={launchCwd} onChange={(event) => setLaunchCwd(event.currentTarget.value)} placeholder="留空则使用应用当前目录" />
+        </label>
+        <div className="table-wrap">
+          <table className="data-table">
+            <thead>
+              <tr>
+                <th>账号</th>
+                <th>目录</th>
+                <th>状态</th>
+                <th>操作</th>
+              </tr>
+            </thead>
+            <tbody>
+              {(accountHomes?.homes ?? []).map((home) => (
+                <tr key={home.account_id}>
+                  <td>{home.account_name}</td>
+                  <td><code>{home.path}</code></td>
+                  <td>{home.materialized ? "已生成" : "未生成"}</td>
+                  <td>
+                    <button type="button" className="btn btn-secondary btn-small" onClick={() => handleLaunchCodex(home.account_id)} disabled={!vaultUnlocked || isActionLoading(`launch-codex-${home.account_id}`)}>
+                      启动 Codex
+                    </button>
+                  </td>
+                </tr>
+              ))}
+            </tbody>
+          </table>
+        </div>
+      </section>
+
+      <section className="view-card">
+        <div className="card-head"><h3>导出与导入</h3></div>
+        <div className="form-grid">
+          <label className="field-label">
+            导出口令
+            <input type="password" value={transferPassphrase} onChange={(event) => setTransferPassphrase(event.currentTarget.value)} placeholder="至少 8 位，导入时需输入相同口令" />
+          </label>
+          <label className="field-label">
+            按标签导出
+            <input value={exportTags} onChange={(event) => setExportTags(event.currentTarget.value)} placeholder="留空且未选账号时导出全部" />
+          </label>
+          <label className="field-label">
+            按账号导出
+            <select
+              multiple
+              value={exportAccountIds}
+              onChange={(event) => setExportAccountIds(Array.from(event.currentTarget.selectedOptions, (option) => option.value))}
+            >
+              {accounts.map((account) => (
+                <option key={account.id} value={account.id}>{account.name}</option>
+              ))}
+            </select>
+          </label>
+          <label className="field-label">
+            导入冲突处理
+            <select value={importStrategy} onChange={(event) => setImportStrategy(event.currentTarget.value as VaultConflictStrategy)}>
+              <option value="skip">跳过已存在或同名账号</option>
+              <option value="overwrite">覆盖已存在账号</option>
+              <option value="rename">同名账号自动重命名</option>
+            </select>
+          </label>
+        </div>
+        <div className="button-row">
+          <button type="button" className="btn btn-secondary" onClick={handleExportVault} disabled={!vaultUnlocked || isActionLoading("export-vault")}>导出账号</button>
+          <button type="button" className="btn btn-secondary" onClick={handleImportVault} disabled={!vaultUnlocked || !transferPassphrase || isActionLoading("import-vault")}>导入文件</button>
+        </div>
+        <p className="muted-text">导出文件以导出口令单独加密，与本机主密码无关；按账号指纹合并，已存在的账号不会重复导入。</p>
+        {importReport && importReport.items.length > 0 && (
+          <div className="table-wrap">
+            <table className="data-table">
+              <thead>
+                <tr>
+                  <th>账号</th>
+                  <th>结果</th>
+                  <th>说明</th>
+                </tr>
+              </thead>
+              <tbody>
+                {importReport.items.map((item) => (
+                  <tr key={`${item.auth_fingerprint}-${item.name}`}>
+                    <td>{item.name}</td>
+                    <td>{vaultImportActionText[item.action] ?? item.action}</td>
+                    <td className="error-cell">{item.message ?? "--"}</td>
+                  </tr>
+                ))}
+              </tbody>
+            </table>
+          </div>
+        )}
+      </section>
+
+      <section className="view-card">
+        <div className="card-head"><h3>回收站</h3></div>
+        <div className="form-grid">
+          <label className="field-label">
+            保留天数
+            <input
+              type="number"
+              min={1}
+              max={365}
+              value={trashRetentionDays}
+              onChange={(event) => setTrashRetentionDaysState(Number(event.currentTarget.value) || 1)}
+            />
+          </label>
+        </div>
+        <div className="button-row">
+          <button type="button" className="btn btn-secondary" onClick={handleSaveTrashRetention} disabled={isActionLoading("save-trash-retention")}>
+            保存
+          </button>
+        </div>
+        <p className="muted-text">到期账号在保险库解锁期间由后台自动永久删除；永久删除需先解锁保险库。</p>
+        {trashedAccounts.length === 0 ? (
+          <p className="muted-text">回收站为空</p>
+        ) : (
+          <div className="table-wrap">
+            <table className="data-table">
+              <thead>
+                <tr>
+                  <th>账号</th>
+                  <th>删除时间</th>
+                  <th>到期时间</th>
+                  <th>操作</th>
+                </tr>
+              </thead>
+              <tbody>
+                {trashedAccounts.map((item) => (
+                  <tr key={item.account.id}>
+                    <td>{item.account.name}</td>
+                    <td>{formatDateTime(item.deleted_at)}</td>
+                    <td>{formatDateTime(item.purge_at)}</td>
+                    <td>
+                      <div className="button-row">
+                        <button type="button" className="btn btn-secondary btn-small" onClick={() => handleRestoreAccount(item)} disabled={isActionLoading(`restore-${item.account.id}`)}>恢复</button>
+                        <button type="button" className="btn btn-danger btn-small" onClick={() => handlePurgeAccount(item)} disabled={!vaultUnlocked || isActionLoading(`purge-${item.account.id}`)}>永久删除</button>
+                      </div>
+                    </td>
+                  </tr>
+                ))}
+              </tbody>
+            </table>
+          </div>
+        )}
+      </section>
+
+      <section className="view-card">
+        <div className="card-head"><h3>账号池</h3></div>
+        <p className="muted-text">把账号按标签或手动挑选组成池，切换时按池的策略挑选下一个账号；当前账号、配额已耗尽和凭据异常的账号会被跳过。</p>
+        <div className="form-grid">
+          <label className="field-label">
+            池名称
+            <input type="text" value={poolName} onChange={(event) => setPoolName(event.currentTarget.value)} placeholder="例如：团队账号" />
+          </label>
+          <label className="field-label">
+            按标签纳入
+            <input type="text" value={poolTag} onChange={(event) => setPoolTag(event.currentTarget.value)} placeholder="可留空，仅使用下方勾选的账号" />
+          </label>
+          <label className="field-label">
+            轮换策略
+            <select value={poolStrategy} onChange={(event) => setPoolStrategy(event.currentTarget.value as RotationStrategy)}>
+              {(Object.keys(rotationStrategyText) as RotationStrategy[]).map((strategy) => (
+                <option key={strategy} value={strategy}>{rotationStrategyText[strategy]}</option>
+              ))}
+            </select>
+          </label>
+        </div>
+        <div className="button-row">
+          {accounts.map((account) => (
+            <label className="checkbox-label" key={account.id}>
+              <input
+                type="checkbox"
+                checked={poolAccountIds.includes(account.id)}
+                onChange={(event) => {
+                  const checked = event.currentTarget.checked;
+                  setPoolAccountIds((prev) => (checked ? [...prev, account.id] : prev.filter((id) => id !== account.id)));
+                }}
+              />
+              {account.name}
+            </label>
+          ))}
+        </div>
+        <div className="button-row">
+          <button
+            type="button"
+            className="btn btn-primary"
+            onClick={handleSaveAccountPool}
+            disabled={!poolName.trim() || (!poolTag.trim() && poolAccountIds.length === 0) || isActionLoading("save-account-pool")}
+          >
+            保存账号池
+          </button>
+        </div>
+        {accountPools.length > 0 && (
+          <div className="table-wrap">
+            <table className="data-table">
+              <thead>
+                <tr>
+                  <th>名称</th>
+                  <th>成员</th>
+                  <th>策略</th>
+                  <th>操作</th>
+                </tr>
+              </thead>
+              <tbody>
+                {accountPools.map((pool) => (
+                  <tr key={pool.id}>
+                    <td>{pool.name}</td>
+                    <td>
+                      {[...pool.account_ids.map(resolveAccountName), ...(pool.tag ? [`标签：${pool.tag}`] : [])].join("、")}
+                    </td>
+                    <td>
+                      <select
+                        value={pool.strategy}
+                        onChange={(event) => void handleChangePoolStrategy(pool, event.currentTarget.value as RotationStrategy)}
+                        disabled={isActionLoading(`pool-strategy-${pool.id}`)}
+                      >
+                        {(Object.keys(rotationStrategyText) as RotationStrategy[]).map((strategy) => (
+                          <option key={strategy} value={strategy}>{rotationStrategyText[strategy]}</option>
+                        ))}
+                      </select>
+                    </td>
+                    <td>
+                      <div className="button-row">
+                        <button type="button" className="btn btn-secondary btn-small" onClick={() => handlePreviewPool(pool)} disabled={isActionLoading(`preview-pool-${pool.id}`)}>预览</button>
+                        <button type="button" className="btn btn-primary btn-small" onClick={() => handleSwitchNextInPool(pool)} disabled={!vaultUnlocked || isActionLoading(`next-pool-${pool.id}`)}>切换到下一个</button>
+                        <button type="button" className="btn btn-danger btn-small" onClick={() => handleDeleteAccountPool(pool)} disabled={isActionLoading(`delete-pool-${pool.id}`)}>删除</button>
+                      </div>
+                    </td>
+                  </tr>
+                ))}
+              </tbody>
+            </table>
+          </div>
+        )}
+        {poolDecision && (
+          <div className="tips-list">
+            <p>{poolDecision.pool_name}（{rotationStrategyText[poolDecision.strategy]}）→ {poolDecision.account_name}：{poolDecision.explanation}</p>
+            {poolDecision.members.map((member) => (
+              <p key={member.account_id} className={member.eligible ? undefined : "muted-text"}>
+                {member.account_id === poolDecision.account_id ? "✓ " : ""}{member.account_name}：{member.note}
+              </p>
+            ))}
+          </div>
+        )}
+      </section>
     </div>
   );
 
@@ -802,6 +1921,151 @@ function App() {
         </div>
       </section>
 
+      <section className="view-card">
+        <div className="card-head"><h3>凭据健康</h3></div>
+        <p className="muted-text">解密并校验每个账号的登录数据，解析令牌过期时间；开启远程校验后会用令牌请求一次校验地址，以发现已被吊销的凭据。</p>
+        <label className="checkbox-label">
+          <input
+            type="checkbox"
+            checked={healthConfig.remote_enabled}
+            onChange={(event) => {
+              const remoteEnabled = event.currentTarget.checked;
+              setHealthConfig((prev) => ({ ...prev, remote_enabled: remoteEnabled }));
+            }}
+          />
+          远程校验令牌是否被吊销
+        </label>
+        <label className="field-label">
+          校验地址
+          <input
+            type="text"
+            value={healthConfig.endpoint}
+            onChange={(event) => {
+              const endpoint = event.currentTarget.value;
+              setHealthConfig((prev) => ({ ...prev, endpoint }));
+            }}
+            placeholder="https://chatgpt.com/backend-api/me"
+          />
+        </label>
+        <div className="button-row">
+          <button type="button" className="btn btn-secondary" onClick={handleSaveHealthConfig} disabled={isActionLoading("health-config")}>保存设置</button>
+          <button type="button" className="btn btn-primary" onClick={handleCheckHealth} disabled={!vaultUnlocked || accounts.length === 0 || isActionLoading("check-health")}>
+            {isActionLoading("check-health") ? "检查中..." : "检查全部凭据"}
+          </button>
+        </div>
+      </section>
+
+      <section className="view-card">
+        <div className="card-head"><h3>切换守卫</h3></div>
+        <p className="muted-text">切换前检查目标账号；设为“阻止切换”的检查未通过时会先询问是否仍然切换，“仅提示”只在结果中提醒。</p>
+        {guardPolicy ? (
+          <div className="form-grid">
+            {(Object.keys(guardLabels) as (keyof SwitchGuardPolicy)[]).map((guard) => (
+              <label className="field-label" key={guard}>
+                {guardLabels[guard]}
+                <select
+                  value={guardPolicy[guard]}
+                  onChange={(event) => handleSaveGuardPolicy({ ...guardPolicy, [guard]: event.currentTarget.value as GuardAction })}
+                  disabled={isActionLoading("guard-policy")}
+                >
+                  {(Object.keys(guardActionText) as GuardAction[]).map((action) => (
+                    <option key={action} value={action}>{guardActionText[action]}</option>
+                  ))}
+                </select>
+              </label>
+            ))}
+          </div>
+        ) : (
+          <div className="empty-block">正在读取切换守卫设置...</div>
+        )}
+      </section>
+
+      <section className="view-card">
+        <div className="card-head"><h3>自动切换</h3></div>
+        <p className="muted-text">后台定期探测当前账号配额，耗尽或低于阈值时按配额状态、偏好标签、剩余额度与重置时间挑选候选账号自动切换；触发后进入冷却，避免反复切换。</p>
+        {failoverConfig ? (
+          <>
+            <label className="checkbox-label">
+              <input
+                type="checkbox"
+                checked={failoverConfig.enabled}
+                onChange={(event) => {
+                  const enabled = event.currentTarget.checked;
+                  setFailoverConfigDraft((prev) => (prev ? { ...prev, enabled } : prev));
+                }}
+              />
+              启用自动切换
+            </label>
+            <div className="form-grid">
+              <label className="field-label">
+                探测间隔（秒）
+                <input
+                  type="number"
+                  min={60}
+                  value={failoverConfig.interval_seconds}
+                  onChange={(event) => {
+                    const intervalSeconds = Number(event.currentTarget.value);
+                    setFailoverConfigDraft((prev) => (prev ? { ...prev, interval_seconds: intervalSeconds } : prev));
+                  }}
+                />
+              </label>
+              <label className="field-label">
+                剩余百分比阈值
+                <input
+                  type="text"
+                  value={failoverThreshold}
+                  onChange={(event) => setFailoverThreshold(event.currentTarget.value)}
+                  placeholder="留空则仅在耗尽时切换"
+                />
+              </label>
+              <label className="field-label">
+                冷却时间（秒）
+                <input
+                  type="number"
+                  min={0}
+                  value={failoverConfig.cooldown_seconds}
+                  onChange={(event) => {
+                    const cooldownSeconds = Number(event.currentTarget.value);
+                    setFailoverConfigDraft((prev) => (prev ? { ...prev, cooldown_seconds: cooldownSeconds } : prev));
+                  }}
+                />
+              </label>
+              <label className="field-label">
+                偏好标签
+                <input
+                  type="text"
+                  value={failoverTags}
+                  onChange={(event) => setFailoverTags(event.currentTarget.value)}
+                  placeholder="留空则优先与当前账号标签相同的账号"
+                />
+              </label>
+              <label className="field-label">
+                切换后
+                <select
+                  value={failoverConfig.restart_mode}
+                  onChange={(event) => {
+                    const restartMode = event.currentTarget.value as RestartMode;
+                    setFailoverConfigDraft((prev) => (prev ? { ...prev, restart_mode: restartMode } : prev));
+                  }}
+                >
+                  {(Object.keys(restartModeText) as RestartMode[]).map((mode) => (
+                    <option key={mode} value={mode}>{restartModeText[mode]}</option>
+                  ))}
+                </select>
+              </label>
+            </div>
+            <div className="button-row">
+              <button type="button" className="btn btn-secondary" onClick={handleSaveFailoverConfig} disabled={isActionLoading("failover-config")}>保存设置</button>
+              <button type="button" className="btn btn-primary" onClick={handleRunFailoverCheck} disabled={!vaultUnlocked || isActionLoading("failover-check")}>
+                {isActionLoading("failover-check") ? "检查中..." : "立即检查"}
+              </button>
+            </div>
+          </>
+        ) : (
+          <div className="empty-block">正在读取自动切换设置...</div>
+        )}
+      </section>
+
       <section className="view-card">
         {quotaDashboard.length === 0 ? (
           <div className="empty-block">暂无配额数据，请先点击刷新。</div>
@@ -823,6 +2087,16 @@ function App() {
                   <p>来源：{snapshot?.source ?? "--"}</p>
                   <p>置信度：{snapshot?.confidence ?? "--"}</p>
                   {snapshot?.reason ? <p className="quota-reason">原因：{snapshot.reason}</p> : null}
+                  <p>
+                    凭据：
+                    {item.health ? (
+                      <span className={`state-pill ${healthStatusClassName(item.health.status)}`} title={`检查于 ${formatDateTime(item.health.checked_at)}`}>
+                        {healthStatusText[item.health.status] ?? item.health.status}
+                      </span>
+                    ) : "未检查"}
+                    {item.health?.expires_at ? `（令牌过期：${formatDateTime(item.health.expires_at)}）` : null}
+                  </p>
+                  {item.health?.reason ? <p className="quota-reason">{item.health.reason}</p> : null}
                   <p>标签：{item.account.tags.join("、") || "无"}</p>
                 </article>
               );
@@ -836,7 +2110,45 @@ function App() {
   const historyView = (
     <div className="view-stack">
       <section className="view-card">
-        <div className="card-head"><h3>切换历史</h3></div>
+        <div className="card-head">
+          <h3>切换历史</h3>
+          <span className="muted-text">共 {historyTotal} 条，已加载 {historyItems.length} 条</span>
+        </div>
+        <div className="form-grid">
+          <label className="field-label">
+            账号
+            <select value={historyFilter.accountId} onChange={(event) => setHistoryFilter((prev) => ({ ...prev, accountId: event.currentTarget.value }))}>
+              <option value="">全部账号</option>
+              {accounts.map((account) => (
+                <option key={account.id} value={account.id}>{account.name}</option>
+              ))}
+              {trashedAccounts.map((item) => (
+                <option key={item.account.id} value={item.account.id}>{item.account.name}（回收站）</option>
+              ))}
+            </select>
+          </label>
+          <label className="field-label">
+            结果
+            <select value={historyFilter.result} onChange={(event) => setHistoryFilter((prev) => ({ ...prev, result: event.currentTarget.value as HistoryFilter["result"] }))}>
+              <option value="">全部结果</option>
+              {Object.entries(historyResultText).map(([value, label]) => (
+                <option key={value} value={value}>{label}</option>
+              ))}
+            </select>
+          </label>
+          <label className="field-label">
+            开始日期
+            <input type="date" value={historyFilter.since} onChange={(event) => setHistoryFilter((prev) => ({ ...prev, since: event.currentTarget.value }))} />
+          </label>
+          <label className="field-label">
+            结束日期
+            <input type="date" value={historyFilter.until} onChange={(event) => setHistoryFilter((prev) => ({ ...prev, until: event.currentTarget.value }))} />
+          </label>
+        </div>
+        <div className="button-row">
+          <button type="button" className="btn btn-primary" onClick={() => handleApplyHistoryFilter(historyFilter)} disabled={isActionLoading("query-history")}>筛选</button>
+          <button type="button" className="btn btn-ghost" onClick={() => handleApplyHistoryFilter(EMPTY_HISTORY_FILTER)} disabled={isActionLoading("query-history")}>重置</button>
+        </div>
         <div className="table-wrap">
           <table className="data-table">
             <thead>
@@ -856,8 +2168,8 @@ function App() {
               {historyItems.map((item) => (
                 <tr key={item.id}>
                   <td>{formatDateTime(item.created_at)}</td>
-                  <td>{resolveAccountName(item.from_account_id)}</td>
-                  <td>{resolveAccountName(item.to_account_id)}</td>
+                  <td>{historyAccountLabel(item.from_account_id, item.from_account_name, item.from_account_trashed, "空")}</td>
+                  <td>{historyAccountLabel(item.to_account_id, item.to_account_name, item.to_account_trashed, "已删除账号")}</td>
                   <td>
                     <span className={`history-pill ${historyResultClassName(item.result)}`}>
                       {historyResultText[item.result] ?? item.result}
@@ -865,7 +2177,293 @@ function App() {
                   </td>
                   <td className="error-cell">{item.error_message ?? "--"}</td>
                   <td>
-                    <button type="button" className="btn btn-secondary btn-small" onClick={() => handleRollback(item)} disabled={!item.snapshot_path || isActionLoading(`rollback-${item.id}`)}>回滚</button>
+                    {item.snapshot_pruned_at ? (
+                      <span className="muted-text" title={formatDateTime(item.snapshot_pruned_at)}>快照已清理</span>
+                    ) : (
+                      <button type="button" className="btn btn-secondary btn-small" onClick={() => handleRollback(item)} disabled={!item.snapshot_path || isActionLoading(`rollback-${item.id}`)}>回滚</button>
+                    )}
+                  </td>
+                </tr>
+              ))}
+            </tbody>
+          </table>
+        </div>
+        {historyCursor && (
+          <div className="button-row">
+            <button type="button" className="btn btn-secondary" onClick={handleLoadMoreHistory} disabled={isActionLoading("load-more-history")}>加载更多</button>
+          </div>
+        )}
+      </section>
+
+      <section className="view-card">
+        <div className="card-head"><h3>快照保留策略</h3></div>
+        <div className="form-grid">
+          <label className="field-label">
+            最多保留数量
+            <input
+              type="number"
+              min={1}
+              value={snapshotRetention.keep_count}
+              onChange={(event) => setSnapshotRetentionState((prev) => ({ ...prev, keep_count: Number(event.currentTarget.value) || 1 }))}
+            />
+          </label>
+          <label className="field-label">
+            最长保留天数
+            <input
+              type="number"
+              min={1}
+              value={snapshotRetention.max_age_days}
+              onChange={(event) => setSnapshotRetentionState((prev) => ({ ...prev, max_age_days: Number(event.currentTarget.value) || 1 }))}
+            />
+          </label>
+          <label className="field-label">
+            始终保留最近历史条数
+            <input
+              type="number"
+              min={0}
+              value={snapshotRetention.protect_recent}
+              onChange={(event) => setSnapshotRetentionState((prev) => ({ ...prev, protect_recent: Number(event.currentTarget.value) || 0 }))}
+            />
+          </label>
+        </div>
+        <div className="button-row">
+          <button type="button" className="btn btn-secondary" onClick={handleSaveSnapshotRetention} disabled={isActionLoading("save-snapshot-retention")}>保存策略</button>
+          <button type="button" className="btn btn-ghost" onClick={() => handlePruneSnapshots(true)} disabled={isActionLoading("preview-prune")}>预览清理</button>
+          <button type="button" className="btn btn-danger" onClick={() => handlePruneSnapshots(false)} disabled={isActionLoading("prune-snapshots")}>立即清理</button>
+        </div>
+        <p className="muted-text">每次切换或回滚后会自动按策略清理；快照包含登录凭据，建议保持较小的保留量。</p>
+        {pruneReport && (
+          <>
+            <p className="muted-text">
+              {pruneReport.dry_run ? "预览" : "结果"}：{pruneReport.dry_run ? "将清理" : "已清理"} {pruneReport.pruned.length} 个，保留 {pruneReport.kept} 个
+            </p>
+            {pruneReport.pruned.length > 0 && (
+              <div className="table-wrap">
+                <table className="data-table">
+                  <thead>
+                    <tr>
+                      <th>快照时间</th>
+                      <th>原因</th>
+                      <th>文件</th>
+                    </tr>
+                  </thead>
+                  <tbody>
+                    {pruneReport.pruned.map((item) => (
+                      <tr key={item.history_id}>
+                        <td>{formatDateTime(item.created_at)}</td>
+                        <td>{item.reason}</td>
+                        <td><code>{item.snapshot_path}</code></td>
+                      </tr>
+                    ))}
+                  </tbody>
+                </table>
+              </div>
+            )}
+          </>
+        )}
+      </section>
+
+      <section className="view-card">
+        <div className="card-head"><h3>使用统计</h3></div>
+        <div className="form-grid">
+          <label className="field-label">
+            统计粒度
+            <select value={usageGranularity} onChange={(event) => setUsageGranularity(event.currentTarget.value as UsageGranularity)}>
+              <option value="day">按天</option>
+              <option value="week">按周</option>
+            </select>
+          </label>
+          <label className="field-label">
+            开始日期
+            <input type="date" value={usageSince} onChange={(event) => setUsageSince(event.currentTarget.value)} />
+          </label>
+          <label className="field-label">
+            结束日期
+            <input type="date" value={usageUntil} onChange={(event) => setUsageUntil(event.currentTarget.value)} />
+          </label>
+        </div>
+        <p className="muted-text">未指定日期时统计最近 30 天；生效时长由切换历史推算。</p>
+        <div className="button-row">
+          <button type="button" className="btn btn-primary" onClick={handleLoadUsage} disabled={isActionLoading("usage-analytics")}>统计</button>
+          {(Object.keys(usageCsvTableText) as UsageCsvTable[]).map((table) => (
+            <button key={table} type="button" className="btn btn-secondary" onClick={() => handleExportUsage(table)} disabled={isActionLoading(`export-usage-${table}`)}>
+              导出{usageCsvTableText[table]} CSV
+            </button>
+          ))}
+        </div>
+        {usageReport && (
+          <>
+            <p className="muted-text">
+              切换 {usageReport.total_switches} 次，失败 {usageReport.failed_switches} 次（{(usageReport.failure_rate * 100).toFixed(1)}%），回滚 {usageReport.rollbacks} 次
+            </p>
+            <div className="table-wrap">
+              <table className="data-table">
+                <thead>
+                  <tr>
+                    <th>账号</th>
+                    <th>生效时长</th>
+                    <th>切入次数</th>
+                    <th>失败次数</th>
+                    <th>失败率</th>
+                  </tr>
+                </thead>
+                <tbody>
+                  {usageReport.accounts.length === 0 && (
+                    <tr><td className="empty-cell" colSpan={5}>该时间段内没有可统计的记录。</td></tr>
+                  )}
+                  {usageReport.accounts.map((item) => (
+                    <tr key={item.account_id ?? "deleted"}>
+                      <td>{historyAccountLabel(item.account_id, item.account_name, false, "已删除账号")}</td>
+                      <td>{formatHours(item.active_seconds)}</td>
+                      <td>{item.switches_in}</td>
+                      <td>{item.failed_switches}</td>
+                      <td>{(item.failure_rate * 100).toFixed(1)}%</td>
+                    </tr>
+                  ))}
+                </tbody>
+              </table>
+            </div>
+            {usageReport.top_pairs.length > 0 && (
+              <div className="table-wrap">
+                <table className="data-table">
+                  <thead>
+                    <tr>
+                      <th>常见切换</th>
+                      <th>次数</th>
+                    </tr>
+                  </thead>
+                  <tbody>
+                    {usageReport.top_pairs.map((pair) => (
+                      <tr key={`${pair.from_account_id ?? "-"}-${pair.to_account_id ?? "-"}`}>
+                        <td>
+                          {historyAccountLabel(pair.from_account_id, pair.from_account_name, false, "空")} → {historyAccountLabel(pair.to_account_id, pair.to_account_name, false, "已删除账号")}
+                        </td>
+                        <td>{pair.count}</td>
+                      </tr>
+                    ))}
+                  </tbody>
+                </table>
+              </div>
+            )}
+            {usageReport.buckets.length > 0 && (
+              <div className="table-wrap">
+                <table className="data-table">
+                  <thead>
+                    <tr>
+                      <th>{usageReport.granularity === "week" ? "周起始" : "日期"}</th>
+                      <th>账号</th>
+                      <th>生效时长</th>
+                    </tr>
+                  </thead>
+                  <tbody>
+                    {usageReport.buckets.map((bucket) => (
+                      <tr key={`${bucket.period_start}-${bucket.account_id ?? "deleted"}`}>
+                        <td>{bucket.period_start}</td>
+                        <td>{historyAccountLabel(bucket.account_id, bucket.account_name, false, "已删除账号")}</td>
+                        <td>{formatHours(bucket.active_seconds)}</td>
+                      </tr>
+                    ))}
+                  </tbody>
+                </table>
+              </div>
+            )}
+          </>
+        )}
+      </section>
+    </div>
+  );
+
+  const projectsView = (
+    <div className="view-stack">
+      <section className="view-card">
+        <div className="card-head"><h3>绑定项目目录</h3></div>
+        <div className="form-grid">
+          <label className="field-label field-span-2">
+            项目目录
+            <div className="picker-row">
+              <input
+                type="text"
+                value={bindingPath}
+                onChange={(event) => setBindingPath(event.currentTarget.value)}
+                placeholder="例如 ~/work/client-a"
+              />
+              <button type="button" className="btn btn-secondary" onClick={handleChooseBindingDir}>选择目录</button>
+            </div>
+          </label>
+          <label className="field-label">
+            绑定账号
+            <select value={bindingAccountId} onChange={(event) => setBindingAccountId(event.currentTarget.value)}>
+              <option value="">按标签匹配</option>
+              {accounts.map((account) => (<option key={account.id} value={account.id}>{account.name}</option>))}
+            </select>
+          </label>
+          <label className="field-label">
+            绑定标签
+            <input
+              type="text"
+              value={bindingTag}
+              onChange={(event) => setBindingTag(event.currentTarget.value)}
+              placeholder="未选择账号时生效，例如：客户A"
+              disabled={Boolean(bindingAccountId)}
+            />
+          </label>
+        </div>
+        <div className="button-row">
+          <button
+            type="button"
+            className="btn btn-primary"
+            onClick={handleSaveProjectBinding}
+            disabled={!bindingPath.trim() || (!bindingAccountId && !bindingTag.trim()) || isActionLoading("save-project-binding")}
+          >
+            保存绑定
+          </button>
+          <button type="button" className="btn btn-secondary" onClick={handleResolveProject} disabled={!bindingPath.trim() || isActionLoading("resolve-project")}>
+            解析该路径
+          </button>
+        </div>
+        {projectResolution && (
+          <div className="tips-list">
+            <p>{projectResolution.message}</p>
+            {projectResolution.binding_path && (
+              <p>来源：{projectResolution.source === "dotfile" ? ".codex-switch.toml" : "应用内绑定"}（<code>{projectResolution.binding_path}</code>）</p>
+            )}
+          </div>
+        )}
+        <label className="checkbox-label">
+          <input
+            type="checkbox"
+            checked={projectAutoSwitch}
+            onChange={(event) => void handleToggleProjectAutoSwitch(event.currentTarget.checked)}
+            disabled={isActionLoading("toggle-project-auto-switch")}
+          />
+          新启动的 Codex 进程位于绑定目录时自动切换账号（需保险库已解锁）
+        </label>
+        <p className="muted-text">也可以在项目根目录放置 <code>.codex-switch.toml</code>，写入 <code>account = "账号名称或 ID"</code> 或 <code>tag = "标签"</code>。</p>
+      </section>
+
+      <section className="view-card">
+        <div className="card-head"><h3>已绑定目录</h3></div>
+        <div className="table-wrap">
+          <table className="data-table">
+            <thead>
+              <tr>
+                <th>目录</th>
+                <th>账号 / 标签</th>
+                <th>更新时间</th>
+                <th>操作</th>
+              </tr>
+            </thead>
+            <tbody>
+              {projectBindings.length === 0 && (
+                <tr><td className="empty-cell" colSpan={4}>暂无项目绑定。</td></tr>
+              )}
+              {projectBindings.map((binding) => (
+                <tr key={binding.id}>
+                  <td><code>{binding.path}</code></td>
+                  <td>{binding.account_id ? resolveAccountName(binding.account_id) : `标签：${binding.tag ?? "--"}`}</td>
+                  <td>{formatDateTime(binding.updated_at)}</td>
+                  <td>
+                    <button type="button" className="btn btn-danger btn-small" onClick={() => handleDeleteProjectBinding(binding)} disabled={isActionLoading(`delete-binding-${binding.id}`)}>删除</button>
                   </td>
                 </tr>
               ))}
@@ -890,18 +2488,137 @@ function App() {
             <div className="diagnostic-grid">
               <article className="diagnostic-tile"><span>认证文件</span><strong>{diagnostics.codex_auth_exists ? "存在" : "缺失"}</strong></article>
               <article className="diagnostic-tile"><span>结构校验</span><strong>{diagnostics.schema_ok ? "正常" : "异常"}</strong></article>
+              <article className="diagnostic-tile"><span>数据库版本</span><strong>v{diagnostics.db_schema_version}</strong></article>
               <article className="diagnostic-tile"><span>进程数量</span><strong>{diagnostics.process_count}</strong></article>
+              <article className="diagnostic-tile"><span>Codex 版本</span><strong>{diagnostics.codex_version ?? "未检测到"}</strong></article>
             </div>
             <div className="path-grid">
               <div><span>认证路径</span><code>{diagnostics.codex_auth_path}</code></div>
               <div><span>数据库路径</span><code>{diagnostics.db_path}</code></div>
               <div><span>应用数据目录</span><code>{diagnostics.app_data_dir}</code></div>
+              <div><span>Codex CLI 路径</span><code>{diagnostics.codex_binary_path ? `${diagnostics.codex_binary_path}（${diagnostics.codex_binary_source ?? "--"}）` : "--"}</code></div>
+              <div><span>兼容配置</span><code>{diagnostics.codex_compat_profile}</code></div>
+            </div>
+            {diagnostics.codex_compat_warning && <div className="inline-notice notice-info">{diagnostics.codex_compat_warning}</div>}
+            <label className="field-label">
+              固定 Codex CLI 路径
+              <input
+                type="text"
+                value={codexBinaryPath}
+                onChange={(event) => setCodexBinaryPathInput(event.currentTarget.value)}
+                placeholder="留空则自动探测，例如 /opt/homebrew/bin/codex"
+              />
+            </label>
+            <div className="button-row">
+              <button type="button" className="btn btn-secondary" onClick={() => handleSaveCodexBinaryPath(codexBinaryPath.trim() || null)} disabled={isActionLoading("save-codex-binary")}>
+                保存路径
+              </button>
+              <button type="button" className="btn btn-ghost" onClick={() => handleSaveCodexBinaryPath(null)} disabled={isActionLoading("save-codex-binary")}>
+                恢复自动探测
+              </button>
             </div>
           </>
         ) : (
           <p className="muted-text">暂无诊断数据</p>
         )}
       </section>
+
+      <section className="view-card">
+        <div className="card-head">
+          <h3>数据完整性</h3>
+          <div className="button-row">
+            <button type="button" className="btn btn-ghost btn-small" onClick={() => handleCheckIntegrity(false)} disabled={isActionLoading("check-integrity")}>
+              {isActionLoading("check-integrity") ? "检查中..." : "检查"}
+            </button>
+            <button type="button" className="btn btn-secondary btn-small" onClick={() => handleCheckIntegrity(true)} disabled={isActionLoading("repair-integrity")}>
+              修复
+            </button>
+          </div>
+        </div>
+        <p className="muted-text">删除账号时，其配额快照与项目绑定一并删除，切换历史保留但不再关联该账号，来源为该账号的切换快照文件会被清除。</p>
+        {integrityReport && (
+          <div className="diagnostic-grid">
+            <article className="diagnostic-tile"><span>孤儿配额快照</span><strong>{integrityReport.orphan_quota_snapshots}</strong></article>
+            <article className="diagnostic-tile"><span>失效历史引用</span><strong>{integrityReport.orphan_history_refs}</strong></article>
+            <article className="diagnostic-tile"><span>孤儿项目绑定</span><strong>{integrityReport.orphan_project_bindings}</strong></article>
+            <article className="diagnostic-tile"><span>缺失快照文件</span><strong>{integrityReport.missing_snapshot_files}</strong></article>
+            <article className="diagnostic-tile"><span>未引用快照文件</span><strong>{integrityReport.unreferenced_snapshot_files}</strong></article>
+          </div>
+        )}
+      </section>
+
+      <section className="view-card">
+        <div className="card-head"><h3>本地控制接口</h3></div>
+        <label className="checkbox-label">
+          <input
+            type="checkbox"
+            checked={controlStatus?.enabled ?? false}
+            onChange={(event) => handleToggleControlApi(event.currentTarget.checked)}
+            disabled={isActionLoading("toggle-control-api")}
+          />
+          允许编辑器插件、Shell 提示符等本机工具通过 JSON-RPC 查询与切换账号
+        </label>
+        {controlStatus && (
+          <div className="path-grid">
+            <div><span>监听地址</span><code>{controlStatus.endpoint}</code></div>
+            <div><span>运行状态</span><code>{controlStatus.running ? "运行中" : "未运行"}</code></div>
+          </div>
+        )}
+        {controlStatus?.error && <div className="inline-notice notice-info">{controlStatus.error}</div>}
+        <ul className="tips-list">
+          <li>接口仅当前用户可访问；客户端需先用令牌调用 authenticate 才能执行切换、解锁、导出等方法；配置令牌后所有方法都需要认证。</li>
+          <li>调用 subscribe 可订阅 account-switched 与 quota-updated 事件。</li>
+        </ul>
+        <div className="form-grid">
+          <label className="field-label">
+            客户端名称
+            <input
+              type="text"
+              value={controlTokenName}
+              onChange={(event) => setControlTokenName(event.currentTarget.value)}
+              placeholder="例如 vscode-plugin"
+            />
+          </label>
+        </div>
+        <div className="button-row">
+          <button type="button" className="btn btn-secondary" onClick={handleCreateControlToken} disabled={!controlTokenName.trim() || isActionLoading("create-control-token")}>
+            创建令牌
+          </button>
+        </div>
+        {createdControlSecret && (
+          <div className="inline-notice notice-info">
+            新令牌（仅显示一次）：<code>{createdControlSecret}</code>
+          </div>
+        )}
+        {controlTokens.length > 0 ? (
+          <table className="data-table">
+            <thead>
+              <tr>
+                <th>客户端</th>
+                <th>创建时间</th>
+                <th>最近使用</th>
+                <th>操作</th>
+              </tr>
+            </thead>
+            <tbody>
+              {controlTokens.map((token) => (
+                <tr key={token.id}>
+                  <td>{token.name}</td>
+                  <td>{formatDateTime(token.created_at)}</td>
+                  <td>{formatDateTime(token.last_used_at)}</td>
+                  <td>
+                    <button type="button" className="btn btn-danger btn-small" onClick={() => handleRevokeControlToken(token)} disabled={isActionLoading(`revoke-control-token-${token.id}`)}>
+                      撤销
+                    </button>
+                  </td>
+                </tr>
+              ))}
+            </tbody>
+          </table>
+        ) : (
+          <p className="muted-text">未配置客户端令牌，本机当前用户的进程只能调用只读查询；切换、解锁、导出等方法需创建令牌后认证</p>
+        )}
+      </section>
     </div>
   );
 
@@ -914,6 +2631,8 @@ function App() {
       ? quotaView
       : activeView === "history"
       ? historyView
+      : activeView === "projects"
+      ? projectsView
       : activeView === "diagnostics"
       ? diagnosticsView
       : overviewView;
@@ -1,14 +1,49 @@
 import { invoke } from "@tauri-apps/api/core";
 import type {
   Account,
+  AccountEvent,
+  AccountHealth,
+  AccountPool,
+  AccountPoolInput,
+  HealthCheckConfig,
+  BulkImportReport,
+  ImportNameSource,
+  ImporterInfo,
+  AccountHomesStatus,
+  CodexBinaryInfo,
   CodexCliStatus,
+  ControlApiStatus,
+  ControlToken,
+  CreatedControlToken,
+  DeviceAuthConfig,
+  DeviceLoginChallenge,
+  FailoverConfig,
+  FailoverEvent,
+  IntegrityReport,
+  ProjectAccountResolution,
+  PoolDecision,
+  PoolSwitchResult,
+  ProjectBinding,
   QuotaDashboardItem,
   QuotaRefreshPolicyInput,
   QuotaSnapshot,
   RuntimeDiagnostics,
   SimpleStatus,
+  SnapshotPruneReport,
+  SnapshotRetentionPolicy,
   SwitchHistory,
+  SwitchHistoryPage,
+  SwitchGuardPolicy,
+  SwitchHistoryQuery,
   SwitchResult,
+  TrashedAccount,
+  UsageCsvTable,
+  UsageQuery,
+  UsageReport,
+  VaultConflictStrategy,
+  VaultExportFilter,
+  VaultExportSummary,
+  VaultImportReport,
 } from "./types";
 
 function invokeCommand<T>(command: string, args?: Record<string, unknown>): Promise<T> {
@@ -39,6 +74,18 @@ export function createAccountFromImport(name: string, tags: string[]): Promise<A
   return invokeCommand("create_account_from_import", { name, tags });
 }
 
+export function bulkImportAuthFiles(source: string, tags: string[], nameSource: ImportNameSource): Promise<BulkImportReport> {
+  return invokeCommand("bulk_import_auth_files", { source, tags, nameSource });
+}
+
+export function listAccountImporters(): Promise<ImporterInfo[]> {
+  return invokeCommand("list_account_importers");
+}
+
+export function importAccountsFrom(importer: string | null, source: string, tags: string[]): Promise<BulkImportReport> {
+  return invokeCommand("import_accounts_from", { importer, source, tags });
+}
+
 export function createAccountFromLogin(name: string, tags: string[]): Promise<Account> {
   return invokeCommand("create_account_from_login", { name, tags });
 }
@@ -47,6 +94,38 @@ export function createAccountFromAuthFile(name: string, tags: string[], authFile
   return invokeCommand("create_account_from_auth_file", { name, tags, path: authFilePath });
 }
 
+export function reauthenticateAccountFromLogin(accountId: string): Promise<Account> {
+  return invokeCommand("reauthenticate_account_from_login", { accountId });
+}
+
+export function reauthenticateAccountFromFile(accountId: string, path: string): Promise<Account> {
+  return invokeCommand("reauthenticate_account_from_file", { accountId, path });
+}
+
+export function listAccountEvents(accountId: string): Promise<AccountEvent[]> {
+  return invokeCommand("list_account_events", { accountId });
+}
+
+export function getDeviceAuthConfig(): Promise<DeviceAuthConfig> {
+  return invokeCommand("get_device_auth_config");
+}
+
+export function setDeviceAuthConfig(config: DeviceAuthConfig): Promise<SimpleStatus> {
+  return invokeCommand("set_device_auth_config", { config });
+}
+
+export function startDeviceLogin(preferNative?: boolean): Promise<DeviceLoginChallenge> {
+  return invokeCommand("start_device_login", { preferNative: preferNative ?? null });
+}
+
+export function completeDeviceLogin(sessionId: string, name: string, tags: string[]): Promise<Account> {
+  return invokeCommand("complete_device_login", { sessionId, name, tags });
+}
+
+export function cancelDeviceLogin(sessionId: string): Promise<SimpleStatus> {
+  return invokeCommand("cancel_device_login", { sessionId });
+}
+
 export function listAccounts(): Promise<Account[]> {
   return invokeCommand("list_accounts");
 }
@@ -59,8 +138,68 @@ export function deleteAccount(id: string): Promise<SimpleStatus> {
   return invokeCommand("delete_account", { id });
 }
 
-export function switchAccount(id: string, forceRestart: boolean): Promise<SwitchResult> {
-  return invokeCommand("switch_account", { id, forceRestart });
+export function listTrashedAccounts(): Promise<TrashedAccount[]> {
+  return invokeCommand("list_trashed_accounts");
+}
+
+export function restoreAccount(id: string): Promise<Account> {
+  return invokeCommand("restore_account", { id });
+}
+
+export function purgeAccount(id: string): Promise<SimpleStatus> {
+  return invokeCommand("purge_account", { id });
+}
+
+export function getTrashRetentionDays(): Promise<number> {
+  return invokeCommand("get_trash_retention_days");
+}
+
+export function setTrashRetentionDays(days: number): Promise<SimpleStatus> {
+  return invokeCommand("set_trash_retention_days", { days });
+}
+
+export function switchAccount(id: string, forceRestart: boolean, overrideGuards = false): Promise<SwitchResult> {
+  return invokeCommand("switch_account", { id, forceRestart, overrideGuards });
+}
+
+export function getSwitchGuardPolicy(): Promise<SwitchGuardPolicy> {
+  return invokeCommand("get_switch_guard_policy");
+}
+
+export function setSwitchGuardPolicy(policy: SwitchGuardPolicy): Promise<SimpleStatus> {
+  return invokeCommand("set_switch_guard_policy", { policy });
+}
+
+export function getFailoverConfig(): Promise<FailoverConfig> {
+  return invokeCommand("get_failover_config");
+}
+
+export function setFailoverConfig(config: FailoverConfig): Promise<SimpleStatus> {
+  return invokeCommand("set_failover_config", { config });
+}
+
+export function runFailoverCheck(): Promise<FailoverEvent | null> {
+  return invokeCommand("run_failover_check");
+}
+
+export function listAccountPools(): Promise<AccountPool[]> {
+  return invokeCommand("list_account_pools");
+}
+
+export function saveAccountPool(pool: AccountPoolInput): Promise<AccountPool> {
+  return invokeCommand("save_account_pool", { pool });
+}
+
+export function deleteAccountPool(id: string): Promise<SimpleStatus> {
+  return invokeCommand("delete_account_pool", { id });
+}
+
+export function previewPoolRotation(poolId: string): Promise<PoolDecision> {
+  return invokeCommand("preview_pool_rotation", { poolId });
+}
+
+export function switchToNextInPool(poolId: string, forceRestart: boolean, overrideGuards = false): Promise<PoolSwitchResult> {
+  return invokeCommand("switch_to_next_in_pool", { poolId, forceRestart, overrideGuards });
 }
 
 export function rollbackToHistory(historyId: string): Promise<SwitchResult> {
@@ -71,6 +210,26 @@ export function listSwitchHistory(limit?: number): Promise<SwitchHistory[]> {
   return invokeCommand("list_switch_history", { limit: limit ?? null });
 }
 
+export function querySwitchHistory(query: SwitchHistoryQuery): Promise<SwitchHistoryPage> {
+  return invokeCommand("query_switch_history", { query });
+}
+
+export function getUsageAnalytics(query: UsageQuery): Promise<UsageReport> {
+  return invokeCommand("get_usage_analytics", { query });
+}
+
+export function exportUsageCsv(query: UsageQuery, table: UsageCsvTable, path: string): Promise<SimpleStatus> {
+  return invokeCommand("export_usage_csv", { query, table, path });
+}
+
+export function exportVault(path: string, passphrase: string, filter: VaultExportFilter): Promise<VaultExportSummary> {
+  return invokeCommand("export_vault", { path, passphrase, filter });
+}
+
+export function importVault(path: string, passphrase: string, strategy: VaultConflictStrategy): Promise<VaultImportReport> {
+  return invokeCommand("import_vault", { path, passphrase, strategy });
+}
+
 export function refreshQuota(accountId?: string, force?: boolean): Promise<QuotaSnapshot[]> {
   return invokeCommand("refresh_quota", {
     accountId: accountId ?? null,
@@ -82,6 +241,18 @@ export function getQuotaDashboard(): Promise<QuotaDashboardItem[]> {
   return invokeCommand("get_quota_dashboard");
 }
 
+export function checkAccountsHealth(accountId?: string, remote?: boolean): Promise<AccountHealth[]> {
+  return invokeCommand("check_accounts_health", { accountId, remote });
+}
+
+export function getHealthCheckConfig(): Promise<HealthCheckConfig> {
+  return invokeCommand("get_health_check_config");
+}
+
+export function setHealthCheckConfig(config: HealthCheckConfig): Promise<SimpleStatus> {
+  return invokeCommand("set_health_check_config", { config });
+}
+
 export function listQuotaSnapshots(accountId: string, limit?: number): Promise<QuotaSnapshot[]> {
   return invokeCommand("list_quota_snapshots", {
     accountId,
@@ -99,10 +270,94 @@ export function setQuotaRefreshPolicy(policy: QuotaRefreshPolicyInput): Promise<
   });
 }
 
+export function getSnapshotRetention(): Promise<SnapshotRetentionPolicy> {
+  return invokeCommand("get_snapshot_retention");
+}
+
+export function setSnapshotRetention(policy: SnapshotRetentionPolicy): Promise<SimpleStatus> {
+  return invokeCommand("set_snapshot_retention", { policy });
+}
+
+export function pruneSnapshots(dryRun: boolean): Promise<SnapshotPruneReport> {
+  return invokeCommand("prune_snapshots", { dryRun });
+}
+
 export function getRuntimeDiagnostics(): Promise<RuntimeDiagnostics> {
   return invokeCommand("get_runtime_diagnostics");
 }
 
+export function checkDataIntegrity(repair: boolean): Promise<IntegrityReport> {
+  return invokeCommand("check_data_integrity", { repair });
+}
+
 export function getCodexCliStatus(): Promise<CodexCliStatus> {
   return invokeCommand("get_codex_cli_status");
 }
+
+export function discoverCodexBinaries(): Promise<CodexBinaryInfo[]> {
+  return invokeCommand("discover_codex_binaries");
+}
+
+export function setCodexBinaryPath(path: string | null): Promise<SimpleStatus> {
+  return invokeCommand("set_codex_binary_path", { path });
+}
+
+export function listProjectBindings(): Promise<ProjectBinding[]> {
+  return invokeCommand("list_project_bindings");
+}
+
+export function setProjectBinding(path: string, accountId: string | null, tag: string | null): Promise<ProjectBinding> {
+  return invokeCommand("set_project_binding", { path, accountId, tag });
+}
+
+export function deleteProjectBinding(id: string): Promise<SimpleStatus> {
+  return invokeCommand("delete_project_binding", { id });
+}
+
+export function resolveProjectAccount(path: string): Promise<ProjectAccountResolution> {
+  return invokeCommand("resolve_project_account", { path });
+}
+
+export function getProjectAutoSwitch(): Promise<boolean> {
+  return invokeCommand("get_project_auto_switch");
+}
+
+export function setProjectAutoSwitch(enabled: boolean): Promise<SimpleStatus> {
+  return invokeCommand("set_project_auto_switch", { enabled });
+}
+
+export function getControlApiStatus(): Promise<ControlApiStatus> {
+  return invokeCommand("get_control_api_status");
+}
+
+export function setControlApiEnabled(enabled: boolean): Promise<ControlApiStatus> {
+  return invokeCommand("set_control_api_enabled", { enabled });
+}
+
+export function listControlTokens(): Promise<ControlToken[]> {
+  return invokeCommand("list_control_tokens");
+}
+
+export function createControlToken(name: string): Promise<CreatedControlToken> {
+  return invokeCommand("create_control_token", { name });
+}
+
+export function revokeControlToken(id: string): Promise<SimpleStatus> {
+  return invokeCommand("revoke_control_token", { id });
+}
+
+export function getAccountHomes(): Promise<AccountHomesStatus> {
+  return invokeCommand("get_account_homes");
+}
+
+export function setAccountHomesConfig(enabled: boolean, shareConfig: boolean): Promise<AccountHomesStatus> {
+  return invokeCommand("set_account_homes_config", { enabled, shareConfig });
+}
+
+export function syncAccountHomes(): Promise<SimpleStatus> {
+  return invokeCommand("sync_account_homes");
+}
+
+export function launchCodexWithAccount(accountId: string, cwd: string | null): Promise<SimpleStatus> {
+  return invokeCommand("launch_codex_with_account", { accountId, cwd });
+}
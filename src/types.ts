@@ -17,21 +17,221 @@ export interface Account {
   last_used_at: string | null;
 }
 
+export interface AccountEvent {
+  id: string;
+  account_id: string;
+  kind: string;
+  detail: string | null;
+  created_at: string;
+}
+
+export interface TrashedAccount {
+  account: Account;
+  deleted_at: string;
+  purge_at: string;
+}
+
 export interface SwitchHistory {
   id: string;
   from_account_id: string | null;
-  to_account_id: string;
+  to_account_id: string | null;
   snapshot_path: string | null;
+  snapshot_hash: string | null;
+  snapshot_pruned_at: string | null;
   result: SwitchHistoryResult;
   error_message: string | null;
   created_at: string;
 }
 
+export interface SwitchHistoryQuery {
+  account_id?: string | null;
+  result?: SwitchHistoryResult | null;
+  since?: string | null;
+  until?: string | null;
+  cursor?: string | null;
+  limit?: number | null;
+}
+
+export interface SwitchHistoryEntry extends SwitchHistory {
+  from_account_name: string | null;
+  to_account_name: string | null;
+  from_account_trashed: boolean;
+  to_account_trashed: boolean;
+}
+
+export interface SwitchHistoryPage {
+  items: SwitchHistoryEntry[];
+  total: number;
+  next_cursor: string | null;
+}
+
+export type UsageGranularity = "day" | "week";
+
+export type UsageCsvTable = "active_time" | "accounts" | "pairs";
+
+export interface UsageQuery {
+  granularity?: UsageGranularity;
+  since?: string | null;
+  until?: string | null;
+  utc_offset_minutes?: number | null;
+}
+
+export interface UsageBucket {
+  period_start: string;
+  account_id: string | null;
+  account_name: string | null;
+  active_seconds: number;
+}
+
+export interface AccountUsage {
+  account_id: string | null;
+  account_name: string | null;
+  active_seconds: number;
+  switches_in: number;
+  failed_switches: number;
+  failure_rate: number;
+}
+
+export interface SwitchPairCount {
+  from_account_id: string | null;
+  from_account_name: string | null;
+  to_account_id: string | null;
+  to_account_name: string | null;
+  count: number;
+}
+
+export interface UsageReport {
+  granularity: UsageGranularity;
+  since: string;
+  until: string;
+  buckets: UsageBucket[];
+  accounts: AccountUsage[];
+  top_pairs: SwitchPairCount[];
+  total_switches: number;
+  failed_switches: number;
+  rollbacks: number;
+  failure_rate: number;
+}
+
+export type VaultConflictStrategy = "skip" | "overwrite" | "rename";
+
+export interface VaultExportFilter {
+  account_ids: string[];
+  tags: string[];
+}
+
+export interface VaultExportSummary {
+  path: string;
+  account_count: number;
+}
+
+export interface VaultImportItem {
+  name: string;
+  auth_fingerprint: string;
+  action: "imported" | "overwritten" | "renamed" | "skipped" | "failed";
+  account_id: string | null;
+  message: string | null;
+}
+
+export interface VaultImportReport {
+  total: number;
+  imported: number;
+  overwritten: number;
+  renamed: number;
+  skipped: number;
+  failed: number;
+  items: VaultImportItem[];
+}
+
+export interface ImporterInfo {
+  id: string;
+  label: string;
+  description: string;
+}
+
+export type ImportNameSource = "filename" | "email";
+
+export interface BulkImportItem {
+  path: string;
+  name: string | null;
+  action: "imported" | "skipped" | "failed";
+  account_id: string | null;
+  message: string | null;
+}
+
+export interface BulkImportReport {
+  total: number;
+  imported: number;
+  skipped: number;
+  failed: number;
+  items: BulkImportItem[];
+}
+
+export type GuardAction = "block" | "warn" | "ignore";
+
+export interface GuardResult {
+  guard: string;
+  action: GuardAction;
+  passed: boolean;
+  message: string;
+}
+
 export interface SwitchResult {
   success: boolean;
   history_id: string;
   snapshot_path: string | null;
   message: string;
+  blocked: boolean;
+  guards: GuardResult[];
+}
+
+export interface SwitchGuardPolicy {
+  token_expiry: GuardAction;
+  health: GuardAction;
+  quota: GuardAction;
+  running_processes: GuardAction;
+}
+
+export type RestartMode = "force" | "never";
+
+export interface FailoverConfig {
+  enabled: boolean;
+  interval_seconds: number;
+  threshold_percent: number | null;
+  cooldown_seconds: number;
+  preferred_tags: string[];
+  restart_mode: RestartMode;
+}
+
+export interface FailoverCandidate {
+  account_id: string;
+  account_name: string;
+  quota_state: string;
+  remaining_value: number | null;
+  remaining_unit: string | null;
+  reset_at: string | null;
+  tag_match: boolean;
+  reason: string;
+}
+
+export interface FailoverEvent {
+  from_account_id: string;
+  from_account_name: string;
+  trigger: string;
+  switched_to: FailoverCandidate | null;
+  result: SwitchResult | null;
+  candidates: FailoverCandidate[];
+  message: string;
+  created_at: string;
+}
+
+export interface IntegrityReport {
+  orphan_quota_snapshots: number;
+  orphan_history_refs: number;
+  orphan_project_bindings: number;
+  missing_snapshot_files: number;
+  unreferenced_snapshot_files: number;
+  repaired: boolean;
 }
 
 export interface RuntimeDiagnostics {
@@ -39,8 +239,21 @@ export interface RuntimeDiagnostics {
   codex_auth_exists: boolean;
   app_data_dir: string;
   db_path: string;
+  db_schema_version: number;
   schema_ok: boolean;
   process_count: number;
+  codex_binary_path: string | null;
+  codex_binary_source: string | null;
+  codex_version: string | null;
+  codex_compat_profile: string;
+  codex_compat_warning: string | null;
+}
+
+export interface CodexBinaryInfo {
+  path: string;
+  version: string | null;
+  source: string;
+  pinned: boolean;
 }
 
 export interface CodexCliStatus {
@@ -63,9 +276,45 @@ export interface QuotaSnapshot {
   created_at: string;
 }
 
+export type HealthStatus = "valid" | "expiring" | "refreshable" | "expired" | "revoked" | "malformed";
+
+export interface AccountHealth {
+  account_id: string;
+  status: HealthStatus;
+  expires_at: string | null;
+  reason: string | null;
+  remote_checked: boolean;
+  checked_at: string;
+}
+
+export interface HealthCheckConfig {
+  remote_enabled: boolean;
+  endpoint: string;
+}
+
 export interface QuotaDashboardItem {
   account: Account;
   snapshot: QuotaSnapshot | null;
+  health: AccountHealth | null;
+}
+
+export interface SnapshotRetentionPolicy {
+  keep_count: number;
+  max_age_days: number;
+  protect_recent: number;
+}
+
+export interface PrunedSnapshot {
+  history_id: string;
+  snapshot_path: string;
+  created_at: string;
+  reason: string;
+}
+
+export interface SnapshotPruneReport {
+  dry_run: boolean;
+  kept: number;
+  pruned: PrunedSnapshot[];
 }
 
 export interface QuotaRefreshPolicyInput {
@@ -74,6 +323,127 @@ export interface QuotaRefreshPolicyInput {
   maxConcurrency: number;
 }
 
+export interface DeviceAuthConfig {
+  device_authorization_url: string;
+  token_url: string;
+  client_id: string;
+  scope: string;
+}
+
+export interface DeviceLoginChallenge {
+  session_id: string;
+  mode: "cli" | "native" | (string & {});
+  user_code: string;
+  verification_uri: string;
+  verification_uri_complete: string | null;
+  expires_at: string;
+  interval_seconds: number;
+}
+
+export interface ProjectBinding {
+  id: string;
+  path: string;
+  account_id: string | null;
+  tag: string | null;
+  created_at: string;
+  updated_at: string;
+}
+
+export type RotationStrategy = "round_robin" | "most_remaining" | "earliest_reset" | "least_recently_used";
+
+export interface AccountPool {
+  id: string;
+  name: string;
+  tag: string | null;
+  account_ids: string[];
+  strategy: RotationStrategy;
+  last_account_id: string | null;
+  created_at: string;
+  updated_at: string;
+}
+
+export interface AccountPoolInput {
+  id?: string | null;
+  name: string;
+  tag: string | null;
+  account_ids: string[];
+  strategy: RotationStrategy;
+}
+
+export interface PoolMember {
+  account_id: string;
+  account_name: string;
+  eligible: boolean;
+  note: string;
+}
+
+export interface PoolDecision {
+  pool_id: string;
+  pool_name: string;
+  strategy: RotationStrategy;
+  account_id: string;
+  account_name: string;
+  explanation: string;
+  members: PoolMember[];
+}
+
+export interface PoolSwitchResult {
+  decision: PoolDecision;
+  result: SwitchResult;
+}
+
+export interface ProjectAccountResolution {
+  path: string;
+  binding_path: string | null;
+  source: "dotfile" | "store" | (string & {}) | null;
+  account_id: string | null;
+  tag: string | null;
+  account: Account | null;
+  message: string;
+}
+
+export interface ProjectAutoSwitchEvent {
+  pid: number;
+  cwd: string;
+  resolution: ProjectAccountResolution;
+  result: SwitchResult;
+  restarted: boolean;
+}
+
+export interface ControlToken {
+  id: string;
+  name: string;
+  created_at: string;
+  last_used_at: string | null;
+}
+
+export interface CreatedControlToken {
+  token: ControlToken;
+  secret: string;
+}
+
+export interface ControlApiStatus {
+  enabled: boolean;
+  running: boolean;
+  endpoint: string;
+  token_count: number;
+  error: string | null;
+}
+
+export interface AccountHome {
+  account_id: string;
+  account_name: string;
+  path: string;
+  materialized: boolean;
+}
+
+export interface AccountHomesStatus {
+  enabled: boolean;
+  share_config: boolean;
+  homes_dir: string;
+  homes: AccountHome[];
+}
+
 export interface AccountDraft {
   name: string;
   tagsText: string;